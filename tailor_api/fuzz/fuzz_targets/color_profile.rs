@@ -0,0 +1,13 @@
+#![no_main]
+
+use std::str::FromStr;
+
+use libfuzzer_sys::fuzz_target;
+use tailor_api::{Color, ColorProfile};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<ColorProfile>(data);
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = Color::from_str(text);
+    }
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tailor_api::FanProfile;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(profile) = serde_json::from_slice::<FanProfile>(data) else {
+        return;
+    };
+    // tailord rejects or clamps faster speeds when loading profiles.
+    let in_range = profile.points.iter().all(|point| point.fan <= 100);
+    for temp in 0..=u8::MAX {
+        let speed = profile.fan_speed_at(temp);
+        assert!(!in_range || speed <= 100);
+        profile.power_limit_at(temp);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tailor_api::ProfileInfo;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(info) = serde_json::from_slice::<ProfileInfo>(data) {
+        // Everything that was read must be written again.
+        let json = serde_json::to_vec(&info).unwrap();
+        assert_eq!(serde_json::from_slice::<ProfileInfo>(&json).unwrap(), info);
+    }
+});
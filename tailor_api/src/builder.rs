@@ -0,0 +1,286 @@
+//! Builders and checked constructors for profiles.
+//!
+//! Profiles are plain structs that can hold anything, which tailord
+//! fixes or rejects when it loads them. The builders check the
+//! invariants while the profile is created instead.
+
+use crate::{
+    Color, ColorPoint, ColorProfile, ColorTransition, FanPrediction, FanProfile, FanProfilePoint,
+    FanSmoothing,
+};
+
+/// A violated invariant of a profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileError {
+    /// A fan profile needs at least one point.
+    NoPoints,
+    /// The temperature of a point isn't higher than that of the point before.
+    TemperatureNotIncreasing { temp: u8 },
+    /// Fan speeds are percentages.
+    FanSpeedOutOfRange { temp: u8, fan: u8 },
+    /// The smoothing alpha is a percentage of at least 1.
+    SmoothingOutOfRange { alpha: u8 },
+    /// A point of a color cycle is never shown.
+    ZeroTransitionTime { index: usize },
+}
+
+impl std::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoPoints => f.write_str("Fan profile has no points"),
+            Self::TemperatureNotIncreasing { temp } => {
+                write!(f, "Temperature in fan profile isn't increasing at {temp}°C")
+            }
+            Self::FanSpeedOutOfRange { temp, fan } => write!(
+                f,
+                "Fan speed can't be larger than 100%, but is {fan}% at {temp}°C"
+            ),
+            Self::SmoothingOutOfRange { alpha } => write!(
+                f,
+                "Smoothing alpha must be between 1% and 100%, but is {alpha}%"
+            ),
+            Self::ZeroTransitionTime { index } => {
+                write!(f, "Color {} has a transition time of 0 ms", index + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl FanProfilePoint {
+    /// A point without power limit, `fan` is at most 100%.
+    pub fn new(temp: u8, fan: u8) -> Result<Self, ProfileError> {
+        if fan > 100 {
+            return Err(ProfileError::FanSpeedOutOfRange { temp, fan });
+        }
+        Ok(Self {
+            temp,
+            fan,
+            power_limit: 0,
+        })
+    }
+}
+
+impl FanProfile {
+    pub fn builder() -> FanProfileBuilder {
+        FanProfileBuilder::default()
+    }
+
+    /// Check the invariants that [`FanProfileBuilder`] enforces, e.g. for
+    /// profiles that were deserialized. Falling speeds are allowed.
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        if self.points.is_empty() {
+            return Err(ProfileError::NoPoints);
+        }
+        if let Some(pair) = self
+            .points
+            .windows(2)
+            .find(|pair| pair[0].temp >= pair[1].temp)
+        {
+            return Err(ProfileError::TemperatureNotIncreasing { temp: pair[1].temp });
+        }
+        if let Some(point) = self.points.iter().find(|point| point.fan > 100) {
+            return Err(ProfileError::FanSpeedOutOfRange {
+                temp: point.temp,
+                fan: point.fan,
+            });
+        }
+        match self.smoothing {
+            Some(FanSmoothing { alpha }) if !(1..=100).contains(&alpha) => {
+                Err(ProfileError::SmoothingOutOfRange { alpha })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Builds a [`FanProfile`] from points in any order.
+#[derive(Debug, Clone)]
+pub struct FanProfileBuilder {
+    profile: FanProfile,
+}
+
+impl Default for FanProfileBuilder {
+    fn default() -> Self {
+        Self {
+            profile: FanProfile {
+                points: Vec::new(),
+                sticky: false,
+                prediction: None,
+                smoothing: None,
+            },
+        }
+    }
+}
+
+impl FanProfileBuilder {
+    /// Run the fans at `fan` percent at `temp` °C.
+    pub fn point(self, temp: u8, fan: u8) -> Self {
+        self.point_with_power_limit(temp, fan, 0)
+    }
+
+    /// Like [`Self::point`], see [`FanProfilePoint::power_limit`].
+    pub fn point_with_power_limit(mut self, temp: u8, fan: u8, power_limit: u8) -> Self {
+        self.profile.points.push(FanProfilePoint {
+            temp,
+            fan,
+            power_limit,
+        });
+        self
+    }
+
+    pub fn sticky(mut self, sticky: bool) -> Self {
+        self.profile.sticky = sticky;
+        self
+    }
+
+    pub fn prediction(mut self, prediction: FanPrediction) -> Self {
+        self.profile.prediction = Some(prediction);
+        self
+    }
+
+    /// Weight of the latest temperature in percent, see [`FanSmoothing`].
+    pub fn smoothing(mut self, alpha: u8) -> Self {
+        self.profile.smoothing = Some(FanSmoothing { alpha });
+        self
+    }
+
+    /// Sort the points by temperature and check the profile.
+    /// Two points at the same temperature are an error.
+    pub fn build(mut self) -> Result<FanProfile, ProfileError> {
+        self.profile.points.sort_by_key(|point| point.temp);
+        self.profile.validate()?;
+        Ok(self.profile)
+    }
+}
+
+impl ColorProfile {
+    pub fn builder() -> ColorProfileBuilder {
+        ColorProfileBuilder::default()
+    }
+
+    /// Check the invariants that [`ColorProfileBuilder`] enforces, e.g. for
+    /// profiles that were deserialized.
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        let Self::Multiple(points) = self else {
+            return Ok(());
+        };
+        match points.iter().position(|point| point.transition_time == 0) {
+            Some(index) => Err(ProfileError::ZeroTransitionTime { index }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Builds a [`ColorProfile`] point by point.
+#[derive(Debug, Clone, Default)]
+pub struct ColorProfileBuilder {
+    points: Vec<ColorPoint>,
+}
+
+impl ColorProfileBuilder {
+    /// Change to `color` within `transition_time` ms and move on to the next
+    /// color once it's reached. After the last color, the cycle starts over.
+    pub fn color(
+        mut self,
+        color: Color,
+        transition: ColorTransition,
+        transition_time: u32,
+    ) -> Self {
+        self.points.push(ColorPoint {
+            color,
+            transition,
+            transition_time,
+        });
+        self
+    }
+
+    /// Without colors, the LEDs are switched off and a single
+    /// color is shown as it is, without any transition.
+    pub fn build(self) -> Result<ColorProfile, ProfileError> {
+        let profile = match <[ColorPoint; 1]>::try_from(self.points) {
+            Ok([point]) => ColorProfile::Single(point.color),
+            Err(points) if points.is_empty() => ColorProfile::None,
+            Err(points) => ColorProfile::Multiple(points),
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Color, ColorProfile, ColorTransition, FanProfile, FanProfilePoint};
+
+    use super::ProfileError;
+
+    #[test]
+    fn fan_profiles() {
+        let profile = FanProfile::builder()
+            .point(80, 100)
+            .point_with_power_limit(40, 20, 10)
+            .smoothing(30)
+            .build()
+            .unwrap();
+        assert_eq!(
+            profile.points,
+            [
+                FanProfilePoint {
+                    temp: 40,
+                    fan: 20,
+                    power_limit: 10,
+                },
+                FanProfilePoint::new(80, 100).unwrap(),
+            ]
+        );
+        assert_eq!(profile.smoothing.unwrap().alpha, 30);
+
+        assert_eq!(FanProfile::builder().build(), Err(ProfileError::NoPoints));
+        assert_eq!(
+            FanProfile::builder().point(40, 20).point(40, 30).build(),
+            Err(ProfileError::TemperatureNotIncreasing { temp: 40 })
+        );
+        assert_eq!(
+            FanProfile::builder().point(40, 120).build(),
+            Err(ProfileError::FanSpeedOutOfRange { temp: 40, fan: 120 })
+        );
+        assert_eq!(
+            FanProfile::builder().point(40, 20).smoothing(0).build(),
+            Err(ProfileError::SmoothingOutOfRange { alpha: 0 })
+        );
+        assert!(FanProfilePoint::new(40, 101).is_err());
+    }
+
+    #[test]
+    fn color_profiles() {
+        let red = Color { r: 255, g: 0, b: 0 };
+        let blue = Color { r: 0, g: 0, b: 255 };
+
+        assert_eq!(ColorProfile::builder().build(), Ok(ColorProfile::None));
+        assert_eq!(
+            ColorProfile::builder()
+                .color(red.clone(), ColorTransition::Linear, 0)
+                .build(),
+            Ok(ColorProfile::Single(red.clone()))
+        );
+        assert_eq!(
+            ColorProfile::builder()
+                .color(red.clone(), ColorTransition::Linear, 1000)
+                .color(blue.clone(), ColorTransition::Linear, 1000)
+                .build(),
+            Ok(ColorProfile::gradient(
+                &[red.clone(), blue.clone()],
+                2000,
+                ColorTransition::Linear
+            ))
+        );
+        assert_eq!(
+            ColorProfile::builder()
+                .color(red, ColorTransition::None, 1000)
+                .color(blue, ColorTransition::None, 0)
+                .build(),
+            Err(ProfileError::ZeroTransitionTime { index: 1 })
+        );
+    }
+}
@@ -0,0 +1,31 @@
+/// Battery charging settings.
+///
+/// Fields that are `None` are left unchanged when the settings are applied.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ChargingSettings {
+    /// Charging profile of the firmware, e.g. `high_capacity` or `stationary`.
+    pub profile: Option<String>,
+    /// Whether charging speed or performance is preferred when charging over USB-C.
+    pub priority: Option<String>,
+    /// Battery level in percent below which charging starts.
+    pub start_threshold: Option<u32>,
+    /// Battery level in percent at which charging stops.
+    pub end_threshold: Option<u32>,
+}
+
+/// Charging settings that are supported by the device.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ChargingCapabilities {
+    /// Available charging profiles, empty if not supported.
+    pub profiles: Vec<String>,
+    /// Available charging priorities, empty if not supported.
+    pub priorities: Vec<String>,
+    /// Whether start and end thresholds can be set.
+    pub thresholds: bool,
+    /// Accepted start thresholds, `None` if any value from 0 to 100 is accepted.
+    pub start_thresholds: Option<Vec<u32>>,
+    /// Accepted end thresholds, `None` if any value from 0 to 100 is accepted.
+    pub end_thresholds: Option<Vec<u32>>,
+}
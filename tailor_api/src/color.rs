@@ -3,6 +3,7 @@ use atoi::FromRadix16;
 use std::{fmt::Display, io, str::FromStr};
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ColorPoint {
     pub color: Color,
     pub transition: ColorTransition,
@@ -11,12 +12,14 @@ pub struct ColorPoint {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ColorTransition {
     None,
     Linear,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ColorProfile {
     None,
     Single(Color),
@@ -27,35 +30,306 @@ impl ColorProfile {
     pub fn default(mode: LedControllerMode) -> Self {
         match mode {
             LedControllerMode::Monochrome => Self::None,
-            LedControllerMode::Rgb => Self::Multiple(vec![
-                ColorPoint {
-                    color: Color { r: 255, g: 0, b: 0 },
-                    transition: ColorTransition::Linear,
-                    transition_time: 6000,
-                },
-                ColorPoint {
-                    color: Color { r: 0, g: 255, b: 0 },
-                    transition: ColorTransition::Linear,
-                    transition_time: 6000,
-                },
-                ColorPoint {
-                    color: Color { r: 0, g: 0, b: 255 },
-                    transition: ColorTransition::Linear,
-                    transition_time: 6000,
-                },
-            ]),
+            // Red, green and blue for 6s each.
+            LedControllerMode::Rgb => Self::rainbow(3, 18000),
+        }
+    }
+
+    /// A cycle through `palette` that takes `duration` ms, where every color gets
+    /// the same transition time (rounded down to full ms).
+    /// A single color is shown as it is and an empty palette switches the LEDs off.
+    pub fn gradient(palette: &[Color], duration: u32, transition: ColorTransition) -> Self {
+        match palette {
+            [] => Self::None,
+            [color] => Self::Single(color.clone()),
+            _ => {
+                let transition_time = duration / palette.len() as u32;
+                Self::Multiple(
+                    palette
+                        .iter()
+                        .map(|color| ColorPoint {
+                            color: color.clone(),
+                            transition: transition.clone(),
+                            transition_time,
+                        })
+                        .collect(),
+                )
+            }
         }
     }
+
+    /// A linear cycle through `colors` hues that takes `duration` ms, starting with red.
+    pub fn rainbow(colors: usize, duration: u32) -> Self {
+        let red = Color { r: 255, g: 0, b: 0 };
+        Self::gradient(&red.hue_cycle(colors), duration, ColorTransition::Linear)
+    }
+
+    /// Duration of one cycle in ms, 0 if the colors don't change.
+    pub fn duration(&self) -> u64 {
+        match self {
+            Self::Multiple(colors) => colors
+                .iter()
+                .map(|point| u64::from(point.transition_time))
+                .sum(),
+            Self::None | Self::Single(_) => 0,
+        }
+    }
+
+    /// The color that is shown `time` ms after the cycle started.
+    pub fn color_at(&self, time: u64) -> Color {
+        let colors = match self {
+            Self::None => return Color { r: 0, g: 0, b: 0 },
+            Self::Single(color) => return color.clone(),
+            Self::Multiple(colors) => colors,
+        };
+        let Some(last) = colors.last() else {
+            return Color { r: 0, g: 0, b: 0 };
+        };
+
+        let mut time = time.checked_rem(self.duration()).unwrap_or_default();
+        let mut prev_color = &last.color;
+        for point in colors {
+            let transition_time = u64::from(point.transition_time);
+            if time < transition_time {
+                return match point.transition {
+                    ColorTransition::None => point.color.clone(),
+                    ColorTransition::Linear => {
+                        prev_color.mix(&point.color, time as f32 / transition_time as f32)
+                    }
+                };
+            }
+            time -= transition_time;
+            prev_color = &point.color;
+        }
+        // Only reached if all transition times are 0.
+        colors[0].color.clone()
+    }
+
+    /// Approximate the cycle with `points` linear transitions of the same length,
+    /// e.g. to smooth out abrupt transitions or to simplify long cycles.
+    /// Profiles without a cycle are kept as they are.
+    pub fn resample(&self, points: usize) -> Self {
+        let duration = self.duration();
+        if duration == 0 || points == 0 {
+            return self.clone();
+        }
+        // Each point is the color at the end of its transition.
+        let palette: Vec<Color> = (1..=points as u64)
+            .map(|idx| self.color_at(duration * idx / points as u64))
+            .collect();
+        Self::gradient(
+            &palette,
+            u32::try_from(duration).unwrap_or(u32::MAX),
+            ColorTransition::Linear,
+        )
+    }
+
+    /// Run the cycle backwards. This is exact for linear transitions, a linear
+    /// transition right after one without transition starts from a different color.
+    pub fn reverse(&self) -> Self {
+        let Self::Multiple(colors) = self else {
+            return self.clone();
+        };
+        let reversed = (0..colors.len())
+            .rev()
+            .map(|idx| {
+                let point = &colors[idx];
+                let color = match point.transition {
+                    // Shown for the whole transition time in both directions.
+                    ColorTransition::None => point.color.clone(),
+                    // Transition back to where the transition started.
+                    ColorTransition::Linear => colors[(idx + colors.len() - 1) % colors.len()]
+                        .color
+                        .clone(),
+                };
+                ColorPoint {
+                    color,
+                    transition: point.transition.clone(),
+                    transition_time: point.transition_time,
+                }
+            })
+            .collect();
+        Self::Multiple(reversed)
+    }
 }
 
+/// Colors are always written as RGB, but can be read
+/// in HSV or HSL as well, e.g. `{ "h": 210, "s": 100, "v": 80 }`.
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "ColorValue")]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
 }
 
+/// A color with the hue in degrees and the saturation and value in percent.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+}
+
+/// A color with the hue in degrees and the saturation and lightness in percent.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+}
+
+/// A color in RGB, HSV or HSL, the hue in degrees and the rest in percent.
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum ColorValue {
+    Rgb { r: u8, g: u8, b: u8 },
+    Hsv(Hsv),
+    Hsl(Hsl),
+}
+
+// The derived schema would only allow RGB.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Color {
+    fn schema_name() -> String {
+        "Color".to_owned()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        ColorValue::json_schema(gen)
+    }
+}
+
+impl From<ColorValue> for Color {
+    fn from(value: ColorValue) -> Self {
+        match value {
+            ColorValue::Rgb { r, g, b } => Self { r, g, b },
+            ColorValue::Hsv(hsv) => hsv.into(),
+            ColorValue::Hsl(hsl) => hsl.into(),
+        }
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Self {
+        Self::from_hsv(hsv.h, hsv.s, hsv.v)
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Self {
+        Self::from_hsl(hsl.h, hsl.s, hsl.l)
+    }
+}
+
 impl Color {
+    /// Hues outside of 0..360° wrap around, the other values are clamped to 0..=100%.
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let h = h.rem_euclid(360.0);
+        let s = (s / 100.0).clamp(0.0, 1.0);
+        let v = (v / 100.0).clamp(0.0, 1.0);
+
+        let chroma = v * s;
+        let x = chroma * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+        let (r, g, b) = match (h / 60.0) as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = v - chroma;
+        let scale = |value: f32| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Self {
+            r: scale(r),
+            g: scale(g),
+            b: scale(b),
+        }
+    }
+
+    /// Hues outside of 0..360° wrap around, the other values are clamped to 0..=100%.
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let s = (s / 100.0).clamp(0.0, 1.0);
+        let l = (l / 100.0).clamp(0.0, 1.0);
+
+        let v = l + s * l.min(1.0 - l);
+        let s = if v > 0.0 { 2.0 * (1.0 - l / v) } else { 0.0 };
+        Self::from_hsv(h, s * 100.0, v * 100.0)
+    }
+
+    /// The hue of grays is 0°.
+    pub fn to_hsv(&self) -> Hsv {
+        let [r, g, b] = [self.r, self.g, self.b].map(|value| f32::from(value) / 255.0);
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let s = if max > 0.0 { delta / max } else { 0.0 };
+        Hsv {
+            h,
+            s: s * 100.0,
+            v: max * 100.0,
+        }
+    }
+
+    /// The hue of grays is 0°.
+    pub fn to_hsl(&self) -> Hsl {
+        let Hsv { h, s, v } = self.to_hsv();
+        let (s, v) = (s / 100.0, v / 100.0);
+
+        let l = v * (1.0 - s / 2.0);
+        let s = if l > 0.0 && l < 1.0 {
+            (v - l) / l.min(1.0 - l)
+        } else {
+            0.0
+        };
+        Hsl {
+            h,
+            s: s * 100.0,
+            l: l * 100.0,
+        }
+    }
+
+    /// Blend linearly towards `other`, `fraction` 0 is this color and 1 is `other`.
+    pub fn mix(&self, other: &Color, fraction: f32) -> Self {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let mix = |from: u8, to: u8| {
+            let from = f32::from(from);
+            (from + (f32::from(to) - from) * fraction).round() as u8
+        };
+        Self {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+        }
+    }
+
+    /// The same saturation and value with the hue rotated by `degrees`.
+    pub fn rotate_hue(&self, degrees: f32) -> Self {
+        let Hsv { h, s, v } = self.to_hsv();
+        Self::from_hsv(h + degrees, s, v)
+    }
+
+    /// `steps` colors with the hue rotated evenly around the color wheel,
+    /// starting with this color, e.g. for a rainbow animation.
+    pub fn hue_cycle(&self, steps: usize) -> Vec<Self> {
+        (0..steps)
+            .map(|step| self.rotate_hue(360.0 * step as f32 / steps as f32))
+            .collect()
+    }
+
     pub fn sysfs_rgb_string(&self, max_brightness: u32) -> String {
         let Color { r, g, b } = *self;
         if max_brightness == 255 {
@@ -119,9 +393,11 @@ impl FromStr for Color {
                 "Incorrect length for 3x8-bit hexadecimal value",
             ))
         } else {
-            let r = u8::from_radix_16(s[0..2].as_bytes());
-            let g = u8::from_radix_16(s[2..4].as_bytes());
-            let b = u8::from_radix_16(s[4..6].as_bytes());
+            // Slice the bytes, other characters than ASCII are rejected below.
+            let bytes = s.as_bytes();
+            let r = u8::from_radix_16(&bytes[0..2]);
+            let g = u8::from_radix_16(&bytes[2..4]);
+            let b = u8::from_radix_16(&bytes[4..6]);
 
             if r.1 == 2 && g.1 == 2 && b.1 == 2 {
                 Ok(Self {
@@ -141,7 +417,11 @@ impl FromStr for Color {
 
 #[cfg(test)]
 mod test {
-    use crate::color::Color;
+    use crate::{
+        color::{Color, ColorPoint, ColorProfile, ColorTransition, Hsl, Hsv},
+        LedControllerMode,
+    };
+    use proptest::prelude::*;
     use std::str::FromStr;
 
     #[test]
@@ -162,4 +442,144 @@ mod test {
         Color::from_str("F").unwrap_err();
         Color::from_str("INVLD!").unwrap_err();
     }
+
+    #[test]
+    fn hsv_and_hsl() {
+        let orange = Color {
+            r: 255,
+            g: 128,
+            b: 0,
+        };
+        assert_eq!(Color::from_hsv(30.0, 100.0, 100.0), orange);
+        assert_eq!(Color::from_hsl(30.0, 100.0, 50.0), orange);
+        assert_eq!(Color::from_hsv(390.0, 100.0, 100.0), orange);
+        assert_eq!(
+            Color::from_hsl(0.0, 0.0, 100.0),
+            Color::from_str("FFFFFF").unwrap()
+        );
+
+        let Hsl { h, s, l } = orange.to_hsl();
+        assert_eq!((h.round(), s.round(), l.round()), (30.0, 100.0, 50.0));
+
+        let red = Color { r: 255, g: 0, b: 0 };
+        assert_eq!(red.rotate_hue(120.0), Color { r: 0, g: 255, b: 0 });
+        assert_eq!(red.rotate_hue(-120.0), Color { r: 0, g: 0, b: 255 });
+        assert_eq!(
+            red.hue_cycle(3),
+            [red.clone(), red.rotate_hue(120.0), red.rotate_hue(240.0)]
+        );
+
+        // Colors can be read in all formats, but are always written as RGB.
+        for json in [
+            r#"{"r":255,"g":128,"b":0}"#,
+            r#"{"h":30,"s":100,"v":100}"#,
+            r#"{"h":30,"s":100,"l":50}"#,
+        ] {
+            let color: Color = serde_json::from_str(json).unwrap();
+            assert_eq!(color, orange);
+        }
+        assert_eq!(
+            serde_json::to_string(&orange).unwrap(),
+            r#"{"r":255,"g":128,"b":0}"#
+        );
+    }
+
+    #[test]
+    fn gradients() {
+        let red = Color { r: 255, g: 0, b: 0 };
+        let green = Color { r: 0, g: 255, b: 0 };
+        let blue = Color { r: 0, g: 0, b: 255 };
+        let linear = |color: &Color, transition_time| ColorPoint {
+            color: color.clone(),
+            transition: ColorTransition::Linear,
+            transition_time,
+        };
+
+        let profile = ColorProfile::default(LedControllerMode::Rgb);
+        assert_eq!(
+            profile,
+            ColorProfile::Multiple(vec![
+                linear(&red, 6000),
+                linear(&green, 6000),
+                linear(&blue, 6000)
+            ])
+        );
+        assert_eq!(
+            ColorProfile::gradient(std::slice::from_ref(&red), 1000, ColorTransition::None),
+            ColorProfile::Single(red.clone())
+        );
+        assert_eq!(profile.duration(), 18000);
+
+        // The first transition starts with the last color.
+        assert_eq!(profile.color_at(0), blue);
+        assert_eq!(
+            profile.color_at(3000),
+            Color {
+                r: 128,
+                g: 0,
+                b: 128
+            }
+        );
+        assert_eq!(profile.color_at(12000 + 18000), green);
+
+        let purple = Color {
+            r: 128,
+            g: 0,
+            b: 128,
+        };
+        let yellow = Color {
+            r: 128,
+            g: 128,
+            b: 0,
+        };
+        let teal = Color {
+            r: 0,
+            g: 128,
+            b: 128,
+        };
+        assert_eq!(
+            profile.resample(6),
+            ColorProfile::Multiple(
+                [&purple, &red, &yellow, &green, &teal, &blue]
+                    .into_iter()
+                    .map(|color| linear(color, 3000))
+                    .collect()
+            )
+        );
+
+        // Blue to green to red instead of blue to red to green.
+        let reversed = profile.reverse();
+        assert_eq!(
+            reversed,
+            ColorProfile::Multiple(vec![
+                linear(&green, 6000),
+                linear(&red, 6000),
+                linear(&blue, 6000)
+            ])
+        );
+        assert_eq!(reversed.reverse(), profile);
+    }
+
+    proptest! {
+        #[test]
+        fn hsv_round_trip(r in any::<u8>(), g in any::<u8>(), b in any::<u8>()) {
+            let color = Color { r, g, b };
+            let Hsv { h, s, v } = color.to_hsv();
+            prop_assert_eq!(Color::from_hsv(h, s, v), color.clone());
+            let Hsl { h, s, l } = color.to_hsl();
+            prop_assert_eq!(Color::from_hsl(h, s, l), color);
+        }
+
+        #[test]
+        fn parsing_never_panics(text in ".*") {
+            let _ = Color::from_str(&text);
+            let _ = serde_json::from_str::<ColorProfile>(&text);
+        }
+
+        #[test]
+        fn color_round_trip(r in any::<u8>(), g in any::<u8>(), b in any::<u8>()) {
+            let color = Color { r, g, b };
+            prop_assert_eq!(Color::from_str(&color.to_string()[2..]).unwrap(), color);
+        }
+    }
 }
@@ -0,0 +1,26 @@
+use crate::LedDeviceInfo;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FanDeviceInfo {
+    /// Index of the fan as used by fan overrides and the order
+    /// of the fan profiles in [`crate::ProfileInfo::fans`].
+    pub index: u8,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SensorInfo {
+    /// Index of the fan whose speed is controlled by this sensor.
+    pub fan_index: u8,
+    /// The hwmon chip and label of the sensor, e.g. `coretemp Package id 0`,
+    /// or `Embedded controller`.
+    pub label: String,
+}
+
+/// All devices detected by tailord.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DeviceList {
+    pub fans: Vec<FanDeviceInfo>,
+    pub leds: Vec<LedDeviceInfo>,
+    pub sensors: Vec<SensorInfo>,
+}
@@ -1,4 +1,7 @@
+use crate::ProfileError;
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FanProfilePoint {
     pub temp: u8,
     pub fan: u8,
@@ -8,7 +11,384 @@ pub struct FanProfilePoint {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FanProfile {
     pub points: Vec<FanProfilePoint>,
     pub sticky: bool,
+    /// Speed the fans up ahead of the curve while the temperature rises quickly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prediction: Option<FanPrediction>,
+    /// Follow a moving average of the temperature instead of
+    /// the minimum of the last few seconds if `sticky` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoothing: Option<FanSmoothing>,
+}
+
+/// Derivative term of the fan control: while the temperature rises, the fan speed
+/// is raised above the curve in proportion to the rise per second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FanPrediction {
+    /// Additional fan speed in percent for each °C per second of rise.
+    pub gain: u8,
+    /// Upper bound of the additional fan speed in percent.
+    pub max_boost: u8,
+}
+
+impl FanPrediction {
+    /// Additional fan speed for a temperature rise of `rise` °C within `duration`.
+    pub fn boost(&self, rise: u8, duration: std::time::Duration) -> u8 {
+        let seconds = duration.as_secs_f64();
+        if seconds <= 0.0 {
+            return 0;
+        }
+        let boost = f64::from(self.gain) * f64::from(rise) / seconds;
+        boost.min(f64::from(self.max_boost)).round() as u8
+    }
+}
+
+/// Exponential moving average of the temperature, which follows sustained
+/// load faster than a sticky profile while still ignoring short spikes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FanSmoothing {
+    /// Weight of the latest temperature in percent from 1 to 100,
+    /// lower values smooth more.
+    pub alpha: u8,
+}
+
+impl FanProfile {
+    /// Fan speed in percent at `temp` °C, interpolated linearly between the points.
+    /// Above the last point, the fans run at full speed.
+    pub fn fan_speed_at(&self, temp: u8) -> u8 {
+        self.interpolate(temp, |point| point.fan).unwrap_or(100)
+    }
+
+    /// Power limit at `temp` °C, interpolated linearly between the points.
+    /// Above the last point, the power limit of the last point applies.
+    pub fn power_limit_at(&self, temp: u8) -> u8 {
+        self.interpolate(temp, |point| point.power_limit)
+            .or_else(|| self.points.last().map(|point| point.power_limit))
+            .unwrap_or_default()
+    }
+
+    /// Returns `None` if `temp` is higher than the temperature of all points.
+    /// The points must be sorted by temperature.
+    fn interpolate(&self, temp: u8, value: impl Fn(&FanProfilePoint) -> u8) -> Option<u8> {
+        // Find the first item that has a greater or equal temperature.
+        let position = self.points.iter().position(|p| p.temp >= temp)?;
+        let point = &self.points[position];
+
+        // If the point fits exactly or it's the first element, use its value directly.
+        if point.temp == temp || position == 0 {
+            Some(value(point))
+        } else {
+            let prev_point = &self.points[position - 1];
+
+            // Interpolate with a linear slope between those two points.
+            // Use i32 to make sure the calculation doesn't overflow.
+            let temp_diff = i32::from(point.temp - prev_point.temp);
+            let curr_temp_diff = i32::from(temp - prev_point.temp);
+            let diff = i32::from(value(point)) - i32::from(value(prev_point));
+
+            Some((i32::from(value(prev_point)) + diff * curr_temp_diff / temp_diff) as u8)
+        }
+    }
+}
+
+/// Built-in fan curves, so a sensible curve can be used without writing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// Keeps the fans off at idle and as slow as possible under load.
+    Silent,
+    /// A compromise between noise and temperature for everyday use.
+    Balanced,
+    /// Cools early and reacts to rising temperatures ahead of the curve.
+    Performance,
+}
+
+impl Preset {
+    pub const ALL: [Self; 3] = [Self::Silent, Self::Balanced, Self::Performance];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Silent => "silent",
+            Self::Balanced => "balanced",
+            Self::Performance => "performance",
+        }
+    }
+
+    /// Temperature and fan speed of each point of the curve.
+    fn curve(&self) -> &'static [(u8, u8)] {
+        match self {
+            Self::Silent => &[(40, 0), (60, 20), (75, 45), (85, 70), (95, 100)],
+            Self::Balanced => &[(35, 10), (50, 25), (65, 45), (80, 75), (90, 100)],
+            Self::Performance => &[(30, 25), (45, 40), (60, 60), (75, 85), (85, 100)],
+        }
+    }
+}
+
+impl std::str::FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name() == s)
+            .ok_or_else(|| format!("Unknown fan preset `{s}`"))
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FanProfile {
+    /// The curve of a built-in preset. The quieter presets are sticky,
+    /// so the fans don't spin up and down with every short load.
+    pub fn preset(preset: Preset) -> Self {
+        Self {
+            points: preset
+                .curve()
+                .iter()
+                .map(|&(temp, fan)| FanProfilePoint {
+                    temp,
+                    fan,
+                    power_limit: 0,
+                })
+                .collect(),
+            sticky: preset != Preset::Performance,
+            prediction: (preset == Preset::Performance).then_some(FanPrediction {
+                gain: 5,
+                max_boost: 30,
+            }),
+            smoothing: None,
+        }
+    }
+}
+
+/// The loudest acceptable fan speed and where it may be exceeded,
+/// see [`FanProfile::noise_limited`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoiseLimit {
+    /// Loudest acceptable fan speed in percent.
+    pub max_speed: u8,
+    /// Above this temperature in °C, the fans may get louder.
+    pub above: u8,
+    /// Temperature in °C at which the fans reach full speed.
+    pub full_speed: u8,
+}
+
+impl FanProfile {
+    /// Cap the curve at `limit.max_speed` up to `limit.above` °C, from where the
+    /// speed rises linearly to 100% at `limit.full_speed` °C.
+    ///
+    /// Prediction is dropped, because it would raise the speed above the limit.
+    pub fn noise_limited(&self, limit: NoiseLimit) -> Result<Self, ProfileError> {
+        let NoiseLimit {
+            max_speed,
+            above,
+            full_speed,
+        } = limit;
+        let mut builder = Self::builder().sticky(self.sticky);
+        if let Some(smoothing) = self.smoothing {
+            builder = builder.smoothing(smoothing.alpha);
+        }
+        for point in self.points.iter().filter(|point| point.temp < above) {
+            builder = builder.point_with_power_limit(
+                point.temp,
+                point.fan.min(max_speed),
+                point.power_limit,
+            );
+        }
+        builder
+            .point_with_power_limit(
+                above,
+                self.fan_speed_at(above).min(max_speed),
+                self.power_limit_at(above),
+            )
+            .point_with_power_limit(full_speed, 100, self.power_limit_at(full_speed))
+            .build()
+    }
+}
+
+/// Live state of a fan as reported by tailord.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct FanStatus {
+    /// Index of the fan, see [`crate::FanDeviceInfo::index`].
+    pub index: u8,
+    /// Temperature of the sensor that controls this fan in °C.
+    pub temperature: u8,
+    /// Current fan speed in percent.
+    pub speed: u8,
+    /// Fan speed in percent the fan is approaching.
+    /// `None` while the fan is controlled by the firmware.
+    #[serde(default)]
+    pub target: Option<u8>,
+    /// Injected idle time in percent, see [`FanProfilePoint::power_limit`].
+    #[serde(default)]
+    pub power_limit: u8,
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use proptest::prelude::*;
+
+    use super::{FanPrediction, FanProfile, FanProfilePoint, NoiseLimit, Preset};
+    use crate::ProfileError;
+
+    fn point(temp: u8, fan: u8, power_limit: u8) -> FanProfilePoint {
+        FanProfilePoint {
+            temp,
+            fan,
+            power_limit,
+        }
+    }
+
+    #[test]
+    fn interpolation() {
+        let profile = FanProfile {
+            points: vec![point(40, 20, 0), point(60, 40, 30), point(80, 80, 10)],
+            sticky: false,
+            prediction: None,
+            smoothing: None,
+        };
+
+        assert_eq!(profile.fan_speed_at(20), 20);
+        assert_eq!(profile.fan_speed_at(50), 30);
+        assert_eq!(profile.fan_speed_at(70), 60);
+        assert_eq!(profile.fan_speed_at(90), 100);
+
+        assert_eq!(profile.power_limit_at(50), 15);
+        assert_eq!(profile.power_limit_at(70), 20);
+        assert_eq!(profile.power_limit_at(90), 10);
+    }
+
+    #[test]
+    fn prediction() {
+        let prediction = FanPrediction {
+            gain: 3,
+            max_boost: 20,
+        };
+
+        assert_eq!(prediction.boost(0, Duration::from_secs(4)), 0);
+        assert_eq!(prediction.boost(4, Duration::from_secs(4)), 3);
+        assert_eq!(prediction.boost(10, Duration::from_secs(2)), 15);
+        assert_eq!(prediction.boost(40, Duration::from_secs(4)), 20);
+        assert_eq!(prediction.boost(40, Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn presets() {
+        for preset in Preset::ALL {
+            let profile = FanProfile::preset(preset);
+            assert_eq!(profile.validate(), Ok(()));
+            assert_eq!(profile.fan_speed_at(100), 100);
+            assert_eq!(preset.name().parse(), Ok(preset));
+        }
+        // Quieter presets are slower at every temperature.
+        for temp in 30..=90 {
+            let [silent, balanced, performance] =
+                Preset::ALL.map(|preset| FanProfile::preset(preset).fan_speed_at(temp));
+            assert!(silent <= balanced && balanced <= performance, "{temp}°C");
+        }
+    }
+
+    #[test]
+    fn noise_limit() {
+        let limit = NoiseLimit {
+            max_speed: 40,
+            above: 75,
+            full_speed: 90,
+        };
+        let profile = FanProfile::preset(Preset::Balanced)
+            .noise_limited(limit)
+            .unwrap();
+        assert_eq!(
+            profile.points,
+            [
+                point(35, 10, 0),
+                point(50, 25, 0),
+                point(65, 40, 0),
+                point(75, 40, 0),
+                point(90, 100, 0)
+            ]
+        );
+        assert!((0..=75).all(|temp| profile.fan_speed_at(temp) <= 40));
+        assert_eq!(profile.fan_speed_at(85), 80);
+
+        let performance = FanProfile::preset(Preset::Performance);
+        assert_eq!(performance.noise_limited(limit).unwrap().prediction, None);
+        assert_eq!(
+            performance.noise_limited(NoiseLimit { above: 90, ..limit }),
+            Err(ProfileError::TemperatureNotIncreasing { temp: 90 })
+        );
+    }
+
+    /// Valid curves: sorted by temperature with speeds of at most 100%.
+    fn curve() -> impl Strategy<Value = FanProfile> {
+        (
+            proptest::collection::btree_map(any::<u8>(), (0..=100u8, any::<u8>()), 1..16),
+            any::<bool>(),
+        )
+            .prop_map(
+                |(points, sticky): (BTreeMap<u8, (u8, u8)>, bool)| FanProfile {
+                    points: points
+                        .into_iter()
+                        .map(|(temp, (fan, power_limit))| point(temp, fan, power_limit))
+                        .collect(),
+                    sticky,
+                    prediction: None,
+                    smoothing: None,
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn fan_speed_between_points(profile in curve()) {
+            let min = profile.points.iter().map(|point| point.fan).min().unwrap();
+            for temp in 0..=u8::MAX {
+                let speed = profile.fan_speed_at(temp);
+                prop_assert!(speed <= 100);
+                prop_assert!(speed >= min);
+            }
+        }
+
+        #[test]
+        fn rising_curves_stay_rising(mut profile in curve()) {
+            let mut fans: Vec<u8> = profile.points.iter().map(|point| point.fan).collect();
+            fans.sort_unstable();
+            for (point, fan) in profile.points.iter_mut().zip(fans) {
+                point.fan = fan;
+            }
+            for temp in 0..u8::MAX {
+                prop_assert!(profile.fan_speed_at(temp) <= profile.fan_speed_at(temp + 1));
+            }
+        }
+
+        #[test]
+        fn evaluating_unchecked_curves_never_panics(
+            points in proptest::collection::vec(any::<(u8, u8, u8)>(), 0..16),
+        ) {
+            let profile = FanProfile {
+                points: points
+                    .into_iter()
+                    .map(|(temp, fan, power_limit)| point(temp, fan, power_limit))
+                    .collect(),
+                sticky: false,
+                prediction: None,
+                smoothing: None,
+            };
+            for temp in 0..=u8::MAX {
+                profile.fan_speed_at(temp);
+                profile.power_limit_at(temp);
+            }
+        }
+    }
 }
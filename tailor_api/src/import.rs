@@ -0,0 +1,564 @@
+//! Conversion of the configurations of other tools to tailord profiles.
+//!
+//! TUXEDO Control Center (TCC) stores its profiles as a JSON array in
+//! [`TCC_PROFILES_PATH`]. ite8291r3-ctl has no configuration file, its
+//! effects are usually set by commands in scripts or systemd units.
+//!
+//! Not everything has an equivalent in tailord, so every conversion
+//! comes with notes about what was approximated or left out.
+
+use serde::Deserialize;
+
+use crate::{Color, ColorPoint, ColorProfile, ColorTransition, FanProfile, Preset, ProfileInfo};
+
+/// Where TCC stores its profiles.
+pub const TCC_PROFILES_PATH: &str = "/etc/tcc/profiles";
+
+/// The result of a conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Imported<T> {
+    pub value: T,
+    /// Settings that couldn't be converted exactly.
+    pub notes: Vec<String>,
+}
+
+/// A profile of TCC, unknown settings are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TccProfile {
+    pub name: String,
+    pub display: TccDisplay,
+    pub cpu: TccCpu,
+    pub webcam: TccWebcam,
+    pub fan: TccFan,
+    pub odm_power_limits: TccPowerLimits,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TccDisplay {
+    /// Brightness in percent.
+    pub brightness: u8,
+    pub use_brightness: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TccCpu {
+    pub governor: Option<String>,
+    pub energy_performance_preference: Option<String>,
+    /// Use the `performance` governor instead of `governor`.
+    pub use_max_perf_gov: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TccWebcam {
+    pub use_status: bool,
+    pub status: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TccFan {
+    /// Whether TCC controls the fans at all.
+    pub use_control: bool,
+    /// `Silent`, `Quiet`, `Balanced`, `Cool`, `Freezy` or `Custom`.
+    pub fan_profile: String,
+    /// Lowest fan speed in percent.
+    pub minimum_fanspeed: u8,
+    /// Added to the speed of every point, in percent.
+    pub offset_fanspeed: i16,
+    pub custom_fan_curve: Option<TccFanCurve>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TccFanCurve {
+    #[serde(rename = "tableCPU")]
+    pub table_cpu: Vec<TccFanPoint>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct TccFanPoint {
+    pub temp: u8,
+    pub speed: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TccPowerLimits {
+    /// Power limits in watts, in the order of [`TCC_POWER_LIMITS`].
+    pub tdp_values: Vec<i32>,
+}
+
+/// Names of the power limits in `tdpValues`.
+const TCC_POWER_LIMITS: [&str; 3] = ["pl1", "pl2", "pl4"];
+
+/// Parse the content of [`TCC_PROFILES_PATH`].
+pub fn parse_tcc_profiles(json: &str) -> Result<Vec<TccProfile>, String> {
+    serde_json::from_str(json).map_err(|err| format!("Invalid TCC profiles: {err}"))
+}
+
+impl TccProfile {
+    /// The fan curve of the profile, `None` if TCC leaves the fans to the firmware.
+    ///
+    /// TCC's own curves are replaced with the closest preset of tailord,
+    /// custom curves are converted point by point.
+    pub fn fan_profile(&self) -> Imported<Option<FanProfile>> {
+        let mut notes = Vec::new();
+        if !self.fan.use_control {
+            notes.push("TCC leaves the fans to the firmware, the fan profiles are kept".to_owned());
+            return Imported { value: None, notes };
+        }
+
+        let custom_curve = self
+            .fan
+            .custom_fan_curve
+            .as_ref()
+            .filter(|curve| self.fan.fan_profile == "Custom" && !curve.table_cpu.is_empty());
+        let mut profile = match custom_curve {
+            Some(curve) => {
+                let mut points = curve.table_cpu.clone();
+                points.sort_by_key(|point| point.temp);
+                // Of several speeds at the same temperature, the last one wins.
+                points.reverse();
+                points.dedup_by_key(|point| point.temp);
+                points.reverse();
+                let builder = points.iter().fold(FanProfile::builder(), |builder, point| {
+                    builder.point(point.temp, point.speed.min(100))
+                });
+                match builder.build() {
+                    Ok(profile) => profile,
+                    Err(err) => {
+                        notes.push(format!(
+                            "Custom fan curve is invalid ({err}), using balanced"
+                        ));
+                        FanProfile::preset(Preset::Balanced)
+                    }
+                }
+            }
+            None => {
+                let (preset, exact) = match self.fan.fan_profile.as_str() {
+                    "Silent" => (Preset::Silent, true),
+                    "Balanced" => (Preset::Balanced, true),
+                    "Quiet" => (Preset::Silent, false),
+                    "Cool" | "Freezy" => (Preset::Performance, false),
+                    other => {
+                        notes.push(format!("Unknown fan profile `{other}`, using balanced"));
+                        (Preset::Balanced, true)
+                    }
+                };
+                if !exact {
+                    notes.push(format!(
+                        "Fan profile `{}` was replaced with the `{preset}` preset",
+                        self.fan.fan_profile
+                    ));
+                }
+                FanProfile::preset(preset)
+            }
+        };
+
+        let (minimum, offset) = (self.fan.minimum_fanspeed, self.fan.offset_fanspeed);
+        for point in &mut profile.points {
+            let fan = (i16::from(point.fan) + offset).clamp(0, 100) as u8;
+            point.fan = fan.max(minimum.min(100));
+        }
+        Imported {
+            value: Some(profile),
+            notes,
+        }
+    }
+
+    /// Apply the settings of the profile to `base`. Fans and LEDs are kept,
+    /// as they refer to the names of fan and LED profiles.
+    pub fn apply_to(&self, base: &ProfileInfo) -> Imported<ProfileInfo> {
+        let mut profile = base.clone();
+        let mut notes = Vec::new();
+
+        if self.display.use_brightness {
+            profile.display_brightness = Some(self.display.brightness.min(100));
+        }
+        if self.webcam.use_status {
+            profile.webcam = Some(self.webcam.status);
+        }
+        if self.cpu.use_max_perf_gov {
+            profile.cpu_governor = Some("performance".to_owned());
+        } else if let Some(governor) = &self.cpu.governor {
+            profile.cpu_governor = Some(governor.clone());
+        }
+        if let Some(preference) = &self.cpu.energy_performance_preference {
+            profile.energy_performance_preference = Some(preference.clone());
+        }
+
+        let values = &self.odm_power_limits.tdp_values;
+        if values.len() > TCC_POWER_LIMITS.len() {
+            notes.push(format!(
+                "Only the first {} of {} power limits were imported",
+                TCC_POWER_LIMITS.len(),
+                values.len()
+            ));
+        }
+        for (name, value) in TCC_POWER_LIMITS.iter().zip(values) {
+            profile.tdp.insert((*name).to_owned(), *value);
+        }
+        Imported {
+            value: profile,
+            notes,
+        }
+    }
+}
+
+/// Highest brightness of ite8291r3-ctl.
+const ITE_MAX_BRIGHTNESS: u8 = 50;
+/// Highest (slowest) speed of ite8291r3-ctl.
+const ITE_MAX_SPEED: u8 = 10;
+
+/// The state of the keyboard after running ite8291r3-ctl `commands`.
+///
+/// Every line that calls `ite8291r3-ctl`, possibly after a path, `sudo`
+/// or `ExecStart=`, is a command, later commands replace earlier effects.
+/// Effects that light up single keys are approximated with colors of the
+/// whole keyboard and the brightness is part of the colors.
+pub fn ite8291r3_ctl(commands: &str) -> Result<Imported<ColorProfile>, String> {
+    let mut keyboard = IteKeyboard {
+        effect: None,
+        brightness: ITE_MAX_BRIGHTNESS,
+        notes: Vec::new(),
+    };
+    for line in commands.lines() {
+        let mut words = line.split_whitespace();
+        let is_command = words
+            .by_ref()
+            .any(|word| word.rsplit(['/', '=']).next() == Some("ite8291r3-ctl"));
+        if is_command {
+            keyboard.run(&words.collect::<Vec<_>>())?;
+        }
+    }
+    let Some(effect) = keyboard.effect else {
+        return Err("No ite8291r3-ctl command sets a color or an effect".to_owned());
+    };
+    let scale = |color: &Color| {
+        let black = Color { r: 0, g: 0, b: 0 };
+        color.mix(
+            &black,
+            1.0 - f32::from(keyboard.brightness) / f32::from(ITE_MAX_BRIGHTNESS),
+        )
+    };
+    let value = match effect {
+        ColorProfile::Single(color) => ColorProfile::Single(scale(&color)),
+        ColorProfile::Multiple(points) => ColorProfile::Multiple(
+            points
+                .into_iter()
+                .map(|point| ColorPoint {
+                    color: scale(&point.color),
+                    ..point
+                })
+                .collect(),
+        ),
+        ColorProfile::None => ColorProfile::None,
+    };
+    Ok(Imported {
+        value,
+        notes: keyboard.notes,
+    })
+}
+
+struct IteKeyboard {
+    effect: Option<ColorProfile>,
+    brightness: u8,
+    notes: Vec<String>,
+}
+
+impl IteKeyboard {
+    fn run(&mut self, args: &[&str]) -> Result<(), String> {
+        let (command, args) = args
+            .split_first()
+            .ok_or("ite8291r3-ctl without a command")?;
+        if matches!(*command, "off" | "monocolor" | "effect") {
+            // The notes were about the effect that is replaced.
+            self.notes.clear();
+        }
+        match *command {
+            "off" => self.effect = Some(ColorProfile::None),
+            "brightness" => self.brightness = parse_brightness(args.first().copied())?,
+            "monocolor" => {
+                let mut color = None;
+                for (option, value) in options(args)? {
+                    match option {
+                        "--name" => color = Some(ite_color(value)?),
+                        "--rgb" => color = Some(parse_rgb(value)?),
+                        "-b" | "--brightness" => self.brightness = parse_brightness(Some(value))?,
+                        _ => self.ignore(option),
+                    }
+                }
+                let color = color.ok_or("monocolor needs `--name` or `--rgb`")?;
+                self.effect = Some(ColorProfile::Single(color));
+            }
+            "effect" => {
+                let (name, args) = args.split_first().ok_or("effect without a name")?;
+                let mut speed = ITE_MAX_SPEED / 2;
+                let mut color = None;
+                for (option, value) in options(args)? {
+                    match option {
+                        "-s" | "--speed" => {
+                            speed = value
+                                .parse::<u8>()
+                                .map_err(|_| format!("Invalid speed `{value}`"))?
+                                .min(ITE_MAX_SPEED)
+                        }
+                        "-b" | "--brightness" => self.brightness = parse_brightness(Some(value))?,
+                        "-c" | "--color" if value != "random" && value != "none" => {
+                            color = Some(ite_color(value)?)
+                        }
+                        "-c" | "--color" => {}
+                        _ => self.ignore(option),
+                    }
+                }
+                self.effect = Some(self.effect(name, speed, color));
+            }
+            // E.g. `query` or `test-pattern`, which don't change the effect.
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// One cycle takes 1s at speed 0 and 11s at the highest speed.
+    fn effect(&mut self, name: &str, speed: u8, color: Option<Color>) -> ColorProfile {
+        let duration = 1000 + u32::from(speed) * 1000;
+        let black = Color { r: 0, g: 0, b: 0 };
+        match (name, color) {
+            ("breathing", Some(color)) => {
+                ColorProfile::gradient(&[color, black], duration, ColorTransition::Linear)
+            }
+            ("breathing", None) => {
+                let palette: Vec<Color> = ITE_COLORS
+                    .iter()
+                    .flat_map(|(_, color)| [color.clone(), black.clone()])
+                    .collect();
+                let duration = duration * ITE_COLORS.len() as u32;
+                ColorProfile::gradient(&palette, duration, ColorTransition::Linear)
+            }
+            ("wave" | "rainbow", _) | (_, None) => {
+                if !matches!(name, "wave" | "rainbow") {
+                    self.notes.push(format!(
+                        "Effect `{name}` lights up single keys, showing a rainbow instead"
+                    ));
+                }
+                ColorProfile::rainbow(ITE_COLORS.len(), duration * 2)
+            }
+            (_, Some(color)) => {
+                self.notes.push(format!(
+                    "Effect `{name}` lights up single keys, showing its color instead"
+                ));
+                ColorProfile::Single(color)
+            }
+        }
+    }
+
+    fn ignore(&mut self, option: &str) {
+        if option != "--save" {
+            self.notes.push(format!(
+                "Option `{option}` has no equivalent and was ignored"
+            ));
+        }
+    }
+}
+
+/// The colors that ite8291r3-ctl knows by name.
+const ITE_COLORS: [(&str, Color); 7] = [
+    ("red", Color { r: 255, g: 0, b: 0 }),
+    (
+        "orange",
+        Color {
+            r: 255,
+            g: 128,
+            b: 0,
+        },
+    ),
+    (
+        "yellow",
+        Color {
+            r: 255,
+            g: 255,
+            b: 0,
+        },
+    ),
+    ("green", Color { r: 0, g: 255, b: 0 }),
+    ("blue", Color { r: 0, g: 0, b: 255 }),
+    (
+        "teal",
+        Color {
+            r: 0,
+            g: 255,
+            b: 255,
+        },
+    ),
+    (
+        "purple",
+        Color {
+            r: 255,
+            g: 0,
+            b: 255,
+        },
+    ),
+];
+
+fn ite_color(name: &str) -> Result<Color, String> {
+    if name == "white" {
+        return Ok(Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        });
+    }
+    ITE_COLORS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, color)| color.clone())
+        .ok_or_else(|| format!("Unknown color `{name}`"))
+}
+
+/// A color like `255,128,0`.
+fn parse_rgb(value: &str) -> Result<Color, String> {
+    let channels: Vec<u8> = value
+        .split(',')
+        .map(|channel| channel.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid color `{value}`"))?;
+    match channels[..] {
+        [r, g, b] => Ok(Color { r, g, b }),
+        _ => Err(format!("Invalid color `{value}`, expected `R,G,B`")),
+    }
+}
+
+fn parse_brightness(value: Option<&str>) -> Result<u8, String> {
+    let value = value.ok_or("brightness without a value")?;
+    value
+        .parse::<u8>()
+        .map(|brightness| brightness.min(ITE_MAX_BRIGHTNESS))
+        .map_err(|_| format!("Invalid brightness `{value}`"))
+}
+
+/// Pairs of options and their values, flags like `--save` have an empty value.
+fn options<'a>(args: &[&'a str]) -> Result<Vec<(&'a str, &'a str)>, String> {
+    let mut options = Vec::new();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            return Err(format!("Unexpected argument `{arg}`"));
+        }
+        if let Some((option, value)) = arg.split_once('=') {
+            options.push((option, value));
+        } else {
+            let value = args.next_if(|value| !value.starts_with('-'));
+            options.push((*arg, value.copied().unwrap_or_default()));
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ite8291r3_ctl, parse_tcc_profiles};
+    use crate::{Color, ColorProfile, ColorTransition, FanProfile, Preset, ProfileInfo};
+
+    const TCC_PROFILES: &str = r#"[
+        {
+            "id": "__default_custom_profile__",
+            "name": "Office",
+            "display": { "brightness": 60, "useBrightness": true, "refreshRate": -1 },
+            "cpu": { "useMaxPerfGov": false, "governor": "powersave",
+                     "energyPerformancePreference": "balance_power", "noTurbo": false },
+            "webcam": { "useStatus": true, "status": false },
+            "fan": { "useControl": true, "fanProfile": "Quiet",
+                     "minimumFanspeed": 20, "offsetFanspeed": 0 },
+            "odmPowerLimits": { "tdpValues": [25, 35, 60] }
+        },
+        {
+            "name": "Custom",
+            "fan": { "useControl": true, "fanProfile": "Custom", "offsetFanspeed": -5,
+                     "customFanCurve": { "tableCPU": [
+                         { "temp": 70, "speed": 60 }, { "temp": 40, "speed": 20 },
+                         { "temp": 70, "speed": 70 }, { "temp": 90, "speed": 120 }
+                     ] } }
+        }
+    ]"#;
+
+    #[test]
+    fn tcc() {
+        let profiles = parse_tcc_profiles(TCC_PROFILES).unwrap();
+        assert_eq!(profiles.len(), 2);
+
+        let office = &profiles[0];
+        let fan = office.fan_profile();
+        let curve = fan.value.unwrap();
+        let silent = FanProfile::preset(Preset::Silent);
+        assert_eq!(curve.fan_speed_at(0), 20);
+        assert_eq!(curve.fan_speed_at(90), silent.fan_speed_at(90));
+        assert_eq!(fan.notes.len(), 1);
+
+        let profile = office.apply_to(&ProfileInfo::default()).value;
+        assert_eq!(profile.display_brightness, Some(60));
+        assert_eq!(profile.webcam, Some(false));
+        assert_eq!(profile.cpu_governor.as_deref(), Some("powersave"));
+        assert_eq!(
+            profile.energy_performance_preference.as_deref(),
+            Some("balance_power")
+        );
+        assert_eq!(profile.tdp["pl4"], 60);
+        assert_eq!(profile.fans, ProfileInfo::default().fans);
+
+        let custom = profiles[1].fan_profile();
+        let points: Vec<_> = custom
+            .value
+            .unwrap()
+            .points
+            .iter()
+            .map(|point| (point.temp, point.fan))
+            .collect();
+        assert_eq!(points, [(40, 15), (70, 65), (90, 95)]);
+        assert!(custom.notes.is_empty());
+
+        assert!(parse_tcc_profiles("{}").is_err());
+    }
+
+    #[test]
+    fn ite8291r3() {
+        let purple = Color {
+            r: 128,
+            g: 0,
+            b: 128,
+        };
+        let imported = ite8291r3_ctl(
+            "[Service]\n\
+             ExecStart=/usr/bin/ite8291r3-ctl effect wave -s 3 -d left\n\
+             ExecStart=/usr/bin/ite8291r3-ctl monocolor --rgb 255,0,255 --brightness 25\n",
+        )
+        .unwrap();
+        assert_eq!(imported.value, ColorProfile::Single(purple));
+        assert!(imported.notes.is_empty());
+
+        let breathing = ite8291r3_ctl("sudo ite8291r3-ctl effect breathing -c red -s 0").unwrap();
+        let black = Color { r: 0, g: 0, b: 0 };
+        assert_eq!(
+            breathing.value,
+            ColorProfile::gradient(
+                &[Color { r: 255, g: 0, b: 0 }, black],
+                1000,
+                ColorTransition::Linear
+            )
+        );
+
+        let ripple = ite8291r3_ctl("ite8291r3-ctl effect ripple --reactive --save").unwrap();
+        assert_eq!(ripple.value, ColorProfile::rainbow(7, 12_000));
+        assert_eq!(ripple.notes.len(), 2);
+
+        assert_eq!(
+            ite8291r3_ctl("ite8291r3-ctl off").unwrap().value,
+            ColorProfile::None
+        );
+        assert!(ite8291r3_ctl("echo hello").is_err());
+        assert!(ite8291r3_ctl("ite8291r3-ctl monocolor --name magenta").is_err());
+    }
+}
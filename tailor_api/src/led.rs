@@ -2,17 +2,65 @@
     Default, Debug, Copy, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash,
 )]
 #[non_exhaustive]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum LedControllerMode {
     #[default]
     Rgb,
     Monochrome,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// How an LED alert flashes its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertPattern {
+    /// The color stays on for the whole alert.
+    Solid,
+    /// On and off twice per second.
+    Blink,
+    /// Fades in and out once per second.
+    Pulse,
+}
+
+impl AlertPattern {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Solid => "solid",
+            Self::Blink => "blink",
+            Self::Pulse => "pulse",
+        }
+    }
+}
+
+impl std::str::FromStr for AlertPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solid" => Ok(Self::Solid),
+            "blink" => Ok(Self::Blink),
+            "pulse" => Ok(Self::Pulse),
+            _ => Err(format!("Unknown alert pattern `{s}`")),
+        }
+    }
+}
+
+impl std::fmt::Display for AlertPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An LED device, identified by its name, function and mode.
+///
+/// The capabilities aren't part of the identity, so devices from profiles,
+/// which don't store them, are equal to the detected devices.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LedDeviceInfo {
     pub device_name: String,
     pub function: String,
     pub mode: LedControllerMode,
+    #[serde(default)]
+    pub capabilities: LedCapabilities,
 }
 
 impl LedDeviceInfo {
@@ -20,8 +68,70 @@ impl LedDeviceInfo {
         let Self {
             device_name,
             function,
-            mode: _mode,
+            ..
         } = self;
         format!("{device_name}::{function}")
     }
+
+    fn identity(&self) -> (&str, &str, LedControllerMode) {
+        (&self.device_name, &self.function, self.mode)
+    }
+}
+
+impl PartialEq for LedDeviceInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for LedDeviceInfo {}
+
+impl std::hash::Hash for LedDeviceInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.identity().hash(state);
+    }
+}
+
+/// What the hardware of an LED device supports, as far as the driver tells.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LedCapabilities {
+    /// Firmware revision of USB keyboard controllers.
+    pub firmware_version: Option<String>,
+    /// Number of zones or keys that the device lights. They always share
+    /// one color, devices of multi-zone keyboards usually light one zone each.
+    pub zones: u32,
+    /// The brightness value of the driver that corresponds to full brightness.
+    pub max_brightness: u32,
+    /// The modes that profiles for this device can use.
+    pub supported_modes: Vec<LedControllerMode>,
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{LedCapabilities, LedControllerMode, LedDeviceInfo};
+
+    #[test]
+    fn identity() {
+        // Written by older versions and stored in profiles.
+        let stored: LedDeviceInfo = serde_json::from_str(
+            r#"{"device_name":"ite_8291","function":"kbd_backlight","mode":"Rgb"}"#,
+        )
+        .unwrap();
+        let detected = LedDeviceInfo {
+            capabilities: LedCapabilities {
+                firmware_version: Some("0003".to_owned()),
+                zones: 1,
+                max_brightness: 50,
+                supported_modes: vec![LedControllerMode::Rgb, LedControllerMode::Monochrome],
+            },
+            ..stored.clone()
+        };
+
+        assert_eq!(stored.capabilities, LedCapabilities::default());
+        assert_eq!(stored, detected);
+        assert!(HashSet::from([stored]).contains(&detected));
+    }
 }
@@ -1,9 +1,31 @@
+mod builder;
+mod charging;
 mod color;
+mod device;
 mod fan;
+pub mod import;
 mod led;
+pub mod metrics;
+mod power;
 mod profile;
+mod self_test;
+mod subsystem;
+mod telemetry;
+pub mod tuning;
+pub mod wire;
 
-pub use color::{Color, ColorPoint, ColorProfile, ColorTransition};
-pub use fan::{FanProfile, FanProfilePoint};
-pub use led::{LedControllerMode, LedDeviceInfo};
-pub use profile::{LedProfile, ProfileInfo};
+pub use builder::{ColorProfileBuilder, FanProfileBuilder, ProfileError};
+pub use charging::{ChargingCapabilities, ChargingSettings};
+pub use color::{Color, ColorPoint, ColorProfile, ColorTransition, Hsl, Hsv};
+pub use device::{DeviceList, FanDeviceInfo, SensorInfo};
+pub use fan::{
+    FanPrediction, FanProfile, FanProfilePoint, FanSmoothing, FanStatus, NoiseLimit, Preset,
+};
+pub use led::{AlertPattern, LedCapabilities, LedControllerMode, LedDeviceInfo};
+pub use power::{PowerLimit, PowerSource, PowerSourceProfiles};
+pub use profile::{
+    LedProfile, LidClosedBehavior, LidClosedFans, ProfileInfo, ProfileSwitch, ProfileTrigger,
+};
+pub use self_test::{SelfTestCheck, SelfTestResult};
+pub use subsystem::{Subsystem, SubsystemStates};
+pub use telemetry::{TelemetryFormat, TelemetrySample};
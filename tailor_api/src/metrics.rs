@@ -0,0 +1,212 @@
+//! The Prometheus text exposition format, shared by the `/metrics`
+//! endpoint of tailord and `tailor metrics`, so dashboards work with both.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{FanStatus, PowerLimit, PowerSource};
+
+/// Name, description and value of a per-fan gauge.
+type FanGauge = (&'static str, &'static str, fn(&FanStatus) -> Option<u8>);
+
+const FAN_GAUGES: [FanGauge; 4] = [
+    (
+        "tailor_fan_temperature_celsius",
+        "Temperature of the sensor that controls the fan.",
+        |status| Some(status.temperature),
+    ),
+    ("tailor_fan_speed_percent", "Current fan speed.", |status| {
+        Some(status.speed)
+    }),
+    (
+        "tailor_fan_target_speed_percent",
+        "Fan speed the fan is approaching, absent while the firmware controls it.",
+        |status| status.target,
+    ),
+    (
+        "tailor_fan_power_limit_percent",
+        "Injected CPU idle time.",
+        |status| Some(status.power_limit),
+    ),
+];
+
+/// Events counted by tailord since it started.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Profile activations by trigger.
+    pub profile_switches: BTreeMap<&'static str, u64>,
+    /// Failed temperature reads by fan index.
+    pub sensor_errors: BTreeMap<u8, u64>,
+    /// Failed fan speed writes by fan index.
+    pub fan_errors: BTreeMap<u8, u64>,
+}
+
+/// Everything that is exported, metrics that are `None`
+/// or empty are left out.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    /// Label and status of each fan.
+    pub fans: Vec<(String, FanStatus)>,
+    pub active_profile: String,
+    pub power_limits: Vec<PowerLimit>,
+    pub power_source: Option<PowerSource>,
+    /// Charge of the battery in percent.
+    pub battery: Option<u8>,
+    /// Only known inside of tailord.
+    pub counters: Option<Counters>,
+}
+
+impl Metrics {
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        for (name, help, value) in FAN_GAUGES {
+            header(&mut out, name, help, "gauge");
+            for (label, status) in &self.fans {
+                if let Some(value) = value(status) {
+                    writeln!(
+                        out,
+                        "{name}{{fan=\"{}\",label=\"{}\"}} {value}",
+                        status.index,
+                        escape(label)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        header(
+            &mut out,
+            "tailor_active_profile_info",
+            "The currently active profile.",
+            "gauge",
+        );
+        writeln!(
+            out,
+            "tailor_active_profile_info{{profile=\"{}\"}} 1",
+            escape(&self.active_profile)
+        )
+        .unwrap();
+
+        if !self.power_limits.is_empty() {
+            header(
+                &mut out,
+                "tailor_power_limit_watts",
+                "Configured power limit (TDP) of the platform.",
+                "gauge",
+            );
+            for limit in &self.power_limits {
+                writeln!(
+                    out,
+                    "tailor_power_limit_watts{{name=\"{}\"}} {}",
+                    escape(&limit.name),
+                    limit.value
+                )
+                .unwrap();
+            }
+        }
+
+        if let Some(source) = self.power_source {
+            header(
+                &mut out,
+                "tailor_on_ac_power",
+                "Whether the power adapter is plugged in.",
+                "gauge",
+            );
+            writeln!(
+                out,
+                "tailor_on_ac_power {}",
+                u8::from(source == PowerSource::Ac)
+            )
+            .unwrap();
+        }
+        if let Some(battery) = self.battery {
+            header(
+                &mut out,
+                "tailor_battery_percent",
+                "Charge of the battery.",
+                "gauge",
+            );
+            writeln!(out, "tailor_battery_percent {battery}").unwrap();
+        }
+
+        if let Some(counters) = &self.counters {
+            header(
+                &mut out,
+                "tailor_profile_switches_total",
+                "Profile activations since tailord started.",
+                "counter",
+            );
+            for (trigger, count) in &counters.profile_switches {
+                writeln!(
+                    out,
+                    "tailor_profile_switches_total{{trigger=\"{trigger}\"}} {count}"
+                )
+                .unwrap();
+            }
+            header(
+                &mut out,
+                "tailor_sensor_errors_total",
+                "Failed reads of the temperature sensor of a fan.",
+                "counter",
+            );
+            for (fan, count) in &counters.sensor_errors {
+                writeln!(out, "tailor_sensor_errors_total{{fan=\"{fan}\"}} {count}").unwrap();
+            }
+            header(
+                &mut out,
+                "tailor_fan_errors_total",
+                "Failed attempts to set the fan speed.",
+                "counter",
+            );
+            for (fan, count) in &counters.fan_errors {
+                writeln!(out, "tailor_fan_errors_total{{fan=\"{fan}\"}} {count}").unwrap();
+            }
+        }
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n"));
+}
+
+/// Escape a label value as required by the text exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{FanStatus, PowerSource};
+
+    use super::Metrics;
+
+    #[test]
+    fn render() {
+        let metrics = Metrics {
+            fans: vec![(
+                "CPU".to_owned(),
+                FanStatus {
+                    index: 0,
+                    temperature: 60,
+                    speed: 40,
+                    target: None,
+                    power_limit: 0,
+                },
+            )],
+            active_profile: "say \"hi\"".to_owned(),
+            power_source: Some(PowerSource::Ac),
+            ..Default::default()
+        };
+        let out = metrics.render();
+        assert!(out.contains("tailor_fan_temperature_celsius{fan=\"0\",label=\"CPU\"} 60\n"));
+        // Fans controlled by the firmware have no target.
+        assert!(!out.contains("tailor_fan_target_speed_percent{"));
+        assert!(out.contains("tailor_active_profile_info{profile=\"say \\\"hi\\\"\"} 1\n"));
+        assert!(out.contains("tailor_on_ac_power 1\n"));
+        assert!(!out.contains("tailor_profile_switches_total"));
+    }
+}
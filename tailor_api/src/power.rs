@@ -0,0 +1,70 @@
+/// Where the device currently draws its power from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    /// A power adapter is connected.
+    Ac,
+    /// The device runs on battery.
+    Battery,
+}
+
+impl PowerSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ac => "ac",
+            Self::Battery => "battery",
+        }
+    }
+}
+
+impl std::str::FromStr for PowerSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ac" => Ok(Self::Ac),
+            "battery" => Ok(Self::Battery),
+            _ => Err(format!("Unknown power source `{s}`")),
+        }
+    }
+}
+
+impl std::fmt::Display for PowerSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Profiles that tailord activates when the power source changes.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PowerSourceProfiles {
+    pub ac: Option<String>,
+    pub battery: Option<String>,
+}
+
+impl PowerSourceProfiles {
+    pub fn get(&self, source: PowerSource) -> Option<&str> {
+        match source {
+            PowerSource::Ac => self.ac.as_deref(),
+            PowerSource::Battery => self.battery.as_deref(),
+        }
+    }
+
+    pub fn set(&mut self, source: PowerSource, profile: Option<String>) {
+        match source {
+            PowerSource::Ac => self.ac = profile,
+            PowerSource::Battery => self.battery = profile,
+        }
+    }
+}
+
+/// A power limit (TDP) of the platform in watts.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PowerLimit {
+    /// Name of the limit, e.g. `pl1`.
+    pub name: String,
+    pub min: i32,
+    pub max: i32,
+    pub value: i32,
+}
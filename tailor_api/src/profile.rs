@@ -1,10 +1,47 @@
+use std::collections::BTreeMap;
+
 use crate::LedControllerMode;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ProfileInfo {
     pub fans: Vec<String>,
+    /// LED profiles of all devices. Besides the list, a map from device ids
+    /// (`<device_name>::<function>`) to profile names is accepted.
+    #[serde(deserialize_with = "deserialize_leds")]
+    #[cfg_attr(feature = "schemars", schemars(with = "LedProfiles"))]
     pub leds: Vec<LedProfile>,
     pub performance_profile: Option<String>,
+    /// Switch the webcam on or off, `None` leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webcam: Option<bool>,
+    /// Switch the Fn-lock on or off, `None` leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fn_lock: Option<bool>,
+    /// Switch the touchpad on or off, `None` leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub touchpad: Option<bool>,
+    /// Power limits in watts by name (e.g. `pl1`), limits that
+    /// aren't listed are left unchanged.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tdp: BTreeMap<String, i32>,
+    /// cpufreq governor of all CPUs (e.g. `powersave`), `None` leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_governor: Option<String>,
+    /// Energy performance preference of all CPUs (e.g. `balance_power`),
+    /// `None` leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy_performance_preference: Option<String>,
+    /// Brightness of the display backlight in percent, `None` leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_brightness: Option<u8>,
+    /// What to change while the lid is closed.
+    #[serde(default, skip_serializing_if = "LidClosedBehavior::is_unchanged")]
+    pub lid_closed: LidClosedBehavior,
+    /// Leave the power limits to other tools while the profile is active,
+    /// neither `tdp` nor the power limits of the fan profiles are applied.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disable_power_limits: bool,
 }
 
 impl Default for ProfileInfo {
@@ -13,11 +50,71 @@ impl Default for ProfileInfo {
             fans: vec!["default".to_owned()],
             leds: Default::default(),
             performance_profile: Default::default(),
+            webcam: Default::default(),
+            fn_lock: Default::default(),
+            touchpad: Default::default(),
+            tdp: Default::default(),
+            cpu_governor: Default::default(),
+            energy_performance_preference: Default::default(),
+            display_brightness: Default::default(),
+            lid_closed: Default::default(),
+            disable_power_limits: false,
         }
     }
 }
 
+impl ProfileInfo {
+    /// The LED profile of a device, if the profile sets one.
+    pub fn led_profile(&self, device_name: &str, function: &str) -> Option<&LedProfile> {
+        self.leds
+            .iter()
+            .find(|led| led.device_name == device_name && led.function == function)
+    }
+
+    /// Use `led` for its device, replacing the previous LED profile of the device.
+    pub fn set_led_profile(&mut self, led: LedProfile) {
+        match self
+            .leds
+            .iter_mut()
+            .find(|old| old.device_name == led.device_name && old.function == led.function)
+        {
+            Some(old) => *old = led,
+            None => self.leds.push(led),
+        }
+    }
+}
+
+/// Changes that only apply while the lid is closed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct LidClosedBehavior {
+    /// How to control the fans, `None` keeps the fan profiles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fans: Option<LidClosedFans>,
+    /// Switch off all LEDs.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub leds_off: bool,
+}
+
+impl LidClosedBehavior {
+    pub fn is_unchanged(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LidClosedFans {
+    /// Hand the fans back to the firmware.
+    Auto,
+    /// Use this fan profile for all fans, e.g. a quiet curve.
+    Profile(String),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LedProfile {
     pub device_name: String,
     pub function: String,
@@ -25,3 +122,165 @@ pub struct LedProfile {
     #[serde(default)]
     pub mode: LedControllerMode,
 }
+
+impl LedProfile {
+    /// The id of the device, as in [`crate::LedDeviceInfo::device_id`].
+    pub fn device_id(&self) -> String {
+        format!("{}::{}", self.device_name, self.function)
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum LedProfiles {
+    List(Vec<LedProfile>),
+    /// Profiles by device id, e.g. `{ "tuxedo_keyboard::kbd_backlight": "rainbow" }`.
+    Map(BTreeMap<String, MappedLedProfile>),
+}
+
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum MappedLedProfile {
+    Name(String),
+    WithMode {
+        profile: String,
+        #[serde(default)]
+        mode: LedControllerMode,
+    },
+}
+
+fn deserialize_leds<'de, D>(deserializer: D) -> Result<Vec<LedProfile>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let map = match serde::Deserialize::deserialize(deserializer)? {
+        LedProfiles::List(leds) => return Ok(leds),
+        LedProfiles::Map(map) => map,
+    };
+    map.into_iter()
+        .map(|(device_id, entry)| {
+            let (device_name, function) = device_id.rsplit_once("::").ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "LED device id `{device_id}` isn't `<device_name>::<function>`"
+                ))
+            })?;
+            let (profile, mode) = match entry {
+                MappedLedProfile::Name(profile) => (profile, LedControllerMode::default()),
+                MappedLedProfile::WithMode { profile, mode } => (profile, mode),
+            };
+            Ok(LedProfile {
+                device_name: device_name.to_owned(),
+                function: function.to_owned(),
+                profile,
+                mode,
+            })
+        })
+        .collect()
+}
+
+/// The reason why tailord activated a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileTrigger {
+    /// The profile was loaded when tailord started.
+    Startup,
+    /// A client requested the profile.
+    User,
+    /// The power source (AC or battery) changed.
+    PowerSource,
+    /// The power mode of power-profiles-daemon changed.
+    PowerProfile,
+    /// The configuration was reloaded, e.g. after `SIGHUP`.
+    Reload,
+}
+
+impl ProfileTrigger {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Startup => "startup",
+            Self::User => "user",
+            Self::PowerSource => "power_source",
+            Self::PowerProfile => "power_profile",
+            Self::Reload => "reload",
+        }
+    }
+}
+
+impl std::fmt::Display for ProfileTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An entry of the profile switch history.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProfileSwitch {
+    /// Time of the activation in seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub profile: String,
+    pub trigger: ProfileTrigger,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{LedControllerMode, LedProfile, ProfileInfo};
+
+    #[test]
+    fn led_map() {
+        let list: ProfileInfo = serde_json::from_str(
+            r#"{
+                "fans": ["default"],
+                "leds": [{
+                    "device_name": "tuxedo_keyboard",
+                    "function": "kbd_backlight",
+                    "profile": "rainbow"
+                }],
+                "performance_profile": null
+            }"#,
+        )
+        .unwrap();
+        let mut map: ProfileInfo = serde_json::from_str(
+            r#"{
+                "fans": ["default"],
+                "leds": {
+                    "tuxedo_keyboard::kbd_backlight": "rainbow",
+                    "lightbar::lightbar": { "profile": "red", "mode": "Monochrome" }
+                },
+                "performance_profile": null
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            list.led_profile("tuxedo_keyboard", "kbd_backlight"),
+            map.led_profile("tuxedo_keyboard", "kbd_backlight")
+        );
+        let lightbar = map.led_profile("lightbar", "lightbar").unwrap();
+        assert_eq!(lightbar.profile, "red");
+        assert_eq!(lightbar.mode, LedControllerMode::Monochrome);
+        assert_eq!(lightbar.device_id(), "lightbar::lightbar");
+
+        // Profiles are still written as a list.
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(serde_json::from_str::<ProfileInfo>(&json).unwrap(), map);
+        assert!(json.contains(r#""leds":["#));
+
+        map.set_led_profile(LedProfile {
+            device_name: "lightbar".to_owned(),
+            function: "lightbar".to_owned(),
+            profile: "off".to_owned(),
+            mode: LedControllerMode::Monochrome,
+        });
+        assert_eq!(map.leds.len(), 2);
+        assert_eq!(
+            map.led_profile("lightbar", "lightbar").unwrap().profile,
+            "off"
+        );
+
+        let invalid =
+            r#"{ "fans": [], "leds": { "keyboard": "rainbow" }, "performance_profile": null }"#;
+        assert!(serde_json::from_str::<ProfileInfo>(invalid).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+/// The result of probing one capability of the hardware when tailord started.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SelfTestCheck {
+    /// For example `fan0_read`, `fan0_write`, `led_white:kbd_backlight`,
+    /// `powerclamp`, `cooling:cooling_device3` or `charging`.
+    pub capability: String,
+    pub result: SelfTestResult,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestResult {
+    Passed,
+    /// The capability exists, but doesn't work. tailord continues without it.
+    Failed(String),
+    /// The hardware doesn't offer the capability.
+    Unavailable,
+    /// The check would have changed the hardware, e.g. writing
+    /// the fans while fan control is disabled.
+    Skipped,
+}
+
+impl SelfTestCheck {
+    pub fn failed(&self) -> bool {
+        matches!(self.result, SelfTestResult::Failed(_))
+    }
+}
@@ -0,0 +1,104 @@
+use std::{fmt::Display, io, str::FromStr};
+
+/// A part of tailord that can be switched off at runtime
+/// to hand its function back to the firmware or other tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    FanControl,
+    LedControl,
+    PowerLimitControl,
+}
+
+impl Subsystem {
+    pub const ALL: [Self; 3] = [Self::FanControl, Self::LedControl, Self::PowerLimitControl];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FanControl => "fan_control",
+            Self::LedControl => "led_control",
+            Self::PowerLimitControl => "power_limit_control",
+        }
+    }
+}
+
+impl Display for Subsystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Subsystem {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|subsystem| subsystem.name() == s)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown subsystem `{s}`"),
+                )
+            })
+    }
+}
+
+/// Stores which subsystems are enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SubsystemStates {
+    pub fan_control: bool,
+    pub led_control: bool,
+    pub power_limit_control: bool,
+}
+
+impl Default for SubsystemStates {
+    fn default() -> Self {
+        Self {
+            fan_control: true,
+            led_control: true,
+            power_limit_control: true,
+        }
+    }
+}
+
+impl SubsystemStates {
+    pub fn is_enabled(&self, subsystem: Subsystem) -> bool {
+        match subsystem {
+            Subsystem::FanControl => self.fan_control,
+            Subsystem::LedControl => self.led_control,
+            Subsystem::PowerLimitControl => self.power_limit_control,
+        }
+    }
+
+    pub fn set_enabled(&mut self, subsystem: Subsystem, enabled: bool) {
+        match subsystem {
+            Subsystem::FanControl => self.fan_control = enabled,
+            Subsystem::LedControl => self.led_control = enabled,
+            Subsystem::PowerLimitControl => self.power_limit_control = enabled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{Subsystem, SubsystemStates};
+
+    #[test]
+    fn subsystem_names() {
+        for subsystem in Subsystem::ALL {
+            assert_eq!(
+                Subsystem::from_str(&subsystem.to_string()).unwrap(),
+                subsystem
+            );
+        }
+        Subsystem::from_str("fans").unwrap_err();
+
+        let states: SubsystemStates = serde_json::from_str(r#"{"led_control":false}"#).unwrap();
+        assert!(states.is_enabled(Subsystem::FanControl));
+        assert!(!states.is_enabled(Subsystem::LedControl));
+    }
+}
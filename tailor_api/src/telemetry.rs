@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use crate::FanStatus;
+
+/// File formats for recorded fan telemetry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryFormat {
+    /// Comma-separated values with a header line, e.g. for spreadsheets.
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+impl TelemetryFormat {
+    /// JSON lines for `.jsonl` and `.json` files, CSV otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl" | "json") => Self::Jsonl,
+            _ => Self::Csv,
+        }
+    }
+
+    /// The first line of a new file, if the format has one.
+    pub fn header(self) -> Option<&'static str> {
+        match self {
+            Self::Csv => Some("timestamp,fan,temperature,speed,target,power_limit"),
+            Self::Jsonl => None,
+        }
+    }
+
+    /// Format one sample as a line without the line break.
+    pub fn format(self, sample: &TelemetrySample) -> String {
+        match self {
+            Self::Csv => {
+                let FanStatus {
+                    index,
+                    temperature,
+                    speed,
+                    target,
+                    power_limit,
+                } = sample.status;
+                // Leave the target empty while the firmware controls the fan.
+                let target = target.map(|target| target.to_string()).unwrap_or_default();
+                format!(
+                    "{:.3},{index},{temperature},{speed},{target},{power_limit}",
+                    sample.timestamp
+                )
+            }
+            Self::Jsonl => serde_json::to_string(sample).unwrap(),
+        }
+    }
+
+    /// Parse a line written by [`Self::format`].
+    /// Headers and empty lines are skipped with `None`.
+    pub fn parse(self, line: &str) -> Result<Option<TelemetrySample>, String> {
+        let line = line.trim();
+        if line.is_empty() || Some(line) == self.header() {
+            return Ok(None);
+        }
+        let sample = match self {
+            Self::Csv => parse_csv(line),
+            Self::Jsonl => serde_json::from_str(line).map_err(|err| err.to_string()),
+        };
+        sample
+            .map(Some)
+            .map_err(|err| format!("Invalid sample `{line}`: {err}"))
+    }
+}
+
+fn parse_csv(line: &str) -> Result<TelemetrySample, String> {
+    let fields: Vec<&str> = line.split(',').collect();
+    let [timestamp, index, temperature, speed, target, power_limit] = fields[..] else {
+        return Err(format!("expected 6 fields, got {}", fields.len()));
+    };
+    let number = |field: &str| field.parse::<u8>().map_err(|err| err.to_string());
+    Ok(TelemetrySample {
+        timestamp: timestamp.parse().map_err(|err| format!("{err}"))?,
+        status: FanStatus {
+            index: number(index)?,
+            temperature: number(temperature)?,
+            speed: number(speed)?,
+            target: match target {
+                "" => None,
+                target => Some(number(target)?),
+            },
+            power_limit: number(power_limit)?,
+        },
+    })
+}
+
+/// The state of a fan at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TelemetrySample {
+    /// Seconds since the Unix epoch.
+    pub timestamp: f64,
+    #[serde(flatten)]
+    pub status: FanStatus,
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{TelemetryFormat, TelemetrySample};
+    use crate::FanStatus;
+
+    #[test]
+    fn formats() {
+        let sample = TelemetrySample {
+            timestamp: 1700000000.25,
+            status: FanStatus {
+                index: 1,
+                temperature: 62,
+                speed: 40,
+                target: None,
+                power_limit: 0,
+            },
+        };
+
+        assert_eq!(
+            TelemetryFormat::Csv.format(&sample),
+            "1700000000.250,1,62,40,,0"
+        );
+        assert_eq!(
+            TelemetryFormat::Jsonl.format(&sample),
+            r#"{"timestamp":1700000000.25,"index":1,"temperature":62,"speed":40,"target":null,"power_limit":0}"#
+        );
+        for format in [TelemetryFormat::Csv, TelemetryFormat::Jsonl] {
+            assert_eq!(format.parse(&format.format(&sample)), Ok(Some(sample)));
+        }
+        assert_eq!(
+            TelemetryFormat::Csv.parse(TelemetryFormat::Csv.header().unwrap()),
+            Ok(None)
+        );
+        assert!(TelemetryFormat::Csv.parse("1700000000.250,1,62").is_err());
+
+        assert_eq!(
+            TelemetryFormat::from_path(Path::new("fans.jsonl")),
+            TelemetryFormat::Jsonl
+        );
+        assert_eq!(
+            TelemetryFormat::from_path(Path::new("fans.csv")),
+            TelemetryFormat::Csv
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Offline tuning of fan curves from recorded telemetry.
+//!
+//! The tuner looks at how hot the fans let the hardware get while
+//! the curve was in control and proposes changes to single points:
+//! raising the curve ahead of temperature peaks and lowering it
+//! if the temperature stayed far away from the goal.
+
+use std::collections::BTreeMap;
+
+use crate::{FanProfile, FanProfilePoint, TelemetrySample};
+
+/// What the tuned curve should achieve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TuningGoal {
+    /// Temperatures in °C above this are peaks that the curve should prevent.
+    pub max_temp: u8,
+    /// Seconds before a peak in which the fans should already run faster.
+    pub lead_seconds: u32,
+    /// Change of a point in percent.
+    pub step: u8,
+    /// The curve is lowered if all temperatures stayed this many °C below `max_temp`.
+    pub headroom: u8,
+}
+
+impl Default for TuningGoal {
+    fn default() -> Self {
+        Self {
+            max_temp: 90,
+            lead_seconds: 30,
+            step: 10,
+            headroom: 15,
+        }
+    }
+}
+
+/// A proposed change of the fan speed at one point of the curve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adjustment {
+    pub temp: u8,
+    /// Speed before the change, interpolated for new points.
+    pub from: u8,
+    pub to: u8,
+    /// Whether the point is added to the curve.
+    pub added: bool,
+    /// Why the point is changed.
+    pub reason: String,
+}
+
+impl std::fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = match (self.added, self.to > self.from) {
+            (true, _) => "add",
+            (false, true) => "raise",
+            (false, false) => "lower",
+        };
+        write!(
+            f,
+            "{verb} point at {}°C from {}% to {}% {}",
+            self.temp, self.from, self.to, self.reason
+        )
+    }
+}
+
+/// A candidate curve and the changes that lead to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tuning {
+    pub profile: FanProfile,
+    pub adjustments: Vec<Adjustment>,
+}
+
+/// Propose a curve that avoids the peaks in `samples`, or is quieter
+/// if there were none. Samples of fans that weren't controlled by
+/// the curve (e.g. while the firmware had them) are ignored.
+pub fn tune(profile: &FanProfile, samples: &[TelemetrySample], goal: &TuningGoal) -> Tuning {
+    let mut fans: BTreeMap<u8, Vec<&TelemetrySample>> = BTreeMap::new();
+    for sample in samples
+        .iter()
+        .filter(|sample| sample.status.target.is_some())
+    {
+        fans.entry(sample.status.index).or_default().push(sample);
+    }
+    for fan in fans.values_mut() {
+        fan.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+    }
+
+    let mut tuning = Tuning {
+        profile: profile.clone(),
+        adjustments: Vec::new(),
+    };
+    let hottest = fans
+        .values()
+        .flatten()
+        .map(|sample| sample.status.temperature)
+        .max();
+    let Some(hottest) = hottest else {
+        return tuning;
+    };
+
+    if hottest > goal.max_temp {
+        // The temperature at which the fans should have started to speed up.
+        let lead_temp = fans
+            .values()
+            .filter_map(|fan| lead_temp(fan, goal))
+            .min()
+            .unwrap_or(goal.max_temp);
+        let reason = format!("to avoid the {hottest}°C peaks");
+        tuning.raise_from(lead_temp, goal.step, &reason);
+    } else if hottest < goal.max_temp.saturating_sub(goal.headroom) {
+        let reason = format!("as the temperature stayed at or below {hottest}°C");
+        tuning.lower_until(hottest, goal.step, &reason);
+    }
+    tuning
+}
+
+/// The lowest temperature within `lead_seconds` before any peak of a fan.
+fn lead_temp(fan: &[&TelemetrySample], goal: &TuningGoal) -> Option<u8> {
+    fan.iter()
+        .enumerate()
+        .filter(|(_, sample)| sample.status.temperature > goal.max_temp)
+        .filter_map(|(idx, peak)| {
+            let start = peak.timestamp - f64::from(goal.lead_seconds);
+            fan[..idx]
+                .iter()
+                .filter(|sample| sample.timestamp >= start)
+                .map(|sample| sample.status.temperature)
+                .min()
+        })
+        .min()
+}
+
+impl Tuning {
+    /// Raise all points from `temp` on, adding a point at `temp` if there's none
+    /// within a step of it, so the curve gets steeper where it matters.
+    fn raise_from(&mut self, temp: u8, step: u8, reason: &str) {
+        let points = &self.profile.points;
+        let has_nearby_point = points
+            .iter()
+            .any(|point| point.temp >= temp && point.temp < temp.saturating_add(step));
+        if !has_nearby_point && points.iter().any(|point| point.temp > temp) {
+            let from = self.profile.fan_speed_at(temp);
+            let to = from.saturating_add(step).min(100);
+            let power_limit = self.profile.power_limit_at(temp);
+            let points = &mut self.profile.points;
+            let index = points.partition_point(|point| point.temp < temp);
+            points.insert(
+                index,
+                FanProfilePoint {
+                    temp,
+                    fan: to,
+                    power_limit,
+                },
+            );
+            self.adjustments.push(Adjustment {
+                temp,
+                from,
+                to,
+                added: true,
+                reason: reason.to_owned(),
+            });
+        }
+        for point in self
+            .profile
+            .points
+            .iter_mut()
+            .filter(|point| point.temp >= temp)
+        {
+            let to = point.fan.saturating_add(step).min(100);
+            if to != point.fan && !self.adjustments.iter().any(|adj| adj.temp == point.temp) {
+                self.adjustments.push(Adjustment {
+                    temp: point.temp,
+                    from: point.fan,
+                    to,
+                    added: false,
+                    reason: reason.to_owned(),
+                });
+                point.fan = to;
+            }
+        }
+    }
+
+    /// Lower all points up to `temp`, the curve above wasn't tested by the recording.
+    fn lower_until(&mut self, temp: u8, step: u8, reason: &str) {
+        for point in self
+            .profile
+            .points
+            .iter_mut()
+            .filter(|point| point.temp <= temp)
+        {
+            let to = point.fan.saturating_sub(step);
+            if to != point.fan {
+                self.adjustments.push(Adjustment {
+                    temp: point.temp,
+                    from: point.fan,
+                    to,
+                    added: false,
+                    reason: reason.to_owned(),
+                });
+                point.fan = to;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tune, TuningGoal};
+    use crate::{FanProfile, FanStatus, Preset, TelemetrySample};
+
+    /// One sample per second of fan 0 under curve control.
+    fn trace(temps: &[u8]) -> Vec<TelemetrySample> {
+        temps
+            .iter()
+            .enumerate()
+            .map(|(idx, &temperature)| TelemetrySample {
+                timestamp: idx as f64,
+                status: FanStatus {
+                    index: 0,
+                    temperature,
+                    speed: 50,
+                    target: Some(50),
+                    power_limit: 0,
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn peaks() {
+        let profile = FanProfile::preset(Preset::Balanced);
+        let goal = TuningGoal::default();
+        // Rises from 52°C to 95°C within 22s.
+        let temps: Vec<u8> = (0..40).map(|sec| (52 + sec * 2).min(95)).collect();
+        let tuning = tune(&profile, &trace(&temps), &goal);
+
+        let changes: Vec<_> = tuning
+            .adjustments
+            .iter()
+            .map(|adj| (adj.temp, adj.from, adj.to, adj.added))
+            .collect();
+        // The fans should have sped up at 52°C already, 10s before the first peak.
+        assert_eq!(
+            changes,
+            [(52, 27, 37, true), (65, 45, 55, false), (80, 75, 85, false)]
+        );
+        assert_eq!(
+            tuning.adjustments[0].to_string(),
+            "add point at 52°C from 27% to 37% to avoid the 95°C peaks"
+        );
+        assert_eq!(tuning.profile.validate(), Ok(()));
+        assert!((0..=u8::MAX)
+            .all(|temp| { tuning.profile.fan_speed_at(temp) >= profile.fan_speed_at(temp) }));
+    }
+
+    #[test]
+    fn quiet() {
+        let profile = FanProfile::preset(Preset::Balanced);
+        let tuning = tune(&profile, &trace(&[45, 50, 55, 52]), &TuningGoal::default());
+        let lowered: Vec<_> = tuning
+            .adjustments
+            .iter()
+            .map(|adj| (adj.temp, adj.to))
+            .collect();
+        assert_eq!(lowered, [(35, 0), (50, 15)]);
+        assert_eq!(tuning.profile.fan_speed_at(90), 100);
+
+        // Neither too hot nor far below the goal.
+        let tuning = tune(&profile, &trace(&[70, 80, 85]), &TuningGoal::default());
+        assert!(tuning.adjustments.is_empty());
+        assert_eq!(tuning.profile, profile);
+
+        // Samples of fans controlled by the firmware don't count.
+        let mut firmware = trace(&[95]);
+        firmware[0].status.target = None;
+        assert!(tune(&profile, &firmware, &TuningGoal::default())
+            .adjustments
+            .is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! Versioned wire format of the profile payloads exchanged with tailord.
+//!
+//! Version 0 is the original format: the plain JSON of the payload,
+//! where fan profiles are a list of points. Since version 1, payloads
+//! are wrapped in an [`Envelope`] and fan profiles are [`FanProfile`] objects.
+//!
+//! Clients negotiate the version once per connection. Payloads sent
+//! to tailord are recognized by their envelope, so both versions are
+//! always accepted.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{ColorProfile, FanProfile, ProfileInfo};
+
+/// Version of payloads without an envelope.
+pub const LEGACY_VERSION: u32 = 0;
+
+/// Newest version supported by this crate.
+pub const WIRE_VERSION: u32 = 1;
+
+/// A payload tagged with the version of its format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Envelope {
+    pub version: u32,
+    pub data: Value,
+}
+
+#[derive(Debug)]
+pub enum WireError {
+    /// The payload uses a version newer than [`WIRE_VERSION`].
+    UnsupportedVersion(u32),
+    /// The payload doesn't match its format.
+    InvalidData(serde_json::Error),
+}
+
+impl std::fmt::Display for WireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported wire format version {version} (newest supported: {WIRE_VERSION})"
+            ),
+            Self::InvalidData(err) => write!(f, "Invalid payload: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+impl From<serde_json::Error> for WireError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::InvalidData(err)
+    }
+}
+
+/// Types that are exchanged in the versioned wire format.
+///
+/// The conversions operate on the JSON value of the payload and only
+/// need to be implemented for types whose format changed.
+pub trait WirePayload: Serialize + DeserializeOwned {
+    /// Convert `data` of an older `version` to the newest format.
+    fn upgrade(_version: u32, data: Value) -> Value {
+        data
+    }
+
+    /// Convert `data` in the newest format to an older `version`.
+    fn downgrade(_version: u32, data: Value) -> Value {
+        data
+    }
+}
+
+impl WirePayload for ColorProfile {}
+
+impl WirePayload for ProfileInfo {}
+
+impl WirePayload for FanProfile {
+    fn upgrade(version: u32, data: Value) -> Value {
+        match (version, data) {
+            (LEGACY_VERSION, points @ Value::Array(_)) => {
+                json!({ "points": points, "sticky": false })
+            }
+            (_, data) => data,
+        }
+    }
+
+    fn downgrade(version: u32, mut data: Value) -> Value {
+        if version == LEGACY_VERSION {
+            data["points"].take()
+        } else {
+            data
+        }
+    }
+}
+
+/// Serialize `payload` in the format of `version`.
+pub fn encode<T: WirePayload>(payload: &T, version: u32) -> Result<String, WireError> {
+    if version > WIRE_VERSION {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+
+    let data = T::downgrade(version, serde_json::to_value(payload)?);
+    Ok(if version == LEGACY_VERSION {
+        data.to_string()
+    } else {
+        serde_json::to_string(&Envelope { version, data })?
+    })
+}
+
+/// Deserialize a payload of any supported version.
+pub fn decode<T: WirePayload>(value: &str) -> Result<T, WireError> {
+    let value: Value = serde_json::from_str(value)?;
+    let (version, data) = match Envelope::deserialize(&value) {
+        Ok(envelope) => (envelope.version, envelope.data),
+        Err(_) => (LEGACY_VERSION, value),
+    };
+
+    if version > WIRE_VERSION {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+    Ok(serde_json::from_value(T::upgrade(version, data))?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FanProfilePoint;
+
+    fn fan_profile() -> FanProfile {
+        FanProfile {
+            points: vec![
+                FanProfilePoint {
+                    temp: 40,
+                    fan: 20,
+                    power_limit: 0,
+                },
+                FanProfilePoint {
+                    temp: 80,
+                    fan: 100,
+                    power_limit: 0,
+                },
+            ],
+            sticky: true,
+            prediction: None,
+            smoothing: None,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let profile = fan_profile();
+        let data = encode(&profile, WIRE_VERSION).unwrap();
+        assert!(data.starts_with(r#"{"version":1,"#));
+        assert_eq!(decode::<FanProfile>(&data).unwrap(), profile);
+    }
+
+    #[test]
+    fn legacy_fan_profile() {
+        let profile = fan_profile();
+        let data = encode(&profile, LEGACY_VERSION).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<FanProfilePoint>>(&data).unwrap(),
+            profile.points
+        );
+
+        let upgraded = decode::<FanProfile>(&data).unwrap();
+        assert_eq!(upgraded.points, profile.points);
+        assert!(!upgraded.sticky);
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let data = r#"{"version":1000,"data":{"points":[],"sticky":false}}"#;
+        assert!(matches!(
+            decode::<FanProfile>(data),
+            Err(WireError::UnsupportedVersion(1000))
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    process::{Child, Command},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use colored::Colorize;
+use eyre::{Context, Result};
+use serde::Serialize;
+use tailor_api::{FanDeviceInfo, FanStatus};
+use tailor_client::TailorConnection;
+
+use crate::cli::BenchCommand;
+
+/// Interval between two samples of the fan status.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Temperatures in °C for which the time above them is reported.
+const THRESHOLDS: [u8; 4] = [60, 70, 80, 90];
+/// Width of the fan duty buckets in percent.
+const DUTY_BUCKET_WIDTH: u8 = 20;
+
+#[derive(Serialize)]
+struct Report {
+    duration_secs: u64,
+    /// Whether the benchmark was stopped before it finished.
+    interrupted: bool,
+    fans: Vec<FanReport>,
+}
+
+#[derive(Serialize)]
+struct FanReport {
+    label: String,
+    samples: usize,
+    max_temperature: u8,
+    average_temperature: f32,
+    max_speed: u8,
+    time_above: Vec<ThresholdTime>,
+    duty: Vec<DutyShare>,
+}
+
+#[derive(Serialize)]
+struct ThresholdTime {
+    temperature: u8,
+    seconds: u64,
+}
+
+/// Share of the samples with a fan speed in `from..=to` percent.
+#[derive(Serialize)]
+struct DutyShare {
+    from: u8,
+    to: u8,
+    percent: f32,
+}
+
+/// Handle benchmark commands
+pub(crate) async fn handle(cmd: BenchCommand, json: bool) -> Result<()> {
+    match cmd {
+        BenchCommand::Thermal {
+            duration,
+            threads,
+            command,
+        } => {
+            let report = thermal(Duration::from_secs(duration), threads, command, json).await?;
+            if json {
+                crate::print_json(&report)
+            } else {
+                print_report(&report);
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn thermal(
+    duration: Duration,
+    threads: Option<usize>,
+    command: Vec<String>,
+    quiet: bool,
+) -> Result<Report> {
+    let connection = TailorConnection::new().await?;
+    let fans = connection.list_devices().await?.fans;
+
+    let mut workload = if command.is_empty() {
+        let threads = threads.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, |threads| threads.get())
+        });
+        if !quiet {
+            println!(
+                "Loading the CPU with {threads} threads for {duration:?}, press Ctrl-C to stop"
+            );
+        }
+        Workload::cpu(threads)
+    } else {
+        if !quiet {
+            println!("Monitoring `{}` until it exits", command.join(" "));
+        }
+        Workload::command(&command)?
+    };
+
+    let start = Instant::now();
+    let mut samples: Vec<Vec<FanStatus>> = Vec::new();
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+    let interrupted = loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = tokio::signal::ctrl_c() => break true,
+        }
+
+        let status = connection.get_fan_status().await?;
+        if !quiet {
+            let temps: Vec<_> = status
+                .iter()
+                .map(|status| format!("{}°C {}%", status.temperature, status.speed))
+                .collect();
+            eprint!(
+                "\r{:>4}s  {}   ",
+                start.elapsed().as_secs(),
+                temps.join("  ")
+            );
+        }
+        samples.push(status);
+
+        if workload.finished(start.elapsed() >= duration)? {
+            break false;
+        }
+    };
+    workload.stop();
+    if !quiet {
+        eprintln!();
+    }
+
+    Ok(Report {
+        duration_secs: start.elapsed().as_secs(),
+        interrupted,
+        fans: fans
+            .iter()
+            .filter_map(|fan| fan_report(fan, &samples))
+            .collect(),
+    })
+}
+
+enum Workload {
+    Cpu {
+        stop: Arc<AtomicBool>,
+        threads: Vec<JoinHandle<()>>,
+    },
+    Command(Child),
+}
+
+impl Workload {
+    fn cpu(threads: usize) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let threads = (0..threads)
+            .map(|_| {
+                let stop = stop.clone();
+                std::thread::spawn(move || {
+                    let mut value = 0u64;
+                    while !stop.load(Ordering::Relaxed) {
+                        for _ in 0..10_000 {
+                            value = std::hint::black_box(value.wrapping_mul(31).wrapping_add(7));
+                        }
+                    }
+                })
+            })
+            .collect();
+        Self::Cpu { stop, threads }
+    }
+
+    fn command(command: &[String]) -> Result<Self> {
+        let child = Command::new(&command[0])
+            .args(&command[1..])
+            .spawn()
+            .wrap_err_with(|| format!("Failed to start `{}`", command[0]))?;
+        Ok(Self::Command(child))
+    }
+
+    /// The CPU load runs until the time is up, a command until it exits.
+    fn finished(&mut self, time_is_up: bool) -> Result<bool> {
+        match self {
+            Self::Cpu { .. } => Ok(time_is_up),
+            Self::Command(child) => Ok(child.try_wait()?.is_some()),
+        }
+    }
+
+    fn stop(self) {
+        match self {
+            Self::Cpu { stop, threads } => {
+                stop.store(true, Ordering::Relaxed);
+                for thread in threads {
+                    thread.join().ok();
+                }
+            }
+            Self::Command(mut child) => {
+                if matches!(child.try_wait(), Ok(None)) {
+                    child.kill().ok();
+                    child.wait().ok();
+                }
+            }
+        }
+    }
+}
+
+fn fan_report(fan: &FanDeviceInfo, samples: &[Vec<FanStatus>]) -> Option<FanReport> {
+    let statuses: Vec<&FanStatus> = samples
+        .iter()
+        .filter_map(|sample| sample.iter().find(|status| status.index == fan.index))
+        .collect();
+    if statuses.is_empty() {
+        return None;
+    }
+
+    let count = statuses.len();
+    let share = |matching: usize| matching as f32 * 100.0 / count as f32;
+    let interval = SAMPLE_INTERVAL.as_secs();
+
+    let time_above = THRESHOLDS
+        .iter()
+        .map(|&temperature| ThresholdTime {
+            temperature,
+            seconds: statuses
+                .iter()
+                .filter(|status| status.temperature > temperature)
+                .count() as u64
+                * interval,
+        })
+        .collect();
+
+    let duty = (0..100)
+        .step_by(DUTY_BUCKET_WIDTH.into())
+        .map(|from| {
+            let to = if from + DUTY_BUCKET_WIDTH >= 100 {
+                100
+            } else {
+                from + DUTY_BUCKET_WIDTH - 1
+            };
+            DutyShare {
+                from,
+                to,
+                percent: share(
+                    statuses
+                        .iter()
+                        .filter(|status| (from..=to).contains(&status.speed))
+                        .count(),
+                ),
+            }
+        })
+        .collect();
+
+    Some(FanReport {
+        label: fan.label.clone(),
+        samples: count,
+        max_temperature: statuses.iter().map(|status| status.temperature).max()?,
+        average_temperature: statuses
+            .iter()
+            .map(|status| f32::from(status.temperature))
+            .sum::<f32>()
+            / count as f32,
+        max_speed: statuses.iter().map(|status| status.speed).max()?,
+        time_above,
+        duty,
+    })
+}
+
+fn print_report(report: &Report) {
+    let mut summary = format!("Thermal benchmark ({}s", report.duration_secs);
+    if report.interrupted {
+        summary.push_str(", interrupted");
+    }
+    println!("\n{}", format!("{summary})").bold());
+
+    for fan in &report.fans {
+        println!("\n{}", fan.label.bold());
+        println!(
+            "  Temperature  max {}°C, average {:.1}°C",
+            fan.max_temperature, fan.average_temperature
+        );
+        println!("  Speed        max {}%", fan.max_speed);
+        for threshold in &fan.time_above {
+            println!(
+                "  Above {}°C   {}s",
+                threshold.temperature, threshold.seconds
+            );
+        }
+        println!("  Fan duty");
+        for share in &fan.duty {
+            // One block per 5% of the samples.
+            let bar = "█".repeat((share.percent / 5.0).round() as usize);
+            println!(
+                "    {:>3}–{:<3}%  {:>5.1}%  {}",
+                share.from,
+                share.to,
+                share.percent,
+                bar.cyan()
+            );
+        }
+    }
+}
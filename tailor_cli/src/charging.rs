@@ -0,0 +1,74 @@
+use colored::Colorize;
+use eyre::{bail, Result};
+use serde::Serialize;
+use tailor_api::{ChargingCapabilities, ChargingSettings};
+use tailor_client::TailorConnection;
+
+use crate::cli::ChargingCommand;
+
+#[derive(Serialize)]
+struct Status {
+    settings: ChargingSettings,
+    capabilities: ChargingCapabilities,
+}
+
+/// Handle charging commands
+pub(crate) async fn handle(cmd: ChargingCommand, json: bool) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        ChargingCommand::Status => {
+            let status = Status {
+                settings: connection.get_charging_settings().await?,
+                capabilities: connection.get_charging_capabilities().await?,
+            };
+            if json {
+                crate::print_json(&status)?;
+            } else {
+                print_status(&status);
+            }
+        }
+        ChargingCommand::Set {
+            profile,
+            priority,
+            start,
+            end,
+        } => {
+            let settings = ChargingSettings {
+                profile,
+                priority,
+                start_threshold: start,
+                end_threshold: end,
+            };
+            if settings == ChargingSettings::default() {
+                bail!("Nothing to change, see `tailor charging set --help`");
+            }
+            connection.set_charging_settings(&settings).await?;
+        }
+    }
+    Ok(())
+}
+
+fn print_status(status: &Status) {
+    let Status {
+        settings,
+        capabilities,
+    } = status;
+
+    if let Some(profile) = &settings.profile {
+        print_row("Profile", profile, &capabilities.profiles);
+    }
+    if let Some(priority) = &settings.priority {
+        print_row("Priority", priority, &capabilities.priorities);
+    }
+    if let (Some(start), Some(end)) = (settings.start_threshold, settings.end_threshold) {
+        println!("{}  {start}% – {end}%", "Thresholds".bold());
+    }
+}
+
+fn print_row(label: &str, value: &str, options: &[String]) {
+    println!(
+        "{}  {value} {}",
+        format!("{label:<10}").bold(),
+        format!("({})", options.join(", ")).dimmed()
+    );
+}
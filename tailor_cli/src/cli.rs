@@ -4,17 +4,202 @@ use clap::{Parser, Subcommand};
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub(crate) struct Opts {
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Control the hardware without tailord (root only, for debugging)
+    ///
+    /// Starts an interactive session that reads temperatures and sets fan
+    /// speeds or LED colors directly. tailord should be stopped first.
+    #[arg(long, exclusive = true)]
+    pub direct: bool,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
 
 #[derive(Subcommand, Debug, Clone)]
 pub(crate) enum Command {
+    /// Show the active profile and the state of fans, LEDs and power
+    Status,
+    /// Monitor temperatures, fan speeds and power limits in the terminal
+    Watch,
     /// Profile commands
     Profile {
         #[command(subcommand)]
         profile_cmd: ProfileCommand,
     },
+    /// Fan commands
+    Fan {
+        #[command(subcommand)]
+        fan_cmd: FanCommand,
+    },
+    /// Keyboard commands
+    Keyboard {
+        #[command(subcommand)]
+        keyboard_cmd: KeyboardCommand,
+    },
+    /// Webcam commands
+    Webcam {
+        #[command(subcommand)]
+        webcam_cmd: WebcamCommand,
+    },
+    /// Fn-lock commands
+    FnLock {
+        #[command(subcommand)]
+        fn_lock_cmd: FnLockCommand,
+    },
+    /// Touchpad commands
+    Touchpad {
+        #[command(subcommand)]
+        touchpad_cmd: TouchpadCommand,
+    },
+    /// Power limit (TDP) commands
+    PowerLimit {
+        #[command(subcommand)]
+        power_limit_cmd: PowerLimitCommand,
+    },
+    /// Battery charging commands
+    Charging {
+        #[command(subcommand)]
+        charging_cmd: ChargingCommand,
+    },
+    /// Benchmarks to evaluate fan profiles
+    Bench {
+        #[command(subcommand)]
+        bench_cmd: BenchCommand,
+    },
+    /// Import the configuration of other tools
+    Import {
+        #[command(subcommand)]
+        import_cmd: ImportCommand,
+    },
+    /// Print temperatures, fan speeds and the active profile for monitoring
+    ///
+    /// The Prometheus format is compatible with the textfile collector of node_exporter.
+    Metrics {
+        #[arg(long, value_enum, default_value_t = MetricsFormat::Prometheus)]
+        format: MetricsFormat,
+
+        /// Write to this file instead of stdout, replacing it atomically
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Record temperatures, fan speeds and power limits to a file
+    ///
+    /// The recording can be plotted with external tools to tune fan curves.
+    /// Stop early with Ctrl+C.
+    Record {
+        /// Path of the recording
+        output: std::path::PathBuf,
+
+        /// How long to record, e.g. 30s or 5m
+        #[arg(long = "for", value_name = "DURATION", default_value = "5m", value_parser = parse_seconds)]
+        seconds: u32,
+
+        /// Time between two samples in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        interval: u64,
+
+        /// Format of the recording (default: by file extension, CSV unless `.jsonl`)
+        #[arg(long, value_enum)]
+        format: Option<RecordFormat>,
+    },
+    /// Change the log level of tailord until it restarts
+    ///
+    /// For example, `tailor log-level trace --target tailord::fancontrol`
+    /// logs every step of the fan control for a bug report.
+    LogLevel {
+        /// `off`, `error`, `warn`, `info`, `debug` or `trace`
+        level: String,
+        /// Only change the level of this module (default: all modules)
+        #[arg(long)]
+        target: Option<String>,
+    },
+    /// Collect logs, hardware details and profiles for a bug report
+    ///
+    /// Host and user names are removed from the archive.
+    Diagnostics {
+        /// Path of the archive (default: tailor-diagnostics-<timestamp>.tar.gz)
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Print the JSON schema of a configuration file format
+    Schema {
+        #[arg(value_enum)]
+        kind: SchemaKind,
+    },
+    /// Print a shell completion script
+    ///
+    /// For example, add `source <(tailor completions bash)` to ~/.bashrc.
+    /// Profile names are completed by querying tailord.
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print values for dynamic shell completions
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        #[arg(value_enum)]
+        kind: CompletionKind,
+    },
+}
+
+/// Values that completion scripts request from tailord
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum CompletionKind {
+    /// Names of the global profiles
+    Profiles,
+}
+
+/// Output formats of `tailor metrics`
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum MetricsFormat {
+    /// Prometheus text exposition format
+    Prometheus,
+}
+
+/// Output formats of `tailor record`
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum RecordFormat {
+    /// Comma-separated values with a header line
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+/// Built-in curves of `tailor fan preset`
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum FanPreset {
+    /// Fans off at idle and as slow as possible under load
+    Silent,
+    /// A compromise for everyday use
+    Balanced,
+    /// Cools early and reacts to rising temperatures ahead of the curve
+    Performance,
+}
+
+/// Flash patterns of `tailor keyboard alert`
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum AlertPattern {
+    /// Keep the color on
+    Solid,
+    /// On and off twice per second
+    Blink,
+    /// Fade in and out once per second
+    Pulse,
+}
+
+/// Configuration file formats in /etc/tailord
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum SchemaKind {
+    /// Fan profiles in /etc/tailord/fan
+    Fan,
+    /// Keyboard color profiles in /etc/tailord/keyboard
+    Keyboard,
+    /// Global profiles in /etc/tailord/profiles
+    Profile,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -29,6 +214,31 @@ pub(crate) enum ProfileCommand {
         name: String,
     },
 
+    /// Show the most recent profile activations
+    History,
+
+    /// Show how the settings of two profiles differ
+    Diff {
+        /// The profile to compare against
+        #[arg()]
+        left: String,
+
+        /// The profile to compare
+        #[arg()]
+        right: String,
+    },
+
+    /// Create a new profile based on the active one
+    New {
+        /// The name of the new profile
+        #[arg()]
+        name: String,
+
+        /// Choose the fan, keyboard, performance and webcam settings step by step
+        #[arg(long)]
+        wizard: bool,
+    },
+
     /// Cycle profiles
     Cycle {
         // Print the new profile to stdout
@@ -40,3 +250,344 @@ pub(crate) enum ProfileCommand {
         notify: bool,
     },
 }
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum ImportCommand {
+    /// Import the profiles of TUXEDO Control Center
+    ///
+    /// Every TCC profile becomes a profile and a fan profile of the same name.
+    /// Settings that TCC doesn't know are copied from the active profile.
+    Tcc {
+        /// The profiles of TCC
+        #[arg(default_value = "/etc/tcc/profiles")]
+        path: std::path::PathBuf,
+
+        /// Replace profiles that already exist
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Only show what would be imported
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Import the keyboard effect of ite8291r3-ctl
+    ///
+    /// The effect is read from the ite8291r3-ctl commands in a script or systemd unit.
+    #[command(name = "ite8291r3-ctl")]
+    Ite8291r3Ctl {
+        /// Script or systemd unit that runs ite8291r3-ctl
+        #[arg()]
+        path: std::path::PathBuf,
+
+        /// The name of the keyboard profile
+        #[arg(long, default_value = "ite8291r3")]
+        name: String,
+
+        /// Also use the keyboard profile for all LEDs of this profile
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Replace the keyboard profile if it already exists
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Only show what would be imported
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum KeyboardCommand {
+    /// Show a solid color on all keyboard LEDs
+    ///
+    /// The color is stored in the active profile unless `--temporary` is given.
+    Set {
+        /// Hex color, e.g. '#00ff88'
+        #[arg()]
+        color: String,
+
+        /// Brightness in percent
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+        brightness: Option<u8>,
+
+        /// Keep the color only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+    /// Flash a color on all keyboard LEDs, e.g. as notification from scripts
+    ///
+    /// The colors of the active profile are restored afterwards.
+    Alert {
+        /// Hex color, e.g. '#ff0000'
+        #[arg()]
+        color: String,
+
+        #[arg(long, value_enum, default_value_t = AlertPattern::Blink)]
+        pattern: AlertPattern,
+
+        /// Duration in seconds (max. 60)
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..=60))]
+        seconds: u32,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum WebcamCommand {
+    /// Switch the webcam on
+    ///
+    /// The state is stored in the active profile unless `--temporary` is given.
+    On {
+        /// Keep the state only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+    /// Switch the webcam off
+    ///
+    /// The state is stored in the active profile unless `--temporary` is given.
+    Off {
+        /// Keep the state only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+    /// Print whether the webcam is switched on
+    Status,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum FnLockCommand {
+    /// Switch the Fn-lock on
+    ///
+    /// The state is stored in the active profile unless `--temporary` is given.
+    On {
+        /// Keep the state only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+    /// Switch the Fn-lock off
+    ///
+    /// The state is stored in the active profile unless `--temporary` is given.
+    Off {
+        /// Keep the state only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+    /// Print whether the Fn-lock is switched on
+    Status,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum TouchpadCommand {
+    /// Switch the touchpad on
+    ///
+    /// The state is stored in the active profile unless `--temporary` is given.
+    On {
+        /// Keep the state only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+    /// Switch the touchpad off
+    ///
+    /// The state is stored in the active profile unless `--temporary` is given.
+    Off {
+        /// Keep the state only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+    /// Print whether the touchpad is switched on
+    Status,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum PowerLimitCommand {
+    /// List the power limits with their current value and range
+    List,
+    /// Set a power limit in watts
+    ///
+    /// The limit is stored in the active profile unless `--temporary` is given.
+    Set {
+        /// Name of the power limit, see `tailor power-limit list`
+        name: String,
+
+        /// Power limit in watts
+        watts: i32,
+
+        /// Keep the limit only until the next profile is activated
+        #[arg(long)]
+        temporary: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum ChargingCommand {
+    /// Show the charging settings and the supported values
+    Status,
+    /// Change the charging settings
+    ///
+    /// Settings that aren't given stay unchanged. The settings are
+    /// applied again whenever tailord starts.
+    Set {
+        /// Charging profile of the firmware, e.g. `stationary`
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Prefer charging speed or performance when charging over USB-C
+        #[arg(long)]
+        priority: Option<String>,
+
+        /// Start charging below this battery level in percent
+        #[arg(long, value_parser = clap::value_parser!(u32).range(0..=100))]
+        start: Option<u32>,
+
+        /// Stop charging at this battery level in percent
+        #[arg(long, value_parser = clap::value_parser!(u32).range(0..=100))]
+        end: Option<u32>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum BenchCommand {
+    /// Load the CPU and report how temperatures and fan speeds developed
+    ///
+    /// Pass a command after `--` to monitor your own workload instead,
+    /// e.g. `tailor bench thermal -- make -j8`.
+    Thermal {
+        /// Duration of the CPU load in seconds
+        #[arg(long, default_value_t = 60)]
+        duration: u64,
+
+        /// Number of threads to load (default: all CPUs)
+        #[arg(long)]
+        threads: Option<usize>,
+
+        /// Workload to monitor until it exits
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum FanCommand {
+    /// Create or edit a fan profile interactively
+    Edit {
+        /// The name of the fan profile
+        #[arg()]
+        name: String,
+    },
+    /// Store a built-in fan curve as fan profile
+    Preset {
+        /// The built-in curve
+        #[arg(value_enum)]
+        preset: FanPreset,
+
+        /// The name of the fan profile (default: the name of the preset)
+        #[arg()]
+        name: Option<String>,
+    },
+    /// Create a fan profile that stays below the loudest fan speed you accept
+    ///
+    /// The fans run at increasing speeds until you reject one. The curve of the
+    /// preset is then capped at the last accepted speed.
+    NoiseTarget {
+        /// The name of the fan profile
+        #[arg()]
+        name: String,
+
+        /// The curve that is capped
+        #[arg(long, value_enum, default_value = "balanced")]
+        base: FanPreset,
+
+        /// Temperature in °C above which the fans may get louder
+        #[arg(long, default_value_t = 80)]
+        above: u8,
+
+        /// Temperature in °C at which the fans reach full speed
+        #[arg(long, default_value_t = 95)]
+        full_speed: u8,
+    },
+    /// Propose changes to a fan profile from a recording of `tailor record`
+    ///
+    /// Points are raised ahead of temperature peaks, or lowered if the
+    /// temperature stayed far below the goal. Nothing is changed without `--save`.
+    Tune {
+        /// The name of the fan profile that was active during the recording
+        #[arg()]
+        name: String,
+
+        /// Path of the recording, CSV or JSON lines
+        #[arg()]
+        recording: std::path::PathBuf,
+
+        /// Temperatures in °C above this are peaks to avoid
+        #[arg(long, default_value_t = 90)]
+        max_temp: u8,
+
+        /// Seconds before a peak in which the fans should already run faster
+        #[arg(long, default_value_t = 30)]
+        lead: u32,
+
+        /// Change of a point in percent
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(1..=100))]
+        step: u8,
+
+        /// Only use the samples of this fan, starting at 1 (default: all fans)
+        #[arg(long)]
+        fan: Option<u8>,
+
+        /// Store the proposed curve as fan profile with this name
+        #[arg(long, value_name = "NAME")]
+        save: Option<String>,
+    },
+    /// Print the points of a fan profile
+    Show {
+        /// The name of the fan profile
+        #[arg()]
+        name: String,
+
+        /// Draw the fan speed and power limit curves as a chart
+        #[arg(long)]
+        plot: bool,
+    },
+    /// Keep fans at a fixed speed for a limited time
+    Override {
+        /// Fan speed in percent
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=100))]
+        percent: u8,
+
+        /// Only override this fan, starting at 1 (default: all fans)
+        #[arg(long)]
+        fan: Option<u8>,
+
+        /// How long to keep the speed, e.g. 30s or 5m (max. 10m)
+        #[arg(long = "for", value_name = "DURATION", default_value = "60s", value_parser = parse_seconds)]
+        seconds: u32,
+    },
+    /// End overrides and boosts, so the fan profile applies again
+    Release,
+    /// Run all fans at full speed for a limited time
+    Boost {
+        /// Duration of the boost in seconds (max. 600)
+        #[arg(default_value_t = 60)]
+        seconds: u32,
+    },
+}
+
+/// Parse durations like `30`, `30s`, `5m` or `1h` into seconds
+fn parse_seconds(value: &str) -> Result<u32, String> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => value.split_at(idx),
+        None => (value, "s"),
+    };
+    let factor = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(format!("unknown unit `{unit}`, use s, m or h")),
+    };
+    number
+        .parse::<u32>()
+        .ok()
+        .and_then(|number| number.checked_mul(factor))
+        .ok_or_else(|| format!("invalid duration `{value}`"))
+}
@@ -0,0 +1,114 @@
+use std::io::{self, Write};
+
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::Shell;
+use eyre::Result;
+use tailor_client::TailorConnection;
+
+use crate::cli::{CompletionKind, Opts};
+
+/// Arguments whose values are completed with data from tailord.
+const DYNAMIC_ARGS: &[(&[&str], CompletionKind)] =
+    &[(&["profile", "set"], CompletionKind::Profiles)];
+
+/// Print the completion script for `shell`
+pub(crate) fn handle(shell: Shell) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    clap_complete::generate(shell, &mut Opts::command(), "tailor", &mut stdout);
+
+    match shell {
+        Shell::Bash => write_bash(&mut stdout)?,
+        Shell::Zsh => write_zsh(&mut stdout)?,
+        Shell::Fish => write_fish(&mut stdout)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Print the values for a dynamic completion, one per line
+pub(crate) async fn complete(kind: CompletionKind) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    let values = match kind {
+        CompletionKind::Profiles => connection.list_global_profiles().await?,
+    };
+    for value in values {
+        println!("{value}");
+    }
+    Ok(())
+}
+
+fn kind_name(kind: CompletionKind) -> String {
+    kind.to_possible_value()
+        .expect("completion kinds aren't skipped")
+        .get_name()
+        .to_owned()
+}
+
+fn write_bash(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "\n_tailor_dynamic() {{")?;
+    for (path, kind) in DYNAMIC_ARGS {
+        writeln!(
+            out,
+            "    if [[ ${{COMP_CWORD}} -eq {} && \"${{COMP_WORDS[*]:1:{}}}\" == \"{}\" ]]; then",
+            path.len() + 1,
+            path.len(),
+            path.join(" ")
+        )?;
+        writeln!(out, "        local IFS=$'\\n'")?;
+        writeln!(
+            out,
+            "        COMPREPLY=( $(compgen -W \"$(tailor __complete {} 2>/dev/null)\" -- \"${{COMP_WORDS[COMP_CWORD]}}\") )",
+            kind_name(*kind)
+        )?;
+        writeln!(out, "        return 0")?;
+        writeln!(out, "    fi")?;
+    }
+    writeln!(out, "    _tailor \"$@\"")?;
+    writeln!(out, "}}")?;
+    writeln!(
+        out,
+        "complete -F _tailor_dynamic -o bashdefault -o default tailor"
+    )
+}
+
+fn write_zsh(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "\n_tailor_dynamic() {{")?;
+    for (path, kind) in DYNAMIC_ARGS {
+        writeln!(
+            out,
+            "    if (( CURRENT == {} )) && [[ \"${{words[2,{}]}}\" == \"{}\" ]]; then",
+            path.len() + 2,
+            path.len() + 1,
+            path.join(" ")
+        )?;
+        writeln!(out, "        local -a values")?;
+        writeln!(
+            out,
+            "        values=(\"${{(@f)$(tailor __complete {} 2>/dev/null)}}\")",
+            kind_name(*kind)
+        )?;
+        writeln!(out, "        compadd -a values")?;
+        writeln!(out, "        return")?;
+        writeln!(out, "    fi")?;
+    }
+    writeln!(out, "    _tailor \"$@\"")?;
+    writeln!(out, "}}")?;
+    writeln!(out, "compdef _tailor_dynamic tailor")
+}
+
+fn write_fish(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out)?;
+    for (path, kind) in DYNAMIC_ARGS {
+        let condition = path
+            .iter()
+            .map(|subcommand| format!("__fish_seen_subcommand_from {subcommand}"))
+            .collect::<Vec<_>>()
+            .join("; and ");
+        writeln!(
+            out,
+            "complete -c tailor -n \"{condition}\" -f -a \"(tailor __complete {} 2>/dev/null)\"",
+            kind_name(*kind)
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use eyre::Result;
+use flate2::{write::GzEncoder, Compression};
+use serde::Serialize;
+use serde_json::json;
+use tailor_client::{ClientResult, TailorConnection};
+
+/// Directory inside the archive.
+const ROOT: &str = "tailor-diagnostics";
+/// Number of fan status samples, taken once per second.
+const TELEMETRY_SAMPLES: usize = 5;
+/// Maximum number of journal lines.
+const JOURNAL_LINES: &str = "1000";
+
+const DMI_FIELDS: [&str; 4] = ["sys_vendor", "product_name", "board_name", "bios_version"];
+
+/// Collect logs, hardware details, profiles and telemetry into an archive
+pub(crate) async fn handle(output: Option<PathBuf>) -> Result<()> {
+    let output = output.unwrap_or_else(|| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        PathBuf::from(format!("tailor-diagnostics-{}.tar.gz", now.as_secs()))
+    });
+
+    let connection = TailorConnection::new().await;
+    if let Ok(connection) = &connection {
+        // Put the internal state of the daemon into the journal. Older
+        // versions of the daemon don't support this.
+        connection.dump_state().await.ok();
+    }
+
+    let mut bundle = Bundle::new(&output)?;
+    bundle.add("system.txt", &system_info())?;
+    bundle.add("journal.txt", &journal())?;
+
+    match connection {
+        Ok(connection) => add_daemon_data(&mut bundle, &connection).await?,
+        Err(err) => bundle.add(
+            "daemon.error.txt",
+            &format!("Can't connect to tailord: {err}"),
+        )?,
+    }
+
+    bundle.finish()?;
+    println!("Wrote {}", output.display());
+    println!("Host name and user name were removed, please check the archive before sharing it.");
+    Ok(())
+}
+
+async fn add_daemon_data(bundle: &mut Bundle, connection: &TailorConnection<'_>) -> Result<()> {
+    bundle.add_json("daemon.json", connection.get_diagnostics().await)?;
+    bundle.add_json("devices.json", connection.list_devices().await)?;
+
+    let active_profile = connection.get_active_global_profile_name().await;
+    bundle.add_json(
+        "state.json",
+        Ok(json!({
+            "active_profile": active_profile.as_ref().ok(),
+            "performance_profile": connection.get_performance_profile().await.ok(),
+            "subsystems": connection.get_subsystems().await.ok(),
+            "power_source_profiles": connection.get_power_source_profiles().await.ok(),
+        })),
+    )?;
+
+    if let Ok(name) = active_profile {
+        let profile = connection.get_global_profile(&name).await;
+        if let Ok(profile) = &profile {
+            let mut fans = profile.fans.clone();
+            fans.sort();
+            fans.dedup();
+            for fan in fans {
+                bundle.add_json(
+                    &format!("fan/{fan}.json"),
+                    connection.get_fan_profile(&fan).await,
+                )?;
+            }
+            let mut keyboard: Vec<_> = profile.leds.iter().map(|led| &led.profile).collect();
+            keyboard.sort();
+            keyboard.dedup();
+            for name in keyboard {
+                bundle.add_json(
+                    &format!("keyboard/{name}.json"),
+                    connection.get_led_profile(name).await,
+                )?;
+            }
+        }
+        bundle.add_json(&format!("profiles/{name}.json"), profile)?;
+    }
+
+    println!("Recording telemetry for {TELEMETRY_SAMPLES} seconds");
+    let mut samples = Vec::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    for _ in 0..TELEMETRY_SAMPLES {
+        interval.tick().await;
+        if let Ok(status) = connection.get_fan_status().await {
+            samples.push(status);
+        }
+    }
+    bundle.add_json(
+        "telemetry.json",
+        Ok(json!({
+            "fan_status": samples,
+            "profile_history": connection.get_profile_history().await.ok(),
+        })),
+    )
+}
+
+fn read_trimmed(path: impl AsRef<Path>) -> String {
+    std::fs::read_to_string(path)
+        .map(|value| value.trim().to_owned())
+        .unwrap_or_else(|err| format!("<{err}>"))
+}
+
+fn system_info() -> String {
+    let mut info = format!("kernel: {}\n", read_trimmed("/proc/sys/kernel/osrelease"));
+
+    let os = std::fs::read_to_string("/etc/os-release").unwrap_or_default();
+    if let Some(name) = os
+        .lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+    {
+        info.push_str(&format!("os: {}\n", name.trim_matches('"')));
+    }
+
+    for field in DMI_FIELDS {
+        info.push_str(&format!(
+            "{field}: {}\n",
+            read_trimmed(Path::new("/sys/class/dmi/id").join(field))
+        ));
+    }
+
+    let mut modules: Vec<String> = std::fs::read_dir("/sys/module")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with("tuxedo"))
+        .collect();
+    modules.sort();
+    info.push_str("modules:\n");
+    for module in modules {
+        let version = std::fs::read_to_string(format!("/sys/module/{module}/version"))
+            .map(|version| version.trim().to_owned())
+            .unwrap_or_default();
+        info.push_str(&format!("  {module} {version}\n"));
+    }
+    info
+}
+
+fn journal() -> String {
+    let output = Command::new("journalctl")
+        .args(["--unit", "tailord", "--boot", "--no-pager", "--lines"])
+        .arg(JOURNAL_LINES)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into(),
+        Ok(output) => format!(
+            "journalctl failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ),
+        Err(err) => format!("Can't run journalctl: {err}"),
+    }
+}
+
+/// A gzipped tarball whose entries are redacted.
+struct Bundle {
+    archive: tar::Builder<GzEncoder<File>>,
+    /// Personal values and their replacements.
+    redactions: Vec<(String, &'static str)>,
+    mtime: u64,
+}
+
+impl Bundle {
+    fn new(path: &Path) -> Result<Self> {
+        let file = File::create(path)?;
+
+        // The home directory usually contains the user name, so it goes first.
+        let mut redactions = Vec::new();
+        if let Some(home) = std::env::var_os("HOME") {
+            redactions.push((home.to_string_lossy().into_owned(), "<home>"));
+        }
+        for var in ["SUDO_USER", "USER"] {
+            if let Ok(user) = std::env::var(var) {
+                redactions.push((user, "<user>"));
+            }
+        }
+        redactions.push((read_trimmed("/proc/sys/kernel/hostname"), "<hostname>"));
+        // Values like `root` or `/` would garble the whole report.
+        redactions.retain(|(value, _)| value.len() >= 3 && value != "root");
+
+        Ok(Self {
+            archive: tar::Builder::new(GzEncoder::new(file, Compression::default())),
+            redactions,
+            mtime: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    fn add(&mut self, path: &str, contents: &str) -> Result<()> {
+        let mut contents = contents.to_owned();
+        for (value, replacement) in &self.redactions {
+            contents = contents.replace(value.as_str(), replacement);
+        }
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(self.mtime);
+        header.set_cksum();
+        self.archive
+            .append_data(&mut header, format!("{ROOT}/{path}"), contents.as_bytes())?;
+        Ok(())
+    }
+
+    /// Add `value` as JSON or the error that occurred while reading it.
+    fn add_json<T: Serialize>(&mut self, path: &str, value: ClientResult<T>) -> Result<()> {
+        match value {
+            Ok(value) => self.add(path, &serde_json::to_string_pretty(&value)?),
+            Err(err) => self.add(&format!("{path}.error.txt"), &err.to_string()),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        self.archive.into_inner()?.finish()?;
+        Ok(())
+    }
+}
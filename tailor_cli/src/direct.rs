@@ -0,0 +1,254 @@
+use std::{
+    io::{self, BufRead, Write},
+    os::unix::fs::MetadataExt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use colored::Colorize;
+use eyre::{bail, Result};
+use tailor_api::Color;
+use tuxedo_ioctl::hal::{traits::HardwareDevice, IoInterface};
+use tuxedo_sysfs::led::{Collection, Controller};
+
+/// Fans are handed back to the firmware at this temperature in °C.
+const CRITICAL_TEMP: u8 = 90;
+/// Interval of the temperature checks while fans are set manually.
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(2);
+
+const WARNING: &str = "\
+This talks to the hardware directly, without tailord.
+Fan speeds you set stay in effect until you switch back to `auto` or quit,
+so low speeds can overheat your device. If a temperature reaches 90°C,
+the fans are handed back to the firmware.
+Stop tailord first (`systemctl stop tailord`), otherwise it overrides your changes.";
+
+const HELP: &str = "\
+Commands:
+  show                     Print temperatures and fan speeds
+  fan <n|all> <speed>      Set the speed of fan n in percent
+  auto                     Let the firmware control the fans again
+  led                      List the LED devices
+  led <n> <color>          Set the color of LED device n, e.g. #00ff88
+  quit                     Restore automatic fan control and LED colors and quit";
+
+/// Start an interactive session that controls the hardware directly
+pub(crate) async fn handle() -> Result<()> {
+    if std::fs::metadata("/proc/self")?.uid() != 0 {
+        bail!("Direct hardware access requires root, try `sudo tailor --direct`");
+    }
+
+    println!("{}\n", WARNING.yellow());
+    print!("Type `yes` to continue: ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() != "yes" {
+        return Ok(());
+    }
+
+    let device = IoInterface::new()?.device;
+
+    // The LED controllers need io_uring, which can't run inside the tokio runtime.
+    let session = {
+        let device = device.clone();
+        tokio::task::spawn_blocking(move || {
+            std::thread::spawn(move || tuxedo_sysfs::runtime::start(session(device))).join()
+        })
+    };
+
+    tokio::select! {
+        result = session => match result? {
+            Ok(result) => result,
+            Err(_) => bail!("The direct hardware session crashed"),
+        },
+        _ = tokio::signal::ctrl_c() => {
+            // The session is still blocked on stdin, so restore the fans here.
+            device.set_fans_auto()?;
+            println!("\nFans are back in automatic mode");
+            std::process::exit(130);
+        }
+    }
+}
+
+/// Hands the fans back to the firmware when the session ends, even on panics.
+struct RestoreFans(Arc<dyn HardwareDevice>);
+
+impl Drop for RestoreFans {
+    fn drop(&mut self) {
+        match self.0.set_fans_auto() {
+            Ok(()) => println!("Fans are back in automatic mode"),
+            Err(err) => eprintln!("{}", format!("Failed to restore the fans: {err}").red()),
+        }
+    }
+}
+
+async fn session(device: Arc<dyn HardwareDevice>) -> Result<()> {
+    let _restore = RestoreFans(device.clone());
+    let manual = Arc::new(AtomicBool::new(false));
+    spawn_watchdog(device.clone(), manual.clone());
+
+    let mut leds = match Collection::new().await {
+        Ok(collection) => collection.into_inner(),
+        Err(err) => {
+            println!("{}", format!("LEDs are unavailable: {err}").yellow());
+            Vec::new()
+        }
+    };
+    // Colors before the first change, restored when quitting.
+    let mut original_colors: Vec<Option<Color>> = vec![None; leds.len()];
+
+    println!("{HELP}\n");
+    print_fans(device.as_ref());
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{} ", "direct>".bold());
+        io::stdout().flush()?;
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        let args: Vec<&str> = line.split_whitespace().collect();
+
+        let result = match args.as_slice() {
+            [] => continue,
+            ["show"] => {
+                print_fans(device.as_ref());
+                Ok(())
+            }
+            ["fan", fan, speed] => set_fans(device.as_ref(), fan, speed).map(|()| {
+                manual.store(true, Ordering::Relaxed);
+            }),
+            ["auto"] => device
+                .set_fans_auto()
+                .map(|()| manual.store(false, Ordering::Relaxed))
+                .map_err(|err| err.to_string()),
+            ["led"] => {
+                print_leds(&leds);
+                Ok(())
+            }
+            ["led", index, color] => set_led(&mut leds, &mut original_colors, index, color).await,
+            ["quit"] => break,
+            ["help"] => {
+                println!("{HELP}");
+                Ok(())
+            }
+            _ => Err("Unknown command, type `help` for a list of commands".to_owned()),
+        };
+
+        if let Err(err) = result {
+            println!("{}", err.red());
+        }
+    }
+
+    for (controller, color) in leds.iter_mut().zip(original_colors) {
+        if let Some(color) = color {
+            if let Err(err) = controller.set_color(&color).await {
+                eprintln!(
+                    "Failed to restore the color of {}: {err}",
+                    controller.function
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Hand the fans back to the firmware if a temperature gets critical.
+fn spawn_watchdog(device: Arc<dyn HardwareDevice>, manual: Arc<AtomicBool>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(WATCHDOG_INTERVAL);
+        if !manual.load(Ordering::Relaxed) {
+            continue;
+        }
+        let critical = (0..device.get_number_fans())
+            .filter_map(|fan| device.get_fan_temperature(fan).ok())
+            .any(|temp| temp >= CRITICAL_TEMP);
+        if critical && device.set_fans_auto().is_ok() {
+            manual.store(false, Ordering::Relaxed);
+            println!(
+                "\n{}",
+                format!("Reached {CRITICAL_TEMP}°C, the fans are back in automatic mode").red()
+            );
+        }
+    });
+}
+
+fn print_fans(device: &dyn HardwareDevice) {
+    for fan in 0..device.get_number_fans() {
+        let temp = device
+            .get_fan_temperature(fan)
+            .map_or_else(|_| "?".to_owned(), |temp| temp.to_string());
+        let speed = device
+            .get_fan_speed_percent(fan)
+            .map_or_else(|_| "?".to_owned(), |speed| speed.to_string());
+        println!("Fan {}  {temp:>3}°C  {speed:>3}%", fan + 1);
+    }
+}
+
+fn set_fans(device: &dyn HardwareDevice, fan: &str, speed: &str) -> Result<(), String> {
+    let speed: u8 = speed
+        .trim_end_matches('%')
+        .parse()
+        .ok()
+        .filter(|speed| *speed <= 100)
+        .ok_or_else(|| format!("Invalid speed `{speed}`"))?;
+
+    let min_speed = device.get_fans_min_speed().unwrap_or_default();
+    let off_available = device.get_fans_off_available().unwrap_or_default();
+    if speed < min_speed && !(speed == 0 && off_available) {
+        return Err(format!("The minimum speed of the fans is {min_speed}%"));
+    }
+
+    let fans = match fan {
+        "all" => 0..device.get_number_fans(),
+        fan => match fan.parse::<u8>() {
+            Ok(fan) if (1..=device.get_number_fans()).contains(&fan) => fan - 1..fan,
+            _ => return Err(format!("There is no fan `{fan}`")),
+        },
+    };
+    for fan in fans {
+        device
+            .set_fan_speed_percent(fan, speed)
+            .map_err(|err| format!("Failed to set fan {}: {err}", fan + 1))?;
+    }
+    Ok(())
+}
+
+fn print_leds(leds: &[Controller]) {
+    if leds.is_empty() {
+        println!("No LED devices found");
+    }
+    for (index, led) in leds.iter().enumerate() {
+        println!("{:>3}  {}::{}", index + 1, led.device_name, led.function);
+    }
+}
+
+async fn set_led(
+    leds: &mut [Controller],
+    original_colors: &mut [Option<Color>],
+    index: &str,
+    color: &str,
+) -> Result<(), String> {
+    let index = index
+        .parse::<usize>()
+        .ok()
+        .filter(|index| (1..=leds.len()).contains(index))
+        .ok_or_else(|| format!("There is no LED device `{index}`"))?
+        - 1;
+    let color = crate::parse_color(color).map_err(|err| err.to_string())?;
+
+    let controller = &mut leds[index];
+    if original_colors[index].is_none() {
+        original_colors[index] = controller.get_color().await.ok();
+    }
+    controller
+        .set_color(&color)
+        .await
+        .map_err(|err| err.to_string())
+}
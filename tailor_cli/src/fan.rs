@@ -0,0 +1,86 @@
+use eyre::{bail, Result};
+use tailor_api::{tuning::TuningGoal, Preset};
+use tailor_client::TailorConnection;
+
+use crate::{
+    cli::{FanCommand, FanPreset},
+    fan_editor, fan_plot, noise_target, tune,
+};
+
+/// Handle fan commands
+pub(crate) async fn handle(cmd: FanCommand) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        FanCommand::Edit { name } => fan_editor::edit(&connection, &name).await?,
+        FanCommand::Preset { preset, name } => {
+            let preset = api_preset(preset);
+            let name = name.unwrap_or_else(|| preset.to_string());
+            let profile = connection.get_fan_preset(preset).await?;
+            connection.add_fan_profile(&name, &profile).await?;
+            fan_editor::print_profile(&profile);
+            println!("Stored the {preset} preset as fan profile `{name}`");
+        }
+        FanCommand::NoiseTarget {
+            name,
+            base,
+            above,
+            full_speed,
+        } => {
+            noise_target::generate(&connection, &name, api_preset(base), above, full_speed).await?
+        }
+        FanCommand::Tune {
+            name,
+            recording,
+            max_temp,
+            lead,
+            step,
+            fan,
+            save,
+        } => {
+            let goal = TuningGoal {
+                max_temp,
+                lead_seconds: lead,
+                step,
+                ..Default::default()
+            };
+            tune::handle(&connection, &name, &recording, goal, fan, save.as_deref()).await?
+        }
+        FanCommand::Show { name, plot } => {
+            let profile = connection.get_fan_profile(&name).await?;
+            if plot {
+                print!("{}", fan_plot::render(&profile));
+            } else {
+                fan_editor::print_profile(&profile);
+            }
+        }
+        FanCommand::Override {
+            percent,
+            fan,
+            seconds,
+        } => {
+            let number_of_fans = connection.get_number_of_fans().await?;
+            let fans = match fan {
+                Some(fan) if (1..=number_of_fans).contains(&fan) => fan - 1..fan,
+                Some(fan) => bail!("There is no fan {fan}, this device has {number_of_fans}"),
+                None => 0..number_of_fans,
+            };
+            for fan_idx in fans {
+                connection.pin_fan_speed(fan_idx, percent, seconds).await?;
+            }
+            println!("Fans run at {percent}% for {seconds}s, `tailor fan release` ends this early");
+        }
+        FanCommand::Release => connection.release_fans().await?,
+        FanCommand::Boost { seconds } => {
+            connection.boost_fans(seconds).await?;
+        }
+    }
+    Ok(())
+}
+
+fn api_preset(preset: FanPreset) -> Preset {
+    match preset {
+        FanPreset::Silent => Preset::Silent,
+        FanPreset::Balanced => Preset::Balanced,
+        FanPreset::Performance => Preset::Performance,
+    }
+}
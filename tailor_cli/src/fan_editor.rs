@@ -0,0 +1,239 @@
+use std::io::{self, BufRead, Write};
+
+use colored::Colorize;
+use eyre::Result;
+use tailor_api::{FanPrediction, FanProfile, FanProfilePoint, FanSmoothing};
+use tailor_client::{ClientError, TailorConnection, TailorError};
+
+/// Temperatures at which the resulting fan speed is previewed.
+const PREVIEW_TEMPS: [u8; 8] = [30, 40, 50, 60, 70, 80, 90, 100];
+
+const HELP: &str = "\
+Commands:
+  add <temp> <speed> [power limit]          Add a point
+  move <n> <temp> <speed> [power limit]     Change point n
+  remove <n>                                Remove point n
+  sticky on|off                             Only lower the speed once the temperature dropped for a while
+  predict <gain> <max boost>|off            Add gain% per °C/s of rise to the speed, up to max boost%
+  smooth <alpha>|off                        Follow a moving average weighting new temperatures with alpha%
+  show                                      Print the profile again
+  save                                      Save the profile and quit
+  quit                                      Quit without saving";
+
+/// Edit a fan profile interactively
+pub(crate) async fn edit(connection: &TailorConnection<'_>, name: &str) -> Result<()> {
+    let mut profile = match connection.get_fan_profile(name).await {
+        Ok(profile) => profile,
+        Err(ClientError::BusError(TailorError::ProfileNotFound(_))) => {
+            println!("Creating new fan profile `{name}`");
+            FanProfile {
+                points: Vec::new(),
+                sticky: false,
+                prediction: None,
+                smoothing: None,
+            }
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    println!("{HELP}\n");
+    print_profile(&profile);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{} ", ">".bold());
+        io::stdout().flush()?;
+        let Some(line) = lines.next() else {
+            return Ok(());
+        };
+        let line = line?;
+        let args: Vec<&str> = line.split_whitespace().collect();
+
+        let result = match args.as_slice() {
+            [] => continue,
+            ["add", values @ ..] => parse_point(values).and_then(|point| add(&mut profile, point)),
+            ["move", index, values @ ..] => parse_index(&profile, index).and_then(|index| {
+                let point = parse_point(values)?;
+                let previous = profile.points.remove(index);
+                add(&mut profile, point).map_err(|err| {
+                    profile.points.insert(index, previous);
+                    err
+                })
+            }),
+            ["remove", index] => parse_index(&profile, index).map(|index| {
+                profile.points.remove(index);
+            }),
+            ["sticky", "on"] => {
+                profile.sticky = true;
+                Ok(())
+            }
+            ["sticky", "off"] => {
+                profile.sticky = false;
+                Ok(())
+            }
+            ["predict", "off"] => {
+                profile.prediction = None;
+                Ok(())
+            }
+            ["predict", gain, max_boost] => parse_value(gain, "gain").and_then(|gain| {
+                let max_boost = parse_value(max_boost, "maximum boost")?;
+                profile.prediction = Some(FanPrediction { gain, max_boost });
+                Ok(())
+            }),
+            ["smooth", "off"] => {
+                profile.smoothing = None;
+                Ok(())
+            }
+            ["smooth", alpha] => parse_value(alpha, "alpha").and_then(|alpha| {
+                if !(1..=100).contains(&alpha) {
+                    return Err("Alpha must be between 1 and 100".to_owned());
+                }
+                profile.smoothing = Some(FanSmoothing { alpha });
+                Ok(())
+            }),
+            ["show"] => Ok(()),
+            ["save"] => {
+                let errors = validate(&profile);
+                if errors.is_empty() {
+                    connection.add_fan_profile(name, &profile).await?;
+                    println!("Saved fan profile `{name}`");
+                    return Ok(());
+                }
+                Err(format!("Can't save the profile: {}", errors.join(", ")))
+            }
+            ["quit"] => return Ok(()),
+            ["help"] => {
+                println!("{HELP}");
+                continue;
+            }
+            _ => Err("Unknown command, type `help` for a list of commands".to_owned()),
+        };
+
+        match result {
+            Ok(()) => print_profile(&profile),
+            Err(err) => println!("{}", err.red()),
+        }
+    }
+}
+
+fn parse_value(value: &str, name: &str) -> Result<u8, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid {name} `{value}`"))
+}
+
+fn parse_index(profile: &FanProfile, index: &str) -> Result<usize, String> {
+    let index: usize = index
+        .parse()
+        .map_err(|_| format!("Invalid point number `{index}`"))?;
+    if (1..=profile.points.len()).contains(&index) {
+        Ok(index - 1)
+    } else {
+        Err(format!("There is no point {index}"))
+    }
+}
+
+fn parse_point(values: &[&str]) -> Result<FanProfilePoint, String> {
+    let (temp, fan, power_limit) = match values {
+        [temp, fan] => (temp, fan, "0"),
+        [temp, fan, power_limit] => (temp, fan, *power_limit),
+        _ => return Err("Expected a temperature, a speed and optionally a power limit".to_owned()),
+    };
+    let point = FanProfilePoint {
+        temp: parse_value(temp.trim_end_matches("°C"), "temperature")?,
+        fan: parse_value(fan.trim_end_matches('%'), "speed")?,
+        power_limit: parse_value(power_limit.trim_end_matches('%'), "power limit")?,
+    };
+
+    if point.temp > 100 {
+        Err("The temperature can't be higher than 100°C".to_owned())
+    } else if point.fan > 100 {
+        Err("The speed can't be higher than 100%".to_owned())
+    } else if point.power_limit > 100 {
+        Err("The power limit can't be higher than 100%".to_owned())
+    } else {
+        Ok(point)
+    }
+}
+
+/// Insert a point, keeping the points sorted by temperature.
+fn add(profile: &mut FanProfile, point: FanProfilePoint) -> Result<(), String> {
+    match profile.points.binary_search_by_key(&point.temp, |p| p.temp) {
+        Ok(_) => Err(format!(
+            "There already is a point at {}°C, use `move` to change it",
+            point.temp
+        )),
+        Err(index) => {
+            profile.points.insert(index, point);
+            Ok(())
+        }
+    }
+}
+
+/// Problems that prevent saving the profile.
+fn validate(profile: &FanProfile) -> Vec<String> {
+    let mut errors = Vec::new();
+    if let Err(err) = profile.validate() {
+        errors.push(err.to_string());
+    }
+    if profile
+        .points
+        .windows(2)
+        .any(|points| points[1].fan < points[0].fan)
+    {
+        errors.push("the speed decreases while the temperature rises".to_owned());
+    }
+    errors
+}
+
+/// Corrections that tailord applies when loading the profile.
+fn warnings(profile: &FanProfile) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if profile.points.last().is_some_and(|point| point.fan < 100) {
+        warnings.push("100% is never reached, tailord adds 100% at 100°C".to_owned());
+    }
+    warnings
+}
+
+pub(crate) fn print_profile(profile: &FanProfile) {
+    println!(
+        "{}",
+        format!("  #  {:>5}  {:>5}  {:>11}", "Temp", "Speed", "Power limit").bold()
+    );
+    for (index, point) in profile.points.iter().enumerate() {
+        println!(
+            "{:>3}  {:>3}°C  {:>4}%  {:>10}%",
+            index + 1,
+            point.temp,
+            point.fan,
+            point.power_limit
+        );
+    }
+    println!("Sticky: {}", if profile.sticky { "on" } else { "off" });
+    match profile.prediction {
+        Some(FanPrediction { gain, max_boost }) => {
+            println!("Prediction: +{gain}% per °C/s, at most +{max_boost}%")
+        }
+        None => println!("Prediction: off"),
+    }
+    match profile.smoothing {
+        Some(FanSmoothing { alpha }) => println!("Smoothing: {alpha}%"),
+        None => println!("Smoothing: off"),
+    }
+
+    if !profile.points.is_empty() {
+        let preview: Vec<_> = PREVIEW_TEMPS
+            .iter()
+            .map(|temp| format!("{temp}°C→{}%", profile.fan_speed_at(*temp)))
+            .collect();
+        println!("Preview: {}", preview.join("  ").dimmed());
+    }
+
+    for error in validate(profile) {
+        println!("{}", format!("Error: {error}").red());
+    }
+    for warning in warnings(profile) {
+        println!("{}", format!("Warning: {warning}").yellow());
+    }
+}
@@ -0,0 +1,83 @@
+use colored::Colorize;
+use tailor_api::FanProfile;
+
+/// Degrees Celsius per column.
+const TEMP_STEP: u8 = 2;
+/// Percent per row.
+const PERCENT_STEP: u8 = 5;
+
+const SPEED_MARK: &str = "●";
+const POWER_LIMIT_MARK: &str = "×";
+
+/// Render the fan speed and power limit curves of `profile` as a chart.
+///
+/// The power limit curve is only drawn if any point sets a power limit.
+pub(crate) fn render(profile: &FanProfile) -> String {
+    let temps: Vec<u8> = (0..=100).step_by(TEMP_STEP.into()).collect();
+    let speeds: Vec<u8> = temps
+        .iter()
+        .map(|temp| profile.fan_speed_at(*temp))
+        .collect();
+    let power_limits: Option<Vec<u8>> = profile
+        .points
+        .iter()
+        .any(|point| point.power_limit > 0)
+        .then(|| {
+            temps
+                .iter()
+                .map(|temp| profile.power_limit_at(*temp))
+                .collect()
+        });
+
+    let mut out = String::new();
+    for percent in (0..=100).rev().step_by(PERCENT_STEP.into()) {
+        if percent % 25 == 0 {
+            out.push_str(&format!("{percent:>4}% ┤"));
+        } else {
+            out.push_str("      │");
+        }
+        for (idx, speed) in speeds.iter().enumerate() {
+            let mark = if row(*speed) == percent {
+                SPEED_MARK.cyan().to_string()
+            } else if power_limits
+                .as_ref()
+                .is_some_and(|limits| row(limits[idx]) == percent)
+            {
+                POWER_LIMIT_MARK.yellow().to_string()
+            } else {
+                " ".to_owned()
+            };
+            out.push_str(&mark);
+        }
+        out.push('\n');
+    }
+
+    out.push_str("      └");
+    for temp in &temps {
+        out.push(if temp % 20 == 0 { '┬' } else { '─' });
+    }
+    out.push('\n');
+
+    // Labels are centered below their tick, e.g. " 40 " below the `┬` at 40°C.
+    let mut labels = " ".repeat(6);
+    for temp in (0..=100).step_by(20) {
+        let column = 7 + usize::from(temp / TEMP_STEP);
+        let label = temp.to_string();
+        let start = column.saturating_sub(label.len() / 2);
+        labels.push_str(&" ".repeat(start.saturating_sub(labels.len())));
+        labels.push_str(&label);
+    }
+    out.push_str(&format!("{labels} °C\n"));
+
+    out.push_str(&format!("        {} fan speed", SPEED_MARK.cyan()));
+    if power_limits.is_some() {
+        out.push_str(&format!("  {} power limit", POWER_LIMIT_MARK.yellow()));
+    }
+    out.push('\n');
+    out
+}
+
+/// Round `value` to the nearest row.
+fn row(value: u8) -> u8 {
+    (value.min(100) + PERCENT_STEP / 2) / PERCENT_STEP * PERCENT_STEP
+}
@@ -0,0 +1,34 @@
+use eyre::Result;
+use tailor_client::TailorConnection;
+
+use crate::cli::FnLockCommand;
+
+/// Handle Fn-lock commands
+pub(crate) async fn handle(cmd: FnLockCommand) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        FnLockCommand::On { temporary } => set(&connection, true, temporary).await?,
+        FnLockCommand::Off { temporary } => set(&connection, false, temporary).await?,
+        FnLockCommand::Status => {
+            let enabled = connection.get_fn_lock_enabled().await?;
+            println!("{}", if enabled { "on" } else { "off" });
+        }
+    }
+    Ok(())
+}
+
+/// Switch the Fn-lock and store the state in the active profile unless `temporary` is set.
+async fn set(connection: &TailorConnection<'_>, enabled: bool, temporary: bool) -> Result<()> {
+    connection.set_fn_lock_enabled(enabled).await?;
+    if !temporary {
+        let active_profile = connection.get_active_global_profile_name().await?;
+        let mut profile = connection.get_global_profile(&active_profile).await?;
+        if profile.fn_lock != Some(enabled) {
+            profile.fn_lock = Some(enabled);
+            connection
+                .add_global_profile(&active_profile, &profile)
+                .await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::{fs, path::Path};
+
+use colored::Colorize;
+use eyre::{bail, eyre, Result, WrapErr};
+use tailor_api::{import, LedProfile};
+use tailor_client::TailorConnection;
+
+use crate::{cli::ImportCommand, fan_editor};
+
+/// Handle import commands
+pub(crate) async fn handle(cmd: ImportCommand) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        ImportCommand::Tcc {
+            path,
+            overwrite,
+            dry_run,
+        } => tcc(&connection, &path, overwrite, dry_run).await,
+        ImportCommand::Ite8291r3Ctl {
+            path,
+            name,
+            profile,
+            overwrite,
+            dry_run,
+        } => {
+            ite8291r3_ctl(
+                &connection,
+                &path,
+                &name,
+                profile.as_deref(),
+                overwrite,
+                dry_run,
+            )
+            .await
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).wrap_err_with(|| format!("Failed to read `{}`", path.display()))
+}
+
+/// Store every TCC profile as profile and fan profile of the same name,
+/// based on the active profile.
+async fn tcc(
+    connection: &TailorConnection<'_>,
+    path: &Path,
+    overwrite: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let profiles = import::parse_tcc_profiles(&read(path)?).map_err(|err| eyre!(err))?;
+    let base = connection
+        .get_global_profile(&connection.get_active_global_profile_name().await?)
+        .await?;
+    let existing = connection.list_global_profiles().await?;
+
+    for tcc in profiles {
+        let name = tcc.name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        if existing.iter().any(|profile| profile == name) && !overwrite {
+            println!("Skipped `{name}`, the profile exists (use `--overwrite` to replace it)");
+            continue;
+        }
+
+        let fan = tcc.fan_profile();
+        let mut profile = tcc.apply_to(&base);
+        if fan.value.is_some() {
+            profile.value.fans = vec![name.to_owned(); base.fans.len().max(1)];
+        }
+        println!("{}", name.bold());
+        for note in fan.notes.iter().chain(&profile.notes) {
+            println!("- {note}");
+        }
+        if let Some(fan) = &fan.value {
+            fan_editor::print_profile(fan);
+        }
+        if dry_run {
+            continue;
+        }
+
+        if let Some(fan) = &fan.value {
+            connection.add_fan_profile(name, fan).await?;
+        }
+        connection.add_global_profile(name, &profile.value).await?;
+        println!("Imported profile `{name}`");
+    }
+    Ok(())
+}
+
+/// Store the keyboard effect of the ite8291r3-ctl commands in `path` as
+/// keyboard profile `name` and use it in `profile`, if given.
+async fn ite8291r3_ctl(
+    connection: &TailorConnection<'_>,
+    path: &Path,
+    name: &str,
+    profile: Option<&str>,
+    overwrite: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let imported = import::ite8291r3_ctl(&read(path)?).map_err(|err| eyre!(err))?;
+    for note in &imported.notes {
+        println!("- {note}");
+    }
+    if dry_run {
+        println!("{}", serde_json::to_string_pretty(&imported.value)?);
+        return Ok(());
+    }
+
+    let exists = connection
+        .list_led_profiles()
+        .await?
+        .iter()
+        .any(|led| led == name);
+    if exists && !overwrite {
+        bail!("Keyboard profile `{name}` exists, use `--overwrite` to replace it");
+    }
+    connection.add_led_profile(name, &imported.value).await?;
+    println!("Imported keyboard profile `{}`", name.bold());
+
+    let Some(profile_name) = profile else {
+        return Ok(());
+    };
+    let mut profile = connection.get_global_profile(profile_name).await?;
+    for device in connection.get_led_devices().await? {
+        profile.set_led_profile(LedProfile {
+            device_name: device.device_name,
+            function: device.function,
+            profile: name.to_owned(),
+            mode: device.mode,
+        });
+    }
+    connection
+        .add_global_profile(profile_name, &profile)
+        .await?;
+    if connection.get_active_global_profile_name().await? == profile_name {
+        connection.reload_led_profile().await?;
+    }
+    println!("Profile `{profile_name}` uses the keyboard profile now");
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use eyre::Result;
+use tailor_api::{Color, ColorProfile, LedProfile};
+use tailor_client::TailorConnection;
+
+use crate::cli::{AlertPattern, KeyboardCommand};
+
+/// Handle keyboard commands
+pub(crate) async fn handle(cmd: KeyboardCommand) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        KeyboardCommand::Set {
+            color,
+            brightness,
+            temporary,
+        } => {
+            let color = dim(crate::parse_color(&color)?, brightness.unwrap_or(100));
+            if temporary {
+                connection.set_temporary_led_color(&color).await?;
+            } else {
+                set_persistent(&connection, color).await?;
+            }
+        }
+        KeyboardCommand::Alert {
+            color,
+            pattern,
+            seconds,
+        } => {
+            let color = crate::parse_color(&color)?;
+            let pattern = match pattern {
+                AlertPattern::Solid => tailor_api::AlertPattern::Solid,
+                AlertPattern::Blink => tailor_api::AlertPattern::Blink,
+                AlertPattern::Pulse => tailor_api::AlertPattern::Pulse,
+            };
+            connection.flash_led_alert(&color, pattern, seconds).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Scale `color` to `brightness` percent.
+fn dim(color: Color, brightness: u8) -> Color {
+    let scale = |value: u8| (u16::from(value) * u16::from(brightness.min(100)) / 100) as u8;
+    Color {
+        r: scale(color.r),
+        g: scale(color.g),
+        b: scale(color.b),
+    }
+}
+
+/// Store the color as keyboard profile `<active profile>-solid`
+/// and use it for all LED devices of the active profile.
+async fn set_persistent(connection: &TailorConnection<'_>, color: Color) -> Result<()> {
+    let active_profile = connection.get_active_global_profile_name().await?;
+    let keyboard_profile = format!("{active_profile}-solid");
+    connection
+        .add_led_profile(&keyboard_profile, &ColorProfile::Single(color))
+        .await?;
+
+    let mut profile = connection.get_global_profile(&active_profile).await?;
+    let leds: Vec<LedProfile> = connection
+        .get_led_devices()
+        .await?
+        .into_iter()
+        .map(|device| LedProfile {
+            device_name: device.device_name,
+            function: device.function,
+            profile: keyboard_profile.clone(),
+            mode: device.mode,
+        })
+        .collect();
+    if profile.leds != leds {
+        profile.leds = leds;
+        connection
+            .add_global_profile(&active_profile, &profile)
+            .await?;
+        connection.reload_led_profile().await?;
+    }
+    Ok(())
+}
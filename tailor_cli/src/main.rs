@@ -1,16 +1,104 @@
+mod bench;
+mod charging;
 mod cli;
+mod completions;
+mod diagnostics;
+mod direct;
+mod fan;
+mod fan_editor;
+mod fan_plot;
+mod fn_lock;
+mod import;
+mod keyboard;
+mod metrics;
+mod noise_target;
+mod power_limit;
 mod profile;
+mod profile_diff;
+mod record;
+mod schema;
+mod status;
+mod touchpad;
+mod tune;
+mod watch;
+mod webcam;
+mod wizard;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use eyre::Result;
+use serde::Serialize;
+use tailor_api::Color;
+use tailor_client::TailorConnection;
 
 use crate::cli::{Command, Opts};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Opts::parse();
-    if let Some(Command::Profile { profile_cmd }) = args.command {
-        profile::handle(profile_cmd).await?;
+    if args.direct {
+        if args.command.is_some() {
+            Opts::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "`--direct` can't be used with a subcommand",
+                )
+                .exit();
+        }
+        return direct::handle().await;
     }
+    match args.command {
+        Some(Command::Status) => status::handle(args.json).await?,
+        Some(Command::Watch) => watch::handle().await?,
+        Some(Command::Profile { profile_cmd }) => profile::handle(profile_cmd, args.json).await?,
+        Some(Command::Fan { fan_cmd }) => fan::handle(fan_cmd).await?,
+        Some(Command::Keyboard { keyboard_cmd }) => keyboard::handle(keyboard_cmd).await?,
+        Some(Command::Webcam { webcam_cmd }) => webcam::handle(webcam_cmd).await?,
+        Some(Command::FnLock { fn_lock_cmd }) => fn_lock::handle(fn_lock_cmd).await?,
+        Some(Command::Touchpad { touchpad_cmd }) => touchpad::handle(touchpad_cmd).await?,
+        Some(Command::PowerLimit { power_limit_cmd }) => {
+            power_limit::handle(power_limit_cmd, args.json).await?
+        }
+        Some(Command::Charging { charging_cmd }) => {
+            charging::handle(charging_cmd, args.json).await?
+        }
+        Some(Command::Bench { bench_cmd }) => bench::handle(bench_cmd, args.json).await?,
+        Some(Command::Import { import_cmd }) => import::handle(import_cmd).await?,
+        Some(Command::Metrics { format, output }) => {
+            metrics::handle(format, output.as_deref()).await?
+        }
+        Some(Command::Record {
+            output,
+            seconds,
+            interval,
+            format,
+        }) => record::handle(&output, seconds, interval, format).await?,
+        Some(Command::LogLevel { level, target }) => {
+            TailorConnection::new()
+                .await?
+                .set_log_level(target.as_deref().unwrap_or_default(), &level)
+                .await?
+        }
+        Some(Command::Diagnostics { output }) => diagnostics::handle(output).await?,
+        Some(Command::Schema { kind }) => schema::handle(kind)?,
+        Some(Command::Completions { shell }) => completions::handle(shell)?,
+        Some(Command::Complete { kind }) => completions::complete(kind).await?,
+        None => {}
+    }
+    Ok(())
+}
+
+/// Parse a color like `#00ff88`, `0x00ff88` or `00ff88`.
+pub(crate) fn parse_color(value: &str) -> Result<Color> {
+    let hex = value
+        .trim_start_matches('#')
+        .trim_start_matches("0x")
+        .to_lowercase();
+    hex.parse()
+        .map_err(|_| eyre::eyre!("Invalid color `{value}`, expected a hex value like #00ff88"))
+}
+
+/// Print `value` for scripts that use `--json`.
+pub(crate) fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
     Ok(())
 }
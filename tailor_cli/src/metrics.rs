@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use eyre::Result;
+use tailor_api::metrics::Metrics;
+use tailor_client::TailorConnection;
+
+use crate::cli::MetricsFormat;
+
+/// Print the current fan and profile state for monitoring systems
+pub(crate) async fn handle(format: MetricsFormat, output: Option<&Path>) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    let metrics = match format {
+        MetricsFormat::Prometheus => metrics(&connection).await?.render(),
+    };
+
+    match output {
+        // Write atomically, so node_exporter never reads a partial file.
+        Some(path) => {
+            let mut tmp = path.as_os_str().to_owned();
+            tmp.push(".tmp");
+            std::fs::write(&tmp, metrics)?;
+            std::fs::rename(&tmp, path)?;
+        }
+        None => print!("{metrics}"),
+    }
+    Ok(())
+}
+
+/// Collect what the D-Bus API exposes. The battery charge and the
+/// event counters are only available at `/metrics` of tailord.
+async fn metrics(connection: &TailorConnection<'_>) -> Result<Metrics> {
+    let devices = connection.list_devices().await?.fans;
+    let fans = connection
+        .get_fan_status()
+        .await?
+        .into_iter()
+        .map(|status| {
+            let label = devices
+                .iter()
+                .find(|fan| fan.index == status.index)
+                .map_or_else(String::new, |fan| fan.label.clone());
+            (label, status)
+        })
+        .collect();
+
+    Ok(Metrics {
+        fans,
+        active_profile: connection.get_active_global_profile_name().await?,
+        // Not every device supports power limits.
+        power_limits: connection.list_power_limits().await.unwrap_or_default(),
+        power_source: connection.get_power_source().await.ok(),
+        battery: None,
+        counters: None,
+    })
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use colored::Colorize;
+use eyre::{bail, Result};
+use tailor_api::{NoiseLimit, Preset};
+use tailor_client::TailorConnection;
+
+use crate::{fan_editor, wizard::confirm};
+
+/// Fan speeds in percent that are played, from quiet to loud.
+const SPEEDS: [u8; 9] = [20, 30, 40, 50, 60, 70, 80, 90, 100];
+
+/// Time for the fans to reach a new speed before asking.
+const SPIN_UP: Duration = Duration::from_secs(5);
+
+/// Pins end on their own if the command is killed while asking.
+const PIN_SECONDS: u32 = 120;
+
+/// Play increasing fan speeds and cap `preset` at the loudest accepted one
+/// up to `above` °C. The fans reach full speed at `full_speed` °C.
+pub(crate) async fn generate(
+    connection: &TailorConnection<'_>,
+    name: &str,
+    preset: Preset,
+    above: u8,
+    full_speed: u8,
+) -> Result<()> {
+    if above >= full_speed {
+        bail!("--above must be lower than --full-speed");
+    }
+    let base = connection.get_fan_preset(preset).await?;
+
+    println!(
+        "The fans will run at increasing speeds, answer `n` once they get too loud.\n\
+         Close other programs first, so the fans aren't sped up by load."
+    );
+    let result = loudest_accepted(connection).await;
+    connection.release_fans().await?;
+    let max_speed = result?;
+
+    let profile = base.noise_limited(NoiseLimit {
+        max_speed,
+        above,
+        full_speed,
+    })?;
+    println!("\nAt most {max_speed}% up to {above}°C, full speed at {full_speed}°C:");
+    fan_editor::print_profile(&profile);
+    if confirm(&format!("Save as fan profile `{name}`?"), true)? {
+        connection.add_fan_profile(name, &profile).await?;
+        println!("Stored fan profile `{name}`");
+    }
+    Ok(())
+}
+
+/// The loudest speed the user accepted, 0 if even the quietest was too loud.
+async fn loudest_accepted(connection: &TailorConnection<'_>) -> Result<u8> {
+    let fans = connection.get_number_of_fans().await?;
+    let mut accepted = 0;
+    for speed in SPEEDS {
+        for fan_idx in 0..fans {
+            connection
+                .pin_fan_speed(fan_idx, speed, PIN_SECONDS)
+                .await?;
+        }
+        println!("{}", format!("Running at {speed}%…").dimmed());
+        tokio::time::sleep(SPIN_UP).await;
+        if !confirm(&format!("Is {speed}% acceptable?"), true)? {
+            break;
+        }
+        accepted = speed;
+    }
+    Ok(accepted)
+}
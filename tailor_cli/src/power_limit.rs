@@ -0,0 +1,42 @@
+use eyre::Result;
+use tailor_client::TailorConnection;
+
+use crate::cli::PowerLimitCommand;
+
+/// Handle power limit commands
+pub(crate) async fn handle(cmd: PowerLimitCommand, json: bool) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        PowerLimitCommand::List => {
+            let limits = connection.list_power_limits().await?;
+            if json {
+                crate::print_json(&limits)?;
+            } else {
+                for limit in limits {
+                    println!(
+                        "{}: {}W ({}W - {}W)",
+                        limit.name, limit.value, limit.min, limit.max
+                    );
+                }
+            }
+        }
+        PowerLimitCommand::Set {
+            name,
+            watts,
+            temporary,
+        } => {
+            connection.set_power_limit(&name, watts).await?;
+            if !temporary {
+                let active_profile = connection.get_active_global_profile_name().await?;
+                let mut profile = connection.get_global_profile(&active_profile).await?;
+                if profile.tdp.get(&name) != Some(&watts) {
+                    profile.tdp.insert(name, watts);
+                    connection
+                        .add_global_profile(&active_profile, &profile)
+                        .await?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
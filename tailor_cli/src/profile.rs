@@ -1,19 +1,27 @@
+use std::time::{Duration, UNIX_EPOCH};
+
 use colored::Colorize;
 use eyre::Result;
+use serde_json::json;
 use tailor_client::TailorConnection;
 
-use crate::cli::ProfileCommand;
+use crate::{cli::ProfileCommand, profile_diff};
 use notify_rust::Notification;
 
 /// Handle profile commands
-pub(crate) async fn handle(cmd: ProfileCommand) -> Result<()> {
+pub(crate) async fn handle(cmd: ProfileCommand, json: bool) -> Result<()> {
     let connection = TailorConnection::new().await?;
     match cmd {
         ProfileCommand::List => {
             let active_profile = connection.get_active_global_profile_name().await?;
-            let inactive_profiles: Vec<String> = connection
-                .list_global_profiles()
-                .await?
+            let profiles = connection.list_global_profiles().await?;
+            if json {
+                return crate::print_json(&json!({
+                    "active": active_profile,
+                    "profiles": profiles,
+                }));
+            }
+            let inactive_profiles: Vec<String> = profiles
                 .into_iter()
                 .filter(|name| name != &active_profile)
                 .collect();
@@ -24,6 +32,34 @@ pub(crate) async fn handle(cmd: ProfileCommand) -> Result<()> {
             connection.set_active_global_profile_name(&name).await?;
             connection.reload().await?;
         }
+        ProfileCommand::Diff { left, right } => {
+            profile_diff::handle(&connection, &left, &right, json).await?;
+        }
+        ProfileCommand::New { name, wizard } => {
+            if wizard {
+                crate::wizard::create(&connection, &name).await?;
+            } else {
+                let active_profile = connection.get_active_global_profile_name().await?;
+                let profile = connection.get_global_profile(&active_profile).await?;
+                connection.add_global_profile(&name, &profile).await?;
+                println!("Created profile `{}` from `{active_profile}`", name.bold());
+            }
+        }
+        ProfileCommand::History => {
+            let history = connection.get_profile_history().await?;
+            if json {
+                return crate::print_json(&history);
+            }
+            for entry in history {
+                let time = UNIX_EPOCH + Duration::from_secs(entry.timestamp);
+                println!(
+                    "{}  {} ({})",
+                    humantime::format_rfc3339_seconds(time),
+                    entry.profile.bold(),
+                    entry.trigger
+                );
+            }
+        }
         ProfileCommand::Cycle { verbose, notify } => {
             let active_profile = connection.get_active_global_profile_name().await?;
             let profiles: Vec<String> = connection.list_global_profiles().await?;
@@ -40,7 +76,9 @@ pub(crate) async fn handle(cmd: ProfileCommand) -> Result<()> {
                     .set_active_global_profile_name(next_profile_name)
                     .await?;
                 connection.reload().await?;
-                if verbose {
+                if json {
+                    crate::print_json(&json!({ "active": next_profile_name }))?;
+                } else if verbose {
                     println!("{}", profile_updated_msg)
                 }
                 if notify {
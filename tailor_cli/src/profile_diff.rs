@@ -0,0 +1,286 @@
+use std::collections::{BTreeSet, HashMap};
+
+use colored::Colorize;
+use eyre::Result;
+use serde::Serialize;
+use tailor_api::{
+    ColorProfile, FanProfile, LedProfile, LidClosedBehavior, LidClosedFans, ProfileInfo,
+};
+use tailor_client::TailorConnection;
+
+/// A setting that differs between the two profiles.
+#[derive(Serialize)]
+struct Difference {
+    setting: String,
+    left: String,
+    right: String,
+}
+
+/// Print how the profiles `left` and `right` differ
+pub(crate) async fn handle(
+    connection: &TailorConnection<'_>,
+    left: &str,
+    right: &str,
+    json: bool,
+) -> Result<()> {
+    let mut differ = Differ {
+        connection,
+        fan_profiles: HashMap::new(),
+        differences: Vec::new(),
+    };
+    let left_profile = connection.get_global_profile(left).await?;
+    let right_profile = connection.get_global_profile(right).await?;
+    differ.profiles(&left_profile, &right_profile).await;
+    let differences = differ.differences;
+
+    if json {
+        return crate::print_json(&differences);
+    }
+    if differences.is_empty() {
+        println!("`{left}` and `{right}` have the same settings");
+        return Ok(());
+    }
+    println!("{}", format!("--- {left}").red());
+    println!("{}", format!("+++ {right}").green());
+    for difference in &differences {
+        println!("{}", difference.setting.bold());
+        println!("  {}", format!("- {}", difference.left).red());
+        println!("  {}", format!("+ {}", difference.right).green());
+    }
+    Ok(())
+}
+
+struct Differ<'a, 'b> {
+    connection: &'a TailorConnection<'b>,
+    fan_profiles: HashMap<String, Option<FanProfile>>,
+    differences: Vec<Difference>,
+}
+
+impl Differ<'_, '_> {
+    fn push(
+        &mut self,
+        setting: impl Into<String>,
+        left: impl Into<String>,
+        right: impl Into<String>,
+    ) {
+        let (left, right) = (left.into(), right.into());
+        if left != right {
+            self.differences.push(Difference {
+                setting: setting.into(),
+                left,
+                right,
+            });
+        }
+    }
+
+    async fn profiles(&mut self, left: &ProfileInfo, right: &ProfileInfo) {
+        let performance = |profile: &ProfileInfo| {
+            profile
+                .performance_profile
+                .clone()
+                .unwrap_or_else(|| "firmware default".to_owned())
+        };
+        self.push("Performance profile", performance(left), performance(right));
+
+        let switch = |state: Option<bool>| match state {
+            Some(true) => "on",
+            Some(false) => "off",
+            None => "unchanged",
+        };
+        self.push("Webcam", switch(left.webcam), switch(right.webcam));
+        self.push("Fn-lock", switch(left.fn_lock), switch(right.fn_lock));
+        self.push("Touchpad", switch(left.touchpad), switch(right.touchpad));
+
+        let cpufreq =
+            |value: &Option<String>| value.clone().unwrap_or_else(|| "unchanged".to_owned());
+        self.push(
+            "CPU governor",
+            cpufreq(&left.cpu_governor),
+            cpufreq(&right.cpu_governor),
+        );
+        self.push(
+            "Energy performance preference",
+            cpufreq(&left.energy_performance_preference),
+            cpufreq(&right.energy_performance_preference),
+        );
+
+        let brightness = |profile: &ProfileInfo| match profile.display_brightness {
+            Some(percent) => format!("{percent}%"),
+            None => "unchanged".to_owned(),
+        };
+        self.push("Display brightness", brightness(left), brightness(right));
+
+        let lid_closed = |profile: &ProfileInfo| {
+            let LidClosedBehavior { fans, leds_off } = &profile.lid_closed;
+            let mut changes = Vec::new();
+            match fans {
+                Some(LidClosedFans::Auto) => changes.push("fans auto".to_owned()),
+                Some(LidClosedFans::Profile(name)) => changes.push(format!("fans `{name}`")),
+                None => {}
+            }
+            if *leds_off {
+                changes.push("LEDs off".to_owned());
+            }
+            if changes.is_empty() {
+                "unchanged".to_owned()
+            } else {
+                changes.join(", ")
+            }
+        };
+        self.push("Lid closed", lid_closed(left), lid_closed(right));
+
+        let power_limits = |profile: &ProfileInfo| {
+            if profile.disable_power_limits {
+                "left to other tools"
+            } else {
+                "applied"
+            }
+        };
+        self.push("Power limits", power_limits(left), power_limits(right));
+
+        let limits: BTreeSet<&String> = left.tdp.keys().chain(right.tdp.keys()).collect();
+        for name in limits {
+            let limit = |profile: &ProfileInfo| match profile.tdp.get(name) {
+                Some(watts) => format!("{watts}W"),
+                None => "unchanged".to_owned(),
+            };
+            self.push(format!("Power limit {name}"), limit(left), limit(right));
+        }
+
+        for idx in 0..left.fans.len().max(right.fans.len()) {
+            self.fans(idx, left.fans.get(idx), right.fans.get(idx))
+                .await;
+        }
+
+        let devices: BTreeSet<(&str, &str)> = left
+            .leds
+            .iter()
+            .chain(&right.leds)
+            .map(|led| (led.device_name.as_str(), led.function.as_str()))
+            .collect();
+        for (device_name, function) in devices {
+            let find =
+                |profile: &'_ ProfileInfo| profile.led_profile(device_name, function).cloned();
+            let device = format!("{device_name}::{function}");
+            self.leds(&device, find(left), find(right)).await;
+        }
+    }
+
+    async fn fans(&mut self, idx: usize, left: Option<&String>, right: Option<&String>) {
+        let fan = format!("Fan {}", idx + 1);
+        let name = |name: Option<&String>| name.cloned().unwrap_or_else(|| "none".to_owned());
+        self.push(format!("{fan} profile"), name(left), name(right));
+
+        let (Some(left), Some(right)) = (left, right) else {
+            return;
+        };
+        if left == right {
+            return;
+        }
+        let (Some(left), Some(right)) =
+            (self.fan_profile(left).await, self.fan_profile(right).await)
+        else {
+            return;
+        };
+
+        // Compare the curves at every temperature that has a point in either profile.
+        let temps: BTreeSet<u8> = left
+            .points
+            .iter()
+            .chain(&right.points)
+            .map(|point| point.temp)
+            .collect();
+        for temp in temps {
+            let at = |profile: &FanProfile| {
+                let mut value = format!("{}%", profile.fan_speed_at(temp));
+                let power_limit = profile.power_limit_at(temp);
+                if power_limit > 0 {
+                    value.push_str(&format!(", power limit {power_limit}%"));
+                }
+                value
+            };
+            self.push(format!("{fan} at {temp}°C"), at(&left), at(&right));
+        }
+
+        let sticky = |profile: &FanProfile| {
+            if profile.sticky {
+                "sticky"
+            } else {
+                "not sticky"
+            }
+        };
+        self.push(format!("{fan} curve"), sticky(&left), sticky(&right));
+
+        let prediction = |profile: &FanProfile| match profile.prediction {
+            Some(prediction) => format!(
+                "+{}% per °C/s, at most +{}%",
+                prediction.gain, prediction.max_boost
+            ),
+            None => "off".to_owned(),
+        };
+        self.push(
+            format!("{fan} prediction"),
+            prediction(&left),
+            prediction(&right),
+        );
+
+        let smoothing = |profile: &FanProfile| match profile.smoothing {
+            Some(smoothing) => format!("{}%", smoothing.alpha),
+            None => "off".to_owned(),
+        };
+        self.push(
+            format!("{fan} smoothing"),
+            smoothing(&left),
+            smoothing(&right),
+        );
+    }
+
+    /// Fan profiles are cached since several fans usually share one.
+    /// Returns `None` if the profile doesn't exist.
+    async fn fan_profile(&mut self, name: &str) -> Option<FanProfile> {
+        if !self.fan_profiles.contains_key(name) {
+            let profile = self.connection.get_fan_profile(name).await.ok();
+            self.fan_profiles.insert(name.to_owned(), profile);
+        }
+        self.fan_profiles[name].clone()
+    }
+
+    async fn leds(&mut self, device: &str, left: Option<LedProfile>, right: Option<LedProfile>) {
+        let name = |led: &Option<LedProfile>| {
+            led.as_ref()
+                .map_or_else(|| "default".to_owned(), |led| led.profile.clone())
+        };
+        self.push(format!("{device} profile"), name(&left), name(&right));
+
+        let (Some(left), Some(right)) = (left, right) else {
+            return;
+        };
+        if left.profile != right.profile {
+            let describe = |profile: Option<ColorProfile>| {
+                profile.map_or_else(|| "missing".to_owned(), |profile| describe_colors(&profile))
+            };
+            let left_colors = describe(self.connection.get_led_profile(&left.profile).await.ok());
+            let right_colors = describe(self.connection.get_led_profile(&right.profile).await.ok());
+            self.push(format!("{device} colors"), left_colors, right_colors);
+        }
+        self.push(
+            format!("{device} mode"),
+            format!("{:?}", left.mode),
+            format!("{:?}", right.mode),
+        );
+    }
+}
+
+fn describe_colors(profile: &ColorProfile) -> String {
+    match profile {
+        ColorProfile::None => "off".to_owned(),
+        ColorProfile::Single(color) => format!("solid {color}"),
+        ColorProfile::Multiple(points) => {
+            let colors: Vec<String> = points
+                .iter()
+                .map(|point| format!("{} ({}ms)", point.color, point.transition_time))
+                .collect();
+            format!("cycling {}", colors.join(" → "))
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use eyre::Result;
+use tailor_api::{TelemetryFormat, TelemetrySample};
+use tailor_client::TailorConnection;
+use tokio::time::Instant;
+
+use crate::cli::RecordFormat;
+
+/// Sample the fan status until the time is up or the user presses Ctrl+C
+pub(crate) async fn handle(
+    output: &Path,
+    seconds: u32,
+    interval: u64,
+    format: Option<RecordFormat>,
+) -> Result<()> {
+    let format = match format {
+        Some(RecordFormat::Csv) => TelemetryFormat::Csv,
+        Some(RecordFormat::Jsonl) => TelemetryFormat::Jsonl,
+        None => TelemetryFormat::from_path(output),
+    };
+    let connection = TailorConnection::new().await?;
+
+    let mut file = BufWriter::new(File::create(output)?);
+    if let Some(header) = format.header() {
+        writeln!(file, "{header}")?;
+    }
+
+    println!(
+        "Recording to `{}` for {seconds}s, press Ctrl+C to stop",
+        output.display()
+    );
+    let deadline = Instant::now() + Duration::from_secs(seconds.into());
+    let mut interval = tokio::time::interval(Duration::from_millis(interval.max(1)));
+    let mut samples = 0;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = tokio::time::sleep_until(deadline) => break,
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs_f64();
+        for status in connection.get_fan_status().await? {
+            writeln!(
+                file,
+                "{}",
+                format.format(&TelemetrySample { timestamp, status })
+            )?;
+        }
+        // Keep the file usable if the recording is interrupted.
+        file.flush()?;
+        samples += 1;
+    }
+
+    println!("Recorded {samples} samples");
+    Ok(())
+}
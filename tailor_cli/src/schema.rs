@@ -0,0 +1,16 @@
+use eyre::Result;
+use schemars::schema_for;
+use tailor_api::{ColorProfile, FanProfile, ProfileInfo};
+
+use crate::cli::SchemaKind;
+
+/// Print the JSON schema of a configuration file format
+pub(crate) fn handle(kind: SchemaKind) -> Result<()> {
+    let schema = match kind {
+        SchemaKind::Fan => schema_for!(FanProfile),
+        SchemaKind::Keyboard => schema_for!(ColorProfile),
+        SchemaKind::Profile => schema_for!(ProfileInfo),
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
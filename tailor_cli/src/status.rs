@@ -0,0 +1,205 @@
+use colored::Colorize;
+use eyre::Result;
+use serde::Serialize;
+use tailor_api::{FanStatus, PowerSource, SelfTestCheck, SelfTestResult};
+use tailor_client::TailorConnection;
+
+/// Width of the first column.
+const LABEL_WIDTH: usize = 14;
+
+#[derive(Serialize)]
+struct Status {
+    profile: String,
+    performance_profile: Option<String>,
+    /// `None` if the webcam can't be controlled.
+    webcam: Option<bool>,
+    /// `None` if the Fn-lock can't be controlled.
+    fn_lock: Option<bool>,
+    /// `None` if the touchpad can't be controlled.
+    touchpad: Option<bool>,
+    power_source: Option<PowerSource>,
+    fans: Vec<FanEntry>,
+    leds: Vec<LedEntry>,
+    /// Other fan control daemons that fight with tailord.
+    conflicting_services: Vec<String>,
+    /// Checks of the self-test at startup that failed.
+    failed_checks: Vec<SelfTestCheck>,
+}
+
+#[derive(Serialize)]
+struct FanEntry {
+    label: String,
+    #[serde(flatten)]
+    status: Option<FanStatus>,
+}
+
+#[derive(Serialize)]
+struct LedEntry {
+    device: String,
+    /// `None` if the active profile doesn't configure this device.
+    profile: Option<String>,
+}
+
+/// Print what the daemon is currently doing
+pub(crate) async fn handle(json: bool) -> Result<()> {
+    let status = read_status().await?;
+    if json {
+        crate::print_json(&status)
+    } else {
+        print_status(&status);
+        Ok(())
+    }
+}
+
+async fn read_status() -> Result<Status> {
+    let connection = TailorConnection::new().await?;
+
+    let active_profile = connection.get_active_global_profile_name().await?;
+    let profile = connection.get_global_profile(&active_profile).await.ok();
+    let devices = connection.list_devices().await?;
+    let fan_status = connection.get_fan_status().await?;
+
+    let fans = devices
+        .fans
+        .into_iter()
+        .map(|fan| FanEntry {
+            status: fan_status
+                .iter()
+                .find(|status| status.index == fan.index)
+                .copied(),
+            label: fan.label,
+        })
+        .collect();
+    let leds = devices
+        .leds
+        .iter()
+        .map(|led| LedEntry {
+            device: led.device_id(),
+            profile: profile.as_ref().and_then(|profile| {
+                profile
+                    .led_profile(&led.device_name, &led.function)
+                    .map(|led_profile| led_profile.profile.clone())
+            }),
+        })
+        .collect();
+
+    Ok(Status {
+        profile: active_profile,
+        performance_profile: connection.get_performance_profile().await.ok(),
+        webcam: connection.get_webcam_enabled().await.ok(),
+        fn_lock: connection.get_fn_lock_enabled().await.ok(),
+        touchpad: connection.get_touchpad_enabled().await.ok(),
+        power_source: connection.get_power_source().await.ok(),
+        fans,
+        leds,
+        conflicting_services: connection
+            .get_conflicting_services()
+            .await
+            .unwrap_or_default(),
+        failed_checks: connection
+            .get_self_test()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(SelfTestCheck::failed)
+            .collect(),
+    })
+}
+
+fn print_status(status: &Status) {
+    print_row("Profile", &status.profile.bold().to_string());
+    print_row(
+        "Performance",
+        &status
+            .performance_profile
+            .clone()
+            .unwrap_or_else(unavailable),
+    );
+    print_row("Webcam", &switch_state(status.webcam));
+    print_row("Fn-lock", &switch_state(status.fn_lock));
+    print_row("Touchpad", &switch_state(status.touchpad));
+    print_row(
+        "Power source",
+        &match status.power_source {
+            Some(PowerSource::Ac) => "AC".to_owned(),
+            Some(PowerSource::Battery) => "Battery".to_owned(),
+            None => unavailable(),
+        },
+    );
+
+    if !status.conflicting_services.is_empty() {
+        print_row(
+            "Conflicts",
+            &format!(
+                "{} also control the fans",
+                status.conflicting_services.join(", ")
+            )
+            .yellow()
+            .to_string(),
+        );
+    }
+
+    for check in &status.failed_checks {
+        if let SelfTestResult::Failed(err) = &check.result {
+            print_row(
+                "Self-test",
+                &format!("{} failed: {err}", check.capability)
+                    .yellow()
+                    .to_string(),
+            );
+        }
+    }
+
+    if !status.fans.is_empty() {
+        println!("{}", "Fans".bold());
+        for fan in &status.fans {
+            let value = fan
+                .status
+                .as_ref()
+                .map(format_fan)
+                .unwrap_or_else(unavailable);
+            print_row(&format!("  {}", fan.label), &value);
+        }
+    }
+
+    if !status.leds.is_empty() {
+        println!("{}", "LEDs".bold());
+        for led in &status.leds {
+            let value = match &led.profile {
+                Some(profile) => profile.clone(),
+                None => "default".dimmed().to_string(),
+            };
+            print_row(&format!("  {}", led.device), &value);
+        }
+    }
+}
+
+fn print_row(label: &str, value: &str) {
+    // Pad before coloring, escape codes would count towards the width.
+    println!("{}  {value}", format!("{label:<LABEL_WIDTH$}").bold());
+}
+
+fn format_fan(status: &FanStatus) -> String {
+    let speed = match status.target {
+        Some(target) if target != status.speed => format!("{:>3}% → {target}%", status.speed),
+        Some(_) => format!("{:>3}%", status.speed),
+        None => format!("{:>3}% {}", status.speed, "(firmware)".dimmed()),
+    };
+    let mut value = format!("{:>3}°C  {speed}", status.temperature);
+    if status.power_limit > 0 {
+        value.push_str(&format!("  power limit {}%", status.power_limit));
+    }
+    value
+}
+
+fn unavailable() -> String {
+    "unavailable".dimmed().to_string()
+}
+
+fn switch_state(state: Option<bool>) -> String {
+    match state {
+        Some(true) => "on".to_owned(),
+        Some(false) => "off".to_owned(),
+        None => unavailable(),
+    }
+}
@@ -0,0 +1,34 @@
+use eyre::Result;
+use tailor_client::TailorConnection;
+
+use crate::cli::TouchpadCommand;
+
+/// Handle touchpad commands
+pub(crate) async fn handle(cmd: TouchpadCommand) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        TouchpadCommand::On { temporary } => set(&connection, true, temporary).await?,
+        TouchpadCommand::Off { temporary } => set(&connection, false, temporary).await?,
+        TouchpadCommand::Status => {
+            let enabled = connection.get_touchpad_enabled().await?;
+            println!("{}", if enabled { "on" } else { "off" });
+        }
+    }
+    Ok(())
+}
+
+/// Switch the touchpad and store the state in the active profile unless `temporary` is set.
+async fn set(connection: &TailorConnection<'_>, enabled: bool, temporary: bool) -> Result<()> {
+    connection.set_touchpad_enabled(enabled).await?;
+    if !temporary {
+        let active_profile = connection.get_active_global_profile_name().await?;
+        let mut profile = connection.get_global_profile(&active_profile).await?;
+        if profile.touchpad != Some(enabled) {
+            profile.touchpad = Some(enabled);
+            connection
+                .add_global_profile(&active_profile, &profile)
+                .await?;
+        }
+    }
+    Ok(())
+}
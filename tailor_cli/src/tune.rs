@@ -0,0 +1,54 @@
+use std::{fs, path::Path};
+
+use eyre::{eyre, Result};
+use tailor_api::{
+    tuning::{self, TuningGoal},
+    TelemetryFormat,
+};
+use tailor_client::TailorConnection;
+
+use crate::fan_editor;
+
+/// Print the changes the tuner proposes for fan profile `name`
+/// and store the result if `save` is given.
+pub(crate) async fn handle(
+    connection: &TailorConnection<'_>,
+    name: &str,
+    recording: &Path,
+    goal: TuningGoal,
+    fan: Option<u8>,
+    save: Option<&str>,
+) -> Result<()> {
+    let format = TelemetryFormat::from_path(recording);
+    let mut samples = Vec::new();
+    for (idx, line) in fs::read_to_string(recording)?.lines().enumerate() {
+        let sample = format
+            .parse(line)
+            .map_err(|err| eyre!("{}:{}: {err}", recording.display(), idx + 1))?;
+        samples.extend(sample);
+    }
+    if let Some(fan) = fan {
+        samples.retain(|sample| Some(sample.status.index) == fan.checked_sub(1));
+    }
+
+    let profile = connection.get_fan_profile(name).await?;
+    let tuning = tuning::tune(&profile, &samples, &goal);
+    if tuning.adjustments.is_empty() {
+        println!("The curve of `{name}` already fits the recording");
+        return Ok(());
+    }
+    for adjustment in &tuning.adjustments {
+        println!("- {adjustment}");
+    }
+    println!();
+    fan_editor::print_profile(&tuning.profile);
+
+    match save {
+        Some(save) => {
+            connection.add_fan_profile(save, &tuning.profile).await?;
+            println!("Stored the proposed curve as fan profile `{save}`");
+        }
+        None => println!("Use `--save <name>` to store the proposed curve"),
+    }
+    Ok(())
+}
@@ -0,0 +1,322 @@
+use std::{io::Stdout, time::Duration};
+
+use crossterm::{
+    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use eyre::Result;
+use futures_util::StreamExt;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Sparkline},
+    Frame, Terminal,
+};
+use tailor_api::{FanDeviceInfo, FanStatus};
+use tailor_client::TailorConnection;
+
+/// Interval between two samples of the graphs.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of samples kept for the graphs.
+const HISTORY_LENGTH: usize = 300;
+/// Duration of a fan boost triggered with `b`.
+const BOOST_SECONDS: u32 = 60;
+
+/// Show live fan data until the user quits
+pub(crate) async fn handle() -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    let mut app = App::new(&connection).await?;
+
+    let mut terminal = TerminalGuard::new()?;
+    let mut status_stream = Box::pin(connection.receive_fan_status().await?);
+    let mut events = EventStream::new();
+    let mut sample_interval = tokio::time::interval(SAMPLE_INTERVAL);
+
+    loop {
+        terminal.0.draw(|frame| app.draw(frame))?;
+
+        tokio::select! {
+            Some(status) = status_stream.next() => app.update_status(status),
+            _ = sample_interval.tick() => app.sample(),
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    if !app.handle_key(&connection, key).await {
+                        break;
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err.into()),
+                None => break,
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Restores the terminal on drop, even if the event loop failed.
+struct TerminalGuard(Terminal<CrosstermBackend<Stdout>>);
+
+impl TerminalGuard {
+    fn new() -> Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = std::io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        Ok(Self(Terminal::new(CrosstermBackend::new(stdout))?))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        disable_raw_mode().ok();
+        execute!(self.0.backend_mut(), LeaveAlternateScreen).ok();
+        self.0.show_cursor().ok();
+    }
+}
+
+struct FanHistory {
+    info: FanDeviceInfo,
+    status: FanStatus,
+    temperatures: Vec<u64>,
+    speeds: Vec<u64>,
+}
+
+struct App {
+    fans: Vec<FanHistory>,
+    profiles: Vec<String>,
+    active_profile: String,
+    selection: ListState,
+    message: Option<String>,
+}
+
+impl App {
+    async fn new(connection: &TailorConnection<'_>) -> Result<Self> {
+        let devices = connection.list_devices().await?;
+        let status = connection.get_fan_status().await?;
+        let fans = devices
+            .fans
+            .into_iter()
+            .map(|info| FanHistory {
+                status: status
+                    .iter()
+                    .find(|status| status.index == info.index)
+                    .copied()
+                    .unwrap_or(FanStatus {
+                        index: info.index,
+                        ..Default::default()
+                    }),
+                info,
+                temperatures: Vec::new(),
+                speeds: Vec::new(),
+            })
+            .collect();
+
+        let profiles = connection.list_global_profiles().await?;
+        let active_profile = connection.get_active_global_profile_name().await?;
+        let mut selection = ListState::default();
+        selection.select(profiles.iter().position(|name| name == &active_profile));
+
+        let mut app = Self {
+            fans,
+            profiles,
+            active_profile,
+            selection,
+            message: None,
+        };
+        app.sample();
+        Ok(app)
+    }
+
+    fn update_status(&mut self, status: FanStatus) {
+        if let Some(fan) = self
+            .fans
+            .iter_mut()
+            .find(|fan| fan.info.index == status.index)
+        {
+            fan.status = status;
+        }
+    }
+
+    /// Add the latest values to the graphs.
+    fn sample(&mut self) {
+        for fan in &mut self.fans {
+            for (history, value) in [
+                (&mut fan.temperatures, fan.status.temperature),
+                (&mut fan.speeds, fan.status.speed),
+            ] {
+                if history.len() == HISTORY_LENGTH {
+                    history.remove(0);
+                }
+                history.push(value.into());
+            }
+        }
+    }
+
+    /// Returns `false` if the user wants to quit.
+    async fn handle_key(&mut self, connection: &TailorConnection<'_>, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Enter => {
+                if let Some(name) = self
+                    .selection
+                    .selected()
+                    .and_then(|idx| self.profiles.get(idx))
+                    .cloned()
+                {
+                    let result = async {
+                        connection.set_active_global_profile_name(&name).await?;
+                        connection.reload().await
+                    }
+                    .await;
+                    self.message = Some(match result {
+                        Ok(()) => {
+                            let message = format!("Activated profile `{name}`");
+                            self.active_profile = name;
+                            message
+                        }
+                        Err(err) => format!("Failed to activate profile: {err}"),
+                    });
+                }
+            }
+            KeyCode::Char('b') => {
+                self.message = Some(match connection.boost_fans(BOOST_SECONDS).await {
+                    Ok(()) => format!("Boosting fans for {BOOST_SECONDS}s"),
+                    Err(err) => format!("Failed to boost fans: {err}"),
+                });
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        if self.profiles.is_empty() {
+            return;
+        }
+        let current = self.selection.selected().unwrap_or(0) as isize;
+        let next = (current + offset).rem_euclid(self.profiles.len() as isize);
+        self.selection.select(Some(next as usize));
+    }
+
+    fn draw(&mut self, frame: &mut Frame<'_>) {
+        let [main, footer] = split(
+            frame.size(),
+            Direction::Vertical,
+            [Constraint::Min(0), Constraint::Length(1)],
+        );
+        let [fans, profiles] = split(
+            main,
+            Direction::Horizontal,
+            [Constraint::Min(0), Constraint::Length(24)],
+        );
+
+        if self.fans.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No fans available").block(Block::default().borders(Borders::ALL)),
+                fans,
+            );
+        } else {
+            let constraints = vec![Constraint::Ratio(1, self.fans.len() as u32); self.fans.len()];
+            let areas = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(constraints)
+                .split(fans);
+            for (fan, area) in self.fans.iter().zip(areas.iter()) {
+                draw_fan(frame, fan, *area);
+            }
+        }
+
+        let items: Vec<_> = self
+            .profiles
+            .iter()
+            .map(|name| {
+                if name == &self.active_profile {
+                    ListItem::new(format!("{name} (active)"))
+                        .style(Style::default().fg(Color::Green))
+                } else {
+                    ListItem::new(name.as_str())
+                }
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Profiles"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, profiles, &mut self.selection);
+
+        let help = "q quit  ↑/↓ select profile  enter activate  b boost fans";
+        let footer_text = match &self.message {
+            Some(message) => format!("{message}  |  {help}"),
+            None => help.to_owned(),
+        };
+        frame.render_widget(
+            Paragraph::new(footer_text).style(Style::default().add_modifier(Modifier::DIM)),
+            footer,
+        );
+    }
+}
+
+fn draw_fan(frame: &mut Frame<'_>, fan: &FanHistory, area: Rect) {
+    let status = &fan.status;
+    let mut title = format!(
+        "{}  {}°C  {}%",
+        fan.info.label, status.temperature, status.speed
+    );
+    match status.target {
+        Some(target) if target != status.speed => title.push_str(&format!(" → {target}%")),
+        Some(_) => {}
+        None => title.push_str(" (firmware)"),
+    }
+    if status.power_limit > 0 {
+        title.push_str(&format!("  power limit {}%", status.power_limit));
+    }
+
+    let block = Block::default().borders(Borders::ALL).title(Span::styled(
+        title,
+        Style::default().add_modifier(Modifier::BOLD),
+    ));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [temperature, speed] = split(
+        inner,
+        Direction::Horizontal,
+        [Constraint::Percentage(50), Constraint::Percentage(50)],
+    );
+    draw_graph(
+        frame,
+        "Temperature",
+        &fan.temperatures,
+        Color::Red,
+        temperature,
+    );
+    draw_graph(frame, "Speed", &fan.speeds, Color::Cyan, speed);
+}
+
+fn draw_graph(frame: &mut Frame<'_>, title: &str, data: &[u64], color: Color, area: Rect) {
+    // Only show the newest samples that fit.
+    let visible = usize::from(area.width.saturating_sub(2));
+    let data = &data[data.len().saturating_sub(visible)..];
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .data(data)
+        .max(100)
+        .style(Style::default().fg(color));
+    frame.render_widget(sparkline, area);
+}
+
+fn split<const N: usize>(
+    area: Rect,
+    direction: Direction,
+    constraints: [Constraint; N],
+) -> [Rect; N] {
+    let areas = Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area);
+    std::array::from_fn(|idx| areas[idx])
+}
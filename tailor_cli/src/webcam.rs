@@ -0,0 +1,34 @@
+use eyre::Result;
+use tailor_client::TailorConnection;
+
+use crate::cli::WebcamCommand;
+
+/// Handle webcam commands
+pub(crate) async fn handle(cmd: WebcamCommand) -> Result<()> {
+    let connection = TailorConnection::new().await?;
+    match cmd {
+        WebcamCommand::On { temporary } => set(&connection, true, temporary).await?,
+        WebcamCommand::Off { temporary } => set(&connection, false, temporary).await?,
+        WebcamCommand::Status => {
+            let enabled = connection.get_webcam_enabled().await?;
+            println!("{}", if enabled { "on" } else { "off" });
+        }
+    }
+    Ok(())
+}
+
+/// Switch the webcam and store the state in the active profile unless `temporary` is set.
+async fn set(connection: &TailorConnection<'_>, enabled: bool, temporary: bool) -> Result<()> {
+    connection.set_webcam_enabled(enabled).await?;
+    if !temporary {
+        let active_profile = connection.get_active_global_profile_name().await?;
+        let mut profile = connection.get_global_profile(&active_profile).await?;
+        if profile.webcam != Some(enabled) {
+            profile.webcam = Some(enabled);
+            connection
+                .add_global_profile(&active_profile, &profile)
+                .await?;
+        }
+    }
+    Ok(())
+}
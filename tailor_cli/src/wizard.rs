@@ -0,0 +1,207 @@
+use std::io::{self, Write};
+
+use colored::Colorize;
+use eyre::{bail, Result};
+use tailor_api::{Color, ColorProfile, LedProfile, PowerSource, ProfileInfo};
+use tailor_client::TailorConnection;
+
+/// Create a global profile by asking for each of its settings
+pub(crate) async fn create(connection: &TailorConnection<'_>, name: &str) -> Result<()> {
+    if connection
+        .list_global_profiles()
+        .await?
+        .iter()
+        .any(|profile| profile == name)
+        && !confirm(
+            &format!("Profile `{name}` already exists, overwrite it?"),
+            false,
+        )?
+    {
+        return Ok(());
+    }
+
+    let fan_profiles = connection.list_fan_profiles().await?;
+    if fan_profiles.is_empty() {
+        bail!("There are no fan profiles, create one with `tailor fan edit <name>` first");
+    }
+    let fan = &fan_profiles[choose("Fan profile", &fan_profiles, 0)?];
+    let fans = vec![fan.clone(); connection.get_number_of_fans().await?.max(1).into()];
+
+    let mut keyboard_profiles = connection.list_led_profiles().await?;
+    let mut leds = Vec::new();
+    for device in connection.get_led_devices().await? {
+        let mut options = keyboard_profiles.clone();
+        options.push("New solid color".to_owned());
+        let question = format!("Keyboard profile for {}", device.device_id());
+        let profile = match keyboard_profiles.get(choose(&question, &options, 0)?) {
+            Some(profile) => profile.clone(),
+            None => {
+                let color = ask_color()?;
+                let profile = unused_name(name, &keyboard_profiles);
+                connection
+                    .add_led_profile(&profile, &ColorProfile::Single(color))
+                    .await?;
+                keyboard_profiles.push(profile.clone());
+                profile
+            }
+        };
+        leds.push(LedProfile {
+            device_name: device.device_name,
+            function: device.function,
+            profile,
+            mode: device.mode,
+        });
+    }
+
+    // Not every device supports performance profiles.
+    let performance_profile = match connection.list_performance_profiles().await {
+        Ok(profiles) if !profiles.is_empty() => {
+            let mut options = vec!["Firmware default".to_owned()];
+            options.extend(profiles.iter().cloned());
+            match choose("Performance profile", &options, 0)? {
+                0 => None,
+                idx => Some(profiles[idx - 1].clone()),
+            }
+        }
+        _ => None,
+    };
+
+    let webcam = if connection.get_webcam_enabled().await.is_ok() {
+        choose_switch("Webcam")?
+    } else {
+        None
+    };
+
+    let fn_lock = if connection.get_fn_lock_enabled().await.is_ok() {
+        choose_switch("Fn-lock")?
+    } else {
+        None
+    };
+
+    let touchpad = if connection.get_touchpad_enabled().await.is_ok() {
+        choose_switch("Touchpad")?
+    } else {
+        None
+    };
+
+    let power_source = match choose(
+        "Activate automatically",
+        &[
+            "Never".to_owned(),
+            "When plugged in".to_owned(),
+            "When on battery".to_owned(),
+        ],
+        0,
+    )? {
+        1 => Some(PowerSource::Ac),
+        2 => Some(PowerSource::Battery),
+        _ => None,
+    };
+
+    let profile = ProfileInfo {
+        fans,
+        leds,
+        performance_profile,
+        webcam,
+        fn_lock,
+        touchpad,
+        tdp: Default::default(),
+        cpu_governor: None,
+        energy_performance_preference: None,
+        display_brightness: None,
+        lid_closed: Default::default(),
+        disable_power_limits: false,
+    };
+    connection.add_global_profile(name, &profile).await?;
+    println!("Created profile `{}`", name.bold());
+
+    if let Some(source) = power_source {
+        connection
+            .set_power_source_profile(source, Some(name))
+            .await?;
+        println!("`{name}` is activated on {source} power");
+    }
+
+    if confirm("Activate the profile now?", true)? {
+        connection.set_active_global_profile_name(name).await?;
+        connection.reload().await?;
+    }
+    Ok(())
+}
+
+/// A keyboard profile name based on `name` that isn't taken yet.
+fn unused_name(name: &str, existing: &[String]) -> String {
+    let mut candidate = name.to_owned();
+    let mut counter = 2;
+    while existing.contains(&candidate) {
+        candidate = format!("{name}-{counter}");
+        counter += 1;
+    }
+    candidate
+}
+
+fn read_line(prompt: &str) -> Result<String> {
+    print!("{prompt} ");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        bail!("Aborted");
+    }
+    Ok(line.trim().to_owned())
+}
+
+/// Let the user pick one of `options`, returning its index.
+fn choose(question: &str, options: &[String], default: usize) -> Result<usize> {
+    println!("{}", question.bold());
+    for (idx, option) in options.iter().enumerate() {
+        println!("  {}) {option}", idx + 1);
+    }
+    loop {
+        let answer = read_line(&format!("Choice [{}]:", default + 1))?;
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        match answer.parse::<usize>() {
+            Ok(choice) if (1..=options.len()).contains(&choice) => return Ok(choice - 1),
+            _ => println!(
+                "{}",
+                format!("Enter a number from 1 to {}", options.len()).red()
+            ),
+        }
+    }
+}
+
+/// Ask whether a switch should be turned on, off or left unchanged.
+fn choose_switch(question: &str) -> Result<Option<bool>> {
+    let options = ["Unchanged".to_owned(), "On".to_owned(), "Off".to_owned()];
+    Ok(match choose(question, &options, 0)? {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    })
+}
+
+pub(crate) fn confirm(question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    loop {
+        match read_line(&format!("{} {hint}", question.bold()))?
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("{}", "Answer yes or no".red()),
+        }
+    }
+}
+
+fn ask_color() -> Result<Color> {
+    loop {
+        let answer = read_line(&format!("{}", "Color (e.g. #00ff88):".bold()))?;
+        match crate::parse_color(&answer) {
+            Ok(color) => return Ok(color),
+            Err(err) => println!("{}", err.to_string().red()),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use zbus::proxy;
+
+use crate::TailorError;
+
+#[proxy(
+    interface = "com.tux.Tailor.Charging",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Charging {
+    /// Read which charging settings are supported.
+    async fn get_capabilities(&self) -> Result<String, TailorError>;
+
+    /// Read the current charging settings from the hardware.
+    async fn get_settings(&self) -> Result<String, TailorError>;
+
+    /// Apply the charging settings. Settings that are `null` are left unchanged.
+    /// The result is persisted and applied again when tailord starts.
+    async fn set_settings(&self, value: &str) -> Result<(), TailorError>;
+}
@@ -1,4 +1,6 @@
-use zbus::{fdo, proxy};
+use zbus::proxy;
+
+use crate::TailorError;
 
 #[proxy(
     interface = "com.tux.Tailor.Fan",
@@ -6,15 +8,43 @@ use zbus::{fdo, proxy};
     default_path = "/com/tux/Tailor"
 )]
 trait Fan {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()>;
+    async fn add_profile(&self, name: &str, value: &str) -> Result<(), TailorError>;
+
+    async fn get_profile(&self, name: &str) -> Result<String, TailorError>;
+
+    async fn list_profiles(&self) -> Result<Vec<String>, TailorError>;
+
+    async fn remove_profile(&self, name: &str) -> Result<(), TailorError>;
+
+    async fn list_presets(&self) -> Result<Vec<String>, TailorError>;
+
+    async fn get_preset(&self, name: &str) -> Result<String, TailorError>;
+
+    async fn rename_profile(&self, from: &str, to: &str) -> Result<Vec<String>, TailorError>;
+
+    async fn override_speed(&self, fan_idx: u8, speed: u8) -> Result<(), TailorError>;
+
+    async fn boost_fans(&self, seconds: u32) -> Result<(), TailorError>;
+
+    async fn pin_speed(&self, fan_idx: u8, speed: u8, seconds: u32) -> Result<(), TailorError>;
+
+    async fn preview_profile(
+        &self,
+        fan_idx: u8,
+        value: &str,
+        seconds: u32,
+    ) -> Result<(), TailorError>;
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String>;
+    async fn release_fans(&self) -> Result<(), TailorError>;
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
+    async fn get_status(&self) -> Result<String, TailorError>;
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()>;
+    #[zbus(signal)]
+    fn status_changed(&self, status: &str) -> zbus::Result<()>;
 
-    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>>;
+    #[zbus(signal)]
+    fn sensor_failed(&self, fan_idx: u8, error: &str) -> zbus::Result<()>;
 
-    async fn override_speed(&self, fan_idx: u8, speed: u8) -> fdo::Result<()>;
+    #[zbus(signal)]
+    fn thermal_trip(&self, fan_idx: u8, temperature: u8) -> zbus::Result<()>;
 }
@@ -1,4 +1,6 @@
-use zbus::{fdo, proxy};
+use zbus::proxy;
+
+use crate::TailorError;
 
 #[proxy(
     interface = "com.tux.Tailor.Led",
@@ -6,15 +8,26 @@ use zbus::{fdo, proxy};
     default_path = "/com/tux/Tailor"
 )]
 trait Led {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()>;
+    async fn add_profile(&self, name: &str, value: &str) -> Result<(), TailorError>;
+
+    async fn get_profile(&self, name: &str) -> Result<String, TailorError>;
+
+    async fn list_profiles(&self) -> Result<Vec<String>, TailorError>;
+
+    async fn remove_profile(&self, name: &str) -> Result<(), TailorError>;
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String>;
+    async fn rename_profile(&self, from: &str, to: &str) -> Result<Vec<String>, TailorError>;
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
+    async fn override_color(&self, color: &str) -> Result<(), TailorError>;
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()>;
+    async fn set_temporary_color(&self, color: &str) -> Result<(), TailorError>;
 
-    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>>;
+    async fn identify_led_device(&self, id: &str) -> Result<(), TailorError>;
 
-    async fn override_color(&self, color: &str) -> fdo::Result<()>;
+    async fn flash_alert(
+        &self,
+        color: &str,
+        pattern: &str,
+        seconds: u32,
+    ) -> Result<(), TailorError>;
 }
@@ -1,9 +1,19 @@
+mod charging;
 mod fan;
+mod fn_lock;
 mod led;
 mod performance;
 mod profiles;
+mod settings;
+mod touchpad;
+mod webcam;
 
+pub(crate) use charging::ChargingProxy;
 pub(crate) use fan::FanProxy;
+pub(crate) use fn_lock::FnLockProxy;
 pub(crate) use led::LedProxy;
 pub(crate) use performance::PerformanceProxy;
 pub(crate) use profiles::ProfilesProxy;
+pub(crate) use settings::SettingsProxy;
+pub(crate) use touchpad::TouchpadProxy;
+pub(crate) use webcam::WebcamProxy;
@@ -1,4 +1,6 @@
-use zbus::{fdo, proxy};
+use zbus::proxy;
+
+use crate::TailorError;
 
 #[proxy(
     interface = "com.tux.Tailor.Performance",
@@ -8,11 +10,17 @@ use zbus::{fdo, proxy};
 trait Performance {
     /// Temporarily override the performance profile. Please note that this will not survive a
     /// restart as the performance profile is handled by the overall profile configuration.
-    async fn set_profile(&self, name: &str, value: &str) -> fdo::Result<()>;
+    async fn set_profile(&self, name: &str) -> Result<(), TailorError>;
 
     /// Read the current performance profile.
-    async fn get_profile(&self, name: &str) -> fdo::Result<String>;
+    async fn get_profile(&self) -> Result<String, TailorError>;
 
     /// Read the list of supported performance profiles.
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
+    async fn list_profiles(&self) -> Result<Vec<String>, TailorError>;
+
+    /// Read the power limits (TDP) of the platform as JSON.
+    async fn list_power_limits(&self) -> Result<String, TailorError>;
+
+    /// Temporarily set a power limit in watts until the next profile is activated.
+    async fn set_power_limit(&self, name: &str, value: i32) -> Result<(), TailorError>;
 }
@@ -1,4 +1,6 @@
-use zbus::{fdo, proxy};
+use zbus::proxy;
+
+use crate::TailorError;
 
 #[proxy(
     interface = "com.tux.Tailor.Profiles",
@@ -6,23 +8,44 @@ use zbus::{fdo, proxy};
     default_path = "/com/tux/Tailor"
 )]
 trait Profiles {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()>;
+    async fn add_profile(&self, name: &str, value: &str) -> Result<(), TailorError>;
+
+    async fn get_profile(&self, name: &str) -> Result<String, TailorError>;
+
+    async fn list_profiles(&self) -> Result<Vec<String>, TailorError>;
+
+    async fn remove_profile(&self, name: &str) -> Result<(), TailorError>;
+
+    async fn rename_profile(&self, from: &str, to: &str) -> Result<Vec<String>, TailorError>;
+
+    async fn negotiate_wire_version(&self, client_version: u32) -> Result<u32, TailorError>;
+
+    async fn set_active_profile_name(&self, name: &str) -> Result<(), TailorError>;
+
+    async fn get_active_profile_name(&self) -> Result<String, TailorError>;
+
+    async fn get_power_source(&self) -> Result<String, TailorError>;
+
+    async fn get_number_of_fans(&self) -> Result<u8, TailorError>;
+
+    async fn get_led_devices(&self) -> Result<String, TailorError>;
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String>;
+    async fn list_devices(&self) -> Result<String, TailorError>;
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>>;
+    async fn reload(&self) -> Result<(), TailorError>;
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()>;
+    async fn reload_fan_profile(&self) -> Result<(), TailorError>;
 
-    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>>;
+    async fn reload_led_profile(&self) -> Result<(), TailorError>;
 
-    async fn set_active_profile_name(&self, name: &str) -> fdo::Result<()>;
+    async fn get_profile_history(&self) -> Result<String, TailorError>;
 
-    async fn get_active_profile_name(&self) -> fdo::Result<String>;
+    async fn get_diagnostics(&self) -> Result<String, TailorError>;
 
-    async fn get_number_of_fans(&self) -> fdo::Result<u8>;
+    async fn get_self_test(&self) -> Result<String, TailorError>;
 
-    async fn get_led_devices(&self) -> fdo::Result<String>;
+    async fn dump_state(&self) -> Result<(), TailorError>;
 
-    async fn reload(&self) -> fdo::Result<()>;
+    #[zbus(signal)]
+    fn profile_activated(&self, switch: &str) -> zbus::Result<()>;
 }
@@ -0,0 +1,34 @@
+use zbus::proxy;
+
+use crate::TailorError;
+
+#[proxy(
+    interface = "com.tux.Tailor.Settings",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Settings {
+    async fn get_subsystems(&self) -> Result<String, TailorError>;
+
+    async fn set_subsystem_enabled(
+        &self,
+        subsystem: &str,
+        enabled: bool,
+    ) -> Result<(), TailorError>;
+
+    async fn get_power_source_profiles(&self) -> Result<String, TailorError>;
+
+    async fn set_power_source_profile(
+        &self,
+        source: &str,
+        profile: &str,
+    ) -> Result<(), TailorError>;
+
+    async fn set_log_level(&self, target: &str, level: &str) -> Result<(), TailorError>;
+
+    #[zbus(property)]
+    fn conflicting_services(&self) -> zbus::Result<Vec<String>>;
+
+    #[zbus(signal)]
+    fn subsystems_changed(&self, subsystems: &str) -> zbus::Result<()>;
+}
@@ -0,0 +1,16 @@
+use zbus::proxy;
+
+use crate::TailorError;
+
+#[proxy(
+    interface = "com.tux.Tailor.Touchpad",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Touchpad {
+    /// Switch the touchpad on or off until the next profile is activated.
+    async fn set_enabled(&self, enabled: bool) -> Result<(), TailorError>;
+
+    /// Whether the touchpad is switched on.
+    async fn get_enabled(&self) -> Result<bool, TailorError>;
+}
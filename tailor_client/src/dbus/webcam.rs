@@ -0,0 +1,16 @@
+use zbus::proxy;
+
+use crate::TailorError;
+
+#[proxy(
+    interface = "com.tux.Tailor.Webcam",
+    default_service = "com.tux.Tailor",
+    default_path = "/com/tux/Tailor"
+)]
+trait Webcam {
+    /// Switch the webcam on or off until the next profile is activated.
+    async fn set_enabled(&self, enabled: bool) -> Result<(), TailorError>;
+
+    /// Whether the webcam is switched on.
+    async fn get_enabled(&self) -> Result<bool, TailorError>;
+}
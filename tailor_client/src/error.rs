@@ -1,10 +1,40 @@
+use tailor_api::wire::WireError;
 use thiserror::Error;
-use zbus::fdo;
+use zbus::DBusError;
 
 #[derive(Error, Debug)]
 pub enum ClientError {
     #[error("Bus response error: `{0}`")]
-    BusError(#[from] fdo::Error),
+    BusError(#[from] TailorError),
     #[error("Serialization error: `{0}`")]
     Serialization(#[from] serde_json::Error),
+    #[error("Wire format error: `{0}`")]
+    Wire(#[from] WireError),
+}
+
+/// Named errors returned by tailord (`com.tux.Tailor.Error.*`).
+///
+/// Errors that don't belong to tailord (e.g. connection problems)
+/// are reported as [`TailorError::ZBus`].
+#[derive(Debug, DBusError)]
+#[zbus(prefix = "com.tux.Tailor.Error")]
+pub enum TailorError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    /// The requested profile doesn't exist.
+    ProfileNotFound(String),
+    /// A fan curve couldn't be used.
+    InvalidCurve(String),
+    /// The requested hardware isn't available on this device.
+    HardwareUnavailable(String),
+    /// The operation isn't permitted.
+    NotAuthorized(String),
+    /// The arguments of a call are invalid.
+    InvalidArgs(String),
+    /// Reading or writing a file failed.
+    IOError(String),
+    /// A stored file has invalid content.
+    InvalidFileContent(String),
+    /// An internal error occurred.
+    Failed(String),
 }
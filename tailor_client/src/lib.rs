@@ -3,8 +3,14 @@
 mod dbus;
 mod error;
 
-pub use error::ClientError;
-use tailor_api::{Color, ColorProfile, FanProfilePoint, LedDeviceInfo, ProfileInfo};
+pub use error::{ClientError, TailorError};
+use futures_util::{Stream, StreamExt};
+use tailor_api::{
+    wire::{self, LEGACY_VERSION, WIRE_VERSION},
+    AlertPattern, ChargingCapabilities, ChargingSettings, Color, ColorProfile, DeviceList,
+    FanProfile, FanStatus, LedDeviceInfo, PowerLimit, PowerSource, PowerSourceProfiles, Preset,
+    ProfileInfo, ProfileSwitch, SelfTestCheck, Subsystem, SubsystemStates,
+};
 use zbus::Connection;
 
 pub type ClientResult<T> = Result<T, ClientError>;
@@ -15,6 +21,13 @@ pub struct TailorConnection<'a> {
     led: dbus::LedProxy<'a>,
     fan: dbus::FanProxy<'a>,
     performance: dbus::PerformanceProxy<'a>,
+    settings: dbus::SettingsProxy<'a>,
+    webcam: dbus::WebcamProxy<'a>,
+    fn_lock: dbus::FnLockProxy<'a>,
+    touchpad: dbus::TouchpadProxy<'a>,
+    charging: dbus::ChargingProxy<'a>,
+    /// Wire format version of profile payloads, see [`tailor_api::wire`].
+    wire_version: u32,
 }
 
 impl<'a> TailorConnection<'a> {
@@ -25,25 +38,42 @@ impl<'a> TailorConnection<'a> {
         let keyboard = dbus::LedProxy::new(&connection).await?;
         let fan = dbus::FanProxy::new(&connection).await?;
         let performance = dbus::PerformanceProxy::new(&connection).await?;
+        let settings = dbus::SettingsProxy::new(&connection).await?;
+        let webcam = dbus::WebcamProxy::new(&connection).await?;
+        let fn_lock = dbus::FnLockProxy::new(&connection).await?;
+        let touchpad = dbus::TouchpadProxy::new(&connection).await?;
+        let charging = dbus::ChargingProxy::new(&connection).await?;
+
+        // Daemons without versioned payloads don't know this method.
+        let wire_version = profiles
+            .negotiate_wire_version(WIRE_VERSION)
+            .await
+            .unwrap_or(LEGACY_VERSION);
 
         Ok(Self {
             profiles,
             led: keyboard,
             fan,
             performance,
+            settings,
+            webcam,
+            fn_lock,
+            touchpad,
+            charging,
+            wire_version,
         })
     }
 }
 
 impl<'a> TailorConnection<'a> {
     pub async fn add_led_profile(&self, name: &str, profile: &ColorProfile) -> ClientResult<()> {
-        let value = serde_json::to_string(profile)?;
+        let value = wire::encode(profile, self.wire_version)?;
         Ok(self.led.add_profile(name, &value).await?)
     }
 
     pub async fn get_led_profile(&self, name: &str) -> ClientResult<ColorProfile> {
         let profile_data = self.led.get_profile(name).await?;
-        Ok(serde_json::from_str(&profile_data)?)
+        Ok(wire::decode(&profile_data)?)
     }
 
     pub async fn list_led_profiles(&self) -> ClientResult<Vec<String>> {
@@ -67,21 +97,43 @@ impl<'a> TailorConnection<'a> {
         let value = serde_json::to_string(color)?;
         Ok(self.led.override_color(&value).await?)
     }
-}
 
-impl<'a> TailorConnection<'a> {
-    pub async fn add_fan_profile(
+    /// Show a solid color on all LED devices until the next profile is activated.
+    pub async fn set_temporary_led_color(&self, color: &Color) -> ClientResult<()> {
+        let value = serde_json::to_string(color)?;
+        Ok(self.led.set_temporary_color(&value).await?)
+    }
+
+    /// Flash `color` on all LED devices for `seconds` (max. 60),
+    /// then show the colors of the active profile again.
+    pub async fn flash_led_alert(
         &self,
-        name: &str,
-        profile: &[FanProfilePoint],
+        color: &Color,
+        pattern: AlertPattern,
+        seconds: u32,
     ) -> ClientResult<()> {
-        let value = serde_json::to_string(profile)?;
+        let value = serde_json::to_string(color)?;
+        Ok(self
+            .led
+            .flash_alert(&value, pattern.name(), seconds)
+            .await?)
+    }
+
+    /// Flash a distinctive pattern on the given LED device for a few seconds.
+    pub async fn identify_led_device(&self, device: &LedDeviceInfo) -> ClientResult<()> {
+        Ok(self.led.identify_led_device(&device.device_id()).await?)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    pub async fn add_fan_profile(&self, name: &str, profile: &FanProfile) -> ClientResult<()> {
+        let value = wire::encode(profile, self.wire_version)?;
         Ok(self.fan.add_profile(name, &value).await?)
     }
 
-    pub async fn get_fan_profile(&self, name: &str) -> ClientResult<Vec<FanProfilePoint>> {
+    pub async fn get_fan_profile(&self, name: &str) -> ClientResult<FanProfile> {
         let profile_data = self.fan.get_profile(name).await?;
-        Ok(serde_json::from_str(&profile_data)?)
+        Ok(wire::decode(&profile_data)?)
     }
 
     pub async fn list_fan_profiles(&self) -> ClientResult<Vec<String>> {
@@ -101,20 +153,108 @@ impl<'a> TailorConnection<'a> {
         Ok(self.fan.remove_profile(name).await?)
     }
 
+    /// Names of the built-in fan curves that tailord offers.
+    pub async fn list_fan_presets(&self) -> ClientResult<Vec<String>> {
+        Ok(self.fan.list_presets().await?)
+    }
+
+    /// The curve of a built-in preset, which can be stored with [`Self::add_fan_profile`].
+    pub async fn get_fan_preset(&self, preset: Preset) -> ClientResult<FanProfile> {
+        let profile_data = self.fan.get_preset(preset.name()).await?;
+        Ok(wire::decode(&profile_data)?)
+    }
+
     pub async fn override_fan_speed(&self, fan_idx: u8, speed: u8) -> ClientResult<()> {
         Ok(self.fan.override_speed(fan_idx, speed).await?)
     }
+
+    /// Run all fans at full speed for the given number of seconds
+    /// and return to automatic control afterwards.
+    pub async fn boost_fans(&self, seconds: u32) -> ClientResult<()> {
+        Ok(self.fan.boost_fans(seconds).await?)
+    }
+
+    /// Keep a fan at `speed` percent for `seconds` (max. 600), ignoring the fan profile.
+    pub async fn pin_fan_speed(&self, fan_idx: u8, speed: u8, seconds: u32) -> ClientResult<()> {
+        Ok(self.fan.pin_speed(fan_idx, speed, seconds).await?)
+    }
+
+    /// Drive a fan with `profile` for `seconds` (max. 600) without storing it.
+    pub async fn preview_fan_profile(
+        &self,
+        fan_idx: u8,
+        profile: &FanProfile,
+        seconds: u32,
+    ) -> ClientResult<()> {
+        let value = wire::encode(profile, self.wire_version)?;
+        Ok(self.fan.preview_profile(fan_idx, &value, seconds).await?)
+    }
+
+    /// End all boosts and pinned fan speeds early.
+    pub async fn release_fans(&self) -> ClientResult<()> {
+        Ok(self.fan.release_fans().await?)
+    }
+
+    /// Read the current temperature and speed of all fans.
+    pub async fn get_fan_status(&self) -> ClientResult<Vec<FanStatus>> {
+        let data = self.fan.get_status().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Receive fan status updates.
+    ///
+    /// The daemon limits how often these are sent,
+    /// so intermediate values might be skipped.
+    pub async fn receive_fan_status(&self) -> ClientResult<impl Stream<Item = FanStatus>> {
+        let stream = self
+            .fan
+            .receive_status_changed()
+            .await
+            .map_err(TailorError::from)?;
+        Ok(stream.filter_map(|signal| async move {
+            let args = signal.args().ok()?;
+            serde_json::from_str(args.status()).ok()
+        }))
+    }
+
+    /// Receive the index of the fan and the error whenever
+    /// a temperature sensor can't be read anymore.
+    pub async fn receive_sensor_failures(&self) -> ClientResult<impl Stream<Item = (u8, String)>> {
+        let stream = self
+            .fan
+            .receive_sensor_failed()
+            .await
+            .map_err(TailorError::from)?;
+        Ok(stream.filter_map(|signal| async move {
+            let args = signal.args().ok()?;
+            Some((*args.fan_idx(), args.error().to_string()))
+        }))
+    }
+
+    /// Receive the index of the fan and the temperature whenever a fan
+    /// reached the passive trip point and runs at full speed.
+    pub async fn receive_thermal_trips(&self) -> ClientResult<impl Stream<Item = (u8, u8)>> {
+        let stream = self
+            .fan
+            .receive_thermal_trip()
+            .await
+            .map_err(TailorError::from)?;
+        Ok(stream.filter_map(|signal| async move {
+            let args = signal.args().ok()?;
+            Some((*args.fan_idx(), *args.temperature()))
+        }))
+    }
 }
 
 impl<'a> TailorConnection<'a> {
     pub async fn add_global_profile(&self, name: &str, profile: &ProfileInfo) -> ClientResult<()> {
-        let value = serde_json::to_string(profile)?;
+        let value = wire::encode(profile, self.wire_version)?;
         Ok(self.profiles.add_profile(name, &value).await?)
     }
 
     pub async fn get_global_profile(&self, name: &str) -> ClientResult<ProfileInfo> {
         let profile_data = self.profiles.get_profile(name).await?;
-        Ok(serde_json::from_str(&profile_data)?)
+        Ok(wire::decode(&profile_data)?)
     }
 
     pub async fn list_global_profiles(&self) -> ClientResult<Vec<String>> {
@@ -142,6 +282,12 @@ impl<'a> TailorConnection<'a> {
         Ok(self.profiles.set_active_profile_name(name).await?)
     }
 
+    /// Whether the device runs on AC or battery.
+    pub async fn get_power_source(&self) -> ClientResult<PowerSource> {
+        let data = self.profiles.get_power_source().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
     pub async fn get_number_of_fans(&self) -> ClientResult<u8> {
         Ok(self.profiles.get_number_of_fans().await?)
     }
@@ -151,25 +297,217 @@ impl<'a> TailorConnection<'a> {
         Ok(serde_json::from_str(&data)?)
     }
 
+    /// List all fans, LED devices and temperature sensors detected by the daemon.
+    pub async fn list_devices(&self) -> ClientResult<DeviceList> {
+        let data = self.profiles.list_devices().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
     pub async fn reload(&self) -> ClientResult<()> {
         Ok(self.profiles.reload().await?)
     }
+
+    /// Reload only the fan profiles of the active profile.
+    pub async fn reload_fan_profile(&self) -> ClientResult<()> {
+        Ok(self.profiles.reload_fan_profile().await?)
+    }
+
+    /// Reload only the LED profiles of the active profile.
+    pub async fn reload_led_profile(&self) -> ClientResult<()> {
+        Ok(self.profiles.reload_led_profile().await?)
+    }
+
+    /// The most recent profile activations of the daemon, oldest first.
+    pub async fn get_profile_history(&self) -> ClientResult<Vec<ProfileSwitch>> {
+        let data = self.profiles.get_profile_history().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Receive every profile activation of the daemon, including the reason.
+    pub async fn receive_profile_switches(
+        &self,
+    ) -> ClientResult<impl Stream<Item = ProfileSwitch>> {
+        let stream = self
+            .profiles
+            .receive_profile_activated()
+            .await
+            .map_err(TailorError::from)?;
+        Ok(stream.filter_map(|signal| async move {
+            let args = signal.args().ok()?;
+            serde_json::from_str(args.switch()).ok()
+        }))
+    }
+
+    /// Read hardware details, version and settings of the daemon for bug reports.
+    /// The format isn't stable, it's meant to be read by humans.
+    pub async fn get_diagnostics(&self) -> ClientResult<serde_json::Value> {
+        let data = self.profiles.get_diagnostics().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Which capabilities of the hardware passed the self-test of the daemon at startup.
+    pub async fn get_self_test(&self) -> ClientResult<Vec<SelfTestCheck>> {
+        let data = self.profiles.get_self_test().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Make the daemon log its internal state, e.g. before collecting the journal.
+    pub async fn dump_state(&self) -> ClientResult<()> {
+        Ok(self.profiles.dump_state().await?)
+    }
 }
 
 impl<'a> TailorConnection<'a> {
     /// Temporarily override the performance profile. Please note that this will not survive a
     /// restart as the performance profile is handled by the overall profile configuration.
-    pub async fn set_performance_profile(&self, name: &str, value: &str) -> ClientResult<()> {
-        Ok(self.performance.set_profile(name, value).await?)
+    pub async fn set_performance_profile(&self, name: &str) -> ClientResult<()> {
+        Ok(self.performance.set_profile(name).await?)
     }
 
     /// Read the current performance profile.
-    pub async fn get_performance_profile(&self, name: &str) -> ClientResult<String> {
-        Ok(self.performance.get_profile(name).await?)
+    pub async fn get_performance_profile(&self) -> ClientResult<String> {
+        Ok(self.performance.get_profile().await?)
     }
 
     /// Read the list of supported performance profiles.
     pub async fn list_performance_profiles(&self) -> ClientResult<Vec<String>> {
         Ok(self.performance.list_profiles().await?)
     }
+
+    /// Read the power limits (TDP) of the platform with their ranges.
+    pub async fn list_power_limits(&self) -> ClientResult<Vec<PowerLimit>> {
+        let value = self.performance.list_power_limits().await?;
+        Ok(serde_json::from_str(&value)?)
+    }
+
+    /// Temporarily set a power limit in watts. Please note that this will not survive
+    /// the next profile activation unless the limit is stored in the profile.
+    pub async fn set_power_limit(&self, name: &str, value: i32) -> ClientResult<()> {
+        Ok(self.performance.set_power_limit(name, value).await?)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Switch the webcam on or off until the next profile is activated.
+    pub async fn set_webcam_enabled(&self, enabled: bool) -> ClientResult<()> {
+        Ok(self.webcam.set_enabled(enabled).await?)
+    }
+
+    /// Whether the webcam is switched on.
+    pub async fn get_webcam_enabled(&self) -> ClientResult<bool> {
+        Ok(self.webcam.get_enabled().await?)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Switch the Fn-lock on or off until the next profile is activated.
+    pub async fn set_fn_lock_enabled(&self, enabled: bool) -> ClientResult<()> {
+        Ok(self.fn_lock.set_enabled(enabled).await?)
+    }
+
+    /// Whether the Fn-lock is switched on.
+    pub async fn get_fn_lock_enabled(&self) -> ClientResult<bool> {
+        Ok(self.fn_lock.get_enabled().await?)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Switch the touchpad on or off until the next profile is activated.
+    pub async fn set_touchpad_enabled(&self, enabled: bool) -> ClientResult<()> {
+        Ok(self.touchpad.set_enabled(enabled).await?)
+    }
+
+    /// Whether the touchpad is switched on.
+    pub async fn get_touchpad_enabled(&self) -> ClientResult<bool> {
+        Ok(self.touchpad.get_enabled().await?)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Read which charging settings are supported.
+    pub async fn get_charging_capabilities(&self) -> ClientResult<ChargingCapabilities> {
+        let data = self.charging.get_capabilities().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Read the current charging settings from the hardware.
+    pub async fn get_charging_settings(&self) -> ClientResult<ChargingSettings> {
+        let data = self.charging.get_settings().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Apply the charging settings, fields that are `None` are left unchanged.
+    /// The settings are persistent.
+    pub async fn set_charging_settings(&self, settings: &ChargingSettings) -> ClientResult<()> {
+        let value = serde_json::to_string(settings)?;
+        Ok(self.charging.set_settings(&value).await?)
+    }
+}
+
+impl<'a> TailorConnection<'a> {
+    /// Read which subsystems of the daemon are enabled.
+    pub async fn get_subsystems(&self) -> ClientResult<SubsystemStates> {
+        let data = self.settings.get_subsystems().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Enable or disable a subsystem of the daemon. Disabled subsystems
+    /// hand control back to the firmware. This setting is persistent.
+    pub async fn set_subsystem_enabled(
+        &self,
+        subsystem: Subsystem,
+        enabled: bool,
+    ) -> ClientResult<()> {
+        Ok(self
+            .settings
+            .set_subsystem_enabled(subsystem.name(), enabled)
+            .await?)
+    }
+
+    /// Change the log level of the daemon for `target`, or the default level
+    /// if `target` is empty, until the daemon restarts.
+    pub async fn set_log_level(&self, target: &str, level: &str) -> ClientResult<()> {
+        Ok(self.settings.set_log_level(target, level).await?)
+    }
+
+    /// Other fan control daemons that were running when the daemon started.
+    pub async fn get_conflicting_services(&self) -> ClientResult<Vec<String>> {
+        Ok(self
+            .settings
+            .conflicting_services()
+            .await
+            .map_err(TailorError::from)?)
+    }
+
+    /// Read which profiles are activated on AC and battery power.
+    pub async fn get_power_source_profiles(&self) -> ClientResult<PowerSourceProfiles> {
+        let data = self.settings.get_power_source_profiles().await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Activate `profile` whenever the device switches to `source`.
+    /// `None` disables the automatic switch. This setting is persistent.
+    pub async fn set_power_source_profile(
+        &self,
+        source: PowerSource,
+        profile: Option<&str>,
+    ) -> ClientResult<()> {
+        Ok(self
+            .settings
+            .set_power_source_profile(source.name(), profile.unwrap_or_default())
+            .await?)
+    }
+
+    /// Receive updates whenever a subsystem is enabled or disabled.
+    pub async fn receive_subsystems(&self) -> ClientResult<impl Stream<Item = SubsystemStates>> {
+        let stream = self
+            .settings
+            .receive_subsystems_changed()
+            .await
+            .map_err(TailorError::from)?;
+        Ok(stream.filter_map(|signal| async move {
+            let args = signal.args().ok()?;
+            serde_json::from_str(args.subsystems()).ok()
+        }))
+    }
 }
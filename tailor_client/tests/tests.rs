@@ -1,5 +1,5 @@
-use tailor_api::{Color, ColorPoint, ColorProfile, ColorTransition, FanProfilePoint};
-use tailor_client::TailorConnection;
+use tailor_api::{Color, ColorPoint, ColorProfile, ColorTransition, FanProfile, FanProfilePoint};
+use tailor_client::{ClientError, TailorConnection, TailorError};
 
 #[tokio::test]
 async fn test_profiles() {
@@ -12,6 +12,11 @@ async fn test_profiles() {
 
     connection.get_number_of_fans().await.unwrap();
     connection.get_led_devices().await.unwrap();
+    let devices = connection.list_devices().await.unwrap();
+    assert_eq!(
+        devices.fans.len(),
+        connection.get_number_of_fans().await.unwrap() as usize
+    );
 
     let active_name = connection.get_active_global_profile_name().await.unwrap();
     let active_profile = connection.get_global_profile(&active_name).await.unwrap();
@@ -116,10 +121,23 @@ async fn test_fan() {
     let name = "__test_fan_profile";
     let second_name = "__test_fan_profile2";
 
-    let profile = vec![
-        FanProfilePoint { temp: 30, fan: 20 },
-        FanProfilePoint { temp: 70, fan: 100 },
-    ];
+    let profile = FanProfile {
+        points: vec![
+            FanProfilePoint {
+                temp: 30,
+                fan: 20,
+                power_limit: 0,
+            },
+            FanProfilePoint {
+                temp: 70,
+                fan: 100,
+                power_limit: 0,
+            },
+        ],
+        sticky: false,
+        prediction: None,
+        smoothing: None,
+    };
 
     // Add profile
     connection.add_fan_profile(name, &profile).await.unwrap();
@@ -176,6 +194,28 @@ async fn test_fan() {
         .unwrap_err();
 }
 
+#[tokio::test]
+async fn test_errors() {
+    let connection = TailorConnection::new().await.unwrap();
+    let name = "__test_missing_profile";
+
+    // Clients can tell a missing profile from other failures.
+    assert!(matches!(
+        connection.get_fan_profile(name).await,
+        Err(ClientError::BusError(TailorError::ProfileNotFound(_)))
+    ));
+    assert!(matches!(
+        connection
+            .rename_fan_profile(name, "__test_missing_profile2")
+            .await,
+        Err(ClientError::BusError(TailorError::ProfileNotFound(_)))
+    ));
+    assert!(matches!(
+        connection.remove_global_profile(name).await,
+        Err(ClientError::BusError(TailorError::ProfileNotFound(_)))
+    ));
+}
+
 #[tokio::test]
 async fn test_keyboard() {
     let connection = TailorConnection::new().await.unwrap();
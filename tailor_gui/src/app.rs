@@ -16,9 +16,11 @@ use tailor_api::ProfileInfo;
 use crate::components::fan_list::FanList;
 use crate::components::hardware_info::HardwareInfo;
 use crate::components::led_list::LedList;
+use crate::components::monitor::Monitor;
 use crate::components::profiles::Profiles;
 use crate::config::{APP_ID, PROFILE};
 use crate::modals::about::AboutDialog;
+use crate::modals::setup_wizard::SetupWizard;
 use crate::state::{initialize_tailor_state, TailorStateInner, STATE};
 
 const CONNECT_ERROR_MSG: &str = r#"Please make sure <a href="https://github.com/AaronErhardt/tuxedo-rs#tailord">tailord</a> is running correctly on your system. Tailor will connect automatically once tailord becomes available."#;
@@ -63,6 +65,7 @@ relm4::new_stateless_action!(PreferencesAction, WindowActionGroup, "preferences"
 relm4::new_stateless_action!(pub(super) ShortcutsAction, WindowActionGroup, "show-help-overlay");
 relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
 relm4::new_stateless_action!(HardwareInfoAction, WindowActionGroup, "hw-info");
+relm4::new_stateless_action!(SetupWizardAction, WindowActionGroup, "setup-wizard");
 
 #[relm4::component(pub)]
 impl Component for App {
@@ -77,6 +80,7 @@ impl Component for App {
                 "_Preferences" => PreferencesAction,
                 "_Keyboard Shortcuts" => ShortcutsAction,
                 "_Hardware information" => HardwareInfoAction,
+                "_Setup wizard" => SetupWizardAction,
                 "_About Tailor" => AboutAction,
             }
         }
@@ -160,6 +164,10 @@ impl Component for App {
                                     add_titled[Some("fan"), "Fan control"] = fan_list -> gtk::ScrolledWindow {} -> {
                                         set_icon_name: Some(icon_names::DATA_BAR_VERTICAL_ASCENDING_FILLED),
                                     },
+                                    #[local_ref]
+                                    add_titled[Some("monitor"), "Monitor"] = monitor_widget -> gtk::ScrolledWindow {} -> {
+                                        set_icon_name: Some(icon_names::SPEEDOMETER),
+                                    },
                                 },
                                 #[name = "view_bar"]
                                 adw::ViewSwitcherBar {
@@ -253,6 +261,10 @@ impl Component for App {
         fan_list.detach_runtime();
         let fan_list = &**fan_list.widget();
 
+        let mut monitor = Monitor::builder().launch(()).detach();
+        monitor.detach_runtime();
+        let monitor_widget = &**monitor.widget();
+
         let mut profiles = Profiles::builder().launch(()).detach();
         profiles.detach_runtime();
         let profile_widget = &**profiles.widget();
@@ -287,6 +299,13 @@ impl Component for App {
             })
         };
 
+        let setup_wizard_action = {
+            let window = widgets.main_window.clone();
+            RelmAction::<SetupWizardAction>::new_stateless(move |_| {
+                show_setup_wizard(&window);
+            })
+        };
+
         let about_action = {
             let sender = model.about_dialog.sender().clone();
             RelmAction::<AboutAction>::new_stateless(move |_| {
@@ -298,6 +317,7 @@ impl Component for App {
         actions.add_action(shortcuts_action);
         actions.add_action(about_action);
         actions.add_action(hardware_action);
+        actions.add_action(setup_wizard_action);
         actions.register_for_widget(&widgets.main_window);
 
         widgets.load_window_size();
@@ -320,7 +340,7 @@ impl Component for App {
         &mut self,
         message: Self::CommandOutput,
         sender: ComponentSender<Self>,
-        _root: &Self::Root,
+        root: &Self::Root,
     ) {
         match message {
             Command::SetInitializedState { error } => {
@@ -330,6 +350,9 @@ impl Component for App {
                     Self::initialize_connection(&sender, Some(Duration::from_secs(5)));
                 } else {
                     self.connection_state = ConnectionState::Ok;
+                    if SetupWizard::is_pending() {
+                        show_setup_wizard(root);
+                    }
                 }
             }
         }
@@ -368,6 +391,15 @@ impl AppWidgets {
     }
 }
 
+fn show_setup_wizard(window: &adw::ApplicationWindow) {
+    let mut wizard = SetupWizard::builder()
+        .transient_for(window)
+        .launch(())
+        .detach();
+    // Keep the wizard running until its window is closed.
+    wizard.detach_runtime();
+}
+
 impl App {
     fn initialize_connection(sender: &ComponentSender<Self>, delay: Option<Duration>) {
         sender.oneshot_command(async move {
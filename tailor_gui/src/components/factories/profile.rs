@@ -13,6 +13,9 @@ use crate::components::profiles::ProfilesInput;
 use crate::state::{hardware_capabilities, TailorStateMsg, STATE};
 use crate::templates;
 
+/// Choices of the webcam combo box, in the order of `None`, `Some(true)` and `Some(false)`.
+const WEBCAM_STATES: [&str; 3] = ["unchanged", "on", "off"];
+
 thread_local! {
     static RADIO_GROUP: Lazy<gtk::CheckButton> = Lazy::new(gtk::CheckButton::default);
 }
@@ -25,6 +28,7 @@ pub struct Profile {
     pub leds: FactoryVecDeque<ProfileItemLed>,
     pub fans: FactoryVecDeque<ProfileItemFan>,
     pub performance: Option<Controller<SimpleComboBox<String>>>,
+    pub webcam: Option<Controller<SimpleComboBox<String>>>,
 }
 
 #[derive(Debug)]
@@ -111,6 +115,26 @@ impl FactoryComponent for Profile {
                 row -> gtk::Box {
                     append?: self.performance.as_ref().map(Controller::widget),
                 }
+            },
+
+            #[template]
+            add_row = &templates::ProfileListItem {
+                set_visible: self.webcam.is_some(),
+
+                #[template_child]
+                image -> gtk::Image {
+                    set_icon_name: Some("camera-web-symbolic"),
+                },
+
+                #[template_child]
+                label -> gtk::Label {
+                    set_label: "webcam"
+                },
+
+                #[template_child]
+                row -> gtk::Box {
+                    append?: self.webcam.as_ref().map(Controller::widget),
+                }
             }
         }
     }
@@ -129,8 +153,9 @@ impl FactoryComponent for Profile {
         let capabilities = hardware_capabilities().unwrap();
 
         if info.fans.len() as u8 != capabilities.num_of_fans {
-            info.fans
-                .resize(capabilities.num_of_fans as usize, "default".to_owned());
+            // Like tailord, use the last listed profile for the remaining fans.
+            let last = info.fans.last().cloned().unwrap_or_else(|| "default".to_owned());
+            info.fans.resize(capabilities.num_of_fans as usize, last);
         }
 
         let mut additional_led_profiles = Vec::new();
@@ -158,6 +183,7 @@ impl FactoryComponent for Profile {
                     device_name: profile.device_name.clone(),
                     function: profile.function.clone(),
                     mode: profile.mode,
+                    capabilities: Default::default(),
                 };
                 let index = led_profiles
                     .iter()
@@ -205,6 +231,20 @@ impl FactoryComponent for Profile {
                     .forward(sender.input_sender(), |_| ProfileInput::UpdateProfile)
             });
 
+        let webcam = capabilities.webcam.then(|| {
+            let active_index = match info.webcam {
+                None => 0,
+                Some(true) => 1,
+                Some(false) => 2,
+            };
+            SimpleComboBox::builder()
+                .launch(SimpleComboBox {
+                    variants: WEBCAM_STATES.map(str::to_owned).to_vec(),
+                    active_index: Some(active_index),
+                })
+                .forward(sender.input_sender(), |_| ProfileInput::UpdateProfile)
+        });
+
         Self {
             name,
             info,
@@ -212,6 +252,7 @@ impl FactoryComponent for Profile {
             leds,
             fans,
             performance,
+            webcam,
         }
     }
 
@@ -236,10 +277,28 @@ impl FactoryComponent for Profile {
                     .as_ref()
                     .and_then(|perf| perf.state().get().model.get_active_elem().cloned());
 
+                let webcam = match &self.webcam {
+                    Some(webcam) => match webcam.state().get().model.active_index {
+                        Some(1) => Some(true),
+                        Some(2) => Some(false),
+                        _ => None,
+                    },
+                    None => self.info.webcam,
+                };
+
                 self.info = ProfileInfo {
                     leds,
                     fans,
                     performance_profile,
+                    webcam,
+                    fn_lock: self.info.fn_lock,
+                    touchpad: self.info.touchpad,
+                    tdp: self.info.tdp.clone(),
+                    cpu_governor: self.info.cpu_governor.clone(),
+                    energy_performance_preference: self.info.energy_performance_preference.clone(),
+                    display_brightness: self.info.display_brightness,
+                    lid_closed: self.info.lid_closed.clone(),
+                    disable_power_limits: self.info.disable_power_limits,
                 };
 
                 let profile = self.info.clone();
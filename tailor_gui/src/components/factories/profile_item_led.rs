@@ -94,6 +94,7 @@ impl ProfileItemLed {
             device_name,
             function,
             mode,
+            ..
         } = self.device_info.clone();
         LedProfile {
             device_name,
@@ -5,14 +5,16 @@ use gtk::cairo::Operator;
 use gtk::gdk;
 use gtk::gdk::RGBA;
 use gtk::glib::{timeout_add_local_once, MainContext, SourceId};
-use gtk::prelude::{BoxExt, ButtonExt, DrawingAreaExt, GestureDragExt, OrientableExt, WidgetExt};
+use gtk::prelude::{
+    BoxExt, ButtonExt, DrawingAreaExt, GestureDragExt, GestureSingleExt, OrientableExt, WidgetExt,
+};
 use relm4::abstractions::DrawHandler;
 use relm4::{
     adw, component, gtk, Component, ComponentController, ComponentParts, ComponentSender,
     Controller, RelmWidgetExt,
 };
 use relm4_components::simple_combo_box::{SimpleComboBox, SimpleComboBoxMsg};
-use tailor_api::FanProfilePoint;
+use tailor_api::{FanPrediction, FanProfile, FanProfilePoint, FanSmoothing};
 
 use crate::state::{hardware_capabilities, tailor_connection, TailorStateMsg, STATE};
 use crate::templates;
@@ -36,9 +38,23 @@ impl Colors {
     }
 }
 
+/// Result of checking the curve while it's being edited.
+enum Validation {
+    Valid,
+    /// The curve can be saved, but might not behave as expected.
+    Warning(String),
+    /// The curve can't be saved.
+    Invalid(String),
+}
+
 pub struct FanEdit {
     profile_name: Option<String>,
     profile: Vec<FanProfilePoint>,
+    sticky: bool,
+    /// Not editable here, but kept when saving.
+    prediction: Option<FanPrediction>,
+    /// Kept like `prediction`.
+    smoothing: Option<FanSmoothing>,
     drawing_handler: DrawHandler,
     drawn_points: Vec<(f64, f64)>,
     colors: Colors,
@@ -47,6 +63,8 @@ pub struct FanEdit {
     drag_into_danger_zone: bool,
     visible: bool,
     last_override_event: Option<SourceId>,
+    /// Whether the daemon currently runs the edited curve.
+    previewing: bool,
     preview_fan: Controller<SimpleComboBox<String>>,
 }
 
@@ -56,6 +74,8 @@ pub enum FanEditInput {
     DragStart((f64, f64)),
     DragUpdate((f64, f64)),
     DragEnd((f64, f64)),
+    RemovePoint((f64, f64)),
+    Preview,
     #[doc(hidden)]
     Cancel,
     #[doc(hidden)]
@@ -68,7 +88,7 @@ pub enum FanEditInput {
 
 #[component(pub)]
 impl Component for FanEdit {
-    type CommandOutput = Option<Vec<FanProfilePoint>>;
+    type CommandOutput = Option<FanProfile>;
     type Init = ();
     type Input = FanEditInput;
     type Output = ();
@@ -125,6 +145,12 @@ impl Component for FanEdit {
                                 sender.input(FanEditInput::DragEnd((x, y)));
                             },
                         },
+                        add_controller = gtk::GestureClick {
+                            set_button: gdk::BUTTON_SECONDARY,
+                            connect_pressed[sender] => move |_, _, x, y| {
+                                sender.input(FanEditInput::RemovePoint((x, y)));
+                            },
+                        },
                         connect_resize[sender] => move |_, _, _| {
                             sender.input(FanEditInput::Update);
                         },
@@ -157,6 +183,36 @@ impl Component for FanEdit {
                         }
                     }
                 },
+                gtk::Box {
+                    set_margin_start: 12,
+                    set_margin_end: 12,
+                    set_margin_bottom: 12,
+                    set_spacing: 12,
+
+                    gtk::Label {
+                        set_hexpand: true,
+                        set_halign: gtk::Align::Start,
+                        set_wrap: true,
+                        #[watch]
+                        set_label: &match model.validate() {
+                            Validation::Valid => "Click to add a point, drag to move it and right-click to remove it".to_owned(),
+                            Validation::Warning(msg) | Validation::Invalid(msg) => msg,
+                        },
+                        #[watch]
+                        set_css_classes: match model.validate() {
+                            Validation::Valid => &["dim-label"],
+                            Validation::Warning(_) => &["warning"],
+                            Validation::Invalid(_) => &["error"],
+                        },
+                    },
+                    gtk::Button {
+                        set_label: "Apply temporarily",
+                        set_tooltip: "Try the curve for a minute without saving it",
+                        #[watch]
+                        set_sensitive: !matches!(model.validate(), Validation::Invalid(_)),
+                        connect_clicked => FanEditInput::Preview,
+                    },
+                },
                 gtk::Separator {},
 
                 #[template]
@@ -167,6 +223,8 @@ impl Component for FanEdit {
                     },
                     #[template_child]
                     save_button -> gtk::Button {
+                        #[watch]
+                        set_sensitive: !matches!(model.validate(), Validation::Invalid(_)),
                         connect_clicked => FanEditInput::Apply,
                     },
                 }
@@ -191,6 +249,9 @@ impl Component for FanEdit {
         let model = Self {
             profile_name: None,
             profile: Vec::new(),
+            sticky: false,
+            prediction: None,
+            smoothing: None,
             drawing_handler: DrawHandler::new(),
             active_drag_info: None,
             colors,
@@ -199,6 +260,7 @@ impl Component for FanEdit {
             selection: None,
             visible: false,
             last_override_event: None,
+            previewing: false,
             preview_fan,
         };
 
@@ -218,6 +280,7 @@ impl Component for FanEdit {
         match input {
             FanEditInput::Load(name) => {
                 self.profile_name = Some(name.clone());
+                self.previewing = false;
 
                 let capabilities = hardware_capabilities().unwrap();
                 if capabilities.num_of_fans <= 1 {
@@ -235,8 +298,8 @@ impl Component for FanEdit {
 
                 let connection = tailor_connection().unwrap();
                 sender.oneshot_command(async move {
-                    if let Ok(profile_points) = connection.get_fan_profile(&name).await {
-                        Some(profile_points)
+                    if let Ok(profile) = connection.get_fan_profile(&name).await {
+                        Some(profile)
                     } else {
                         tracing::error!("Couldn't load fan profile");
                         None
@@ -246,12 +309,33 @@ impl Component for FanEdit {
             FanEditInput::Apply => {
                 self.visible = false;
                 if let Some(name) = self.profile_name.clone() {
-                    let profile = self.profile.drain(..).collect();
+                    let profile = FanProfile {
+                        points: self.profile.drain(..).collect(),
+                        sticky: self.sticky,
+                        prediction: self.prediction,
+                        smoothing: self.smoothing,
+                    };
                     STATE.emit(TailorStateMsg::AddFanProfile { name, profile });
                 }
+                self.end_preview();
             }
             FanEditInput::Cancel => {
                 self.visible = false;
+                self.end_preview();
+            }
+            FanEditInput::Preview => {
+                if !matches!(self.validate(), Validation::Invalid(_)) {
+                    self.previewing = true;
+                    STATE.emit(TailorStateMsg::PreviewFanProfile {
+                        fan_idx: self.selected_fan(),
+                        profile: FanProfile {
+                            points: self.profile.clone(),
+                            sticky: self.sticky,
+                            prediction: self.prediction,
+                            smoothing: self.smoothing,
+                        },
+                    });
+                }
             }
             FanEditInput::Update => {
                 self.update_drawn_points();
@@ -284,6 +368,16 @@ impl Component for FanEdit {
                 self.drag_into_danger_zone = false;
                 self.active_drag_info = None;
             }
+            FanEditInput::RemovePoint((x, y)) => {
+                // Keep at least one point, an empty curve is invalid.
+                if self.profile.len() > 1 {
+                    if let Some(idx) = self.nearest_point(x, y, 15.0) {
+                        self.profile.remove(idx);
+                        self.selection = None;
+                        self.update_drawn_points();
+                    }
+                }
+            }
         }
         self.draw();
     }
@@ -294,7 +388,16 @@ impl Component for FanEdit {
         _sender: ComponentSender<Self>,
         _root: &Self::Root,
     ) {
-        self.profile = profile.unwrap_or_default();
+        let profile = profile.unwrap_or(FanProfile {
+            points: Vec::new(),
+            sticky: false,
+            prediction: None,
+            smoothing: None,
+        });
+        self.profile = profile.points;
+        self.sticky = profile.sticky;
+        self.prediction = profile.prediction;
+        self.smoothing = profile.smoothing;
         self.visible = true;
 
         self.update_drawn_points();
@@ -313,6 +416,55 @@ impl FanEdit {
         self.profile.last().map(|p| p.temp as f64).unwrap_or(100.0) - 15.0
     }
 
+    fn selected_fan(&self) -> u8 {
+        self.preview_fan
+            .state()
+            .get()
+            .model
+            .active_index
+            .unwrap_or_default() as u8
+    }
+
+    /// Hand the fans back to the stored curves if a preview is running.
+    fn end_preview(&mut self) {
+        if self.previewing {
+            self.previewing = false;
+            STATE.emit(TailorStateMsg::ReloadFanProfile);
+        }
+    }
+
+    /// Check the curve the same way the daemon does when it loads it.
+    fn validate(&self) -> Validation {
+        let Some(last) = self.profile.last() else {
+            return Validation::Invalid("Add at least one point".to_owned());
+        };
+        if self
+            .profile
+            .windows(2)
+            .any(|pair| pair[0].temp >= pair[1].temp)
+        {
+            return Validation::Invalid("Two points have the same temperature".to_owned());
+        }
+        if let Some(point) = self
+            .profile
+            .iter()
+            .find(|point| point.fan < point.temp.saturating_sub(75).saturating_mul(5))
+        {
+            return Validation::Warning(format!(
+                "{}% at {}°C might not be enough to cool the device",
+                point.fan, point.temp
+            ));
+        }
+        if last.fan < 100 {
+            return Validation::Warning(if last.temp < 100 {
+                "The fan never reaches 100%, full speed will be used at 100°C".to_owned()
+            } else {
+                "The fan never reaches 100%".to_owned()
+            });
+        }
+        Validation::Valid
+    }
+
     fn eliminate_duplicates(&mut self) {
         self.profile.dedup();
     }
@@ -347,7 +499,18 @@ impl FanEdit {
             return None;
         }
 
-        let new_profile = FanProfilePoint { temp, fan };
+        let power_limit = FanProfile {
+            points: self.profile.clone(),
+            sticky: self.sticky,
+            prediction: self.prediction,
+            smoothing: self.smoothing,
+        }
+        .power_limit_at(temp);
+        let new_profile = FanProfilePoint {
+            temp,
+            fan,
+            power_limit,
+        };
 
         Some(
             if let Some(idx) = self.profile.iter().position(|p| p.temp > temp) {
@@ -538,13 +701,7 @@ impl FanEdit {
             }
 
             // Don't override the value immediately, but wait a bit for other events to arrive.
-            let fan_idx = self
-                .preview_fan
-                .state()
-                .get()
-                .model
-                .active_index
-                .unwrap_or_default() as u8;
+            let fan_idx = self.selected_fan();
             self.last_override_event = Some(timeout_add_local_once(
                 Duration::from_millis(60),
                 move || {
@@ -14,6 +14,9 @@ use super::new_entry::{NewEntryDialog, NewEntryInit, NewEntryOutput};
 use crate::state::{TailorStateInner, TailorStateMsg, STATE};
 use crate::templates;
 
+/// Duration of a fan boost triggered from the GUI.
+const BOOST_SECONDS: u32 = 60;
+
 #[tracker::track]
 pub struct FanList {
     #[do_not_track]
@@ -30,6 +33,7 @@ pub enum FanListInput {
     Edit(usize),
     Remove(DynamicIndex),
     Add,
+    Boost,
 }
 
 impl ListMsg for FanListInput {
@@ -77,6 +81,12 @@ impl Component for FanList {
                             gtk::Box {
                                 set_hexpand: true,
                             },
+                            gtk::Button {
+                                set_margin_end: 6,
+                                set_icon_name: icon_names::SPEEDOMETER,
+                                set_tooltip_text: Some("Boost fans for one minute"),
+                                connect_clicked => FanListInput::Boost,
+                            },
                             gtk::Button {
                                 set_icon_name: icon_names::PLUS,
                                 connect_clicked => FanListInput::Add,
@@ -176,6 +186,10 @@ impl Component for FanList {
                     self.set_toast(Some(adw::Toast::new("There must be at least one profile")));
                 }
             }
+            FanListInput::Boost => {
+                STATE.emit(TailorStateMsg::BoostFans(BOOST_SECONDS));
+                self.set_toast(Some(adw::Toast::new("Fans boosted for one minute")));
+            }
             FanListInput::Add => {
                 let profiles = self.profiles.iter().map(|i| i.name.to_string()).collect();
                 let mut new_entry = NewEntryDialog::builder()
@@ -0,0 +1,455 @@
+use gtk::cairo::{Context, Operator};
+use gtk::gdk::RGBA;
+use gtk::prelude::{BoxExt, ButtonExt, DrawingAreaExt, GestureDragExt, OrientableExt, WidgetExt};
+use relm4::abstractions::DrawHandler;
+use relm4::{
+    adw, component, gtk, Component, ComponentController, ComponentParts, ComponentSender,
+    Controller, RelmWidgetExt,
+};
+use tailor_api::{Color, ColorProfile, LedDeviceInfo, LedProfile, ProfileInfo};
+
+use super::color_button::ColorButton;
+use crate::state::{hardware_capabilities, tailor_connection, TailorStateMsg, STATE};
+use crate::templates;
+
+const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+
+/// Keyboards with up to this many zones are split into vertical stripes,
+/// keyboards with more zones are assumed to have one zone per key.
+const MAX_STRIPE_ZONES: usize = 4;
+
+/// Width of each key in units of a regular key, row by row.
+const ROWS: [&[f64]; 6] = [
+    &[1.0; 15],
+    &[
+        1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.0,
+    ],
+    &[
+        1.5, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.5,
+    ],
+    &[
+        1.75, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.25,
+    ],
+    &[2.25, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.75],
+    &[1.25, 1.25, 1.25, 6.25, 1.25, 1.25, 1.25, 1.25],
+];
+/// Width of every row in units of a regular key.
+const ROW_UNITS: f64 = 15.0;
+/// Gap between two keys in pixels.
+const KEY_GAP: f64 = 4.0;
+
+/// The LED devices of a keyboard, ordered by zone.
+///
+/// Multi-zone keyboards expose one device per zone with the functions
+/// `kbd_backlight`, `kbd_backlight_1`, `kbd_backlight_2` and so on.
+pub fn keyboard_zones(devices: &[LedDeviceInfo]) -> Vec<LedDeviceInfo> {
+    let mut zones: Vec<(usize, &LedDeviceInfo)> = devices
+        .iter()
+        .filter_map(|device| {
+            let suffix = device.function.strip_prefix("kbd_backlight")?;
+            let index = if suffix.is_empty() {
+                0
+            } else {
+                suffix.strip_prefix('_')?.parse().ok()?
+            };
+            Some((index, device))
+        })
+        .collect();
+    zones.sort_by_key(|(index, _)| *index);
+    zones
+        .into_iter()
+        .map(|(_, device)| device.clone())
+        .collect()
+}
+
+struct Zone {
+    device: LedDeviceInfo,
+    color: Color,
+}
+
+/// A key drawn on the canvas and the zone it belongs to.
+struct Key {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    zone: Option<usize>,
+}
+
+impl Key {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// The colors of the zones of the active profile.
+#[derive(Debug)]
+pub struct LoadedZones {
+    name: String,
+    profile: ProfileInfo,
+    colors: Vec<Color>,
+}
+
+pub struct KeyboardZones {
+    visible: bool,
+    drawing_handler: DrawHandler,
+    brush: Controller<ColorButton>,
+    zones: Vec<Zone>,
+    /// The active global profile, the zones are assigned to it.
+    profile: Option<(String, ProfileInfo)>,
+    outline: RGBA,
+}
+
+#[derive(Debug)]
+pub enum KeyboardZonesInput {
+    Load,
+    Paint(f64, f64),
+    Apply,
+    Cancel,
+    #[doc(hidden)]
+    Redraw,
+}
+
+#[component(pub)]
+impl Component for KeyboardZones {
+    type CommandOutput = Option<LoadedZones>;
+    type Init = ();
+    type Input = KeyboardZonesInput;
+    type Output = ();
+
+    view! {
+        #[template]
+        templates::DialogWindow {
+            #[watch]
+            set_visible: model.visible,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+
+                gtk::WindowHandle {
+                    gtk::CenterBox {
+                        #[wrap(Some)]
+                        set_center_widget = &gtk::Label {
+                            add_css_class: "title-4",
+                            set_margin_all: 12,
+                            #[watch]
+                            set_label: &match &model.profile {
+                                Some((name, _)) => format!("Keyboard colors of profile '{name}'"),
+                                None => "Keyboard colors".to_owned(),
+                            },
+                        },
+
+                        #[local_ref]
+                        #[wrap(Some)]
+                        set_start_widget = brush_widget -> gtk::Button {
+                            set_margin_start: 6,
+                            set_valign: gtk::Align::Center,
+                            set_tooltip: "Brush color",
+                        },
+                    },
+                },
+
+                gtk::Box {
+                    add_css_class: "background",
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 12,
+                    set_margin_all: 12,
+                    set_vexpand: true,
+
+                    gtk::Frame {
+                        #[watch]
+                        set_visible: model.zones.len() > 1,
+
+                        #[local_ref]
+                        drawing_area -> gtk::DrawingArea {
+                            set_hexpand: true,
+                            set_content_height: 240,
+                            connect_resize[sender] => move |_, _, _| {
+                                sender.input(KeyboardZonesInput::Redraw);
+                            },
+
+                            add_controller = gtk::GestureDrag {
+                                connect_drag_begin[sender] => move |_, x, y| {
+                                    sender.input(KeyboardZonesInput::Paint(x, y));
+                                },
+                                connect_drag_update[sender] => move |gesture, x, y| {
+                                    if let Some((start_x, start_y)) = gesture.start_point() {
+                                        sender.input(KeyboardZonesInput::Paint(start_x + x, start_y + y));
+                                    }
+                                },
+                            },
+                        },
+                    },
+
+                    gtk::Label {
+                        add_css_class: "dim-label",
+                        set_wrap: true,
+                        #[watch]
+                        set_label: if model.zones.len() > 1 {
+                            "Pick a brush color and click or drag over the keys to paint their zone."
+                        } else {
+                            "Your keyboard doesn't have multiple lighting zones. Use the LED profiles to change its color."
+                        },
+                    },
+                },
+
+                gtk::Separator {},
+
+                #[template]
+                templates::MsgDialogButtons {
+                    #[template_child]
+                    cancel_button -> gtk::Button {
+                        connect_clicked => KeyboardZonesInput::Cancel,
+                    },
+                    #[template_child]
+                    save_button -> gtk::Button {
+                        #[watch]
+                        set_sensitive: model.zones.len() > 1 && model.profile.is_some(),
+                        connect_clicked => KeyboardZonesInput::Apply,
+                    },
+                }
+            }
+        }
+    }
+
+    fn init(
+        _: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let brush = ColorButton::builder()
+            .launch(Color {
+                r: 255,
+                g: 255,
+                b: 255,
+            })
+            .detach();
+
+        let model = Self {
+            visible: false,
+            drawing_handler: DrawHandler::new(),
+            brush,
+            zones: Vec::new(),
+            profile: None,
+            outline: outline_color(),
+        };
+
+        let brush_widget = model.brush.widget();
+        let drawing_area = model.drawing_handler.drawing_area();
+        let widgets = view_output!();
+
+        adw::StyleManager::default().connect_color_scheme_notify(move |_| {
+            sender.input(KeyboardZonesInput::Redraw);
+        });
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, input: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
+        match input {
+            KeyboardZonesInput::Load => {
+                self.zones = hardware_capabilities()
+                    .map(|capabilities| keyboard_zones(&capabilities.led_devices))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|device| Zone {
+                        device,
+                        color: BLACK,
+                    })
+                    .collect();
+                self.profile = None;
+
+                let devices: Vec<LedDeviceInfo> =
+                    self.zones.iter().map(|zone| zone.device.clone()).collect();
+                let connection = tailor_connection().unwrap();
+                sender.oneshot_command(async move {
+                    let name = connection.get_active_global_profile_name().await.ok()?;
+                    let profile = connection.get_global_profile(&name).await.ok()?;
+
+                    let mut colors = Vec::with_capacity(devices.len());
+                    for device in &devices {
+                        let led_profile =
+                            profile.led_profile(&device.device_name, &device.function);
+                        let color_profile = match led_profile {
+                            Some(led) => connection.get_led_profile(&led.profile).await.ok(),
+                            None => None,
+                        };
+                        colors.push(match color_profile {
+                            Some(ColorProfile::Single(color)) => color,
+                            Some(ColorProfile::Multiple(points)) => points
+                                .into_iter()
+                                .next()
+                                .map(|point| point.color)
+                                .unwrap_or(BLACK),
+                            Some(ColorProfile::None) | None => BLACK,
+                        });
+                    }
+
+                    Some(LoadedZones {
+                        name,
+                        profile,
+                        colors,
+                    })
+                });
+            }
+            KeyboardZonesInput::Paint(x, y) => {
+                let width = self.drawing_handler.width() as f64;
+                let height = self.drawing_handler.height() as f64;
+                let zone = self
+                    .keys(width, height)
+                    .into_iter()
+                    .find(|key| key.contains(x, y))
+                    .and_then(|key| key.zone);
+                if let Some(zone) = zone {
+                    let color = self.brush.model().color.clone();
+                    if self.zones[zone].color != color {
+                        self.zones[zone].color = color;
+                        self.draw();
+                    }
+                }
+            }
+            KeyboardZonesInput::Apply => {
+                if let Some((name, mut profile)) = self.profile.take() {
+                    let mut led_profiles = Vec::with_capacity(self.zones.len());
+                    for (idx, zone) in self.zones.iter().enumerate() {
+                        let led_name = format!("{name}-zone{}", idx + 1);
+                        profile.set_led_profile(LedProfile {
+                            device_name: zone.device.device_name.clone(),
+                            function: zone.device.function.clone(),
+                            profile: led_name.clone(),
+                            mode: zone.device.mode,
+                        });
+                        led_profiles.push((led_name, ColorProfile::Single(zone.color.clone())));
+                    }
+
+                    STATE.emit(TailorStateMsg::SetKeyboardZones {
+                        name,
+                        profile,
+                        led_profiles,
+                    });
+                }
+                self.visible = false;
+            }
+            KeyboardZonesInput::Cancel => {
+                self.visible = false;
+            }
+            KeyboardZonesInput::Redraw => {
+                self.outline = outline_color();
+                self.draw();
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        loaded: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        if let Some(LoadedZones {
+            name,
+            profile,
+            colors,
+        }) = loaded
+        {
+            for (zone, color) in self.zones.iter_mut().zip(colors) {
+                zone.color = color;
+            }
+            self.profile = Some((name, profile));
+        } else {
+            tracing::error!("Couldn't load the keyboard colors of the active profile");
+        }
+        self.visible = true;
+        self.draw();
+    }
+}
+
+impl KeyboardZones {
+    /// Lay out the keyboard in the given area.
+    fn keys(&self, width: f64, height: f64) -> Vec<Key> {
+        if self.zones.is_empty() {
+            return Vec::new();
+        }
+        let unit = width / ROW_UNITS;
+        let row_height = height / ROWS.len() as f64;
+        let stripes = self.zones.len() <= MAX_STRIPE_ZONES;
+
+        let mut keys = Vec::new();
+        for (row, widths) in ROWS.iter().enumerate() {
+            let mut x = 0.0;
+            for key_width in widths.iter() {
+                let key_width = key_width * unit;
+                let zone = if stripes {
+                    // The zone below the center of the key.
+                    let center = x + key_width / 2.0;
+                    Some(
+                        ((center / width * self.zones.len() as f64) as usize)
+                            .min(self.zones.len() - 1),
+                    )
+                } else {
+                    Some(keys.len()).filter(|idx| *idx < self.zones.len())
+                };
+                keys.push(Key {
+                    x: x + KEY_GAP / 2.0,
+                    y: row as f64 * row_height + KEY_GAP / 2.0,
+                    width: key_width - KEY_GAP,
+                    height: row_height - KEY_GAP,
+                    zone,
+                });
+                x += key_width;
+            }
+        }
+        keys
+    }
+
+    fn draw(&mut self) {
+        let ctx = self.drawing_handler.get_context();
+        let width = self.drawing_handler.width() as f64;
+        let height = self.drawing_handler.height() as f64;
+
+        // Clear the image surface
+        ctx.set_operator(Operator::Source);
+        ctx.set_source_rgba(0.0, 0.0, 0.0, 0.0);
+        ctx.rectangle(0.0, 0.0, width, height);
+        ctx.fill().unwrap();
+        ctx.set_operator(Operator::Over);
+
+        for key in self.keys(width, height) {
+            ctx.rectangle(key.x, key.y, key.width, key.height);
+            match key.zone {
+                Some(zone) => {
+                    let Color { r, g, b } = self.zones[zone].color;
+                    ctx.set_source_rgb(
+                        f64::from(r) / 255.0,
+                        f64::from(g) / 255.0,
+                        f64::from(b) / 255.0,
+                    );
+                    ctx.fill_preserve().unwrap();
+                }
+                None => {
+                    // Keys without a zone can't be lit.
+                    set_source_rgba(&ctx, &self.outline, 0.2);
+                    ctx.fill_preserve().unwrap();
+                }
+            }
+            set_source_rgba(&ctx, &self.outline, 1.0);
+            ctx.set_line_width(1.0);
+            ctx.stroke().unwrap();
+        }
+    }
+}
+
+fn outline_color() -> RGBA {
+    let label = gtk::Label::new(None);
+    label.add_css_class("dim-label");
+    label.color()
+}
+
+fn set_source_rgba(ctx: &Context, color: &RGBA, alpha: f64) {
+    ctx.set_source_rgba(
+        color.red() as f64,
+        color.green() as f64,
+        color.blue() as f64,
+        alpha,
+    );
+}
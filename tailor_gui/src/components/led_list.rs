@@ -4,11 +4,12 @@ use relm4::factory::FactoryVecDeque;
 use relm4::prelude::DynamicIndex;
 use relm4::{
     adw, component, gtk, Component, ComponentController, ComponentParts, ComponentSender,
-    Controller,
+    Controller, RelmWidgetExt,
 };
 use relm4_icons::icon_names;
 
 use super::factories::list_item::{ListItem, ListMsg};
+use super::keyboard_zones::{KeyboardZones, KeyboardZonesInput};
 use super::led_edit::{LedEdit, LedEditInput};
 use super::new_entry::{NewEntryDialog, NewEntryInit, NewEntryOutput};
 use crate::state::{TailorStateInner, TailorStateMsg, STATE};
@@ -20,6 +21,8 @@ pub struct LedList {
     profiles: FactoryVecDeque<ListItem<LedListInput>>,
     #[do_not_track]
     led_edit: Controller<LedEdit>,
+    #[do_not_track]
+    keyboard_zones: Controller<KeyboardZones>,
     toast: Option<adw::Toast>,
 }
 
@@ -30,6 +33,7 @@ pub enum LedListInput {
     Edit(usize),
     Remove(DynamicIndex),
     Add,
+    EditKeyboard,
 }
 
 impl ListMsg for LedListInput {
@@ -77,6 +81,12 @@ impl Component for LedList {
                             gtk::Box {
                                 set_hexpand: true,
                             },
+                            gtk::Button {
+                                set_icon_name: icon_names::COLOR,
+                                set_tooltip: "Keyboard zones",
+                                set_margin_end: 6,
+                                connect_clicked => LedListInput::EditKeyboard,
+                            },
                             gtk::Button {
                                 set_icon_name: icon_names::PLUS,
                                 connect_clicked => LedListInput::Add,
@@ -119,10 +129,15 @@ impl Component for LedList {
             .forward(sender.input_sender(), |msg| msg);
 
         let led_edit = LedEdit::builder().transient_for(&*root).launch(()).detach();
+        let keyboard_zones = KeyboardZones::builder()
+            .transient_for(&*root)
+            .launch(())
+            .detach();
 
         let model = Self {
             profiles,
             led_edit,
+            keyboard_zones,
             toast: None,
             tracker: 0,
         };
@@ -150,6 +165,9 @@ impl Component for LedList {
                     self.led_edit.emit(LedEditInput::Load(name));
                 }
             }
+            LedListInput::EditKeyboard => {
+                self.keyboard_zones.emit(KeyboardZonesInput::Load);
+            }
             LedListInput::Rename(index, name) => {
                 let index = index.current_index();
                 let current_name = &self.profiles[index].name;
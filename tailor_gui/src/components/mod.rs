@@ -3,7 +3,9 @@ pub mod factories;
 pub mod fan_edit;
 pub mod fan_list;
 pub mod hardware_info;
+pub mod keyboard_zones;
 pub mod led_edit;
 pub mod led_list;
+pub mod monitor;
 pub mod new_entry;
 pub mod profiles;
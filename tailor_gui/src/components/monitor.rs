@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures::{FutureExt, StreamExt};
+use gtk::cairo::{Context, Operator};
+use gtk::gdk::RGBA;
+use gtk::prelude::{BoxExt, DrawingAreaExt, OrientableExt, WidgetExt};
+use relm4::abstractions::DrawHandler;
+use relm4::{adw, component, gtk, Component, ComponentParts, ComponentSender};
+use tailor_api::FanStatus;
+
+use crate::state::tailor_connection;
+use crate::templates;
+
+/// Number of samples shown per chart.
+const HISTORY_LEN: usize = 120;
+/// Time between two samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Height of the chart of a single fan.
+const CHART_HEIGHT: i32 = 160;
+
+struct Colors {
+    temperature: RGBA,
+    speed: RGBA,
+    power_limit: RGBA,
+    grid: RGBA,
+}
+
+impl Colors {
+    fn new() -> Self {
+        let color = |class: &str| {
+            let label = gtk::Label::new(None);
+            label.add_css_class(class);
+            label.color()
+        };
+
+        Self {
+            temperature: color("error"),
+            speed: color("accent"),
+            power_limit: color("warning"),
+            grid: color("dim-label"),
+        }
+    }
+}
+
+pub struct Monitor {
+    drawing_handler: DrawHandler,
+    /// Samples of each fan, oldest first.
+    history: Vec<VecDeque<FanStatus>>,
+    colors: Colors,
+}
+
+#[derive(Debug)]
+pub enum MonitorInput {
+    #[doc(hidden)]
+    Redraw,
+    #[doc(hidden)]
+    UpdateColors,
+}
+
+#[component(pub)]
+impl Component for Monitor {
+    type CommandOutput = Vec<FanStatus>;
+    type Init = ();
+    type Input = MonitorInput;
+    type Output = ();
+
+    view! {
+        #[template]
+        templates::CustomClamp {
+            #[template_child]
+            clamp {
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 6,
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_spacing: 12,
+
+                        gtk::Label {
+                            add_css_class: "heading",
+                            set_label: "Live monitoring",
+                        },
+                        gtk::Box {
+                            set_hexpand: true,
+                        },
+                        gtk::Label {
+                            add_css_class: "error",
+                            set_label: "Temperature",
+                        },
+                        gtk::Label {
+                            add_css_class: "accent",
+                            set_label: "Fan speed",
+                        },
+                        gtk::Label {
+                            add_css_class: "warning",
+                            set_label: "Power limit",
+                        },
+                    },
+
+                    gtk::Frame {
+                        #[local_ref]
+                        drawing_area -> gtk::DrawingArea {
+                            set_hexpand: true,
+                            #[watch]
+                            set_content_height: CHART_HEIGHT * model.history.len().max(1) as i32,
+                            connect_resize[sender] => move |_, _, _| {
+                                sender.input(MonitorInput::Redraw);
+                            },
+                        },
+                    },
+
+                    gtk::Label {
+                        add_css_class: "dim-label",
+                        set_halign: gtk::Align::Start,
+                        set_wrap: true,
+                        set_label: "The last two minutes are shown. Temperatures are in °C, \
+                            fan speed and power limit in percent.",
+                    },
+                }
+            }
+        }
+    }
+
+    fn init(
+        _: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            drawing_handler: DrawHandler::new(),
+            history: Vec::new(),
+            colors: Colors::new(),
+        };
+
+        let drawing_area = model.drawing_handler.drawing_area();
+        let widgets = view_output!();
+
+        sender.command(|out, shutdown| {
+            shutdown
+                .register(async move {
+                    // The connection is established after the window was created.
+                    let connection = loop {
+                        if let Some(connection) = tailor_connection() {
+                            break connection;
+                        }
+                        tokio::time::sleep(SAMPLE_INTERVAL).await;
+                    };
+
+                    let mut latest = connection.get_fan_status().await.unwrap_or_default();
+                    let updates = match connection.receive_fan_status().await {
+                        Ok(updates) => updates,
+                        Err(err) => {
+                            tracing::error!("Couldn't subscribe to the fan status: `{err}`");
+                            return;
+                        }
+                    };
+                    let mut updates = std::pin::pin!(updates);
+
+                    // The daemon only reports changes,
+                    // so sample the latest values at a fixed rate.
+                    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+                    loop {
+                        tokio::select! {
+                            Some(status) = updates.next() => {
+                                if let Some(entry) = latest.iter_mut().find(|s| s.index == status.index) {
+                                    *entry = status;
+                                } else {
+                                    latest.push(status);
+                                }
+                            }
+                            _ = interval.tick() => {
+                                if out.send(latest.clone()).is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                })
+                .drop_on_shutdown()
+                .boxed()
+        });
+
+        adw::StyleManager::default().connect_color_scheme_notify(move |_| {
+            sender.input(MonitorInput::UpdateColors);
+        });
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, input: Self::Input, _sender: ComponentSender<Self>, _root: &Self::Root) {
+        match input {
+            MonitorInput::Redraw => {}
+            MonitorInput::UpdateColors => {
+                self.colors = Colors::new();
+            }
+        }
+        self.draw();
+    }
+
+    fn update_cmd(
+        &mut self,
+        samples: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        for status in samples {
+            let idx = status.index as usize;
+            if self.history.len() <= idx {
+                self.history.resize_with(idx + 1, VecDeque::new);
+            }
+            let history = &mut self.history[idx];
+            if history.len() == HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(status);
+        }
+        self.draw();
+    }
+}
+
+impl Monitor {
+    fn draw(&mut self) {
+        let ctx = self.drawing_handler.get_context();
+        let width = self.drawing_handler.width() as f64;
+        let height = self.drawing_handler.height() as f64;
+
+        // Clear the image surface
+        ctx.set_operator(Operator::Source);
+        ctx.set_source_rgba(0.0, 0.0, 0.0, 0.0);
+        ctx.rectangle(0.0, 0.0, width, height);
+        ctx.fill().unwrap();
+        ctx.set_operator(Operator::Over);
+
+        if self.history.is_empty() {
+            return;
+        }
+
+        let chart_height = height / self.history.len() as f64;
+        for (idx, history) in self.history.iter().enumerate() {
+            let top = idx as f64 * chart_height;
+            self.draw_chart(&ctx, history, top, width, chart_height);
+        }
+    }
+
+    /// Draw the chart of one fan into the area starting at `top`.
+    fn draw_chart(
+        &self,
+        ctx: &Context,
+        history: &VecDeque<FanStatus>,
+        top: f64,
+        width: f64,
+        height: f64,
+    ) {
+        const PADDING: f64 = 12.0;
+        let (top, height) = (top + PADDING, height - 2.0 * PADDING);
+        // Maps a value from 0 to 100 onto the chart.
+        let to_y = |value: u8| top + height - f64::from(value.min(100)) * height / 100.0;
+        let step = width / (HISTORY_LEN - 1) as f64;
+        // The newest sample is always drawn on the right edge.
+        let offset = (HISTORY_LEN - history.len()) as f64 * step;
+
+        ctx.set_line_width(1.0);
+        set_source_rgba(ctx, &self.colors.grid, 0.3);
+        for value in [0, 50, 100] {
+            ctx.move_to(0.0, to_y(value));
+            ctx.line_to(width, to_y(value));
+        }
+        ctx.stroke().unwrap();
+
+        let lines: [(&RGBA, fn(&FanStatus) -> u8); 3] = [
+            (&self.colors.power_limit, |status| status.power_limit),
+            (&self.colors.speed, |status| status.speed),
+            (&self.colors.temperature, |status| status.temperature),
+        ];
+        ctx.set_line_width(2.0);
+        for (color, value) in lines {
+            set_source_rgba(ctx, color, 1.0);
+            for (idx, status) in history.iter().enumerate() {
+                ctx.line_to(offset + idx as f64 * step, to_y(value(status)));
+            }
+            ctx.stroke().unwrap();
+        }
+
+        if let Some(status) = history.back() {
+            set_source_rgba(ctx, &self.colors.grid, 1.0);
+            ctx.move_to(PADDING, top + PADDING);
+            ctx.show_text(&format!(
+                "Fan {}: {}°C, {}%, power limit {}%",
+                status.index + 1,
+                status.temperature,
+                status.speed,
+                status.power_limit
+            ))
+            .unwrap();
+        }
+    }
+}
+
+fn set_source_rgba(ctx: &Context, color: &RGBA, alpha: f64) {
+    ctx.set_source_rgba(
+        color.red() as f64,
+        color.green() as f64,
+        color.blue() as f64,
+        alpha,
+    );
+}
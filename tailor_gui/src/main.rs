@@ -4,6 +4,7 @@ mod app;
 pub mod components;
 mod config;
 mod modals;
+mod presets;
 mod setup;
 pub mod state;
 pub mod templates;
@@ -1,2 +1,3 @@
 pub mod about;
 pub mod add_profile;
+pub mod setup_wizard;
@@ -0,0 +1,236 @@
+use adw::prelude::ActionRowExt;
+use gtk::prelude::{
+    BoxExt, ButtonExt, CheckButtonExt, GtkWindowExt, OrientableExt, SettingsExt, WidgetExt,
+};
+use relm4::{adw, gtk, Component, ComponentParts, ComponentSender, RelmWidgetExt};
+use tailor_api::{DeviceList, LedControllerMode};
+
+use crate::config::APP_ID;
+use crate::presets::PRESETS;
+use crate::state::{hardware_capabilities, tailor_connection, TailorStateMsg, STATE};
+use crate::templates;
+
+/// The preset that is activated unless the user picks another one.
+const DEFAULT_PRESET: usize = 1;
+
+pub struct SetupWizard {
+    devices: Option<DeviceList>,
+    /// Index of the preset in [`PRESETS`] that will be activated.
+    active: usize,
+}
+
+#[derive(Debug)]
+pub enum SetupWizardInput {
+    SetActive(usize),
+    Apply,
+    Skip,
+}
+
+#[relm4::component(pub)]
+impl Component for SetupWizard {
+    type CommandOutput = Option<DeviceList>;
+    type Init = ();
+    type Input = SetupWizardInput;
+    type Output = ();
+
+    view! {
+        #[template]
+        templates::DialogWindow {
+            set_visible: true,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+
+                gtk::WindowHandle {
+                    gtk::CenterBox {
+                        #[wrap(Some)]
+                        set_center_widget = &gtk::Label {
+                            add_css_class: "title-4",
+                            set_margin_all: 12,
+                            set_label: "Welcome to Tailor",
+                        },
+                    },
+                },
+
+                gtk::ScrolledWindow {
+                    add_css_class: "background",
+                    set_vexpand: true,
+
+                    adw::Clamp {
+                        set_margin_all: 12,
+
+                        gtk::Box {
+                            set_orientation: gtk::Orientation::Vertical,
+                            set_spacing: 12,
+
+                            gtk::Label {
+                                set_wrap: true,
+                                set_halign: gtk::Align::Start,
+                                set_label: "Tailor can create a few profiles that match your hardware. \
+                                    You can change or remove them later.",
+                            },
+
+                            gtk::Label {
+                                add_css_class: "dim-label",
+                                set_wrap: true,
+                                set_halign: gtk::Align::Start,
+                                #[watch]
+                                set_label: &model.hardware_summary(),
+                            },
+
+                            #[local_ref]
+                            preset_list -> gtk::ListBox {
+                                add_css_class: "boxed-list",
+                                set_selection_mode: gtk::SelectionMode::None,
+                            },
+
+                            gtk::Label {
+                                add_css_class: "dim-label",
+                                set_wrap: true,
+                                set_halign: gtk::Align::Start,
+                                set_label: "The selected profile will be activated. \
+                                    Existing profiles with the same names are overwritten.",
+                            },
+                        }
+                    }
+                },
+
+                gtk::Separator {},
+
+                #[template]
+                templates::MsgDialogButtons {
+                    #[template_child]
+                    cancel_button -> gtk::Button {
+                        set_label: "Skip",
+                        connect_clicked => SetupWizardInput::Skip,
+                    },
+                    #[template_child]
+                    save_button -> gtk::Button {
+                        set_label: "Create profiles",
+                        #[watch]
+                        set_sensitive: model.devices.is_some(),
+                        connect_clicked => SetupWizardInput::Apply,
+                    },
+                }
+            }
+        }
+    }
+
+    fn init(
+        _: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let preset_list = gtk::ListBox::default();
+        let mut group: Option<gtk::CheckButton> = None;
+        for (idx, preset) in PRESETS.iter().enumerate() {
+            let check_button = gtk::CheckButton::builder()
+                .active(idx == DEFAULT_PRESET)
+                .valign(gtk::Align::Center)
+                .build();
+            check_button.set_group(group.as_ref());
+            let input_sender = sender.input_sender().clone();
+            check_button.connect_toggled(move |button| {
+                if button.is_active() {
+                    input_sender.emit(SetupWizardInput::SetActive(idx));
+                }
+            });
+
+            let row = adw::ActionRow::builder()
+                .title(preset.name)
+                .subtitle(preset.description)
+                .activatable_widget(&check_button)
+                .build();
+            row.add_prefix(&check_button);
+            preset_list.append(&row);
+            group.get_or_insert(check_button);
+        }
+
+        let model = Self {
+            devices: None,
+            active: DEFAULT_PRESET,
+        };
+
+        let widgets = view_output!();
+
+        let connection = tailor_connection().unwrap();
+        sender.oneshot_command(async move {
+            match connection.list_devices().await {
+                Ok(devices) => Some(devices),
+                Err(err) => {
+                    tracing::error!("Couldn't detect the hardware: `{err}`");
+                    None
+                }
+            }
+        });
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, input: Self::Input, _sender: ComponentSender<Self>, root: &Self::Root) {
+        match input {
+            SetupWizardInput::SetActive(idx) => {
+                self.active = idx;
+            }
+            SetupWizardInput::Apply => {
+                if let Some(devices) = &self.devices {
+                    let performance_profiles = hardware_capabilities()
+                        .and_then(|capabilities| capabilities.performance_profiles.as_deref());
+                    let presets = PRESETS
+                        .iter()
+                        .map(|preset| preset.profiles(devices, performance_profiles))
+                        .collect();
+                    STATE.emit(TailorStateMsg::AddPresets {
+                        presets,
+                        active: PRESETS[self.active].name.to_owned(),
+                    });
+                }
+                finish(root);
+            }
+            SetupWizardInput::Skip => finish(root),
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        devices: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        self.devices = devices;
+    }
+}
+
+impl SetupWizard {
+    fn hardware_summary(&self) -> String {
+        match &self.devices {
+            Some(devices) => {
+                let rgb = devices
+                    .leds
+                    .iter()
+                    .filter(|led| led.mode == LedControllerMode::Rgb)
+                    .count();
+                format!(
+                    "Detected {} fan(s), {} RGB and {} monochrome LED device(s).",
+                    devices.fans.len(),
+                    rgb,
+                    devices.leds.len() - rgb,
+                )
+            }
+            None => "Detecting hardware...".to_owned(),
+        }
+    }
+
+    /// Whether the wizard should be shown when Tailor starts.
+    pub fn is_pending() -> bool {
+        !gtk::gio::Settings::new(APP_ID).boolean("setup-completed")
+    }
+}
+
+/// Don't show the wizard again and close it.
+fn finish(root: &adw::Window) {
+    if let Err(err) = gtk::gio::Settings::new(APP_ID).set_boolean("setup-completed", true) {
+        tracing::error!("Couldn't store the setup state: `{err}`");
+    }
+    root.destroy();
+}
@@ -0,0 +1,127 @@
+//! Profiles that are offered by the setup wizard.
+
+use tailor_api::{
+    Color, ColorProfile, DeviceList, FanProfile, LedControllerMode, LedProfile,
+    Preset as FanPreset, ProfileInfo,
+};
+
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The built-in fan curve.
+    fan: FanPreset,
+    /// Color of RGB keyboards, monochrome keyboards are always white.
+    color: Color,
+    /// Brightness of monochrome keyboards.
+    brightness: u8,
+    /// Performance profiles of the firmware, in order of preference.
+    performance_profiles: &'static [&'static str],
+}
+
+pub const PRESETS: [Preset; 3] = [
+    Preset {
+        name: "quiet",
+        description: "Low fan noise and dimmed keyboard lights",
+        fan: FanPreset::Silent,
+        color: Color {
+            r: 0,
+            g: 64,
+            b: 128,
+        },
+        brightness: 64,
+        performance_profiles: &["quiet", "power_saving", "power_save"],
+    },
+    Preset {
+        name: "balanced",
+        description: "A good compromise for everyday use",
+        fan: FanPreset::Balanced,
+        color: Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        },
+        brightness: 160,
+        performance_profiles: &["entertainment", "power_save", "power_saving"],
+    },
+    Preset {
+        name: "performance",
+        description: "Keeps the hardware cool under heavy load",
+        fan: FanPreset::Performance,
+        color: Color { r: 255, g: 0, b: 0 },
+        brightness: 255,
+        performance_profiles: &["performance", "overboost", "enthusiast"],
+    },
+];
+
+/// Everything that needs to be stored to add a preset to tailord.
+#[derive(Debug, Clone)]
+pub struct PresetProfiles {
+    pub name: String,
+    pub fan_profile: FanProfile,
+    pub led_profiles: Vec<(String, ColorProfile)>,
+    pub profile: ProfileInfo,
+}
+
+impl Preset {
+    /// Create the profiles of the preset for the detected hardware.
+    pub fn profiles(
+        &self,
+        devices: &DeviceList,
+        performance_profiles: Option<&[String]>,
+    ) -> PresetProfiles {
+        let fan_profile = FanProfile::preset(self.fan);
+
+        // One LED profile for each kind of controller that is present.
+        let mut led_profiles: Vec<(String, ColorProfile)> = Vec::new();
+        let mut leds = Vec::with_capacity(devices.leds.len());
+        for device in &devices.leds {
+            let (led_name, color) = match device.mode {
+                LedControllerMode::Monochrome => (
+                    format!("{}-monochrome", self.name),
+                    Color {
+                        r: self.brightness,
+                        g: self.brightness,
+                        b: self.brightness,
+                    },
+                ),
+                _ => (self.name.to_owned(), self.color.clone()),
+            };
+            if !led_profiles.iter().any(|(name, _)| name == &led_name) {
+                led_profiles.push((led_name.clone(), ColorProfile::Single(color)));
+            }
+            leds.push(LedProfile {
+                device_name: device.device_name.clone(),
+                function: device.function.clone(),
+                profile: led_name,
+                mode: device.mode,
+            });
+        }
+
+        let performance_profile = performance_profiles.and_then(|available| {
+            self.performance_profiles
+                .iter()
+                .find(|name| available.iter().any(|available| available == *name))
+                .map(|name| name.to_string())
+        });
+
+        PresetProfiles {
+            name: self.name.to_owned(),
+            fan_profile,
+            led_profiles,
+            profile: ProfileInfo {
+                fans: vec![self.name.to_owned(); devices.fans.len()],
+                leds,
+                performance_profile,
+                webcam: None,
+                fn_lock: None,
+                touchpad: None,
+                tdp: Default::default(),
+                cpu_governor: None,
+                energy_performance_preference: None,
+                display_brightness: None,
+                lid_closed: Default::default(),
+                disable_power_limits: false,
+            },
+        }
+    }
+}
@@ -2,15 +2,19 @@ use std::time::Duration;
 
 use relm4::tokio::sync::OnceCell;
 use relm4::{Reducer, Reducible};
-use tailor_api::{Color, ColorProfile, FanProfilePoint, LedDeviceInfo, ProfileInfo};
+use tailor_api::{Color, ColorProfile, FanProfile, LedDeviceInfo, ProfileInfo};
 use tailor_client::{ClientError, TailorConnection};
 
 use crate::app::FullProfileInfo;
+use crate::presets::PresetProfiles;
 
 pub static STATE: Reducer<TailorState> = Reducer::new();
 static CONNECTION: OnceCell<TailorConnection<'static>> = OnceCell::const_new();
 static HARDWARE_CAPABILITIES: OnceCell<HardwareCapabilities> = OnceCell::const_new();
 
+/// How long a fan curve preview lasts before the stored curves are used again.
+const FAN_PREVIEW_SECONDS: u32 = 60;
+
 pub fn tailor_connection() -> Option<&'static TailorConnection<'static>> {
     CONNECTION.get()
 }
@@ -24,6 +28,8 @@ pub struct HardwareCapabilities {
     pub num_of_fans: u8,
     pub led_devices: Vec<LedDeviceInfo>,
     pub performance_profiles: Option<Vec<String>>,
+    /// Whether the webcam can be switched on and off.
+    pub webcam: bool,
 }
 
 pub enum TailorState {
@@ -78,12 +84,24 @@ pub enum TailorStateMsg {
     },
     AddFanProfile {
         name: String,
-        profile: Vec<FanProfilePoint>,
+        profile: FanProfile,
     },
     AddLedProfile {
         name: String,
         profile: ColorProfile,
     },
+    /// Add the profiles of the setup wizard and activate one of them.
+    AddPresets {
+        presets: Vec<PresetProfiles>,
+        active: String,
+    },
+    /// Store the LED profiles of all keyboard zones
+    /// and assign them to the global profile `name`.
+    SetKeyboardZones {
+        name: String,
+        profile: ProfileInfo,
+        led_profiles: Vec<(String, ColorProfile)>,
+    },
     CopyProfile {
         from: String,
         to: String,
@@ -116,6 +134,12 @@ pub enum TailorStateMsg {
         fan_idx: u8,
         speed: u8,
     },
+    BoostFans(u32),
+    PreviewFanProfile {
+        fan_idx: u8,
+        profile: FanProfile,
+    },
+    ReloadFanProfile,
     Error(String),
 }
 
@@ -211,6 +235,107 @@ impl Reducible for TailorState {
                     }
                 }
             }
+            TailorStateMsg::AddPresets { presets, active } => {
+                if let Some(state) = self.get_mut() {
+                    {
+                        let presets = presets.clone();
+                        let active = active.clone();
+                        let connection = state.connection.clone();
+                        // Global profiles refer to the fan and LED profiles,
+                        // so they have to be stored in order.
+                        relm4::spawn(async move {
+                            for preset in &presets {
+                                handle_result(
+                                    connection
+                                        .add_fan_profile(&preset.name, &preset.fan_profile)
+                                        .await,
+                                );
+                                for (led_name, color_profile) in &preset.led_profiles {
+                                    handle_result(
+                                        connection.add_led_profile(led_name, color_profile).await,
+                                    );
+                                }
+                                handle_result(
+                                    connection
+                                        .add_global_profile(&preset.name, &preset.profile)
+                                        .await,
+                                );
+                            }
+                            handle_result(connection.set_active_global_profile_name(&active).await);
+                            handle_result(connection.reload().await);
+                        });
+                    }
+
+                    for preset in presets {
+                        if !state.fan_profiles.contains(&preset.name) {
+                            state.get_mut_fan_profiles().push(preset.name.clone());
+                        }
+                        for (led_name, _) in preset.led_profiles {
+                            if !state.led_profiles.contains(&led_name) {
+                                state.get_mut_led_profiles().push(led_name);
+                            }
+                        }
+                        let profiles = state.get_mut_profiles();
+                        if let Some(full_profile) = profiles
+                            .iter_mut()
+                            .find(|full_profile| full_profile.name == preset.name)
+                        {
+                            full_profile.data = preset.profile;
+                        } else {
+                            profiles.push(FullProfileInfo {
+                                name: preset.name,
+                                data: preset.profile,
+                            });
+                        }
+                    }
+                    *state.get_mut_active_profile_name() = active;
+                }
+            }
+            TailorStateMsg::SetKeyboardZones {
+                name,
+                profile,
+                led_profiles,
+            } => {
+                if let Some(state) = self.get_mut() {
+                    let new_led_profiles: Vec<String> = led_profiles
+                        .iter()
+                        .map(|(led_name, _)| led_name.clone())
+                        .filter(|led_name| !state.led_profiles.contains(led_name))
+                        .collect();
+                    {
+                        let name = name.clone();
+                        let profile = profile.clone();
+                        let connection = state.connection.clone();
+                        // The LED profiles must exist before the global profile refers to them.
+                        relm4::spawn(async move {
+                            for (led_name, color_profile) in &led_profiles {
+                                handle_result(
+                                    connection.add_led_profile(led_name, color_profile).await,
+                                );
+                            }
+                            handle_result(connection.add_global_profile(&name, &profile).await);
+                            if let Some(active_name) =
+                                handle_result(connection.get_active_global_profile_name().await)
+                            {
+                                if active_name == name {
+                                    handle_result(connection.reload_led_profile().await);
+                                }
+                            }
+                        });
+                    }
+
+                    if !new_led_profiles.is_empty() {
+                        state.get_mut_led_profiles().extend(new_led_profiles);
+                    }
+                    if let Some(full_profile) = state
+                        .get_mut_profiles()
+                        .iter_mut()
+                        .find(|full_profile| full_profile.name == name)
+                    {
+                        full_profile.data = profile;
+                    }
+                }
+            }
             TailorStateMsg::CopyProfile { from, to } => {
                 if let Some(state) = self.get_mut() {
                     {
@@ -362,6 +487,37 @@ impl Reducible for TailorState {
                 }
                 return false;
             }
+            TailorStateMsg::BoostFans(seconds) => {
+                if let Some(state) = self.get() {
+                    let connection = state.connection.clone();
+                    relm4::spawn(async move {
+                        handle_result(connection.boost_fans(seconds).await);
+                    });
+                }
+                return false;
+            }
+            TailorStateMsg::PreviewFanProfile { fan_idx, profile } => {
+                if let Some(state) = self.get() {
+                    let connection = state.connection.clone();
+                    relm4::spawn(async move {
+                        handle_result(
+                            connection
+                                .preview_fan_profile(fan_idx, &profile, FAN_PREVIEW_SECONDS)
+                                .await,
+                        );
+                    });
+                }
+                return false;
+            }
+            TailorStateMsg::ReloadFanProfile => {
+                if let Some(state) = self.get() {
+                    let connection = state.connection.clone();
+                    relm4::spawn(async move {
+                        handle_result(connection.reload_fan_profile().await);
+                    });
+                }
+                return false;
+            }
             TailorStateMsg::Error(error) => {
                 if let Some(state) = self.get_mut() {
                     state.set_error(Some(error));
@@ -388,10 +544,13 @@ pub async fn initialize_tailor_state() -> Result<(), String> {
         .map_err(|err| tracing::info!("No performance handler available: {err}"))
         .ok();
 
+    let webcam = connection.get_webcam_enabled().await.is_ok();
+
     let capabilities = HardwareCapabilities {
         num_of_fans,
         led_devices,
         performance_profiles,
+        webcam,
     };
 
     let active_profile_name = connection
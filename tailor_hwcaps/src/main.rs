@@ -28,7 +28,7 @@ fn main() {
 
     ioctl();
 
-    tokio_uring::start(sysfs());
+    tuxedo_sysfs::runtime::start(sysfs());
 }
 
 fn ioctl() {
@@ -41,6 +41,7 @@ fn ioctl() {
         }
     };
 
+    print_value("Backend", &io.backend);
     print_value("Module version", &io.module_version);
 
     print_result("Device interface ID", &io.device.device_interface_id_str());
@@ -72,6 +73,18 @@ fn ioctl() {
         print_info("Webcam control is not available");
     }
 
+    if let Some(fn_lock) = &io.fn_lock {
+        print_result("Fn-lock enabled", &fn_lock.get_fn_lock());
+    } else {
+        print_info("Fn-lock control is not available");
+    }
+
+    if let Some(touchpad) = &io.touchpad {
+        print_result("Touchpad enabled", &touchpad.get_touchpad());
+    } else {
+        print_info("Touchpad control is not available");
+    }
+
     if let Some(tdp) = &io.tdp {
         let number_of_tdp_devices = tdp.get_number_tdps();
         print_result("number_of_tdp_devices", &number_of_tdp_devices);
@@ -94,6 +107,8 @@ fn ioctl() {
     } else {
         print_info("TDP control is not available");
     }
+
+    print_value("Charging control", &io.has_charging_control());
 }
 
 async fn sysfs() {
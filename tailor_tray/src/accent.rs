@@ -0,0 +1,134 @@
+//! Show the accent color of the desktop on the keyboard.
+//!
+//! The color is read from the settings portal, where GNOME, KDE Plasma
+//! and other desktops publish the accent color that the user picked.
+
+use futures_util::StreamExt;
+use tailor_api::Color;
+use tailor_client::TailorConnection;
+use zbus::{proxy, zvariant::Value, Connection};
+
+const NAMESPACE: &str = "org.freedesktop.appearance";
+const KEY: &str = "accent-color";
+
+#[proxy(
+    interface = "org.freedesktop.portal.Settings",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait Settings {
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::Result<zbus::zvariant::OwnedValue>;
+
+    #[zbus(signal)]
+    fn setting_changed(&self, namespace: &str, key: &str, value: Value<'_>) -> zbus::Result<()>;
+}
+
+/// The accent color and its changes, if the desktop provides one.
+pub struct AccentColor {
+    color: Option<Color>,
+    changes: SettingChangedStream<'static>,
+}
+
+impl AccentColor {
+    pub async fn new(session: &Connection) -> zbus::Result<Self> {
+        let portal = SettingsProxy::new(session).await?;
+        let changes = portal.receive_setting_changed().await?;
+        let color = match portal.read_one(NAMESPACE, KEY).await {
+            Ok(value) => to_color(&value),
+            Err(err) => {
+                tracing::warn!("Failed to read the accent color: `{err}`");
+                None
+            }
+        };
+        Ok(Self { color, changes })
+    }
+
+    /// Wait for the next accent color the user picks.
+    pub async fn next(&mut self) -> Color {
+        while let Some(signal) = self.changes.next().await {
+            let Ok(args) = signal.args() else {
+                continue;
+            };
+            if args.namespace != NAMESPACE || args.key != KEY {
+                continue;
+            }
+            if let Some(color) = to_color(&args.value) {
+                self.color = Some(color.clone());
+                return color;
+            }
+        }
+        std::future::pending().await
+    }
+}
+
+/// Wait for the next accent color, or forever if it isn't synced.
+pub async fn next_color(accent: &mut Option<AccentColor>) -> Color {
+    match accent {
+        Some(accent) => accent.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Show the last accent color again, e.g. after a profile replaced it.
+pub async fn reapply(tailor: &TailorConnection<'_>, accent: &Option<AccentColor>) {
+    if let Some(color) = accent.as_ref().and_then(|accent| accent.color.as_ref()) {
+        apply(tailor, color).await;
+    }
+}
+
+/// Show `color` on the keyboard until tailord activates a profile.
+pub async fn apply(tailor: &TailorConnection<'_>, color: &Color) {
+    tracing::info!("Showing the accent color {color} on the keyboard");
+    if let Err(err) = tailor.set_temporary_led_color(color).await {
+        tracing::error!("Failed to show the accent color: `{err}`");
+    }
+}
+
+/// The portal sends the color as RGB from 0 to 1, values
+/// out of range mean that the user didn't pick a color.
+fn to_color(value: &Value<'_>) -> Option<Color> {
+    match value {
+        Value::Value(inner) => to_color(inner),
+        Value::Structure(structure) => {
+            let [Value::F64(r), Value::F64(g), Value::F64(b)] = structure.fields() else {
+                return None;
+            };
+            let channel = |value: f64| {
+                (0.0..=1.0)
+                    .contains(&value)
+                    .then(|| (value * 255.0).round() as u8)
+            };
+            Some(Color {
+                r: channel(*r)?,
+                g: channel(*g)?,
+                b: channel(*b)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tailor_api::Color;
+    use zbus::zvariant::Value;
+
+    use super::to_color;
+
+    #[test]
+    fn accent_colors() {
+        let blue = Value::from((0.2078, 0.5176, 0.8941));
+        assert_eq!(
+            to_color(&blue),
+            Some(Color {
+                r: 53,
+                g: 132,
+                b: 228
+            })
+        );
+        // Read returns the value wrapped in another variant.
+        assert!(to_color(&Value::Value(Box::new(blue))).is_some());
+        assert_eq!(to_color(&Value::from((-1.0, -1.0, -1.0))), None);
+        assert_eq!(to_color(&Value::from("blue")), None);
+    }
+}
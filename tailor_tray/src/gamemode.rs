@@ -0,0 +1,92 @@
+//! Switch to a designated profile while games use Feral GameMode
+//! and back to the previous profile once the last game exited.
+//!
+//! GameMode runs on the session bus, which tailord can't see,
+//! so the tray watches it on behalf of the user.
+
+use futures_util::StreamExt;
+use tailor_client::{ClientResult, TailorConnection};
+use zbus::{proxy, proxy::PropertyStream, Connection};
+
+#[proxy(
+    interface = "com.feralinteractive.GameMode",
+    default_service = "com.feralinteractive.GameMode",
+    default_path = "/com/feralinteractive/GameMode"
+)]
+trait GameMode {
+    /// Number of games that currently requested GameMode.
+    #[zbus(property)]
+    fn client_count(&self) -> zbus::Result<i32>;
+}
+
+pub struct GameModeSwitcher {
+    profile: String,
+    /// The profile that was active before the first game started.
+    previous: Option<String>,
+}
+
+impl GameModeSwitcher {
+    pub fn new(profile: String) -> Self {
+        Self {
+            profile,
+            previous: None,
+        }
+    }
+
+    /// Apply the number of games that use GameMode.
+    pub async fn update(
+        &mut self,
+        tailor: &TailorConnection<'_>,
+        clients: i32,
+    ) -> ClientResult<()> {
+        if clients > 0 && self.previous.is_none() {
+            let active = tailor.get_active_global_profile_name().await?;
+            if active != self.profile {
+                tracing::info!("A game started, switching to profile `{}`", self.profile);
+                activate(tailor, &self.profile).await?;
+            }
+            self.previous = Some(active);
+        } else if clients == 0 {
+            let Some(previous) = self.previous.take() else {
+                return Ok(());
+            };
+            // Keep profiles the user picked while playing.
+            if tailor.get_active_global_profile_name().await? == self.profile
+                && previous != self.profile
+            {
+                tracing::info!("All games exited, switching back to profile `{previous}`");
+                activate(tailor, &previous).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn activate(tailor: &TailorConnection<'_>, profile: &str) -> ClientResult<()> {
+    tailor.set_active_global_profile_name(profile).await?;
+    tailor.reload().await
+}
+
+/// Changes of the number of games that use GameMode.
+pub async fn receive_client_count(
+    session: &Connection,
+) -> zbus::Result<PropertyStream<'static, i32>> {
+    Ok(GameModeProxy::new(session)
+        .await?
+        .receive_client_count_changed()
+        .await)
+}
+
+/// Wait for the next change, or forever if GameMode isn't watched.
+pub async fn next_client_count(stream: &mut Option<PropertyStream<'static, i32>>) -> i32 {
+    let Some(stream) = stream else {
+        return std::future::pending().await;
+    };
+    while let Some(change) = stream.next().await {
+        match change.get().await {
+            Ok(count) => return count,
+            Err(err) => tracing::warn!("Failed to read the GameMode client count: `{err}`"),
+        }
+    }
+    std::future::pending().await
+}
@@ -0,0 +1,94 @@
+//! The `org.kde.StatusNotifierItem` object that tray hosts display.
+
+use std::sync::{Arc, Mutex};
+
+use zbus::{interface, proxy, zvariant::OwnedObjectPath, SignalContext};
+
+use crate::{menu::MENU_PATH, state::TrayState};
+
+pub const ITEM_PATH: &str = "/StatusNotifierItem";
+
+/// Icon installed by Tailor GUI.
+pub const ICON_NAME: &str = "com.github.aaronerhardt.Tailor";
+
+/// Icon name, icon pixmaps, title and description.
+type ToolTip = (String, Vec<(i32, i32, Vec<u8>)>, String, String);
+
+#[proxy(
+    interface = "org.kde.StatusNotifierWatcher",
+    default_service = "org.kde.StatusNotifierWatcher",
+    default_path = "/StatusNotifierWatcher"
+)]
+pub trait StatusNotifierWatcher {
+    fn register_status_notifier_item(&self, service: &str) -> zbus::Result<()>;
+}
+
+pub struct Item {
+    pub state: Arc<Mutex<TrayState>>,
+}
+
+#[interface(name = "org.kde.StatusNotifierItem")]
+impl Item {
+    /// Left click: open the GUI.
+    fn activate(&self, _x: i32, _y: i32) {
+        crate::open_gui();
+    }
+
+    fn secondary_activate(&self, _x: i32, _y: i32) {}
+
+    fn context_menu(&self, _x: i32, _y: i32) {}
+
+    fn scroll(&self, _delta: i32, _orientation: &str) {}
+
+    #[zbus(property)]
+    fn category(&self) -> &str {
+        "Hardware"
+    }
+
+    #[zbus(property)]
+    fn id(&self) -> &str {
+        "tailor"
+    }
+
+    #[zbus(property)]
+    fn title(&self) -> String {
+        format!("Tailor: {}", self.state.lock().unwrap().summary())
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> &str {
+        "Active"
+    }
+
+    #[zbus(property)]
+    fn icon_name(&self) -> &str {
+        ICON_NAME
+    }
+
+    #[zbus(property)]
+    fn tool_tip(&self) -> ToolTip {
+        let summary = self.state.lock().unwrap().summary();
+        (
+            ICON_NAME.to_owned(),
+            Vec::new(),
+            "Tailor".to_owned(),
+            summary,
+        )
+    }
+
+    #[zbus(property)]
+    fn menu(&self) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(MENU_PATH).unwrap()
+    }
+
+    #[zbus(property)]
+    fn item_is_menu(&self) -> bool {
+        false
+    }
+
+    #[zbus(signal)]
+    pub async fn new_title(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    pub async fn new_tool_tip(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+}
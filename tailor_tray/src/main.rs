@@ -0,0 +1,187 @@
+//! Tray indicator that shows the temperature and the active profile
+//! and offers quick actions without opening Tailor GUI.
+
+mod accent;
+mod gamemode;
+mod item;
+mod menu;
+mod notifications;
+mod state;
+
+use std::{
+    process::Command,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use eyre::Result;
+use futures_util::StreamExt;
+use tailor_client::TailorConnection;
+use tokio::sync::mpsc;
+use zbus::connection;
+
+use accent::AccentColor;
+use gamemode::GameModeSwitcher;
+use item::{Item, StatusNotifierWatcherProxy, ITEM_PATH};
+use menu::{Menu, MENU_PATH};
+use notifications::{Message, Notifier};
+use state::{fetch_profiles, TrayState};
+
+/// tailord has no signal for added or removed profiles, so the list is polled.
+const PROFILE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    let args: Vec<String> = std::env::args().collect();
+    let show_notifications = !args.iter().any(|arg| arg == "--no-notifications");
+    let gamemode_profile = args
+        .iter()
+        .position(|arg| arg == "--gamemode-profile")
+        .and_then(|idx| args.get(idx + 1))
+        .cloned();
+    let sync_accent_color = args.iter().any(|arg| arg == "--sync-accent-color");
+
+    let tailor = TailorConnection::new().await?;
+    let state = Arc::new(Mutex::new(TrayState::default()));
+    {
+        let status = tailor.get_fan_status().await.unwrap_or_default();
+        let profiles = fetch_profiles(&tailor).await?;
+        let mut state = state.lock().unwrap();
+        for status in &status {
+            state.update_temperature(status);
+        }
+        state.set_profiles(profiles);
+    }
+
+    let (quit_sender, mut quit_receiver) = mpsc::channel(1);
+    let name = format!("org.kde.StatusNotifierItem-{}-1", std::process::id());
+    let session = connection::Builder::session()?
+        .name(name.as_str())?
+        .serve_at(
+            ITEM_PATH,
+            Item {
+                state: state.clone(),
+            },
+        )?
+        .serve_at(
+            MENU_PATH,
+            Menu {
+                state: state.clone(),
+                connection: tailor.clone(),
+                quit_sender,
+            },
+        )?
+        .build()
+        .await?;
+
+    StatusNotifierWatcherProxy::new(&session)
+        .await?
+        .register_status_notifier_item(&name)
+        .await?;
+
+    let item = session
+        .object_server()
+        .interface::<_, Item>(ITEM_PATH)
+        .await?;
+    let menu = session
+        .object_server()
+        .interface::<_, Menu>(MENU_PATH)
+        .await?;
+
+    let fan_status = tailor.receive_fan_status().await?;
+    let mut fan_status = std::pin::pin!(fan_status);
+    let profile_switches = tailor.receive_profile_switches().await?;
+    let mut profile_switches = std::pin::pin!(profile_switches);
+    let sensor_failures = tailor.receive_sensor_failures().await?;
+    let mut sensor_failures = std::pin::pin!(sensor_failures);
+    let thermal_trips = tailor.receive_thermal_trips().await?;
+    let mut thermal_trips = std::pin::pin!(thermal_trips);
+    let mut interval = tokio::time::interval(PROFILE_REFRESH_INTERVAL);
+    let mut notifier = Notifier::default();
+
+    let mut gamemode_switcher = gamemode_profile.map(GameModeSwitcher::new);
+    let mut gamemode_clients = match gamemode_switcher {
+        Some(_) => Some(gamemode::receive_client_count(&session).await?),
+        None => None,
+    };
+
+    let mut accent_color = match sync_accent_color {
+        true => Some(AccentColor::new(&session).await?),
+        false => None,
+    };
+    accent::reapply(&tailor, &accent_color).await;
+
+    loop {
+        let mut message: Option<Message> = None;
+        let changed = tokio::select! {
+            Some(status) = fan_status.next() => {
+                message = notifier.fan_status(&status);
+                state.lock().unwrap().update_temperature(&status)
+            }
+            Some(switch) = profile_switches.next() => {
+                message = notifier.profile_switch(&switch);
+                // The profile brought its own colors.
+                accent::reapply(&tailor, &accent_color).await;
+                refresh_profiles(&tailor, &state).await
+            }
+            Some((fan_idx, error)) = sensor_failures.next() => {
+                message = Some(notifier.sensor_failure(fan_idx, &error));
+                false
+            }
+            Some((fan_idx, temperature)) = thermal_trips.next() => {
+                message = Some(notifier.thermal_trip(fan_idx, temperature));
+                false
+            }
+            clients = gamemode::next_client_count(&mut gamemode_clients) => {
+                if let Some(switcher) = &mut gamemode_switcher {
+                    if let Err(err) = switcher.update(&tailor, clients).await {
+                        tracing::error!("Failed to switch the profile for GameMode: `{err}`");
+                    }
+                }
+                false
+            }
+            color = accent::next_color(&mut accent_color) => {
+                accent::apply(&tailor, &color).await;
+                false
+            }
+            _ = interval.tick() => refresh_profiles(&tailor, &state).await,
+            _ = quit_receiver.recv() => break,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+
+        if let Some(message) = message.filter(|_| show_notifications) {
+            message.show().await;
+        }
+
+        if changed {
+            let revision = state.lock().unwrap().bump_revision();
+            Item::new_title(item.signal_context()).await?;
+            Item::new_tool_tip(item.signal_context()).await?;
+            Menu::layout_updated(menu.signal_context(), revision, 0).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the profiles changed.
+async fn refresh_profiles(tailor: &TailorConnection<'_>, state: &Mutex<TrayState>) -> bool {
+    match fetch_profiles(tailor).await {
+        Ok(profiles) => state.lock().unwrap().set_profiles(profiles),
+        Err(err) => {
+            tracing::warn!("Failed to read the profiles: `{err}`");
+            false
+        }
+    }
+}
+
+/// Start Tailor GUI without blocking the tray.
+fn open_gui() {
+    match Command::new("tailor_gui").spawn() {
+        // Reap the process once the window is closed.
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(err) => tracing::error!("Failed to start tailor_gui: `{err}`"),
+    }
+}
@@ -0,0 +1,346 @@
+//! The `com.canonical.dbusmenu` object that tray hosts show when
+//! the item is right-clicked.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tailor_api::Color;
+use tailor_client::TailorConnection;
+use tokio::sync::mpsc;
+use zbus::{
+    fdo, interface,
+    zvariant::{OwnedValue, Structure, Value},
+    SignalContext,
+};
+
+use crate::state::TrayState;
+
+pub const MENU_PATH: &str = "/MenuBar";
+
+/// Duration of a fan boost triggered from the tray.
+const BOOST_SECONDS: u32 = 60;
+
+/// Id, properties and children (each a variant containing a layout).
+type Layout = (i32, HashMap<String, Value<'static>>, Vec<Value<'static>>);
+
+// Ids of the menu items. The root item must have id 0.
+const ROOT_ID: i32 = 0;
+const STATUS_ID: i32 = 1;
+const PROFILES_ID: i32 = 2;
+const BOOST_ID: i32 = 3;
+const LIGHTING_ID: i32 = 4;
+const OPEN_GUI_ID: i32 = 5;
+const QUIT_ID: i32 = 6;
+/// Separators can't be clicked, so their ids only need to be unique.
+const SEPARATOR_IDS: [i32; 2] = [50, 51];
+/// The n-th profile has the id `PROFILE_ID_OFFSET + n`.
+const PROFILE_ID_OFFSET: i32 = 100;
+
+struct MenuItem {
+    id: i32,
+    properties: HashMap<String, Value<'static>>,
+    children: Vec<MenuItem>,
+}
+
+impl MenuItem {
+    fn new(id: i32, label: &str) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert("label".to_owned(), Value::from(label.to_owned()));
+        Self {
+            id,
+            properties,
+            children: Vec::new(),
+        }
+    }
+
+    fn separator(id: i32) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert("type".to_owned(), Value::from("separator"));
+        Self {
+            id,
+            properties,
+            children: Vec::new(),
+        }
+    }
+
+    fn with(mut self, name: &str, value: impl Into<Value<'static>>) -> Self {
+        self.properties.insert(name.to_owned(), value.into());
+        self
+    }
+
+    fn toggle(self, kind: &'static str, active: bool) -> Self {
+        self.with("toggle-type", kind)
+            .with("toggle-state", i32::from(active))
+    }
+
+    fn find(self, id: i32) -> Option<MenuItem> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.into_iter().find_map(|child| child.find(id))
+    }
+
+    /// Only the requested properties, all if `names` is empty.
+    fn filter_properties(
+        properties: HashMap<String, Value<'static>>,
+        names: &[String],
+    ) -> HashMap<String, Value<'static>> {
+        properties
+            .into_iter()
+            .filter(|(name, _)| names.is_empty() || names.contains(name))
+            .collect()
+    }
+
+    /// Serialize the item and `depth` levels of children (all if negative).
+    fn into_layout(self, depth: i32, names: &[String]) -> Layout {
+        let children = if depth == 0 {
+            Vec::new()
+        } else {
+            self.children
+                .into_iter()
+                .map(|child| {
+                    let layout = child.into_layout(depth - 1, names);
+                    Value::from(Structure::from(layout))
+                })
+                .collect()
+        };
+        (
+            self.id,
+            Self::filter_properties(self.properties, names),
+            children,
+        )
+    }
+}
+
+/// Build the menu from the current state.
+fn build(state: &TrayState) -> MenuItem {
+    let mut profiles = MenuItem::new(PROFILES_ID, "Profile").with("children-display", "submenu");
+    profiles.children = state
+        .profiles
+        .iter()
+        .zip(PROFILE_ID_OFFSET..)
+        .map(|(name, id)| MenuItem::new(id, name).toggle("radio", *name == state.active_profile))
+        .collect();
+
+    let mut root = MenuItem::new(ROOT_ID, "").with("children-display", "submenu");
+    root.children = vec![
+        MenuItem::new(STATUS_ID, &state.summary()).with("enabled", false),
+        MenuItem::separator(SEPARATOR_IDS[0]),
+        profiles,
+        MenuItem::new(BOOST_ID, "Boost fans for one minute"),
+        MenuItem::new(LIGHTING_ID, "Keyboard lighting").toggle("checkmark", state.lighting),
+        MenuItem::separator(SEPARATOR_IDS[1]),
+        MenuItem::new(OPEN_GUI_ID, "Open Tailor"),
+        MenuItem::new(QUIT_ID, "Quit"),
+    ];
+    root
+}
+
+pub struct Menu {
+    pub state: Arc<Mutex<TrayState>>,
+    pub connection: TailorConnection<'static>,
+    pub quit_sender: mpsc::Sender<()>,
+}
+
+#[interface(name = "com.canonical.dbusmenu")]
+impl Menu {
+    fn get_layout(
+        &self,
+        parent_id: i32,
+        recursion_depth: i32,
+        property_names: Vec<String>,
+    ) -> fdo::Result<(u32, Layout)> {
+        let state = self.state.lock().unwrap();
+        let item = build(&state)
+            .find(parent_id)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("No menu item with id {parent_id}")))?;
+        Ok((
+            state.revision,
+            item.into_layout(recursion_depth, &property_names),
+        ))
+    }
+
+    fn get_group_properties(
+        &self,
+        ids: Vec<i32>,
+        property_names: Vec<String>,
+    ) -> Vec<(i32, HashMap<String, Value<'static>>)> {
+        let state = self.state.lock().unwrap();
+        ids.into_iter()
+            .filter_map(|id| build(&state).find(id))
+            .map(|item| {
+                (
+                    item.id,
+                    MenuItem::filter_properties(item.properties, &property_names),
+                )
+            })
+            .collect()
+    }
+
+    fn get_property(&self, id: i32, name: String) -> fdo::Result<Value<'static>> {
+        let state = self.state.lock().unwrap();
+        build(&state)
+            .find(id)
+            .and_then(|mut item| item.properties.remove(&name))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("No property {name} for item {id}")))
+    }
+
+    async fn event(
+        &self,
+        id: i32,
+        event_id: &str,
+        _data: OwnedValue,
+        _timestamp: u32,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) {
+        if event_id == "clicked" {
+            self.clicked(id, &ctxt).await;
+        }
+    }
+
+    async fn event_group(
+        &self,
+        events: Vec<(i32, String, OwnedValue, u32)>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Vec<i32> {
+        for (id, event_id, _, _) in events {
+            if event_id == "clicked" {
+                self.clicked(id, &ctxt).await;
+            }
+        }
+        Vec::new()
+    }
+
+    fn about_to_show(&self, _id: i32) -> bool {
+        false
+    }
+
+    fn about_to_show_group(&self, _ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+        (Vec::new(), Vec::new())
+    }
+
+    #[zbus(property)]
+    fn version(&self) -> u32 {
+        3
+    }
+
+    #[zbus(property)]
+    fn text_direction(&self) -> &str {
+        "ltr"
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> &str {
+        "normal"
+    }
+
+    #[zbus(property)]
+    fn icon_theme_path(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(signal)]
+    pub async fn layout_updated(
+        ctxt: &SignalContext<'_>,
+        revision: u32,
+        parent: i32,
+    ) -> zbus::Result<()>;
+}
+
+impl Menu {
+    async fn clicked(&self, id: i32, ctxt: &SignalContext<'_>) {
+        let result = match id {
+            BOOST_ID => self.connection.boost_fans(BOOST_SECONDS).await,
+            LIGHTING_ID => self.toggle_lighting().await,
+            OPEN_GUI_ID => {
+                crate::open_gui();
+                Ok(())
+            }
+            QUIT_ID => {
+                self.quit_sender.send(()).await.ok();
+                Ok(())
+            }
+            PROFILE_ID_OFFSET.. => {
+                let profile = self
+                    .state
+                    .lock()
+                    .unwrap()
+                    .profiles
+                    .get((id - PROFILE_ID_OFFSET) as usize)
+                    .cloned();
+                match profile {
+                    Some(profile) => self.set_profile(profile).await,
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(err) = result {
+            tracing::error!("Menu action failed: `{err}`");
+        }
+        let revision = self.state.lock().unwrap().bump_revision();
+        Self::layout_updated(ctxt, revision, ROOT_ID).await.ok();
+    }
+
+    async fn set_profile(&self, profile: String) -> tailor_client::ClientResult<()> {
+        self.connection
+            .set_active_global_profile_name(&profile)
+            .await?;
+        self.state.lock().unwrap().active_profile = profile;
+        Ok(())
+    }
+
+    /// Switch the keyboard off or back to the colors of the active profile.
+    async fn toggle_lighting(&self) -> tailor_client::ClientResult<()> {
+        let lighting = self.state.lock().unwrap().lighting;
+        if lighting {
+            self.connection
+                .set_temporary_led_color(&Color { r: 0, g: 0, b: 0 })
+                .await?;
+        } else {
+            self.connection.reload_led_profile().await?;
+        }
+        self.state.lock().unwrap().lighting = !lighting;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zbus::zvariant::Type;
+
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let mut state = TrayState::default();
+        state.set_profiles((
+            vec!["default".to_owned(), "quiet".to_owned()],
+            "quiet".to_owned(),
+        ));
+        assert_eq!(Layout::signature(), "(ia{sv}av)");
+
+        let (id, _, children) = build(&state).into_layout(-1, &[]);
+        assert_eq!(id, ROOT_ID);
+        assert_eq!(children.len(), 8);
+        for child in &children {
+            assert_eq!(child.value_signature(), "(ia{sv}av)");
+        }
+
+        let profiles = build(&state).find(PROFILES_ID).unwrap();
+        let states: Vec<_> = profiles
+            .children
+            .iter()
+            .map(|item| i32::try_from(&item.properties["toggle-state"]).unwrap())
+            .collect();
+        assert_eq!(states, [0, 1]);
+
+        // Children are omitted at depth 0.
+        let (_, properties, children) = build(&state).into_layout(0, &["label".to_owned()]);
+        assert!(children.is_empty());
+        assert_eq!(properties.keys().collect::<Vec<_>>(), ["label"]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Desktop notifications for events of tailord.
+
+use std::collections::BTreeSet;
+
+use notify_rust::{Notification, Urgency};
+use tailor_api::{FanStatus, ProfileSwitch, ProfileTrigger};
+
+/// Temperature in °C from which on a fan is considered overheating.
+const OVERHEAT_TEMPERATURE: u8 = 90;
+/// The warning is repeated only after the temperature dropped by this much.
+const OVERHEAT_HYSTERESIS: u8 = 5;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Message {
+    pub summary: String,
+    pub body: String,
+    pub critical: bool,
+}
+
+impl Message {
+    pub async fn show(self) {
+        let urgency = if self.critical {
+            Urgency::Critical
+        } else {
+            Urgency::Normal
+        };
+        let result = Notification::new()
+            .summary(&self.summary)
+            .body(&self.body)
+            .appname("tailor")
+            .icon(crate::item::ICON_NAME)
+            .urgency(urgency)
+            .show_async()
+            .await;
+        if let Err(err) = result {
+            tracing::warn!("Failed to show notification: `{err}`");
+        }
+    }
+}
+
+/// Decides which events deserve a notification.
+#[derive(Debug, Default)]
+pub struct Notifier {
+    /// Fans that were reported as overheating.
+    overheating: BTreeSet<u8>,
+}
+
+impl Notifier {
+    /// Warn once when a fan crosses [`OVERHEAT_TEMPERATURE`].
+    pub fn fan_status(&mut self, status: &FanStatus) -> Option<Message> {
+        if status.temperature >= OVERHEAT_TEMPERATURE {
+            self.overheating.insert(status.index).then(|| Message {
+                summary: "Overheating".to_owned(),
+                body: format!(
+                    "Fan {} reports {}°C. Consider a quieter workload or a more aggressive fan profile.",
+                    status.index + 1,
+                    status.temperature
+                ),
+                critical: true,
+            })
+        } else {
+            if status.temperature + OVERHEAT_HYSTERESIS <= OVERHEAT_TEMPERATURE {
+                self.overheating.remove(&status.index);
+            }
+            None
+        }
+    }
+
+    /// Notify about profiles that weren't activated by the user.
+    pub fn profile_switch(&self, switch: &ProfileSwitch) -> Option<Message> {
+        let (summary, reason) = match switch.trigger {
+            ProfileTrigger::PowerSource => ("Profile switched", "because the power source changed"),
+            // The user chose the power mode of the desktop or edited the configuration.
+            ProfileTrigger::User
+            | ProfileTrigger::Startup
+            | ProfileTrigger::PowerProfile
+            | ProfileTrigger::Reload => return None,
+        };
+        Some(Message {
+            summary: summary.to_owned(),
+            body: format!("Activated profile `{}` {reason}.", switch.profile),
+            critical: false,
+        })
+    }
+
+    pub fn sensor_failure(&self, fan_idx: u8, error: &str) -> Message {
+        Message {
+            summary: "Temperature sensor failed".to_owned(),
+            body: format!(
+                "The temperature of fan {} can't be read (`{error}`). Its speed isn't adjusted anymore.",
+                fan_idx + 1
+            ),
+            critical: true,
+        }
+    }
+
+    pub fn thermal_trip(&self, fan_idx: u8, temperature: u8) -> Message {
+        Message {
+            summary: "CPU throttled".to_owned(),
+            body: format!(
+                "Fan {} reached the passive trip point at {temperature}°C and runs at full speed \
+                until the temperature drops.",
+                fan_idx + 1
+            ),
+            critical: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(temperature: u8) -> FanStatus {
+        FanStatus {
+            index: 0,
+            temperature,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn overheating_is_reported_once() {
+        let mut notifier = Notifier::default();
+        assert!(notifier.fan_status(&status(80)).is_none());
+        assert!(notifier.fan_status(&status(92)).is_some());
+        assert!(notifier.fan_status(&status(95)).is_none());
+        // Not cool enough to reset the warning.
+        assert!(notifier.fan_status(&status(88)).is_none());
+        assert!(notifier.fan_status(&status(91)).is_none());
+
+        assert!(notifier.fan_status(&status(85)).is_none());
+        assert!(notifier.fan_status(&status(90)).is_some());
+    }
+
+    #[test]
+    fn user_switches_are_ignored() {
+        let notifier = Notifier::default();
+        let mut switch = ProfileSwitch {
+            timestamp: 0,
+            profile: "quiet".to_owned(),
+            trigger: ProfileTrigger::User,
+        };
+        assert!(notifier.profile_switch(&switch).is_none());
+
+        switch.trigger = ProfileTrigger::Reload;
+        assert!(notifier.profile_switch(&switch).is_none());
+
+        switch.trigger = ProfileTrigger::PowerSource;
+        assert!(!notifier.profile_switch(&switch).unwrap().critical);
+    }
+
+    #[test]
+    fn thermal_trips_are_critical() {
+        let message = Notifier::default().thermal_trip(1, 95);
+        assert!(message.critical);
+        assert!(message
+            .body
+            .starts_with("Fan 2 reached the passive trip point at 95°C"));
+    }
+}
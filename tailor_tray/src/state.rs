@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use tailor_api::FanStatus;
+use tailor_client::{ClientResult, TailorConnection};
+
+/// All global profiles and the name of the active one.
+pub type Profiles = (Vec<String>, String);
+
+/// Read the global profiles from tailord.
+pub async fn fetch_profiles(connection: &TailorConnection<'_>) -> ClientResult<Profiles> {
+    Ok((
+        connection.list_global_profiles().await?,
+        connection.get_active_global_profile_name().await?,
+    ))
+}
+
+/// Everything the tray shows, shared between the D-Bus objects and the main loop.
+#[derive(Debug)]
+pub struct TrayState {
+    pub profiles: Vec<String>,
+    pub active_profile: String,
+    /// Latest temperature of each fan.
+    temperatures: BTreeMap<u8, u8>,
+    /// Whether the keyboard lighting follows the active profile or is switched off.
+    pub lighting: bool,
+    /// Incremented whenever the menu changes.
+    pub revision: u32,
+}
+
+impl Default for TrayState {
+    fn default() -> Self {
+        Self {
+            profiles: Vec::new(),
+            active_profile: String::new(),
+            temperatures: BTreeMap::new(),
+            lighting: true,
+            revision: 0,
+        }
+    }
+}
+
+impl TrayState {
+    /// The highest temperature of all fans.
+    pub fn temperature(&self) -> Option<u8> {
+        self.temperatures.values().max().copied()
+    }
+
+    /// Returns whether the displayed temperature changed.
+    pub fn update_temperature(&mut self, status: &FanStatus) -> bool {
+        let previous = self.temperature();
+        self.temperatures.insert(status.index, status.temperature);
+        previous != self.temperature()
+    }
+
+    /// Short summary like "54°C, profile performance".
+    pub fn summary(&self) -> String {
+        match self.temperature() {
+            Some(temp) => format!("{temp}°C, profile {}", self.active_profile),
+            None => format!("Profile {}", self.active_profile),
+        }
+    }
+
+    /// Returns whether the profiles changed.
+    pub fn set_profiles(&mut self, (profiles, active_profile): Profiles) -> bool {
+        if profiles == self.profiles && active_profile == self.active_profile {
+            return false;
+        }
+        self.profiles = profiles;
+        self.active_profile = active_profile;
+        true
+    }
+
+    /// Mark the menu as changed and return the new revision.
+    pub fn bump_revision(&mut self) -> u32 {
+        self.revision = self.revision.wrapping_add(1);
+        self.revision
+    }
+}
@@ -0,0 +1,97 @@
+//! Display brightness through the sysfs backlight interface.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use tuxedo_ioctl::hal::attribute;
+
+const BACKLIGHT_PATH: &str = "/sys/class/backlight";
+
+/// Interfaces in order of preference. Firmware interfaces know the
+/// limits of the panel best, raw ones write to the GPU directly.
+const TYPES: [&str; 3] = ["firmware", "platform", "raw"];
+
+/// Set the brightness of the display in percent.
+///
+/// The backlight is never switched off entirely, because
+/// the display would be unreadable until the next profile.
+pub fn apply(percent: u8) -> io::Result<()> {
+    apply_in(Path::new(BACKLIGHT_PATH), percent)
+}
+
+fn apply_in(root: &Path, percent: u8) -> io::Result<()> {
+    let device = preferred_device(root)?;
+    let max: u32 = read_number(&device.join("max_brightness"))?;
+    let value = (u64::from(max) * u64::from(percent.min(100)) / 100).max(1);
+    attribute::write(&device.join("brightness"), &value.to_string())?;
+    tracing::info!(
+        "Set the display brightness of `{}` to {percent}% ({value}/{max})",
+        device.display()
+    );
+    Ok(())
+}
+
+/// The backlight device with the most preferred type.
+fn preferred_device(root: &Path) -> io::Result<PathBuf> {
+    let mut devices: Vec<(usize, PathBuf)> = fs::read_dir(root)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|device| {
+            let kind = fs::read_to_string(device.join("type")).ok()?;
+            let rank = TYPES.iter().position(|known| *known == kind.trim())?;
+            Some((rank, device))
+        })
+        .collect();
+    devices.sort();
+    devices
+        .into_iter()
+        .next()
+        .map(|(_, device)| device)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No display backlight available"))
+}
+
+fn read_number(path: &Path) -> io::Result<u32> {
+    fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::apply_in;
+
+    #[test]
+    fn apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (device, kind, max) in [
+            ("acpi_video0", "firmware", "15"),
+            ("intel_backlight", "raw", "96000"),
+        ] {
+            let device = root.join(device);
+            fs::create_dir_all(&device).unwrap();
+            fs::write(device.join("type"), format!("{kind}\n")).unwrap();
+            fs::write(device.join("max_brightness"), format!("{max}\n")).unwrap();
+            fs::write(device.join("brightness"), "0\n").unwrap();
+        }
+        let brightness = |device: &str| fs::read_to_string(root.join(device).join("brightness"));
+
+        apply_in(root, 40).unwrap();
+        assert_eq!(brightness("acpi_video0").unwrap(), "6");
+        assert_eq!(brightness("intel_backlight").unwrap(), "0\n");
+
+        // Without the firmware interface, the raw one is used.
+        fs::remove_dir_all(root.join("acpi_video0")).unwrap();
+        apply_in(root, 40).unwrap();
+        assert_eq!(brightness("intel_backlight").unwrap(), "38400");
+        apply_in(root, 0).unwrap();
+        assert_eq!(brightness("intel_backlight").unwrap(), "1");
+
+        assert!(apply_in(&root.join("missing"), 40).is_err());
+    }
+}
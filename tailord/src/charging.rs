@@ -0,0 +1,264 @@
+use std::io;
+
+use tailor_api::{ChargingCapabilities, ChargingSettings};
+use tokio::sync::{mpsc, oneshot};
+use tuxedo_sysfs::charging::{BatteryChargeControl, ChargingPriority, ChargingProfile};
+
+use crate::error::{self, TailorError};
+
+/// Charge type that allows custom start and end thresholds.
+const CUSTOM_CHARGE_TYPE: &str = "Custom";
+
+enum ChargingRequest {
+    Get(oneshot::Sender<error::Result<ChargingSettings>>),
+    Set(ChargingSettings, oneshot::Sender<error::Result<()>>),
+}
+
+#[derive(Clone)]
+pub struct ChargingHandle {
+    pub capabilities: ChargingCapabilities,
+    request_sender: mpsc::Sender<ChargingRequest>,
+}
+
+impl ChargingHandle {
+    /// Read the current settings from the hardware.
+    pub async fn get(&self) -> error::Result<ChargingSettings> {
+        let (sender, receiver) = oneshot::channel();
+        self.request(ChargingRequest::Get(sender), receiver).await
+    }
+
+    /// Apply all settings that aren't `None`.
+    pub async fn set(&self, settings: ChargingSettings) -> error::Result<()> {
+        self.validate(&settings)?;
+        let (sender, receiver) = oneshot::channel();
+        self.request(ChargingRequest::Set(settings, sender), receiver)
+            .await
+    }
+
+    async fn request<T>(
+        &self,
+        request: ChargingRequest,
+        receiver: oneshot::Receiver<error::Result<T>>,
+    ) -> error::Result<T> {
+        self.request_sender
+            .send(request)
+            .await
+            .map_err(|err| TailorError::Failed(err.to_string()))?;
+        receiver
+            .await
+            .map_err(|err| TailorError::Failed(err.to_string()))?
+    }
+
+    fn validate(&self, settings: &ChargingSettings) -> error::Result<()> {
+        let capabilities = &self.capabilities;
+        let unsupported = |setting: &str| {
+            Err(TailorError::HardwareUnavailable(format!(
+                "Setting the {setting} isn't supported"
+            )))
+        };
+
+        if let Some(profile) = &settings.profile {
+            if capabilities.profiles.is_empty() {
+                return unsupported("charging profile");
+            }
+            if !capabilities.profiles.contains(profile) {
+                return Err(TailorError::InvalidArgs(format!(
+                    "Unknown charging profile `{profile}`"
+                )));
+            }
+        }
+        if let Some(priority) = &settings.priority {
+            if capabilities.priorities.is_empty() {
+                return unsupported("charging priority");
+            }
+            if !capabilities.priorities.contains(priority) {
+                return Err(TailorError::InvalidArgs(format!(
+                    "Unknown charging priority `{priority}`"
+                )));
+            }
+        }
+
+        let thresholds = [
+            (
+                "start",
+                settings.start_threshold,
+                &capabilities.start_thresholds,
+            ),
+            ("end", settings.end_threshold, &capabilities.end_thresholds),
+        ];
+        for (name, threshold, available) in thresholds {
+            let Some(threshold) = threshold else {
+                continue;
+            };
+            if !capabilities.thresholds {
+                return unsupported("charge thresholds");
+            }
+            let valid = match available {
+                Some(available) => available.contains(&threshold),
+                None => threshold <= 100,
+            };
+            if !valid {
+                return Err(TailorError::InvalidArgs(format!(
+                    "Unsupported {name} threshold {threshold}%"
+                )));
+            }
+        }
+        if let (Some(start), Some(end)) = (settings.start_threshold, settings.end_threshold) {
+            if start >= end {
+                return Err(TailorError::InvalidArgs(format!(
+                    "The start threshold ({start}%) must be below the end threshold ({end}%)"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Owns the sysfs files of the charging settings, which can't be shared between threads.
+pub struct ChargingRuntime {
+    profile: Option<ChargingProfile>,
+    priority: Option<ChargingPriority>,
+    control: Option<BatteryChargeControl>,
+    request_receiver: mpsc::Receiver<ChargingRequest>,
+}
+
+impl ChargingRuntime {
+    /// Detect the charging settings of the device.
+    /// Returns `None` if none of them are supported.
+    pub async fn new() -> Option<(ChargingHandle, Self)> {
+        let profile = ChargingProfile::new().await.unwrap_or_else(|err| {
+            tracing::warn!("Failed to open the charging profile: `{err}`");
+            None
+        });
+        let priority = ChargingPriority::new().await.unwrap_or_else(|err| {
+            tracing::warn!("Failed to open the charging priority: `{err}`");
+            None
+        });
+        let control = BatteryChargeControl::new_first_battery()
+            .await
+            .unwrap_or_else(|err| {
+                tracing::warn!("Failed to open the charge thresholds: `{err}`");
+                None
+            });
+
+        if profile.is_none() && priority.is_none() && control.is_none() {
+            return None;
+        }
+
+        let capabilities = ChargingCapabilities {
+            profiles: profile
+                .as_ref()
+                .map(|profile| profile.available_charging_profiles.clone())
+                .unwrap_or_default(),
+            priorities: priority
+                .as_ref()
+                .map(|priority| priority.available_charging_priorities.clone())
+                .unwrap_or_default(),
+            thresholds: control.is_some(),
+            start_thresholds: control
+                .as_ref()
+                .and_then(|control| control.available_start_thresholds.clone()),
+            end_thresholds: control
+                .as_ref()
+                .and_then(|control| control.available_end_thresholds.clone()),
+        };
+        let (request_sender, request_receiver) = mpsc::channel(1);
+
+        Some((
+            ChargingHandle {
+                capabilities,
+                request_sender,
+            },
+            Self {
+                profile,
+                priority,
+                control,
+                request_receiver,
+            },
+        ))
+    }
+
+    /// Apply the stored `settings` and handle requests of the D-Bus interface.
+    #[tracing::instrument(skip_all)]
+    pub async fn run(mut self, settings: ChargingSettings) {
+        if settings != ChargingSettings::default() {
+            tracing::info!("Applying charging settings: {settings:?}");
+            if let Err(err) = self.set(settings).await {
+                tracing::warn!("Failed to apply the charging settings: `{err}`");
+            }
+        }
+
+        while let Some(request) = self.request_receiver.recv().await {
+            match request {
+                ChargingRequest::Get(sender) => {
+                    sender
+                        .send(self.get().await.map_err(TailorError::from))
+                        .ok();
+                }
+                ChargingRequest::Set(settings, sender) => {
+                    sender
+                        .send(self.set(settings).await.map_err(TailorError::from))
+                        .ok();
+                }
+            }
+        }
+        tracing::warn!("Stopping runtime, the charging request sender has probably dropped");
+    }
+
+    async fn get(&mut self) -> Result<ChargingSettings, io::Error> {
+        let mut settings = ChargingSettings::default();
+        if let Some(profile) = &mut self.profile {
+            settings.profile = Some(profile.get_charging_profile().await?);
+        }
+        if let Some(priority) = &mut self.priority {
+            settings.priority = Some(priority.get_charging_priority().await?);
+        }
+        if let Some(control) = &mut self.control {
+            settings.start_threshold = Some(control.get_start_threshold().await?);
+            settings.end_threshold = Some(control.get_end_threshold().await?);
+        }
+        Ok(settings)
+    }
+
+    async fn set(&mut self, settings: ChargingSettings) -> Result<(), io::Error> {
+        if let (Some(profile), Some(value)) = (&mut self.profile, settings.profile) {
+            profile.set_charging_profile(value).await?;
+        }
+        if let (Some(priority), Some(value)) = (&mut self.priority, settings.priority) {
+            priority.set_charging_priority(value).await?;
+        }
+
+        let Some(control) = &mut self.control else {
+            return Ok(());
+        };
+        if settings.start_threshold.is_none() && settings.end_threshold.is_none() {
+            return Ok(());
+        }
+        if control.get_charge_type().await? != CUSTOM_CHARGE_TYPE {
+            control
+                .set_charge_type(CUSTOM_CHARGE_TYPE.to_owned())
+                .await?;
+        }
+
+        // The driver may reject a start threshold above the current end
+        // threshold and vice versa, so the order of the writes matters.
+        let current_end = control.get_end_threshold().await?;
+        let start_first = settings
+            .start_threshold
+            .is_some_and(|start| start < current_end);
+        if start_first {
+            if let Some(start) = settings.start_threshold {
+                control.set_start_threshold(start).await?;
+            }
+        }
+        if let Some(end) = settings.end_threshold {
+            control.set_end_threshold(end).await?;
+        }
+        if !start_first {
+            if let Some(start) = settings.start_threshold {
+                control.set_start_threshold(start).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! Detect other daemons that control the fans as well. They would fight
+//! with tailord over the same EC registers and make the fans jump around.
+
+use std::{fs, path::Path};
+
+use once_cell::sync::OnceCell;
+
+static CONFLICTS: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Look for conflicting daemons once and warn about them.
+pub fn detect() -> &'static [String] {
+    CONFLICTS.get_or_init(|| {
+        let conflicts = find_conflicts(Path::new("/proc"));
+        for name in &conflicts {
+            tracing::warn!(
+                conflict = name,
+                "`{name}` is running and controls the fans as well, \
+                disable it to avoid both daemons fighting over the fans"
+            );
+        }
+        conflicts
+    })
+}
+
+/// The conflicting daemons found at startup.
+pub fn get() -> &'static [String] {
+    CONFLICTS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+fn find_conflicts(proc: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(proc) else {
+        return Vec::new();
+    };
+    let mut conflicts: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|entry| {
+            let comm = fs::read_to_string(entry.path().join("comm")).ok()?;
+            let cmdline = fs::read(entry.path().join("cmdline")).unwrap_or_default();
+            conflict(comm.trim(), &cmdline)
+        })
+        .map(ToOwned::to_owned)
+        .collect();
+    conflicts.sort();
+    conflicts.dedup();
+    conflicts
+}
+
+/// The name of the daemon if the process conflicts with tailord.
+fn conflict(comm: &str, cmdline: &[u8]) -> Option<&'static str> {
+    match comm {
+        // The daemon of the TUXEDO Control Center.
+        "tccd" => Some("tccd"),
+        // The fan control script of lm-sensors.
+        "fancontrol" => Some("fancontrol"),
+        // thermald only drives the fans in adaptive mode.
+        "thermald" => cmdline
+            .split(|b| *b == 0)
+            .any(|arg| arg == b"--adaptive")
+            .then_some("thermald"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::find_conflicts;
+
+    #[test]
+    fn find() {
+        let dir = tempfile::tempdir().unwrap();
+        let proc = dir.path();
+        for (pid, comm, cmdline) in [
+            ("1", "systemd", "/sbin/init\0"),
+            (
+                "20",
+                "thermald",
+                "/usr/sbin/thermald\0--systemd\0--dbus-enable\0",
+            ),
+            ("21", "tccd", "/opt/tccd\0"),
+            ("22", "fancontrol", "/bin/sh\0/usr/sbin/fancontrol\0"),
+            ("23", "tccd", "/opt/tccd\0"),
+        ] {
+            let dir = proc.join(pid);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("comm"), format!("{comm}\n")).unwrap();
+            fs::write(dir.join("cmdline"), cmdline).unwrap();
+        }
+        fs::create_dir_all(proc.join("self")).unwrap();
+
+        assert_eq!(find_conflicts(proc), ["fancontrol", "tccd"]);
+
+        fs::write(
+            proc.join("20").join("cmdline"),
+            "/usr/sbin/thermald\0--adaptive\0",
+        )
+        .unwrap();
+        assert_eq!(find_conflicts(proc), ["fancontrol", "tccd", "thermald"]);
+    }
+}
@@ -0,0 +1,129 @@
+//! CPU frequency scaling through the cpufreq sysfs interface.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use tuxedo_ioctl::hal::attribute;
+
+const CPUFREQ_PATH: &str = "/sys/devices/system/cpu/cpufreq";
+
+const GOVERNOR: &str = "scaling_governor";
+const AVAILABLE_GOVERNORS: &str = "scaling_available_governors";
+const EPP: &str = "energy_performance_preference";
+const AVAILABLE_EPPS: &str = "energy_performance_available_preferences";
+
+/// The cpufreq settings of a profile, `None` leaves a setting unchanged.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CpuFreq {
+    pub governor: Option<String>,
+    pub energy_performance_preference: Option<String>,
+}
+
+impl CpuFreq {
+    /// Apply the settings to all CPUs.
+    pub fn apply(&self) -> io::Result<()> {
+        self.apply_in(Path::new(CPUFREQ_PATH))
+    }
+
+    fn apply_in(&self, root: &Path) -> io::Result<()> {
+        if self.governor.is_none() && self.energy_performance_preference.is_none() {
+            return Ok(());
+        }
+
+        for policy in policies(root)? {
+            // The governor comes first, because intel_pstate
+            // rejects most preferences with `performance`.
+            if let Some(governor) = &self.governor {
+                write_checked(&policy, GOVERNOR, AVAILABLE_GOVERNORS, governor)?;
+            }
+            if let Some(epp) = &self.energy_performance_preference {
+                write_checked(&policy, EPP, AVAILABLE_EPPS, epp)?;
+            }
+        }
+        tracing::info!("Applied cpufreq settings: {self:?}");
+        Ok(())
+    }
+}
+
+/// The `policyN` directories, each covers one or more CPUs.
+fn policies(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut policies: Vec<PathBuf> = fs::read_dir(root)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("policy"))
+        .map(|entry| entry.path())
+        .collect();
+    policies.sort();
+
+    if policies.is_empty() {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No cpufreq policies available",
+        ))
+    } else {
+        Ok(policies)
+    }
+}
+
+fn write_checked(policy: &Path, name: &str, available: &str, value: &str) -> io::Result<()> {
+    let available = fs::read_to_string(policy.join(available))?;
+    if !available.split_whitespace().any(|option| option == value) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Unsupported {name} `{value}`, available: {}",
+                available.trim()
+            ),
+        ));
+    }
+    attribute::write(&policy.join(name), value)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for policy in ["policy0", "policy1"] {
+            let policy = root.join(policy);
+            fs::create_dir_all(&policy).unwrap();
+            fs::write(policy.join(GOVERNOR), "performance\n").unwrap();
+            fs::write(policy.join(AVAILABLE_GOVERNORS), "performance powersave\n").unwrap();
+            fs::write(policy.join(EPP), "performance\n").unwrap();
+            fs::write(
+                policy.join(AVAILABLE_EPPS),
+                "default performance balance_performance balance_power power\n",
+            )
+            .unwrap();
+        }
+
+        let settings = CpuFreq {
+            governor: Some("powersave".to_owned()),
+            energy_performance_preference: Some("balance_power".to_owned()),
+        };
+        settings.apply_in(root).unwrap();
+        for policy in ["policy0", "policy1"] {
+            let policy = root.join(policy);
+            assert_eq!(
+                fs::read_to_string(policy.join(GOVERNOR)).unwrap(),
+                "powersave"
+            );
+            assert_eq!(
+                fs::read_to_string(policy.join(EPP)).unwrap(),
+                "balance_power"
+            );
+        }
+
+        let invalid = CpuFreq {
+            governor: Some("ondemand".to_owned()),
+            energy_performance_preference: None,
+        };
+        assert!(invalid.apply_in(root).is_err());
+    }
+}
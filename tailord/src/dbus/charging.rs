@@ -0,0 +1,71 @@
+use tailor_api::ChargingSettings;
+use zbus::{interface, SignalContext};
+
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
+use crate::{
+    charging::ChargingHandle,
+    error::{self, TailorError},
+    settings::Settings,
+};
+
+pub struct ChargingInterface {
+    pub handle: Option<ChargingHandle>,
+}
+
+impl ChargingInterface {
+    fn handle(&self) -> error::Result<&ChargingHandle> {
+        self.handle.as_ref().ok_or(TailorError::HardwareUnavailable(
+            "Charging control isn't available".to_string(),
+        ))
+    }
+}
+
+#[interface(name = "com.tux.Tailor.Charging")]
+impl ChargingInterface {
+    /// Read which charging settings are supported.
+    async fn get_capabilities(&self) -> error::Result<String> {
+        Ok(serde_json::to_string(&self.handle()?.capabilities).unwrap())
+    }
+
+    /// Read the current charging settings from the hardware.
+    async fn get_settings(&self) -> error::Result<String> {
+        let settings = self.handle()?.get().await?;
+        Ok(serde_json::to_string(&settings).unwrap())
+    }
+
+    /// Apply the charging settings. Settings that are `null` are left unchanged.
+    /// The result is persisted and applied again when tailord starts.
+    async fn set_settings(&self, value: &str) -> error::Result<()> {
+        let settings: ChargingSettings =
+            serde_json::from_str(value).map_err(|err| TailorError::InvalidArgs(err.to_string()))?;
+        let handle = self.handle()?;
+        handle.set(settings).await?;
+        Settings::set_charging(handle.get().await?).await
+    }
+
+    /// Emitted when the battery dropped to one of the configured thresholds.
+    #[zbus(signal)]
+    pub async fn battery_threshold(
+        ctxt: &SignalContext<'_>,
+        threshold: u8,
+        battery: u8,
+    ) -> zbus::Result<()>;
+}
+
+#[cfg(feature = "rpc-socket")]
+impl ChargingInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "GetCapabilities" => rpc::to_value(self.get_capabilities().await),
+            "GetSettings" => rpc::to_value(self.get_settings().await),
+            "SetSettings" => rpc::to_value(self.set_settings(&params.get::<String>(0)?).await),
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
+}
@@ -1,32 +1,45 @@
-use tailor_api::{FanProfilePoint, ProfileInfo};
-use zbus::{fdo, interface};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use tailor_api::{Preset, ProfileInfo};
+use zbus::{interface, message::Header, SignalContext};
 
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
 use crate::{
-    fancontrol::FanRuntimeHandle,
-    profiles::{Profile, FAN_DIR, PROFILE_DIR},
-    util,
+    error::{self, TailorError},
+    fancontrol::{
+        profile::FanProfile, FanHandles, FanRuntimeHandle, PinRequest, PinSource, MAX_PIN_SECONDS,
+    },
+    profiles::{self, Profile, FAN_DIR, PROFILE_DIR},
+    util, wire,
 };
 
+/// Incremented by every preview, so an older preview doesn't end a newer one.
+static PREVIEW_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 pub struct FanInterface {
-    pub handles: Vec<FanRuntimeHandle>,
+    pub handles: FanHandles,
 }
 
 #[interface(name = "com.tux.Tailor.Fan")]
 impl FanInterface {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()> {
-        // Verify correctness of the file.
-        serde_json::from_str::<Vec<FanProfilePoint>>(value)
-            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
-        util::write_file(FAN_DIR, name, value.as_bytes()).await?;
+    async fn add_profile(&self, name: &str, value: &str) -> error::Result<()> {
+        // Verify correctness of the file and store it in the newest format.
+        let profile: tailor_api::FanProfile = tailor_api::wire::decode(value)
+            .map_err(|err| TailorError::InvalidCurve(err.to_string()))?;
+        util::write_json(FAN_DIR, name, &profile).await?;
 
         // Reload if the fan profile is part of the active global profile
         let info = Profile::get_active_profile_info()?;
         if info.fans.iter().any(|info| info == name) {
-            let info = Profile::load();
-            for (idx, handle) in self.handles.iter().enumerate() {
+            let fans = Profile::load_fans();
+            for (idx, handle) in self.handles.get().iter().enumerate() {
                 handle
                     .profile_sender
-                    .send(info.fans.get(idx).cloned().unwrap_or_default())
+                    .send(profiles::fan_profile(&fans, idx))
                     .await
                     .unwrap();
             }
@@ -34,21 +47,37 @@ impl FanInterface {
         Ok(())
     }
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String> {
-        util::read_file(FAN_DIR, name).await
+    async fn get_profile(
+        &self,
+        name: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> error::Result<String> {
+        wire::read_profile::<tailor_api::FanProfile>(FAN_DIR, name, wire::version(&header)).await
     }
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+    async fn list_profiles(&self) -> error::Result<Vec<String>> {
         util::get_profiles(FAN_DIR).await
     }
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()> {
+    async fn remove_profile(&self, name: &str) -> error::Result<()> {
         util::remove_file(FAN_DIR, name).await
     }
 
-    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>> {
+    async fn list_presets(&self) -> Vec<String> {
+        Preset::ALL.iter().map(ToString::to_string).collect()
+    }
+
+    async fn get_preset(
+        &self,
+        name: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> error::Result<String> {
+        preset(name, wire::version(&header))
+    }
+
+    async fn rename_profile(&self, from: &str, to: &str) -> error::Result<Vec<String>> {
         if self.list_profiles().await?.contains(&to.to_string()) {
-            Err(fdo::Error::InvalidArgs(format!(
+            Err(TailorError::InvalidArgs(format!(
                 "File `{to}` already exists"
             )))
         } else {
@@ -81,17 +110,212 @@ impl FanInterface {
         }
     }
 
-    async fn override_speed(&mut self, fan_idx: u8, speed: u8) -> fdo::Result<()> {
-        if let Some(handle) = self.handles.get(fan_idx as usize) {
+    async fn override_speed(&mut self, fan_idx: u8, speed: u8) -> error::Result<()> {
+        if let Some(handle) = self.handles.get().get(fan_idx as usize) {
             handle
                 .fan_speed_sender
                 .send(speed)
                 .await
-                .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))
+                .map_err(|err| TailorError::Failed(format!("Internal error: `{err}`")))
         } else {
-            Err(fdo::Error::InvalidArgs(
+            Err(TailorError::HardwareUnavailable(
                 "No fan found at requested index".to_owned(),
             ))
         }
     }
+
+    async fn boost_fans(&self, seconds: u32) -> error::Result<()> {
+        self.send_all(pin_request(100, seconds, PinSource::Client)?)
+            .await
+    }
+
+    /// Keep a fan at `speed` percent for `seconds`, ignoring the fan profile.
+    async fn pin_speed(&self, fan_idx: u8, speed: u8, seconds: u32) -> error::Result<()> {
+        if speed > 100 {
+            return Err(TailorError::InvalidArgs(
+                "Fan speed must be between 0 and 100".to_owned(),
+            ));
+        }
+        let request = pin_request(speed, seconds, PinSource::Client)?;
+        let handles = self.handles.get();
+        let handle = handles.get(fan_idx as usize).ok_or_else(|| {
+            TailorError::HardwareUnavailable("No fan found at requested index".to_owned())
+        })?;
+        send_pin(handle, request).await
+    }
+
+    /// Drive a fan with the given curve for `seconds` without storing it.
+    /// Afterwards, all fans return to the curves of the active profile.
+    async fn preview_profile(&self, fan_idx: u8, value: &str, seconds: u32) -> error::Result<()> {
+        if seconds == 0 || seconds > MAX_PIN_SECONDS {
+            return Err(TailorError::InvalidArgs(format!(
+                "Duration must be between 1 and {MAX_PIN_SECONDS} seconds"
+            )));
+        }
+        let profile: tailor_api::FanProfile = tailor_api::wire::decode(value)
+            .map_err(|err| TailorError::InvalidCurve(err.to_string()))?;
+        let profile = FanProfile::from_curve(profile)?;
+        let handles = self.handles.get();
+        let handle = handles.get(fan_idx as usize).ok_or_else(|| {
+            TailorError::HardwareUnavailable("No fan found at requested index".to_owned())
+        })?;
+        handle
+            .profile_sender
+            .send(profile)
+            .await
+            .map_err(|err| TailorError::Failed(format!("Internal error: `{err}`")))?;
+
+        // Only the most recent preview restores the stored curves.
+        let generation = PREVIEW_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::task::spawn_local(async move {
+            tokio::time::sleep(Duration::from_secs(seconds.into())).await;
+            if PREVIEW_GENERATION.load(Ordering::SeqCst) == generation {
+                let fans = Profile::load_fans();
+                for (idx, handle) in handles.iter().enumerate() {
+                    handle
+                        .profile_sender
+                        .send(profiles::fan_profile(&fans, idx))
+                        .await
+                        .ok();
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// End all boosts and pinned speeds early.
+    async fn release_fans(&self) -> error::Result<()> {
+        self.send_all(PinRequest::Release(None)).await
+    }
+
+    /// Current temperature and speed of all fans.
+    async fn get_status(&self) -> error::Result<String> {
+        let status: Vec<_> = self
+            .handles
+            .get()
+            .iter()
+            .map(|handle| *handle.status.borrow())
+            .collect();
+        Ok(serde_json::to_string(&status).unwrap())
+    }
+
+    /// Emitted when the temperature or speed of a fan changed.
+    #[zbus(signal)]
+    pub async fn status_changed(ctxt: &SignalContext<'_>, status: &str) -> zbus::Result<()>;
+
+    /// Emitted when the temperature sensor of a fan can't be read anymore.
+    #[zbus(signal)]
+    pub async fn sensor_failed(
+        ctxt: &SignalContext<'_>,
+        fan_idx: u8,
+        error: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a fan reached the passive trip point and runs at full speed.
+    #[zbus(signal)]
+    pub async fn thermal_trip(
+        ctxt: &SignalContext<'_>,
+        fan_idx: u8,
+        temperature: u8,
+    ) -> zbus::Result<()>;
+}
+
+#[cfg(feature = "rpc-socket")]
+impl FanInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "AddProfile" => rpc::to_value(
+                self.add_profile(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            "GetProfile" => rpc::to_value(
+                wire::read_profile::<tailor_api::FanProfile>(
+                    FAN_DIR,
+                    &params.get::<String>(0)?,
+                    tailor_api::wire::WIRE_VERSION,
+                )
+                .await,
+            ),
+            "ListProfiles" => rpc::to_value(self.list_profiles().await),
+            "RemoveProfile" => rpc::to_value(self.remove_profile(&params.get::<String>(0)?).await),
+            "ListPresets" => rpc::to_value(Ok(self.list_presets().await)),
+            "GetPreset" => rpc::to_value(preset(
+                &params.get::<String>(0)?,
+                tailor_api::wire::WIRE_VERSION,
+            )),
+            "RenameProfile" => rpc::to_value(
+                self.rename_profile(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            "OverrideSpeed" => {
+                rpc::to_value(self.override_speed(params.get(0)?, params.get(1)?).await)
+            }
+            "BoostFans" => rpc::to_value(self.boost_fans(params.get(0)?).await),
+            "PinSpeed" => rpc::to_value(
+                self.pin_speed(params.get(0)?, params.get(1)?, params.get(2)?)
+                    .await,
+            ),
+            "PreviewProfile" => rpc::to_value(
+                self.preview_profile(params.get(0)?, &params.get::<String>(1)?, params.get(2)?)
+                    .await,
+            ),
+            "ReleaseFans" => rpc::to_value(self.release_fans().await),
+            "GetStatus" => rpc::to_value(self.get_status().await),
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
+}
+
+/// The curve of a built-in preset, serialized for a client.
+fn preset(name: &str, version: u32) -> error::Result<String> {
+    let preset: Preset = name.parse().map_err(TailorError::InvalidArgs)?;
+    tailor_api::wire::encode(&tailor_api::FanProfile::preset(preset), version)
+        .map_err(|err| TailorError::Failed(err.to_string()))
+}
+
+impl FanInterface {
+    /// Boost all fans when the fan boost key is pressed, see `crate::hotkey`.
+    pub async fn boost_fans_by_key(&self, seconds: u32) -> error::Result<()> {
+        self.send_all(pin_request(100, seconds, PinSource::BoostKey)?)
+            .await
+    }
+
+    /// End the boost of the fan boost key, unless a client pinned the fans since.
+    pub async fn release_key_boost(&self) -> error::Result<()> {
+        self.send_all(PinRequest::Release(Some(PinSource::BoostKey)))
+            .await
+    }
+
+    async fn send_all(&self, request: PinRequest) -> error::Result<()> {
+        for handle in &self.handles.get() {
+            send_pin(handle, request).await?;
+        }
+        Ok(())
+    }
+}
+
+fn pin_request(speed: u8, seconds: u32, source: PinSource) -> error::Result<PinRequest> {
+    if seconds == 0 || seconds > MAX_PIN_SECONDS {
+        return Err(TailorError::InvalidArgs(format!(
+            "Duration must be between 1 and {MAX_PIN_SECONDS} seconds"
+        )));
+    }
+    Ok(PinRequest::Pin {
+        speed,
+        duration: Duration::from_secs(seconds.into()),
+        source,
+    })
+}
+
+async fn send_pin(handle: &FanRuntimeHandle, request: PinRequest) -> error::Result<()> {
+    handle
+        .pin_sender
+        .send(request)
+        .await
+        .map_err(|err| TailorError::Failed(format!("Internal error: `{err}`")))
 }
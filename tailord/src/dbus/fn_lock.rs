@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use tuxedo_ioctl::hal::traits::FnLockDevice;
+use zbus::interface;
+
+use crate::error::{self, TailorError};
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
+
+pub struct FnLockInterface {
+    pub fn_lock: Option<Arc<dyn FnLockDevice>>,
+}
+
+impl FnLockInterface {
+    fn fn_lock(&self) -> error::Result<&dyn FnLockDevice> {
+        self.fn_lock
+            .as_deref()
+            .ok_or(TailorError::HardwareUnavailable(
+                "Fn-lock control isn't available".to_string(),
+            ))
+    }
+}
+
+#[interface(name = "com.tux.Tailor.FnLock")]
+impl FnLockInterface {
+    /// Switch the Fn-lock on or off until the next profile is activated.
+    async fn set_enabled(&self, enabled: bool) -> error::Result<()> {
+        self.fn_lock()?
+            .set_fn_lock(enabled)
+            .map_err(|err| TailorError::Failed(format!("unable to switch the Fn-lock: {err}")))?;
+        tracing::info!("Fn-lock {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Whether the Fn-lock is switched on.
+    async fn get_enabled(&self) -> error::Result<bool> {
+        self.fn_lock()?
+            .get_fn_lock()
+            .map_err(|err| TailorError::Failed(format!("unable to read the Fn-lock state: {err}")))
+    }
+}
+
+#[cfg(feature = "rpc-socket")]
+impl FnLockInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "SetEnabled" => rpc::to_value(self.set_enabled(params.get::<bool>(0)?).await),
+            "GetEnabled" => rpc::to_value(self.get_enabled().await),
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
+}
@@ -1,62 +1,65 @@
-use tailor_api::{Color, ColorProfile, ProfileInfo};
-use zbus::{fdo, interface};
+use std::time::Duration;
 
+use tailor_api::{AlertPattern, Color, ColorProfile, ProfileInfo};
+use zbus::{interface, message::Header};
+
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
 use crate::{
-    led::LedRuntimeHandle,
+    error::{self, TailorError},
+    led::{Alert, LedRuntimeHandle},
     profiles::{Profile, KEYBOARD_DIR, PROFILE_DIR},
-    util,
+    util, wire,
 };
 
+/// Upper bound for alerts, they're meant to catch attention, not to replace profiles.
+const MAX_ALERT_SECONDS: u32 = 60;
+
 pub struct LedInterface {
     pub handles: Vec<LedRuntimeHandle>,
 }
 
 #[interface(name = "com.tux.Tailor.Led")]
 impl LedInterface {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()> {
-        // Verify correctness of the file.
-        serde_json::from_str::<ColorProfile>(value)
-            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
-        util::write_file(KEYBOARD_DIR, name, value.as_bytes()).await?;
+    async fn add_profile(&self, name: &str, value: &str) -> error::Result<()> {
+        // Verify correctness of the file and store it in the newest format.
+        let profile: ColorProfile = wire::decode(value)?;
+        util::write_json(KEYBOARD_DIR, name, &profile).await?;
 
         // Reload if the keyboard profile is part of the active global profile
         let info = Profile::get_active_profile_info()?;
         if info.leds.iter().any(|prof| prof.profile == name) {
-            let info = Profile::load();
+            let leds = Profile::load_leds();
             for handle in &self.handles {
-                let profile = info
-                    .leds
-                    .iter()
-                    .find_map(|(info, profile)| {
-                        if info == &handle.info {
-                            Some(profile.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| ColorProfile::default(handle.info.mode));
-
-                handle.profile_sender.send(profile).await.unwrap();
+                handle
+                    .profile_sender
+                    .send(leds.get(&handle.info))
+                    .await
+                    .unwrap();
             }
         }
         Ok(())
     }
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String> {
-        util::read_file(KEYBOARD_DIR, name).await
+    async fn get_profile(
+        &self,
+        name: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> error::Result<String> {
+        wire::read_profile::<ColorProfile>(KEYBOARD_DIR, name, wire::version(&header)).await
     }
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+    async fn list_profiles(&self) -> error::Result<Vec<String>> {
         util::get_profiles(KEYBOARD_DIR).await
     }
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()> {
+    async fn remove_profile(&self, name: &str) -> error::Result<()> {
         util::remove_file(KEYBOARD_DIR, name).await
     }
 
-    async fn rename_profile(&self, from: &str, to: &str) -> fdo::Result<Vec<String>> {
+    async fn rename_profile(&self, from: &str, to: &str) -> error::Result<Vec<String>> {
         if self.list_profiles().await?.contains(&to.to_string()) {
-            Err(fdo::Error::InvalidArgs(format!(
+            Err(TailorError::InvalidArgs(format!(
                 "File `{to}` already exists"
             )))
         } else {
@@ -89,16 +92,120 @@ impl LedInterface {
         }
     }
 
-    async fn override_color(&mut self, color: &str) -> fdo::Result<()> {
+    async fn override_color(&mut self, color: &str) -> error::Result<()> {
         let color: Color =
-            serde_json::from_str(color).map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+            serde_json::from_str(color).map_err(|err| TailorError::InvalidArgs(err.to_string()))?;
         for handle in &self.handles {
             handle
                 .color_sender
                 .send(color.clone())
                 .await
-                .map_err(|err| fdo::Error::Failed(format!("Internal error: `{err}`")))?;
+                .map_err(|err| TailorError::Failed(format!("Internal error: `{err}`")))?;
+        }
+        Ok(())
+    }
+
+    /// Show a solid color on all LED devices until the next profile is activated.
+    /// Unlike `add_profile`, nothing is written to disk.
+    async fn set_temporary_color(&mut self, color: &str) -> error::Result<()> {
+        let color: Color =
+            serde_json::from_str(color).map_err(|err| TailorError::InvalidArgs(err.to_string()))?;
+        for handle in &self.handles {
+            handle
+                .profile_sender
+                .send(ColorProfile::Single(color.clone()))
+                .await
+                .map_err(|err| TailorError::Failed(format!("Internal error: `{err}`")))?;
+        }
+        Ok(())
+    }
+
+    /// Flash `color` on all LED devices for `seconds` with a pattern
+    /// (`solid`, `blink` or `pulse`), then restore the profile.
+    async fn flash_alert(&self, color: &str, pattern: &str, seconds: u32) -> error::Result<()> {
+        let color: Color =
+            serde_json::from_str(color).map_err(|err| TailorError::InvalidArgs(err.to_string()))?;
+        let pattern: AlertPattern = pattern.parse().map_err(TailorError::InvalidArgs)?;
+        if seconds == 0 || seconds > MAX_ALERT_SECONDS {
+            return Err(TailorError::InvalidArgs(format!(
+                "Duration must be between 1 and {MAX_ALERT_SECONDS} seconds"
+            )));
+        }
+        tracing::info!("Flashing {pattern} alert with {color} for {seconds}s");
+        let alert = Alert {
+            color,
+            pattern,
+            duration: Duration::from_secs(seconds.into()),
+        };
+        for handle in &self.handles {
+            handle
+                .alert_sender
+                .send(alert.clone())
+                .await
+                .map_err(|err| TailorError::Failed(format!("Internal error: `{err}`")))?;
         }
         Ok(())
     }
+
+    async fn identify_led_device(&self, id: &str) -> error::Result<()> {
+        let handle = self
+            .handles
+            .iter()
+            .find(|handle| handle.info.device_id() == id)
+            .ok_or_else(|| {
+                TailorError::HardwareUnavailable(format!("Couldn't find LED device `{id}`"))
+            })?;
+        handle
+            .identify_sender
+            .send(())
+            .await
+            .map_err(|err| TailorError::Failed(format!("Internal error: `{err}`")))
+    }
+}
+
+#[cfg(feature = "rpc-socket")]
+impl LedInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "AddProfile" => rpc::to_value(
+                self.add_profile(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            "GetProfile" => rpc::to_value(
+                wire::read_profile::<ColorProfile>(
+                    KEYBOARD_DIR,
+                    &params.get::<String>(0)?,
+                    tailor_api::wire::WIRE_VERSION,
+                )
+                .await,
+            ),
+            "ListProfiles" => rpc::to_value(self.list_profiles().await),
+            "RemoveProfile" => rpc::to_value(self.remove_profile(&params.get::<String>(0)?).await),
+            "RenameProfile" => rpc::to_value(
+                self.rename_profile(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            "OverrideColor" => rpc::to_value(self.override_color(&params.get::<String>(0)?).await),
+            "SetTemporaryColor" => {
+                rpc::to_value(self.set_temporary_color(&params.get::<String>(0)?).await)
+            }
+            "FlashAlert" => rpc::to_value(
+                self.flash_alert(
+                    &params.get::<String>(0)?,
+                    &params.get::<String>(1)?,
+                    params.get(2)?,
+                )
+                .await,
+            ),
+            "IdentifyLedDevice" => {
+                rpc::to_value(self.identify_led_device(&params.get::<String>(0)?).await)
+            }
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
 }
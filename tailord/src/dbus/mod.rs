@@ -1,9 +1,19 @@
+mod charging;
 mod fan;
+mod fn_lock;
 mod led;
 mod performance;
 mod profiles;
+mod settings;
+mod touchpad;
+mod webcam;
 
+pub use charging::ChargingInterface;
 pub use fan::FanInterface;
+pub use fn_lock::FnLockInterface;
 pub use led::LedInterface;
 pub use performance::PerformanceInterface;
 pub use profiles::ProfileInterface;
+pub use settings::SettingsInterface;
+pub use touchpad::TouchpadInterface;
+pub use webcam::WebcamInterface;
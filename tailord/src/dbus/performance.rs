@@ -1,21 +1,35 @@
-use zbus::{fdo, interface};
+use std::sync::Arc;
 
-use crate::performance::PerformanceProfileRuntimeHandle;
+use tokio::sync::watch;
+use tuxedo_ioctl::hal::traits::TdpDevice;
+use zbus::interface;
+
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
+use crate::{
+    error::{self, TailorError},
+    performance::{self, PerformanceProfileRuntimeHandle},
+    profiles, settings,
+};
 
 pub struct PerformanceInterface {
-    pub handler: Option<PerformanceProfileRuntimeHandle>,
+    pub handler: watch::Receiver<Option<PerformanceProfileRuntimeHandle>>,
+    pub tdp: Option<Arc<dyn TdpDevice>>,
 }
 
 impl PerformanceInterface {
-    fn handler(&self) -> fdo::Result<&PerformanceProfileRuntimeHandle> {
-        self.handler.as_ref().ok_or(fdo::Error::Failed(
-            "No performance profile handler available".to_string(),
-        ))
+    fn handler(&self) -> error::Result<PerformanceProfileRuntimeHandle> {
+        self.handler
+            .borrow()
+            .clone()
+            .ok_or(TailorError::HardwareUnavailable(
+                "No performance profile handler available".to_string(),
+            ))
     }
 
-    fn handler_mut(&mut self) -> fdo::Result<&mut PerformanceProfileRuntimeHandle> {
-        self.handler.as_mut().ok_or(fdo::Error::Failed(
-            "No performance profile handler available".to_string(),
+    fn tdp(&self) -> error::Result<&dyn TdpDevice> {
+        self.tdp.as_deref().ok_or(TailorError::HardwareUnavailable(
+            "Power limits can't be changed on this device".to_string(),
         ))
     }
 }
@@ -24,31 +38,90 @@ impl PerformanceInterface {
 impl PerformanceInterface {
     /// Temporarily override the performance profile. Please note that this will not survive a
     /// restart as the performance profile is handled by the overall profile configuration.
-    async fn set_profile(&mut self, name: &str) -> fdo::Result<()> {
+    async fn set_profile(&mut self, name: &str) -> error::Result<()> {
         self.handler()?
             .profile_sender
             .send(name.to_string())
             .await
             .map_err(|err| {
-                fdo::Error::IOError(format!("unable to set performance profile {name}: {err}"))
+                TailorError::Failed(format!("unable to set performance profile {name}: {err}"))
             })?;
-        self.handler_mut()?.set_active_performance_profile(name);
+        self.handler()?.set_active_performance_profile(name);
         Ok(())
     }
 
     /// Read the current performance profile.
-    async fn get_profile(&self) -> fdo::Result<String> {
-        Ok(self.handler()?.get_active_performance_profile().to_string())
+    async fn get_profile(&self) -> error::Result<String> {
+        Ok(self.handler()?.get_active_performance_profile())
     }
 
     /// Read the list of supported performance profiles.
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+    async fn list_profiles(&self) -> error::Result<Vec<String>> {
         self.handler()?
             .get_availables_performance_profiles()
             .map_err(|err| {
-                fdo::Error::IOError(format!(
+                TailorError::HardwareUnavailable(format!(
                     "unable to list available performance profiles: {err}"
                 ))
             })
     }
+
+    /// Read the power limits (TDP) of the platform with their ranges.
+    async fn list_power_limits(&self) -> error::Result<String> {
+        let limits = performance::power_limits(self.tdp()?).map_err(|err| {
+            TailorError::HardwareUnavailable(format!("unable to read the power limits: {err}"))
+        })?;
+        Ok(serde_json::to_string(&limits).unwrap())
+    }
+
+    /// Temporarily set a power limit in watts until the next profile is activated.
+    /// Fails while power-limit control is disabled, globally or by the active profile.
+    async fn set_power_limit(&self, name: &str, value: i32) -> error::Result<()> {
+        if !settings::subsystems().power_limit_control || !profiles::power_limits_allowed() {
+            return Err(TailorError::NotAuthorized(
+                "Power-limit control is disabled".to_string(),
+            ));
+        }
+        let tdp = self.tdp()?;
+        let limits = performance::power_limits(tdp).map_err(|err| {
+            TailorError::HardwareUnavailable(format!("unable to read the power limits: {err}"))
+        })?;
+        let Some(idx) = limits.iter().position(|limit| limit.name == name) else {
+            return Err(TailorError::InvalidArgs(format!(
+                "Unknown power limit `{name}`"
+            )));
+        };
+        let limit = &limits[idx];
+        if !(limit.min..=limit.max).contains(&value) {
+            return Err(TailorError::InvalidArgs(format!(
+                "The power limit `{name}` must be between {}W and {}W",
+                limit.min, limit.max
+            )));
+        }
+        tdp.set_tdp(idx as u8, value).map_err(|err| {
+            TailorError::Failed(format!("unable to set the power limit {name}: {err}"))
+        })
+    }
+}
+
+#[cfg(feature = "rpc-socket")]
+impl PerformanceInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "SetProfile" => rpc::to_value(self.set_profile(&params.get::<String>(0)?).await),
+            "GetProfile" => rpc::to_value(self.get_profile().await),
+            "ListProfiles" => rpc::to_value(self.list_profiles().await),
+            "ListPowerLimits" => rpc::to_value(self.list_power_limits().await),
+            "SetPowerLimit" => rpc::to_value(
+                self.set_power_limit(&params.get::<String>(0)?, params.get::<i32>(1)?)
+                    .await,
+            ),
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
 }
@@ -1,45 +1,75 @@
-use tailor_api::{ColorProfile, LedDeviceInfo, ProfileInfo};
-use zbus::{fdo, interface};
+use std::sync::Arc;
 
+use tailor_api::{
+    Color, ColorProfile, DeviceList, LedDeviceInfo, LidClosedBehavior, LidClosedFans, ProfileInfo,
+    ProfileTrigger,
+};
+use tokio::sync::watch;
+use tuxedo_ioctl::hal::traits::{FnLockDevice, TdpDevice, TouchpadDevice, WebcamDevice};
+use zbus::{interface, message::Header, SignalContext};
+
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
 use crate::{
-    fancontrol::FanRuntimeHandle,
+    backlight, diagnostics, dump,
+    error::{self, TailorError},
+    fancontrol::{profile::FanProfile, FanHandles},
+    history,
     led::LedRuntimeHandle,
-    performance::PerformanceProfileRuntimeHandle,
-    profiles::{Profile, PROFILE_DIR},
-    util,
+    performance::{self, PerformanceProfileRuntimeHandle},
+    power_source,
+    profiles::{self, LedProfiles, Profile, PROFILE_DIR},
+    self_test, settings, util, wire,
 };
 
+#[derive(Clone)]
 pub struct ProfileInterface {
-    pub fan_handles: Vec<FanRuntimeHandle>,
+    pub fan_handles: FanHandles,
     pub led_handles: Vec<LedRuntimeHandle>,
-    pub performance_profile_handle: Option<PerformanceProfileRuntimeHandle>,
+    pub performance_profile_handle: watch::Receiver<Option<PerformanceProfileRuntimeHandle>>,
+    pub webcam: Option<Arc<dyn WebcamDevice>>,
+    pub fn_lock: Option<Arc<dyn FnLockDevice>>,
+    pub touchpad: Option<Arc<dyn TouchpadDevice>>,
+    pub tdp: Option<Arc<dyn TdpDevice>>,
 }
 
 #[interface(name = "com.tux.Tailor.Profiles")]
 impl ProfileInterface {
-    async fn add_profile(&self, name: &str, value: &str) -> fdo::Result<()> {
-        // Verify correctness of the file.
-        serde_json::from_str::<ProfileInfo>(value)
-            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+    async fn add_profile(&self, name: &str, value: &str) -> error::Result<()> {
+        // Verify correctness of the file and store it in the newest format.
+        let profile: ProfileInfo = wire::decode(value)?;
+        util::write_json(PROFILE_DIR, name, &profile).await
+    }
 
-        util::write_file(PROFILE_DIR, name, value.as_bytes()).await
+    async fn get_profile(
+        &self,
+        name: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> error::Result<String> {
+        wire::read_profile::<ProfileInfo>(PROFILE_DIR, name, wire::version(&header)).await
     }
 
-    async fn get_profile(&self, name: &str) -> fdo::Result<String> {
-        util::read_file(PROFILE_DIR, name).await
+    /// Agree on the wire format version of profile payloads for the calling
+    /// client. Returns the version the client has to use.
+    async fn negotiate_wire_version(
+        &self,
+        client_version: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> error::Result<u32> {
+        Ok(wire::negotiate(&header, client_version))
     }
 
-    async fn list_profiles(&self) -> fdo::Result<Vec<String>> {
+    async fn list_profiles(&self) -> error::Result<Vec<String>> {
         util::get_profiles(PROFILE_DIR).await
     }
 
-    async fn remove_profile(&self, name: &str) -> fdo::Result<()> {
+    async fn remove_profile(&self, name: &str) -> error::Result<()> {
         util::remove_file(PROFILE_DIR, name).await
     }
 
-    async fn rename_profile(&mut self, from: &str, to: &str) -> fdo::Result<Vec<String>> {
+    async fn rename_profile(&mut self, from: &str, to: &str) -> error::Result<Vec<String>> {
         if self.list_profiles().await?.contains(&to.to_string()) {
-            Err(fdo::Error::InvalidArgs(format!(
+            Err(TailorError::InvalidArgs(format!(
                 "File `{to}` already exists"
             )))
         } else {
@@ -54,19 +84,27 @@ impl ProfileInterface {
         }
     }
 
-    async fn set_active_profile_name(&self, name: &str) -> fdo::Result<()> {
+    async fn set_active_profile_name(&self, name: &str) -> error::Result<()> {
         Profile::set_active_profile_name(name).await
     }
 
-    async fn get_active_profile_name(&self) -> fdo::Result<String> {
+    async fn get_active_profile_name(&self) -> error::Result<String> {
         Profile::get_active_profile_name().await
     }
 
-    async fn get_number_of_fans(&self) -> fdo::Result<u8> {
-        Ok(self.fan_handles.len() as u8)
+    /// Whether the device runs on AC or battery.
+    async fn get_power_source(&self) -> error::Result<String> {
+        let source = power_source::current().ok_or_else(|| {
+            TailorError::HardwareUnavailable("Couldn't detect the power source".to_owned())
+        })?;
+        Ok(serde_json::to_string(&source).unwrap())
     }
 
-    async fn get_led_devices(&self) -> fdo::Result<String> {
+    async fn get_number_of_fans(&self) -> error::Result<u8> {
+        Ok(self.fan_handles.get().len() as u8)
+    }
+
+    async fn get_led_devices(&self) -> error::Result<String> {
         let devices: Vec<LedDeviceInfo> = self
             .led_handles
             .iter()
@@ -75,45 +113,259 @@ impl ProfileInterface {
         Ok(serde_json::to_string(&devices).unwrap())
     }
 
-    async fn reload(&mut self) -> fdo::Result<()> {
+    /// List all detected fans, LED devices and temperature sensors.
+    async fn list_devices(&self) -> error::Result<String> {
+        let handles = self.fan_handles.get();
+        let fans = handles.iter().map(|handle| handle.info.clone()).collect();
+        let sensors = handles.iter().map(|handle| handle.sensor.clone()).collect();
+        let leds = self
+            .led_handles
+            .iter()
+            .map(|handle| handle.info.clone())
+            .collect();
+
+        let devices = DeviceList {
+            fans,
+            leds,
+            sensors,
+        };
+        Ok(serde_json::to_string(&devices).unwrap())
+    }
+
+    async fn reload(&mut self) -> error::Result<()> {
+        self.activate(ProfileTrigger::User).await
+    }
+
+    async fn reload_fan_profile(&self) -> error::Result<()> {
+        self.send_fan_profiles(Profile::load_fans()).await
+    }
+
+    async fn reload_led_profile(&self) -> error::Result<()> {
+        self.send_led_profiles(Profile::load_leds()).await
+    }
+
+    /// The most recent profile activations, oldest first.
+    async fn get_profile_history(&self) -> error::Result<String> {
+        Ok(serde_json::to_string(&history::entries()).unwrap())
+    }
+
+    /// Hardware details, version and settings of tailord for bug reports.
+    async fn get_diagnostics(&self) -> error::Result<String> {
+        Ok(diagnostics::report().await.to_string())
+    }
+
+    /// Which capabilities of the hardware passed the self-test at startup.
+    async fn get_self_test(&self) -> error::Result<String> {
+        Ok(serde_json::to_string(self_test::report()).unwrap())
+    }
+
+    /// Log the internal state of tailord, like SIGUSR1.
+    async fn dump_state(&self) -> error::Result<()> {
+        dump::dump_state(&self.fan_handles.get(), &self.led_handles).await;
+        Ok(())
+    }
+
+    /// Emitted whenever a profile was activated, including the reason.
+    #[zbus(signal)]
+    pub async fn profile_activated(ctxt: &SignalContext<'_>, switch: &str) -> zbus::Result<()>;
+}
+
+impl ProfileInterface {
+    /// Apply the active profile to all runtimes and record the activation.
+    pub async fn activate(&mut self, trigger: ProfileTrigger) -> error::Result<()> {
         let Profile {
             fans,
             leds,
             performance_profile,
+            webcam,
+            fn_lock,
+            touchpad,
+            tdp,
+            cpufreq,
+            display_brightness,
+            power_limits,
         } = Profile::load();
+        profiles::allow_power_limits(power_limits);
+
+        self.send_fan_profiles(fans).await?;
+        self.send_led_profiles(leds).await?;
+
+        let perf_handle = self.performance_profile_handle.borrow().clone();
+        if let Some(perf_handle) = perf_handle {
+            if let Some(performance_profile) = performance_profile {
+                perf_handle
+                    .profile_sender
+                    .send(performance_profile.to_string())
+                    .await
+                    .map_err(|err| TailorError::Failed(err.to_string()))?;
+                perf_handle.set_active_performance_profile(&performance_profile.to_string());
+            }
+        }
+
+        if let (Some(device), Some(enabled)) = (&self.webcam, webcam) {
+            device
+                .set_webcam(enabled)
+                .map_err(|err| TailorError::Failed(err.to_string()))?;
+        }
+
+        if let (Some(device), Some(enabled)) = (&self.fn_lock, fn_lock) {
+            device
+                .set_fn_lock(enabled)
+                .map_err(|err| TailorError::Failed(err.to_string()))?;
+        }
 
-        for (idx, fan_handle) in self.fan_handles.iter().enumerate() {
-            let profile = fans.get(idx).cloned().unwrap_or_default();
+        if let (Some(device), Some(enabled)) = (&self.touchpad, touchpad) {
+            device
+                .set_touchpad(enabled)
+                .map_err(|err| TailorError::Failed(err.to_string()))?;
+        }
+
+        if let Some(device) = &self.tdp {
+            if !tdp.is_empty() && power_limits && settings::subsystems().power_limit_control {
+                performance::apply_power_limits(device.as_ref(), &tdp)
+                    .map_err(|err| TailorError::Failed(err.to_string()))?;
+            }
+        }
+
+        cpufreq
+            .apply()
+            .map_err(|err| TailorError::Failed(err.to_string()))?;
+
+        if let Some(percent) = display_brightness {
+            backlight::apply(percent).map_err(|err| TailorError::Failed(err.to_string()))?;
+        }
+
+        match Profile::get_active_profile_name().await {
+            Ok(name) => history::record(&name, trigger),
+            Err(err) => tracing::warn!("Failed to read the active profile name: `{err:?}`"),
+        }
+        Ok(())
+    }
+
+    /// Apply the lid-closed behavior of a profile.
+    pub async fn apply_lid_closed(&self, behavior: &LidClosedBehavior) -> error::Result<()> {
+        match &behavior.fans {
+            Some(LidClosedFans::Auto) => {
+                for fan_handle in &self.fan_handles.get() {
+                    fan_handle
+                        .release_sender
+                        .send(true)
+                        .await
+                        .map_err(|err| TailorError::Failed(err.to_string()))?;
+                }
+            }
+            Some(LidClosedFans::Profile(name)) => {
+                let profile = profiles::load_fan_profile(name)?;
+                self.send_fan_profiles(vec![profile; self.fan_handles.get().len()])
+                    .await?;
+            }
+            None => {}
+        }
+
+        if behavior.leds_off {
+            for led_handle in &self.led_handles {
+                led_handle
+                    .profile_sender
+                    .send(ColorProfile::Single(Color { r: 0, g: 0, b: 0 }))
+                    .await
+                    .map_err(|err| TailorError::Failed(err.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Undo [`Self::apply_lid_closed`] with the fans and LEDs of the active profile.
+    pub async fn revert_lid_closed(&self, behavior: &LidClosedBehavior) -> error::Result<()> {
+        match &behavior.fans {
+            Some(LidClosedFans::Auto) => {
+                for fan_handle in &self.fan_handles.get() {
+                    fan_handle
+                        .release_sender
+                        .send(false)
+                        .await
+                        .map_err(|err| TailorError::Failed(err.to_string()))?;
+                }
+            }
+            Some(LidClosedFans::Profile(_)) => {
+                self.send_fan_profiles(Profile::load_fans()).await?;
+            }
+            None => {}
+        }
+
+        if behavior.leds_off {
+            self.send_led_profiles(Profile::load_leds()).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_fan_profiles(&self, fans: Vec<FanProfile>) -> error::Result<()> {
+        for (idx, fan_handle) in self.fan_handles.get().iter().enumerate() {
+            let profile = profiles::fan_profile(&fans, idx);
             fan_handle
                 .profile_sender
                 .send(profile)
                 .await
-                .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+                .map_err(|err| TailorError::Failed(err.to_string()))?;
         }
+        Ok(())
+    }
 
+    async fn send_led_profiles(&self, leds: LedProfiles) -> error::Result<()> {
         for led_handle in &self.led_handles {
-            let profile = match leds.get(&led_handle.info).cloned() {
-                Some(color_profile) => color_profile,
-                None => ColorProfile::default(led_handle.info.mode),
-            };
             led_handle
                 .profile_sender
-                .send(profile)
+                .send(leds.get(&led_handle.info))
                 .await
-                .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+                .map_err(|err| TailorError::Failed(err.to_string()))?;
         }
+        Ok(())
+    }
+}
 
-        if let Some(perf_handle) = self.performance_profile_handle.as_mut() {
-            if let Some(performance_profile) = performance_profile {
-                perf_handle
-                    .profile_sender
-                    .send(performance_profile.to_string())
-                    .await
-                    .map_err(|err| fdo::Error::Failed(err.to_string()))?;
-                perf_handle.set_active_performance_profile(&performance_profile.to_string());
-            }
+#[cfg(feature = "rpc-socket")]
+impl ProfileInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "AddProfile" => rpc::to_value(
+                self.add_profile(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            "GetProfile" => rpc::to_value(
+                wire::read_profile::<ProfileInfo>(
+                    PROFILE_DIR,
+                    &params.get::<String>(0)?,
+                    tailor_api::wire::WIRE_VERSION,
+                )
+                .await,
+            ),
+            "ListProfiles" => rpc::to_value(self.list_profiles().await),
+            "RemoveProfile" => rpc::to_value(self.remove_profile(&params.get::<String>(0)?).await),
+            "RenameProfile" => rpc::to_value(
+                self.rename_profile(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            "SetActiveProfileName" => rpc::to_value(
+                self.set_active_profile_name(&params.get::<String>(0)?)
+                    .await,
+            ),
+            "GetActiveProfileName" => rpc::to_value(self.get_active_profile_name().await),
+            "GetPowerSource" => rpc::to_value(self.get_power_source().await),
+            "GetNumberOfFans" => rpc::to_value(self.get_number_of_fans().await),
+            "GetLedDevices" => rpc::to_value(self.get_led_devices().await),
+            "ListDevices" => rpc::to_value(self.list_devices().await),
+            "Reload" => rpc::to_value(self.reload().await),
+            "ReloadFanProfile" => rpc::to_value(self.reload_fan_profile().await),
+            "ReloadLedProfile" => rpc::to_value(self.reload_led_profile().await),
+            "GetProfileHistory" => rpc::to_value(self.get_profile_history().await),
+            "GetDiagnostics" => rpc::to_value(self.get_diagnostics().await),
+            "GetSelfTest" => rpc::to_value(self.get_self_test().await),
+            "DumpState" => rpc::to_value(self.dump_state().await),
+            _ => Err(rpc::RpcError::MethodNotFound),
         }
-
-        Ok(())
     }
 }
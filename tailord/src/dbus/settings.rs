@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use tailor_api::{PowerSource, Subsystem};
+use zbus::{interface, SignalContext};
+
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
+use crate::{
+    conflicts,
+    error::{self, TailorError},
+    logging,
+    profiles::PROFILE_DIR,
+    settings::{self, Settings},
+    util,
+};
+
+pub struct SettingsInterface;
+
+#[interface(name = "com.tux.Tailor.Settings")]
+impl SettingsInterface {
+    /// Read which subsystems are enabled.
+    async fn get_subsystems(&self) -> error::Result<String> {
+        Ok(serde_json::to_string(&settings::subsystems()).unwrap())
+    }
+
+    /// Enable or disable a subsystem. Disabled subsystems hand control
+    /// back to the firmware until they are enabled again.
+    /// The setting is persisted across restarts.
+    async fn set_subsystem_enabled(&self, subsystem: &str, enabled: bool) -> error::Result<()> {
+        let subsystem = Subsystem::from_str(subsystem)
+            .map_err(|err| TailorError::InvalidArgs(err.to_string()))?;
+
+        Settings::set_subsystem_enabled(subsystem, enabled).await?;
+
+        tracing::info!(
+            %subsystem,
+            enabled,
+            "{} subsystem `{subsystem}`",
+            if enabled { "Enabled" } else { "Disabled" }
+        );
+        Ok(())
+    }
+
+    /// Read which profiles are activated on AC and battery power.
+    async fn get_power_source_profiles(&self) -> error::Result<String> {
+        Ok(serde_json::to_string(&settings::power_source_profiles()).unwrap())
+    }
+
+    /// Activate `profile` whenever the device switches to `source` (`ac` or `battery`).
+    /// An empty profile name disables the automatic switch.
+    async fn set_power_source_profile(&self, source: &str, profile: &str) -> error::Result<()> {
+        let source = PowerSource::from_str(source).map_err(TailorError::InvalidArgs)?;
+        let profile = if profile.is_empty() {
+            None
+        } else if util::get_profiles(PROFILE_DIR)
+            .await?
+            .iter()
+            .any(|name| name == profile)
+        {
+            Some(profile.to_owned())
+        } else {
+            return Err(TailorError::ProfileNotFound(format!(
+                "Couldn't find profile `{profile}`"
+            )));
+        };
+
+        let mut profiles = settings::power_source_profiles();
+        profiles.set(source, profile);
+        Settings::set_power_source_profiles(profiles).await
+    }
+
+    /// Change the log level of a target, e.g. `tailord::fancontrol`, or the default
+    /// level if the target is empty. The change is lost when tailord restarts.
+    async fn set_log_level(&self, target: &str, level: &str) -> error::Result<()> {
+        logging::set_level(target, level)
+    }
+
+    /// Other fan control daemons that were running when tailord started.
+    #[zbus(property)]
+    async fn conflicting_services(&self) -> Vec<String> {
+        conflicts::get().to_vec()
+    }
+
+    /// Emitted when a subsystem was enabled or disabled.
+    #[zbus(signal)]
+    pub async fn subsystems_changed(ctxt: &SignalContext<'_>, subsystems: &str)
+        -> zbus::Result<()>;
+}
+
+#[cfg(feature = "rpc-socket")]
+impl SettingsInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "GetSubsystems" => rpc::to_value(self.get_subsystems().await),
+            "SetSubsystemEnabled" => rpc::to_value(
+                self.set_subsystem_enabled(&params.get::<String>(0)?, params.get(1)?)
+                    .await,
+            ),
+            "GetPowerSourceProfiles" => rpc::to_value(self.get_power_source_profiles().await),
+            "SetPowerSourceProfile" => rpc::to_value(
+                self.set_power_source_profile(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            "SetLogLevel" => rpc::to_value(
+                self.set_log_level(&params.get::<String>(0)?, &params.get::<String>(1)?)
+                    .await,
+            ),
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use tuxedo_ioctl::hal::traits::TouchpadDevice;
+use zbus::interface;
+
+use crate::error::{self, TailorError};
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
+
+pub struct TouchpadInterface {
+    pub touchpad: Option<Arc<dyn TouchpadDevice>>,
+}
+
+impl TouchpadInterface {
+    fn touchpad(&self) -> error::Result<&dyn TouchpadDevice> {
+        self.touchpad
+            .as_deref()
+            .ok_or(TailorError::HardwareUnavailable(
+                "Touchpad control isn't available".to_string(),
+            ))
+    }
+}
+
+#[interface(name = "com.tux.Tailor.Touchpad")]
+impl TouchpadInterface {
+    /// Switch the touchpad on or off until the next profile is activated.
+    async fn set_enabled(&self, enabled: bool) -> error::Result<()> {
+        self.touchpad()?
+            .set_touchpad(enabled)
+            .map_err(|err| TailorError::Failed(format!("unable to switch the touchpad: {err}")))?;
+        tracing::info!("Touchpad {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Whether the touchpad is switched on.
+    async fn get_enabled(&self) -> error::Result<bool> {
+        self.touchpad()?
+            .get_touchpad()
+            .map_err(|err| TailorError::Failed(format!("unable to read the touchpad state: {err}")))
+    }
+}
+
+#[cfg(feature = "rpc-socket")]
+impl TouchpadInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "SetEnabled" => rpc::to_value(self.set_enabled(params.get::<bool>(0)?).await),
+            "GetEnabled" => rpc::to_value(self.get_enabled().await),
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use tuxedo_ioctl::hal::traits::WebcamDevice;
+use zbus::interface;
+
+use crate::error::{self, TailorError};
+#[cfg(feature = "rpc-socket")]
+use crate::rpc;
+
+pub struct WebcamInterface {
+    pub webcam: Option<Arc<dyn WebcamDevice>>,
+}
+
+impl WebcamInterface {
+    fn webcam(&self) -> error::Result<&dyn WebcamDevice> {
+        self.webcam
+            .as_deref()
+            .ok_or(TailorError::HardwareUnavailable(
+                "Webcam control isn't available".to_string(),
+            ))
+    }
+}
+
+#[interface(name = "com.tux.Tailor.Webcam")]
+impl WebcamInterface {
+    /// Switch the webcam on or off until the next profile is activated.
+    async fn set_enabled(&self, enabled: bool) -> error::Result<()> {
+        self.webcam()?
+            .set_webcam(enabled)
+            .map_err(|err| TailorError::Failed(format!("unable to switch the webcam: {err}")))?;
+        tracing::info!("Webcam {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Whether the webcam is switched on.
+    async fn get_enabled(&self) -> error::Result<bool> {
+        self.webcam()?
+            .get_webcam()
+            .map_err(|err| TailorError::Failed(format!("unable to read the webcam state: {err}")))
+    }
+}
+
+#[cfg(feature = "rpc-socket")]
+impl WebcamInterface {
+    /// Dispatch a JSON-RPC call to the method of the same name.
+    pub async fn rpc_call(
+        &mut self,
+        member: &str,
+        params: rpc::Params,
+    ) -> Result<serde_json::Value, rpc::RpcError> {
+        match member {
+            "SetEnabled" => rpc::to_value(self.set_enabled(params.get::<bool>(0)?).await),
+            "GetEnabled" => rpc::to_value(self.get_enabled().await),
+            _ => Err(rpc::RpcError::MethodNotFound),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Details for bug reports that clients can't read themselves,
+//! such as the state of the ioctl interface.
+
+use once_cell::sync::OnceCell;
+use serde_json::{json, Value};
+use tuxedo_ioctl::hal::{IoInterface, IoctlResult};
+
+use crate::{conflicts, power_source, self_test, settings};
+
+static HARDWARE: OnceCell<HardwareInfo> = OnceCell::new();
+
+/// What tailord found out about the hardware at startup.
+#[derive(Debug, Default, serde::Serialize)]
+struct HardwareInfo {
+    /// Why the ioctl interface couldn't be opened.
+    io_error: Option<String>,
+    /// `ioctl` or `hwmon`.
+    backend: Option<String>,
+    module_version: Option<String>,
+    interface: Option<String>,
+    model: Option<String>,
+    number_of_fans: u8,
+    fans_min_speed: Option<u8>,
+    fans_off_available: Option<bool>,
+    performance_profiles: Vec<String>,
+    default_performance_profile: Option<String>,
+    webcam: bool,
+    fn_lock: bool,
+    touchpad: bool,
+    tdp: bool,
+    charging: bool,
+}
+
+/// Remember the result of opening the ioctl interface.
+pub fn record_hardware(io: &IoctlResult<IoInterface>) {
+    let info = match io {
+        Ok(io) => HardwareInfo {
+            io_error: None,
+            backend: Some(format!("{:?}", io.backend).to_lowercase()),
+            module_version: Some(io.module_version.clone()),
+            interface: io.device.device_interface_id_str().ok(),
+            model: io.device.device_model_id_str().ok(),
+            number_of_fans: io.supported_fans().len() as u8,
+            fans_min_speed: io.device.get_fans_min_speed().ok(),
+            fans_off_available: io.device.get_fans_off_available().ok(),
+            performance_profiles: io.available_performance_profiles(),
+            default_performance_profile: io.device.get_default_odm_performance_profile().ok(),
+            webcam: io.has_webcam_control(),
+            fn_lock: io.has_fn_lock_control(),
+            touchpad: io.has_touchpad_control(),
+            tdp: io.has_tdp_control(),
+            charging: io.has_charging_control(),
+        },
+        Err(err) => HardwareInfo {
+            io_error: Some(err.to_string()),
+            ..Default::default()
+        },
+    };
+    HARDWARE.set(info).ok();
+}
+
+/// Collect the diagnostics. Secrets in the settings are redacted.
+pub async fn report() -> Value {
+    let mut settings = settings::Settings::load();
+    if settings.http_api.token.is_some() {
+        settings.http_api.token = Some("<redacted>".to_owned());
+    }
+    if settings.mqtt.password.is_some() {
+        settings.mqtt.password = Some("<redacted>".to_owned());
+    }
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": {
+            "rpc_socket": cfg!(feature = "rpc-socket"),
+            "http_api": cfg!(feature = "http-api"),
+            "mqtt": cfg!(feature = "mqtt"),
+            "mock": cfg!(feature = "mock"),
+        },
+        "hardware": HARDWARE.get(),
+        "conflicting_services": conflicts::get(),
+        "self_test": self_test::report(),
+        "settings": settings,
+        "power_source": power_source::current(),
+    })
+}
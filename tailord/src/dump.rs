@@ -0,0 +1,94 @@
+//! Dump the internal state to the log on SIGUSR1 or through the `DumpState`
+//! D-Bus method, to make reports about hangs and misbehavior actionable.
+
+use futures::StreamExt;
+use signal_hook::consts::SIGUSR1;
+use signal_hook_tokio::Signals;
+use tokio::sync::mpsc;
+
+use crate::{
+    conflicts,
+    fancontrol::{FanHandles, FanRuntimeHandle},
+    history,
+    led::LedRuntimeHandle,
+    power_source,
+    profiles::Profile,
+    settings,
+};
+
+pub fn spawn(fan_handles: FanHandles, led_handles: Vec<LedRuntimeHandle>) {
+    let signals = match Signals::new([SIGUSR1]) {
+        Ok(signals) => signals,
+        Err(err) => {
+            tracing::error!("Failed to register the SIGUSR1 handler: `{err}`");
+            return;
+        }
+    };
+    tokio::task::spawn_local(handle_signals(signals, fan_handles, led_handles));
+}
+
+#[tracing::instrument(skip_all)]
+async fn handle_signals(
+    mut signals: Signals,
+    fan_handles: FanHandles,
+    led_handles: Vec<LedRuntimeHandle>,
+) {
+    while signals.next().await.is_some() {
+        tracing::info!("Received SIGUSR1");
+        dump_state(&fan_handles.get(), &led_handles).await;
+    }
+}
+
+/// Log the daemon state and ask all runtimes to log theirs.
+pub async fn dump_state(fan_handles: &[FanRuntimeHandle], led_handles: &[LedRuntimeHandle]) {
+    tracing::info!(
+        subsystems = ?settings::subsystems(),
+        power_source = ?power_source::current(),
+        conflicting_services = ?conflicts::get(),
+        "State dump"
+    );
+
+    let name = Profile::get_active_profile_name().await;
+    match (name, Profile::get_active_profile_info()) {
+        (Ok(name), Ok(info)) => {
+            tracing::info!(profile = name, "Active profile `{name}`: {info:?}");
+        }
+        (name, info) => tracing::warn!("Failed to read the active profile: {name:?}, {info:?}"),
+    }
+    tracing::info!("Profile history: {:?}", history::entries());
+
+    for handle in fan_handles {
+        let fan = handle.info.index;
+        tracing::info!(
+            fan,
+            profile_backlog = backlog(&handle.profile_sender),
+            speed_backlog = backlog(&handle.fan_speed_sender),
+            pin_backlog = backlog(&handle.pin_sender),
+            release_backlog = backlog(&handle.release_sender),
+            "Fan {fan}: Channel backlogs"
+        );
+        // Don't wait for a runtime that hangs, the backlogs show that already.
+        if handle.dump_sender.try_send(()).is_err() {
+            tracing::warn!(fan, "Fan {fan}: The runtime doesn't respond");
+        }
+    }
+
+    for handle in led_handles {
+        let led = handle.info.device_id();
+        tracing::info!(
+            led,
+            profile_backlog = backlog(&handle.profile_sender),
+            color_backlog = backlog(&handle.color_sender),
+            identify_backlog = backlog(&handle.identify_sender),
+            "LED `{led}`: Channel backlogs"
+        );
+        if handle.dump_sender.try_send(()).is_err() {
+            tracing::warn!(led, "LED `{led}`: The runtime doesn't respond");
+        }
+    }
+}
+
+/// Number of messages the runtime hasn't received yet.
+fn backlog<T>(sender: &mpsc::Sender<T>) -> usize {
+    sender.max_capacity() - sender.capacity()
+}
@@ -0,0 +1,54 @@
+use std::io;
+
+use zbus::DBusError;
+
+/// Errors returned by the D-Bus interfaces of tailord.
+///
+/// Every variant is sent as a named D-Bus error (e.g. `com.tux.Tailor.Error.ProfileNotFound`),
+/// so clients can match on the kind of error instead of parsing the message.
+#[derive(Debug, DBusError)]
+#[zbus(prefix = "com.tux.Tailor.Error")]
+pub enum TailorError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    /// The requested profile doesn't exist.
+    ProfileNotFound(String),
+    /// A fan curve couldn't be used.
+    InvalidCurve(String),
+    /// The requested hardware isn't available on this device.
+    HardwareUnavailable(String),
+    /// The operation isn't permitted.
+    NotAuthorized(String),
+    /// The arguments of a call are invalid.
+    InvalidArgs(String),
+    /// Reading or writing a file failed.
+    IOError(String),
+    /// A stored file has invalid content.
+    InvalidFileContent(String),
+    /// An internal error occurred.
+    Failed(String),
+}
+
+pub type Result<T> = std::result::Result<T, TailorError>;
+
+impl TailorError {
+    /// Map an I/O error that occurred while accessing the profile `name`.
+    pub fn from_profile_io(err: io::Error, name: &str) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => {
+                Self::ProfileNotFound(format!("Couldn't find profile `{name}`"))
+            }
+            io::ErrorKind::PermissionDenied => Self::NotAuthorized(err.to_string()),
+            _ => Self::IOError(err.to_string()),
+        }
+    }
+}
+
+impl From<io::Error> for TailorError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => Self::NotAuthorized(err.to_string()),
+            _ => Self::IOError(err.to_string()),
+        }
+    }
+}
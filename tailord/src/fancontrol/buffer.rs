@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 const TEMP_HISTORY_LENGTH: usize = 40; // 4 seconds
 
 #[derive(Debug)]
@@ -32,6 +34,33 @@ impl TemperatureBuffer {
         current - min
     }
 
+    /// The temperatures in the order they were recorded.
+    pub(super) fn oldest_first(&self) -> Vec<u8> {
+        let (newer, older) = self.temp_history.split_at(self.position + 1);
+        older.iter().chain(newer).copied().collect()
+    }
+
+    /// Exponential moving average of the history, where each temperature
+    /// is weighted with `alpha` percent and the average before with the rest.
+    pub(super) fn moving_average(&self, alpha: u8) -> u8 {
+        let alpha = f64::from(alpha.min(100)) / 100.0;
+        let (newer, older) = self.temp_history.split_at(self.position + 1);
+        let mut temps = older.iter().chain(newer).map(|temp| f64::from(*temp));
+        let oldest = temps.next().unwrap_or_default();
+        temps
+            .fold(oldest, |average, temp| average + alpha * (temp - average))
+            .round() as u8
+    }
+
+    /// How much the temperature rose from the oldest to the latest value and
+    /// the time in between, if a value was recorded every `interval`.
+    /// Falling temperatures count as no rise.
+    pub(super) fn rise(&self, interval: Duration) -> (u8, Duration) {
+        let oldest = self.temp_history[(self.position + 1) % TEMP_HISTORY_LENGTH];
+        let rise = self.get_latest().saturating_sub(oldest);
+        (rise, interval * (TEMP_HISTORY_LENGTH as u32 - 1))
+    }
+
     pub(crate) fn get_latest(&self) -> u8 {
         self.temp_history[self.position]
     }
@@ -1,20 +1,128 @@
 use std::{sync::Arc, time::Duration};
 
-use tokio::sync::{broadcast, mpsc};
-use tuxedo_ioctl::hal::traits::HardwareDevice;
+use once_cell::sync::Lazy;
+use tailor_api::{FanDeviceInfo, FanStatus, SensorInfo, SubsystemStates};
+use tokio::{
+    sync::{broadcast, mpsc, watch},
+    time::Instant,
+};
+use tuxedo_ioctl::hal::{traits::HardwareDevice, IoctlResult};
 
-use crate::suspend::get_suspend_receiver;
+use crate::{
+    metrics, profiles,
+    settings::{self, get_subsystem_receiver},
+    suspend, thermal,
+};
 
-use self::{buffer::TemperatureBuffer, profile::FanProfile};
+use self::{
+    buffer::TemperatureBuffer, power_limit::PowerLimits, profile::FanProfile, retry::retry,
+};
 
 mod buffer;
+mod power_limit;
 pub mod profile;
+mod retry;
 mod runtime;
+#[cfg(test)]
+mod simulation;
+
+/// Index of the fan and the error of its temperature sensor.
+pub type SensorFailure = (u8, String);
+
+static SENSOR_FAILURE_CHANNEL: Lazy<broadcast::Sender<SensorFailure>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// Subscribe to failures of the temperature sensors.
+pub fn get_sensor_failure_receiver() -> broadcast::Receiver<SensorFailure> {
+    SENSOR_FAILURE_CHANNEL.subscribe()
+}
+
+/// Index of the fan and the temperature at which it reached the passive trip point.
+pub type ThermalTrip = (u8, u8);
+
+static THERMAL_TRIP_CHANNEL: Lazy<broadcast::Sender<ThermalTrip>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// Subscribe to fans that run at full speed because the passive trip point was reached.
+pub fn get_thermal_trip_receiver() -> broadcast::Receiver<ThermalTrip> {
+    THERMAL_TRIP_CHANNEL.subscribe()
+}
+
+/// Index of the fan that entered the failsafe and when it ends.
+type Failsafe = (u8, Instant);
+
+/// The firmware can only take over all fans at once, so all runtimes
+/// leave their fans to it while one of them is in the failsafe.
+static FAILSAFE_CHANNEL: Lazy<broadcast::Sender<Failsafe>> = Lazy::new(|| broadcast::channel(16).0);
+
+/// Upper bound for boosts and pinned speeds, so a forgotten pin doesn't
+/// override the fan profile indefinitely.
+pub const MAX_PIN_SECONDS: u32 = 600;
+
+/// Keeps a fan at a fixed speed for a limited time, ignoring the profile.
+#[derive(Debug, Clone, Copy)]
+pub enum PinRequest {
+    Pin {
+        speed: u8,
+        duration: Duration,
+        source: PinSource,
+    },
+    /// End the current pin early. With a source, only if the pin came from it.
+    Release(Option<PinSource>),
+}
+
+/// Who pinned the speed, so the fan boost key only ends its own boosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinSource {
+    /// D-Bus or the RPC socket.
+    Client,
+    /// The fan boost key, see `crate::hotkey`.
+    BoostKey,
+}
 
 #[derive(Clone)]
 pub struct FanRuntimeHandle {
+    pub info: FanDeviceInfo,
+    /// The temperature sensor that the fan follows.
+    pub sensor: SensorInfo,
     pub fan_speed_sender: mpsc::Sender<u8>,
     pub profile_sender: mpsc::Sender<FanProfile>,
+    pub pin_sender: mpsc::Sender<PinRequest>,
+    /// Hand the fan to the firmware (`true`) until it's taken back (`false`).
+    pub release_sender: mpsc::Sender<bool>,
+    /// Log the internal state of the runtime.
+    pub dump_sender: mpsc::Sender<()>,
+    pub status: watch::Receiver<FanStatus>,
+}
+
+/// The handles of all fan runtimes. The fans are added later if the
+/// hardware interface wasn't available at startup.
+#[derive(Clone)]
+pub struct FanHandles(watch::Receiver<Vec<FanRuntimeHandle>>);
+
+impl FanHandles {
+    pub fn new(handles: Vec<FanRuntimeHandle>) -> (watch::Sender<Vec<FanRuntimeHandle>>, Self) {
+        let (sender, receiver) = watch::channel(handles);
+        (sender, Self(receiver))
+    }
+
+    /// The fans that are currently controlled.
+    pub fn get(&self) -> Vec<FanRuntimeHandle> {
+        self.0.borrow().clone()
+    }
+
+    pub fn statuses(&self) -> Vec<watch::Receiver<FanStatus>> {
+        self.0
+            .borrow()
+            .iter()
+            .map(|handle| handle.status.clone())
+            .collect()
+    }
+
+    /// Wait until fans were added, `false` if that can't happen anymore.
+    pub async fn changed(&mut self) -> bool {
+        self.0.changed().await.is_ok()
+    }
 }
 
 #[derive(Debug)]
@@ -29,12 +137,50 @@ pub struct FanRuntimeData {
     io: Arc<dyn HardwareDevice>,
     /// The configuration.
     profile: FanProfile,
-    suspend_receiver: broadcast::Receiver<bool>,
+    /// Enabled subsystems, used to check whether power limits should be set.
+    subsystems: watch::Receiver<SubsystemStates>,
+    /// Publishes the current temperature and fan speed.
+    status_sender: watch::Sender<FanStatus>,
+    /// Whether the last attempt to read the temperature failed.
+    sensor_failed: bool,
+    /// Number of hardware accesses that failed even after retrying.
+    errors: u64,
+    /// Number of fan control steps in a row with errors.
+    failed_steps: u32,
+    /// The fan is left to the firmware until then after persistent errors.
+    failsafe_until: Option<Instant>,
+    /// Whether the temperature reached the passive trip point.
+    throttled: bool,
+    /// The states of the cooling devices, kept when the fan control loop is
+    /// interrupted, so the devices can be reset when power limits end.
+    power_limits: PowerLimits,
+}
+
+/// A fixed speed that replaces the profile for a while.
+#[derive(Debug, Clone, Copy)]
+struct PinnedSpeed {
+    speed: u8,
+    until: Instant,
+    source: PinSource,
+    /// When the speed is written again.
+    next_step: Instant,
 }
 
 pub struct FanRuntime {
     profile_receiver: mpsc::Receiver<FanProfile>,
     fan_speed_receiver: mpsc::Receiver<u8>,
+    pin_receiver: mpsc::Receiver<PinRequest>,
+    pin: Option<PinnedSpeed>,
+    release_receiver: mpsc::Receiver<bool>,
+    /// Whether the fan was handed to the firmware through the release channel.
+    released: bool,
+    /// Whether the temperature and speed of the fan could be read yet.
+    /// The firmware keeps controlling the fan until then.
+    initialized: bool,
+    dump_receiver: mpsc::Receiver<()>,
+    suspend_receiver: broadcast::Receiver<bool>,
+    failsafe_receiver: broadcast::Receiver<Failsafe>,
+    subsystem_receiver: watch::Receiver<SubsystemStates>,
     data: FanRuntimeData,
 }
 
@@ -45,40 +191,101 @@ impl FanRuntime {
         io: Arc<dyn HardwareDevice>,
         profile: FanProfile,
     ) -> (FanRuntimeHandle, FanRuntime) {
-        let fan_speed = io.get_fan_speed_percent(fan_idx).unwrap();
-        let temp = io.get_fan_temperature(fan_idx).unwrap();
-        let temp_history = TemperatureBuffer::new(temp);
-
         let (profile_sender, profile_receiver) = mpsc::channel(1);
         let (fan_speed_sender, fan_speed_receiver) = mpsc::channel(1);
-        let suspend_receiver = get_suspend_receiver();
+        let (pin_sender, pin_receiver) = mpsc::channel(1);
+        let (release_sender, release_receiver) = mpsc::channel(1);
+        let (dump_sender, dump_receiver) = mpsc::channel(1);
+        let suspend_receiver = suspend::get_suspend_receiver();
+        let (status_sender, status) = watch::channel(FanStatus {
+            index: fan_idx,
+            temperature: 0,
+            speed: 0,
+            target: None,
+            power_limit: 0,
+        });
+
+        let sensor = SensorInfo {
+            fan_index: fan_idx,
+            label: io
+                .get_fan_sensor(fan_idx)
+                .unwrap_or_else(|_| format!("{} temperature", fan_label(fan_idx))),
+        };
+        let mut data = FanRuntimeData {
+            temp_history: TemperatureBuffer::new(0),
+            fan_speed: 0,
+            io,
+            profile,
+            fan_idx,
+            subsystems: get_subsystem_receiver(),
+            status_sender,
+            sensor_failed: false,
+            errors: 0,
+            failed_steps: 0,
+            failsafe_until: None,
+            throttled: false,
+            power_limits: PowerLimits::default(),
+        };
+        let initialized = match data.initialize() {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!(
+                    "Fan {fan_idx}: Failed to initialize, leaving the fan to the firmware \
+                    and retrying every {HARDWARE_RETRY_INTERVAL:?}: `{err}`"
+                );
+                false
+            }
+        };
 
         (
             FanRuntimeHandle {
+                info: FanDeviceInfo {
+                    index: fan_idx,
+                    label: fan_label(fan_idx),
+                },
+                sensor,
                 fan_speed_sender,
                 profile_sender,
+                pin_sender,
+                release_sender,
+                dump_sender,
+                status,
             },
             FanRuntime {
-                data: FanRuntimeData {
-                    temp_history,
-                    fan_speed,
-                    io,
-                    profile,
-                    fan_idx,
-                    suspend_receiver,
-                },
+                data,
                 profile_receiver,
                 fan_speed_receiver,
+                pin_receiver,
+                pin: None,
+                release_receiver,
+                released: false,
+                initialized,
+                dump_receiver,
+                suspend_receiver,
+                failsafe_receiver: FAILSAFE_CHANNEL.subscribe(),
+                subsystem_receiver: get_subsystem_receiver(),
             },
         )
     }
 
+    #[tracing::instrument(name = "fan_runtime", skip(self), fields(fan = self.data.fan_idx))]
     pub async fn run(mut self) {
+        if !self.initialized || !self.subsystem_receiver.borrow().fan_control {
+            self.data.release_fans();
+        }
+
         loop {
+            let fan_control = self.initialized
+                && self.subsystem_receiver.borrow_and_update().fan_control
+                && !self.released;
+            let next_pin_step = self.pin.map(|pin| pin.next_step);
+
             tokio::select! {
                 new_config = self.profile_receiver.recv() => {
                     if let Some(config) = new_config {
                         self.data.profile = config;
+                        // The new profile may disable power limits.
+                        self.data.check_power_limits();
                     } else {
                         break;
                     }
@@ -100,11 +307,113 @@ impl FanRuntime {
                                 _ = tokio::time::sleep(Duration::from_millis(1000)) => break,
                             }
                         }
+                        if !fan_control {
+                            self.data.release_fans();
+                        }
                     } else {
                         break;
                     }
                 }
-                _ = self.data.fan_control_loop() => {},
+                // Keep the fan at a fixed speed until the pin expires.
+                // Another pin replaces the speed and restarts the timer.
+                pin = self.pin_receiver.recv() => match pin {
+                    Some(PinRequest::Pin { speed, duration, source }) => {
+                        tracing::info!("Fan {}: Pinning at {speed}% for {duration:?}", self.data.fan_idx);
+                        let now = Instant::now();
+                        self.pin = Some(PinnedSpeed { speed, until: now + duration, source, next_step: now });
+                    }
+                    Some(PinRequest::Release(source)) => {
+                        let owned = self.pin.is_some_and(|pin| source.map_or(true, |source| pin.source == source));
+                        if owned && self.pin.take().is_some() {
+                            tracing::info!("Fan {}: Released pinned speed", self.data.fan_idx);
+                            self.data.end_pin(fan_control);
+                        }
+                    }
+                    None => break,
+                },
+                _ = tokio::time::sleep_until(next_pin_step.unwrap_or_else(Instant::now)), if next_pin_step.is_some() => {
+                    if let Some(mut pin) = self.pin.take() {
+                        if Instant::now() >= pin.until {
+                            tracing::info!("Fan {}: Pinned speed expired", self.data.fan_idx);
+                            self.data.end_pin(fan_control);
+                        } else if self.data.pin_step(pin.speed).await {
+                            pin.next_step = (Instant::now() + PIN_REFRESH_INTERVAL).min(pin.until);
+                            self.pin = Some(pin);
+                        } else {
+                            self.data.end_pin(fan_control);
+                        }
+                    }
+                }
+                release = self.release_receiver.recv() => match release {
+                    Some(released) => {
+                        let pinned = released && self.pin.take().is_some();
+                        if released && (fan_control || pinned) {
+                            self.data.release_fans();
+                        }
+                        self.released = released;
+                        if !released && !fan_control && self.initialized && self.subsystem_receiver.borrow().fan_control {
+                            self.data.resume_fan_control();
+                        }
+                    }
+                    None => break,
+                },
+                Ok((fan_idx, until)) = self.failsafe_receiver.recv() => {
+                    if fan_idx != self.data.fan_idx {
+                        self.data.follow_failsafe(fan_idx, until);
+                    }
+                }
+                Some(()) = self.dump_receiver.recv() => {
+                    self.data.dump_state(fan_control, self.pin.map(|pin| pin.speed));
+                }
+                changed = self.subsystem_receiver.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    self.data.check_power_limits();
+                    let enabled = self.initialized
+                        && self.subsystem_receiver.borrow().fan_control
+                        && !self.released;
+                    if !self.subsystem_receiver.borrow().fan_control && self.pin.take().is_some() {
+                        tracing::info!("Fan {}: Ending pinned speed, fan control was disabled", self.data.fan_idx);
+                        self.data.release_fans();
+                    } else if enabled != fan_control {
+                        if enabled {
+                            self.data.resume_fan_control();
+                        } else {
+                            self.data.release_fans();
+                        }
+                    }
+                }
+                // Hand the fans back to the firmware while the system is suspended
+                // Some firmware keeps a pinned speed while the system is suspended,
+                // so the pin ends as well.
+                _ = suspend::wait_for_sleep(&mut self.suspend_receiver) => {
+                    if self.pin.take().is_some() {
+                        tracing::info!("Fan {}: Ending pinned speed for suspend", self.data.fan_idx);
+                    }
+                    self.data.release_fans();
+                    suspend::sleep_until_wake_up(&mut self.suspend_receiver).await;
+                    if self.initialized && self.subsystem_receiver.borrow().fan_control && !self.released {
+                        self.data.resume_fan_control();
+                    }
+                }
+                _ = self.data.fan_control_loop(), if fan_control && next_pin_step.is_none() => {},
+                // Keep the status up to date while the firmware controls the fans
+                _ = tokio::time::sleep(Duration::from_millis(settings::fan_control().firmware_status_interval_ms)), if !fan_control && self.initialized && next_pin_step.is_none() => {
+                    self.data.refresh_status().await;
+                }
+                _ = tokio::time::sleep(HARDWARE_RETRY_INTERVAL), if !self.initialized => {
+                    match self.data.initialize() {
+                        Ok(()) => {
+                            tracing::info!("Fan {}: Initialized after all", self.data.fan_idx);
+                            self.initialized = true;
+                            if self.subsystem_receiver.borrow().fan_control && !self.released {
+                                self.data.resume_fan_control();
+                            }
+                        }
+                        Err(err) => tracing::debug!("Fan {}: Still failing to initialize: `{err}`", self.data.fan_idx),
+                    }
+                }
             }
         }
         tracing::error!(
@@ -116,29 +425,242 @@ impl FanRuntime {
     }
 }
 
+/// How long the firmware controls a fan after persistent errors
+/// before tailord tries again.
+const FAILSAFE_DURATION: Duration = Duration::from_secs(30);
+
+/// How often to retry reading a fan that failed at startup.
+const HARDWARE_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a pinned speed is written again, some firmware
+/// falls back to automatic mode otherwise.
+const PIN_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Temperature in °C at which a pin ends if there are no trip points.
+const PIN_MAX_TEMP: u8 = 90;
+
+/// Label the fans like TCC does: the first fan cools the CPU
+/// and the second one the GPU.
+pub fn fan_label(fan_idx: u8) -> String {
+    match fan_idx {
+        0 => "CPU".to_owned(),
+        1 => "GPU".to_owned(),
+        _ => format!("Fan {}", fan_idx + 1),
+    }
+}
+
 impl FanRuntimeData {
+    /// Read the current temperature and fan speed to start from.
+    fn initialize(&mut self) -> IoctlResult<()> {
+        let fan_speed = self.io.get_fan_speed_percent(self.fan_idx)?;
+        let temp = self.io.get_fan_temperature(self.fan_idx)?;
+        self.fan_speed = fan_speed;
+        self.temp_history = TemperatureBuffer::new(temp);
+        self.publish_status(temp, None, 0);
+        Ok(())
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
     /// Adds entries to history ring buffer.
-    fn update_temp(&mut self) -> u8 {
-        match self.io.get_fan_temperature(self.fan_idx) {
+    async fn update_temp(&mut self) -> u8 {
+        match retry(|| self.io.get_fan_temperature(self.fan_idx)).await {
             Ok(temp) => {
+                if self.sensor_failed {
+                    tracing::info!("Fan {}: Temperature sensor recovered", self.fan_idx);
+                    self.sensor_failed = false;
+                }
                 self.temp_history.update(temp);
                 temp
             }
             Err(err) => {
-                tracing::error!("Failed reading the current temperature: `{err}`");
+                tracing::error!(
+                    temperature = self.temp_history.get_latest(),
+                    "Failed reading the current temperature: `{err}`"
+                );
+                metrics::count_sensor_error(self.fan_idx);
+                self.errors += 1;
+                // Only report the first failure in a row.
+                if !self.sensor_failed {
+                    self.sensor_failed = true;
+                    SENSOR_FAILURE_CHANNEL
+                        .send((self.fan_idx, err.to_string()))
+                        .ok();
+                }
                 self.temp_history.get_latest()
             }
         }
     }
 
+    /// Publish the current status if it changed.
+    fn publish_status(&self, temperature: u8, target: Option<u8>, power_limit: u8) {
+        let status = FanStatus {
+            index: self.fan_idx,
+            temperature,
+            speed: self.fan_speed,
+            target,
+            power_limit,
+        };
+        self.status_sender.send_if_modified(|current| {
+            let modified = *current != status;
+            *current = status;
+            modified
+        });
+    }
+
+    /// Read temperature and fan speed from the hardware
+    /// while the fans aren't controlled by tailord.
+    async fn refresh_status(&mut self) {
+        let temp = self.update_temp().await;
+        match retry(|| self.io.get_fan_speed_percent(self.fan_idx)).await {
+            Ok(speed) => self.fan_speed = speed,
+            Err(err) => {
+                tracing::error!("Failed reading the current fan speed: `{err}`");
+                self.errors += 1;
+            }
+        }
+        self.publish_status(temp, None, 0);
+    }
+
+    /// Hand the fans back to the firmware.
+    fn release_fans(&mut self) {
+        tracing::info!(
+            "Fan {}: Handing fan control back to the firmware",
+            self.fan_idx
+        );
+        if let Err(err) = self.io.set_fans_auto() {
+            tracing::error!("Failed to set fans to automatic mode: `{err}`");
+        }
+        self.power_limits.reset();
+    }
+
+    /// Whether the subsystem and the active profile allow power limits.
+    fn power_limits_enabled(&self) -> bool {
+        self.subsystems.borrow().power_limit_control && profiles::power_limits_allowed()
+    }
+
+    /// Reset the cooling devices if power limits were disabled.
+    fn check_power_limits(&mut self) {
+        if !self.power_limits_enabled() {
+            self.power_limits.reset();
+        }
+    }
+
+    /// Take over fan control from the firmware again.
+    fn resume_fan_control(&mut self) {
+        tracing::info!("Fan {}: Resuming fan control", self.fan_idx);
+        // The firmware changed the speed in the meantime.
+        match self.io.get_fan_speed_percent(self.fan_idx) {
+            Ok(speed) => self.fan_speed = speed,
+            Err(err) => tracing::error!("Failed reading the current fan speed: `{err}`"),
+        }
+    }
+
+    /// Log everything that influences the fan speed.
+    fn dump_state(&self, fan_control: bool, pinned: Option<u8>) {
+        tracing::info!(
+            fan_control,
+            pinned,
+            sensor_failed = self.sensor_failed,
+            errors = self.errors,
+            failsafe = self.failsafe_until.is_some(),
+            status = ?*self.status_sender.borrow(),
+            temperature_history = ?self.temp_history.oldest_first(),
+            profile = ?self.profile,
+            "Fan {}: State dump",
+            self.fan_idx
+        );
+    }
+
+    /// Write the pinned speed once. Returns `false` if the pin has to end,
+    /// because writing failed or the temperature reached a trip point.
+    /// Pins at full speed are kept, as they can only help.
+    async fn pin_step(&mut self, speed: u8) -> bool {
+        let temp = self.update_temp().await;
+        let trip_points = thermal::trip_points();
+        let max_temp = trip_points
+            .passive
+            .or(trip_points.critical)
+            .unwrap_or(PIN_MAX_TEMP);
+        if speed < 100 && temp >= max_temp {
+            tracing::warn!(
+                "Fan {}: Ending pinned speed of {speed}%, the temperature reached {temp}°C",
+                self.fan_idx
+            );
+            return false;
+        }
+        if let Err(err) = retry(|| self.io.set_fan_speed_percent(self.fan_idx, speed)).await {
+            tracing::error!("Failed to pin fan speed: `{err}`");
+            metrics::count_fan_error(self.fan_idx);
+            self.errors += 1;
+            return false;
+        }
+        self.fan_speed = speed;
+        // Pins ignore the power limits of the profile.
+        self.power_limits.reset();
+        self.publish_status(temp, Some(speed), 0);
+        true
+    }
+
+    /// Continue after a pin with the profile or the firmware.
+    fn end_pin(&mut self, fan_control: bool) {
+        self.power_limits.reset();
+        if fan_control {
+            self.resume_fan_control();
+        } else {
+            self.release_fans();
+        }
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
-    fn set_speed(&mut self, new_speed: u8) {
+    async fn set_speed(&mut self, new_speed: u8) {
         if self.fan_speed != new_speed {
-            self.fan_speed = new_speed;
-            if let Err(err) = self.io.set_fan_speed_percent(self.fan_idx, new_speed) {
-                tracing::error!("Failed setting new fan speed: `{err}`");
+            match retry(|| self.io.set_fan_speed_percent(self.fan_idx, new_speed)).await {
+                // Only remember speeds that were set, so failed writes are repeated.
+                Ok(()) => self.fan_speed = new_speed,
+                Err(err) => {
+                    tracing::error!("Failed setting new fan speed: `{err}`");
+                    metrics::count_fan_error(self.fan_idx);
+                    self.errors += 1;
+                }
             }
         }
     }
+
+    /// Hand the fan to the firmware for a while if too many
+    /// fan control steps in a row had errors.
+    fn check_failsafe(&mut self, failed: bool) {
+        if !failed {
+            self.failed_steps = 0;
+            return;
+        }
+        self.failed_steps += 1;
+        if self.failed_steps >= settings::fan_control().failsafe_after_errors {
+            tracing::error!(
+                "Fan {}: {} fan control steps in a row failed, handing the fan \
+                to the firmware for {FAILSAFE_DURATION:?}",
+                self.fan_idx,
+                self.failed_steps
+            );
+            self.failed_steps = 0;
+            let until = Instant::now() + FAILSAFE_DURATION;
+            self.failsafe_until = Some(until);
+            self.release_fans();
+            FAILSAFE_CHANNEL.send((self.fan_idx, until)).ok();
+        }
+    }
+
+    /// Leave the fan to the firmware as well while another fan is in the failsafe,
+    /// setting its speed would take the released fans over again.
+    fn follow_failsafe(&mut self, fan_idx: u8, until: Instant) {
+        if self.failsafe_until.is_some_and(|own| own >= until) {
+            return;
+        }
+        tracing::warn!(
+            "Fan {}: Leaving the fan to the firmware while fan {fan_idx} is in the failsafe",
+            self.fan_idx
+        );
+        self.failed_steps = 0;
+        self.failsafe_until = Some(until);
+        self.power_limits.reset();
+    }
 }
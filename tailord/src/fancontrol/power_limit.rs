@@ -0,0 +1,103 @@
+//! Power limits of fan profiles, enforced by thermal cooling devices like
+//! intel_powerclamp that inject idle time or throttle the CPU.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use tuxedo_ioctl::hal::attribute;
+
+use crate::settings::CoolingDevice;
+
+impl CoolingDevice {
+    /// The state for a power limit in percent.
+    pub fn state(&self, power_limit: u8) -> u32 {
+        let power_limit = u64::from(power_limit.min(100));
+        ((power_limit * u64::from(self.max_state) + 50) / 100) as u32
+    }
+}
+
+/// The states that were written to the cooling devices last.
+#[derive(Debug, Default)]
+pub(super) struct PowerLimits {
+    states: BTreeMap<PathBuf, u32>,
+}
+
+impl PowerLimits {
+    /// Write the power limit to all `devices` whose state changed.
+    /// Devices that were used before, but aren't part of `devices` anymore,
+    /// are reset once and left alone afterwards.
+    pub(super) fn apply(&mut self, devices: &[CoolingDevice], power_limit: u8) {
+        self.states.retain(|path, state| {
+            let used = devices.iter().any(|device| &device.path == path);
+            if !used && *state != 0 {
+                if let Err(err) = attribute::write(path, "0") {
+                    tracing::error!("Failed resetting the power limit: `{err}`");
+                }
+            }
+            used
+        });
+
+        for device in devices {
+            let state = device.state(power_limit);
+            if self.states.get(&device.path) != Some(&state) {
+                if let Err(err) = attribute::write(&device.path, &state.to_string()) {
+                    tracing::error!(
+                        "Failed setting new power limit of `{}`: `{err}`",
+                        device.path.display()
+                    );
+                }
+                self.states.insert(device.path.clone(), state);
+            }
+        }
+    }
+
+    /// Reset all devices that were limited, e.g. when the fan is handed to the firmware.
+    pub(super) fn reset(&mut self) {
+        self.apply(&[], 0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::PowerLimits;
+    use crate::settings::CoolingDevice;
+
+    #[test]
+    fn apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let devices = [
+            CoolingDevice {
+                path: root.join("powerclamp"),
+                max_state: 100,
+            },
+            CoolingDevice {
+                path: root.join("processor"),
+                max_state: 3,
+            },
+        ];
+        let [powerclamp, processor] = &devices;
+        let read = |device: &CoolingDevice| fs::read_to_string(&device.path).unwrap();
+
+        let mut power_limits = PowerLimits::default();
+        power_limits.apply(&devices, 40);
+        assert_eq!(read(powerclamp), "40");
+        assert_eq!(read(processor), "1");
+
+        power_limits.apply(&devices, 100);
+        assert_eq!(read(powerclamp), "100");
+        assert_eq!(read(processor), "3");
+
+        // The processor is reset once after it was removed from the settings.
+        power_limits.apply(&devices[..1], 20);
+        assert_eq!(read(powerclamp), "20");
+        assert_eq!(read(processor), "0");
+        fs::write(&processor.path, "2").unwrap();
+        power_limits.apply(&devices[..1], 30);
+        assert_eq!(read(processor), "2");
+
+        power_limits.reset();
+        assert_eq!(read(powerclamp), "0");
+    }
+}
@@ -1,7 +1,11 @@
 use std::path::Path;
 
-use tailor_api::FanProfilePoint;
-use zbus::fdo;
+use tailor_api::{FanPrediction, FanProfilePoint, FanSmoothing};
+
+use crate::{
+    error::{self, TailorError},
+    thermal,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 #[serde(transparent)]
@@ -10,15 +14,37 @@ pub struct FanProfile {
 }
 
 impl FanProfile {
-    pub fn load_config(file_name: impl AsRef<Path>) -> fdo::Result<Self> {
+    pub fn load_config(file_name: impl AsRef<Path>) -> error::Result<Self> {
         let file_name = file_name.as_ref();
-        let content =
-            std::fs::read(file_name).map_err(|err| fdo::Error::IOError(err.to_string()))?;
-        let mut inner: tailor_api::FanProfile = serde_json::from_slice(&content)
-            .map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))?;
+        let content = std::fs::read(file_name)
+            .map_err(|err| TailorError::from_profile_io(err, &file_name.to_string_lossy()))?;
+        let profile = Self::parse(&content, file_name)?;
+        profile.check_trip_points(file_name);
+        Ok(profile)
+    }
+
+    /// Warn if the CPU is throttled before the fans run at full speed.
+    fn check_trip_points(&self, file_name: &Path) {
+        if let Some(passive) = thermal::trip_points().passive {
+            let speed = self.calc_target_fan_speed(passive);
+            if speed < 100 {
+                tracing::warn!(
+                    "Fan speed is only {speed}% at the passive trip point at {passive}°C, \
+                    the CPU is throttled before the fans run at full speed: `{file_name:?}`"
+                );
+            }
+        }
+    }
+
+    /// Parse the content of a fan profile and fix what can be fixed.
+    fn parse(content: &[u8], file_name: &Path) -> error::Result<Self> {
+        let mut inner: tailor_api::FanProfile = serde_json::from_slice(content)
+            .map_err(|err| TailorError::InvalidFileContent(err.to_string()))?;
 
         if inner.points.is_empty() {
-            return Err(fdo::Error::FileNotFound("Empty configuration".to_string()));
+            return Err(TailorError::InvalidCurve(format!(
+                "Fan profile has no points: `{file_name:?}`"
+            )));
         }
 
         // Make sure the temperature is increasing with each point.
@@ -91,74 +117,61 @@ impl FanProfile {
             })
         }
 
+        if let Some(smoothing) = &mut inner.smoothing {
+            if !(1..=100).contains(&smoothing.alpha) {
+                tracing::warn!(
+                    "Smoothing alpha must be between 1% and 100%, using {}%: `{file_name:?}`",
+                    smoothing.alpha.clamp(1, 100)
+                );
+                smoothing.alpha = smoothing.alpha.clamp(1, 100);
+            }
+        }
+
+        Ok(Self { inner })
+    }
+
+    /// Use a curve that wasn't loaded from a file, e.g. for previews.
+    /// Unlike [`Self::load_config`], invalid curves are rejected instead of fixed,
+    /// but the fan still reaches full speed at 100°C.
+    pub fn from_curve(mut inner: tailor_api::FanProfile) -> error::Result<Self> {
+        inner
+            .validate()
+            .map_err(|err| TailorError::InvalidCurve(err.to_string()))?;
+        if inner
+            .points
+            .last()
+            .is_some_and(|point| point.fan < 100 && point.temp < 100)
+        {
+            inner.points.push(FanProfilePoint {
+                temp: 100,
+                fan: 100,
+                power_limit: 0,
+            });
+        }
         Ok(Self { inner })
     }
 
     // Use the temp profile in the configuration to calculate the
     // corresponding fan speed.
     pub fn calc_target_fan_speed(&self, current_temp: u8) -> u8 {
-        let points = &self.inner.points;
-
-        // Find the first item that has a greater or equal temperature.
-        let position = points.iter().position(|p| p.temp >= current_temp);
-
-        if let Some(position) = position {
-            let profile_point = &points[position];
-
-            // If the profile point fits exact or it's the first element,
-            // directly use its temperature.
-            if profile_point.temp == current_temp || position == 0 {
-                profile_point.fan
-            } else {
-                let prev_point = &points[position - 1];
-
-                // Interpolate with a linear slope between those two points.
-                // Use u16 to make sure the multiplication doesn't overflow.
-                let temp_diff = (profile_point.temp - prev_point.temp) as u16;
-                let curr_temp_diff = (current_temp - prev_point.temp) as u16;
-                let fan_diff = (profile_point.fan - prev_point.fan) as u16;
-
-                prev_point.fan + (fan_diff * curr_temp_diff / temp_diff) as u8
-            }
-        } else {
-            // The temperature is higher than anything in the list.
-            100
-        }
+        self.inner.fan_speed_at(current_temp)
     }
 
     pub fn calc_target_power_limit(&self, current_temp: u8) -> u8 {
-        let points = &self.inner.points;
-
-        // Find the first item that has a greater or equal temperature.
-        let position = points.iter().position(|p| p.temp >= current_temp);
-
-        if let Some(position) = position {
-            let profile_point = &points[position];
-
-            // If the profile point fits exact or it's the first element,
-            // directly use its temperature.
-            if profile_point.temp == current_temp || position == 0 {
-                profile_point.power_limit
-            } else {
-                let prev_point = &points[position - 1];
-
-                // Interpolate with a linear slope between those two points.
-                // Use u16 to make sure the multiplication doesn't overflow.
-                let temp_diff = (profile_point.temp - prev_point.temp) as u16;
-                let curr_temp_diff = (current_temp - prev_point.temp) as u16;
-                let diff = (profile_point.power_limit - prev_point.power_limit) as u16;
-
-                prev_point.power_limit + (diff * curr_temp_diff / temp_diff) as u8
-            }
-        } else {
-            // The temperature is higher than anything in the list.
-            points.last().unwrap().power_limit
-        }
+        self.inner.power_limit_at(current_temp)
     }
 
     pub fn is_sticky(&self) -> bool {
         self.inner.sticky
     }
+
+    pub fn prediction(&self) -> Option<FanPrediction> {
+        self.inner.prediction
+    }
+
+    pub fn smoothing(&self) -> Option<FanSmoothing> {
+        self.inner.smoothing
+    }
 }
 
 impl Default for FanProfile {
@@ -208,7 +221,46 @@ impl Default for FanProfile {
                     },
                 ],
                 sticky: false,
+                prediction: None,
+                smoothing: None,
             },
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    use super::FanProfile;
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(content in proptest::collection::vec(any::<u8>(), 0..256)) {
+            let _ = FanProfile::parse(&content, Path::new("fuzz"));
+        }
+
+        #[test]
+        fn fan_speed_in_range(
+            points in proptest::collection::vec((any::<u8>(), any::<u8>(), any::<u8>()), 0..16),
+            sticky in any::<bool>(),
+        ) {
+            let points: Vec<_> = points
+                .into_iter()
+                .map(|(temp, fan, power_limit)| {
+                    json!({ "temp": temp, "fan": fan, "power_limit": power_limit })
+                })
+                .collect();
+            let content = json!({ "points": points, "sticky": sticky }).to_string();
+
+            if let Ok(profile) = FanProfile::parse(content.as_bytes(), Path::new("fuzz")) {
+                for temp in 0..=u8::MAX {
+                    prop_assert!(profile.calc_target_fan_speed(temp) <= 100);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Retry hardware access, because the EC doesn't always answer in time.
+
+use std::time::Duration;
+
+use tuxedo_ioctl::hal::IoctlResult;
+
+/// Attempts of an operation, including the first one.
+const ATTEMPTS: u32 = 3;
+/// Delay before the first retry, doubled for every further retry.
+const BACKOFF: Duration = Duration::from_millis(10);
+
+/// Run `op` until it succeeds or all attempts failed.
+pub(super) async fn retry<T>(mut op: impl FnMut() -> IoctlResult<T>) -> IoctlResult<T> {
+    let mut delay = BACKOFF;
+    for attempt in 1.. {
+        match op() {
+            Err(err) if attempt < ATTEMPTS => {
+                tracing::debug!("Attempt {attempt} failed, retrying in {delay:?}: `{err}`");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+    unreachable!()
+}
@@ -1,56 +1,60 @@
-use crate::suspend::process_suspend;
+use super::{buffer::TemperatureBuffer, FanRuntimeData, THERMAL_TRIP_CHANNEL};
 
-use super::{buffer::TemperatureBuffer, FanRuntimeData};
-
-use std::path::Path;
 use std::time::Duration;
-use tokio::io;
-use tokio_uring::fs;
-
-async fn rw_file<P>(path: P) -> Result<fs::File, io::Error>
-where
-    P: AsRef<Path>,
-{
-    fs::OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(path)
-        .await
-}
+use tokio::time::Instant;
 
-async fn write_buffer<V>(file: &mut fs::File, value: V) -> Result<(), io::Error>
-where
-    V: tokio_uring::buf::IoBuf,
-{
-    file.write_at(value, 0).submit().await.0?;
-    Ok(())
-}
-
-async fn write_string(file: &mut fs::File, string: String) -> Result<(), io::Error> {
-    write_buffer(file, string.into_bytes()).await
-}
-async fn write_int(file: &mut fs::File, int: u32) -> Result<(), io::Error> {
-    write_string(file, format!("{}", int)).await
-}
+use crate::{
+    settings::{self, FanControlSettings},
+    thermal,
+};
 
 impl FanRuntimeData {
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn fan_control_loop(&mut self) {
-        let mut powerclamp_file = rw_file("/sys/class/thermal/cooling_device20/cur_state")
-            .await
-            .unwrap();
-        let mut previous_powerclamp: Option<u8> = None;
-
         loop {
+            let fan_control = settings::fan_control();
+            let FanControlSettings {
+                interval_ms,
+                firmware_status_interval_ms,
+                power_limit_max,
+                ..
+            } = fan_control;
+
+            if let Some(until) = self.failsafe_until {
+                if Instant::now() < until {
+                    self.refresh_status().await;
+                    tokio::time::sleep(Duration::from_millis(firmware_status_interval_ms)).await;
+                    continue;
+                }
+                tracing::info!("Fan {}: Trying to control the fan again", self.fan_idx);
+                self.failsafe_until = None;
+                self.resume_fan_control();
+            }
+            let errors = self.errors;
+
             // Add the current temperature to history
-            let act_current_temp = self.update_temp();
-            let current_temp = if self.profile.is_sticky() {
+            let act_current_temp = self.update_temp().await;
+            let current_temp = if let Some(smoothing) = self.profile.smoothing() {
+                self.temp_history.moving_average(smoothing.alpha)
+            } else if self.profile.is_sticky() {
                 *self.temp_history.temp_history.iter().min().unwrap()
             } else {
                 act_current_temp
             };
 
-            let target_fan_speed = self.profile.calc_target_fan_speed(current_temp);
+            // Get ahead of thermal spikes while the temperature rises quickly.
+            let boost = self.profile.prediction().map_or(0, |prediction| {
+                let (rise, duration) = self.temp_history.rise(Duration::from_millis(interval_ms));
+                prediction.boost(rise, duration)
+            });
+            let target_fan_speed = if self.is_throttled(act_current_temp) {
+                100
+            } else {
+                self.profile
+                    .calc_target_fan_speed(current_temp)
+                    .saturating_add(boost)
+                    .min(100)
+            };
             let fan_diff = self.fan_speed.abs_diff(target_fan_speed);
 
             // Make small steps to decrease or increase fan speed.
@@ -63,43 +67,82 @@ impl FanRuntimeData {
             } else if target_fan_speed < self.fan_speed {
                 fan_increment = fan_increment.min(3);
             }
+            // Never step past the target, the fan would oscillate around it otherwise.
+            fan_increment = fan_increment.min(fan_diff);
 
             // Update fan speed
             self.set_speed(if target_fan_speed > self.fan_speed {
                 self.fan_speed.saturating_add(fan_increment).min(100)
             } else {
                 self.fan_speed.saturating_sub(fan_increment)
-            });
-
-            // update intel_powerclamp
-            let target_power_limit = self.profile.calc_target_power_limit(act_current_temp);
-            if previous_powerclamp.map_or(true, |prev| prev != target_power_limit) {
-                if let Err(err) = write_int(&mut powerclamp_file, target_power_limit as u32).await {
-                    tracing::error!("Failed setting new power limit: `{err}`");
-                }
-                previous_powerclamp = Some(target_power_limit);
-            }
+            })
+            .await;
+            self.check_failsafe(self.errors != errors);
+
+            // Devices that were used before are reset after power-limit control
+            // was disabled or the devices were changed.
+            let cooling_devices = if self.power_limits_enabled() {
+                fan_control.cooling_devices()
+            } else {
+                Vec::new()
+            };
+            let target_power_limit = if cooling_devices.is_empty() {
+                0
+            } else {
+                self.profile
+                    .calc_target_power_limit(act_current_temp)
+                    .min(power_limit_max)
+            };
+            self.power_limits
+                .apply(&cooling_devices, target_power_limit);
+            self.publish_status(act_current_temp, Some(target_fan_speed), target_power_limit);
 
             //let delay = suitable_delay(&self.temp_history, fan_diff);
-            let delay = Duration::from_millis(100);
+            let delay = Duration::from_millis(interval_ms);
 
             tracing::debug!(
-                "Fan {}: Current temperature is {act_current_temp}°C, pretending it is {current_temp}°C, fan speed: {}%, target fan speed: {target_fan_speed} \
-                fan diff: {fan_diff}, fan increment {fan_increment}, target power_limit: {target_power_limit}, delay: {delay:?}, sticky: {:?}", self.fan_idx, self.fan_speed, self.profile.is_sticky(),
+                temperature = act_current_temp,
+                effective_temperature = current_temp,
+                speed = self.fan_speed,
+                target_speed = target_fan_speed,
+                boost,
+                fan_diff,
+                fan_increment,
+                power_limit = target_power_limit,
+                ?delay,
+                sticky = self.profile.is_sticky(),
+                smoothing = ?self.profile.smoothing(),
+                "Updated the fan speed",
             );
 
-            tokio::select! {
-                _ = tokio::time::sleep(delay) => {},
-                _ = process_suspend(&mut self.suspend_receiver) => {
-                    self.set_speed(if self.fan_speed == 100 {
-                        self.fan_speed - 1
-                    } else {
-                        self.fan_speed + 1
-                    });
-                    tokio::time::sleep(Duration::from_millis(250)).await;
-                }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+impl FanRuntimeData {
+    /// Whether the temperature reached the passive trip point, where the CPU gets
+    /// throttled. The fans run at full speed then, whatever the profile says.
+    fn is_throttled(&mut self, temp: u8) -> bool {
+        let throttled = thermal::trip_points()
+            .passive
+            .is_some_and(|passive| temp >= passive);
+        if throttled != self.throttled {
+            self.throttled = throttled;
+            if throttled {
+                tracing::warn!(
+                    "Fan {}: Reached the passive trip point at {temp}°C, running at full speed",
+                    self.fan_idx
+                );
+                THERMAL_TRIP_CHANNEL.send((self.fan_idx, temp)).ok();
+            } else {
+                tracing::info!(
+                    "Fan {}: Cooled down below the passive trip point",
+                    self.fan_idx
+                );
             }
         }
+        throttled
     }
 }
 
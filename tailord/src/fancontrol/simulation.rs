@@ -0,0 +1,439 @@
+//! Runs the fan control loop against temperature traces on a virtual clock,
+//! so changes of the control algorithm can be compared by their overshoot,
+//! settling time and number of writes instead of by ear.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tailor_api::{FanPrediction, FanSmoothing, FanStatus, SubsystemStates};
+use tokio::{sync::watch, time::Instant};
+use tuxedo_ioctl::hal::{traits::HardwareDevice, IoctlResult};
+
+use crate::settings::{self, CoolingDevice};
+
+use super::{
+    buffer::TemperatureBuffer, power_limit::PowerLimits, profile::FanProfile, FanRuntimeData,
+};
+
+/// Speeds within this many percent of the target count as settled,
+/// the fan control loop ignores smaller differences on purpose.
+const SETTLED_TOLERANCE: u8 = 3;
+
+/// Temperatures over time, interpolated linearly between keyframes.
+/// Keyframes can be synthetic or taken from a recording,
+/// e.g. the temperature history of a state dump.
+#[derive(Debug, Clone)]
+struct Trace {
+    keyframes: Vec<(Duration, u8)>,
+}
+
+impl Trace {
+    fn new(keyframes: &[(u64, u8)]) -> Self {
+        Self {
+            keyframes: keyframes
+                .iter()
+                .map(|&(secs, temp)| (Duration::from_secs(secs), temp))
+                .collect(),
+        }
+    }
+
+    /// A desktop that barely changes temperature.
+    fn idle() -> Self {
+        Self::new(&[(0, 45), (20, 46), (40, 45), (60, 45)])
+    }
+
+    /// A game that heats up the CPU quickly and exits after half a minute.
+    fn gaming_spike() -> Self {
+        Self::new(&[(0, 45), (10, 45), (15, 85), (40, 85), (50, 50), (90, 50)])
+    }
+
+    /// A render job that keeps the CPU close to its limit.
+    fn sustained_render() -> Self {
+        Self::new(&[(0, 50), (30, 92), (150, 92)])
+    }
+
+    /// A sensor that jumps by several degrees every second under constant load.
+    fn noisy() -> Self {
+        let keyframes: Vec<(u64, u8)> = (0..=60)
+            .map(|secs| (secs, if secs % 2 == 0 { 60 } else { 66 }))
+            .collect();
+        Self::new(&keyframes)
+    }
+
+    fn duration(&self) -> Duration {
+        self.keyframes.last().map(|(at, _)| *at).unwrap_or_default()
+    }
+
+    fn temperature_at(&self, at: Duration) -> u8 {
+        let next = self.keyframes.iter().position(|(time, _)| *time > at);
+        match next {
+            Some(0) => self.keyframes[0].1,
+            Some(next) => {
+                let (start, from) = self.keyframes[next - 1];
+                let (end, to) = self.keyframes[next];
+                let progress = (at - start).as_secs_f64() / (end - start).as_secs_f64();
+                (f64::from(from) + (f64::from(to) - f64::from(from)) * progress).round() as u8
+            }
+            None => self
+                .keyframes
+                .last()
+                .map(|(_, temp)| *temp)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// State of the simulated fan at one fan control step.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Duration,
+    temperature: u8,
+    speed: u8,
+}
+
+/// A fan whose temperature follows a trace and that records what tailord does.
+#[derive(Debug)]
+struct SimulatedFan {
+    trace: Trace,
+    start: Instant,
+    speed: Mutex<u8>,
+    writes: Mutex<usize>,
+    samples: Mutex<Vec<Sample>>,
+}
+
+impl SimulatedFan {
+    fn new(trace: Trace) -> Self {
+        Self {
+            trace,
+            start: Instant::now(),
+            speed: Mutex::new(0),
+            writes: Mutex::new(0),
+            samples: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl HardwareDevice for SimulatedFan {
+    fn device_interface_id_str(&self) -> IoctlResult<String> {
+        Ok("simulation".to_owned())
+    }
+
+    fn device_model_id_str(&self) -> IoctlResult<String> {
+        Ok("simulation".to_owned())
+    }
+
+    fn set_enable_mode_set(&self, _enabled: bool) -> IoctlResult<()> {
+        Ok(())
+    }
+
+    fn get_number_fans(&self) -> u8 {
+        1
+    }
+
+    fn set_fans_auto(&self) -> IoctlResult<()> {
+        Ok(())
+    }
+
+    fn set_fan_speed_percent(&self, _fan: u8, fan_speed_percent: u8) -> IoctlResult<()> {
+        *self.speed.lock().unwrap() = fan_speed_percent.min(100);
+        *self.writes.lock().unwrap() += 1;
+        Ok(())
+    }
+
+    fn get_fan_speed_percent(&self, _fan: u8) -> IoctlResult<u8> {
+        Ok(*self.speed.lock().unwrap())
+    }
+
+    /// Every fan control step reads the temperature once.
+    fn get_fan_temperature(&self, _fan: u8) -> IoctlResult<u8> {
+        let at = self.start.elapsed();
+        let temperature = self.trace.temperature_at(at);
+        self.samples.lock().unwrap().push(Sample {
+            at,
+            temperature,
+            speed: *self.speed.lock().unwrap(),
+        });
+        Ok(temperature)
+    }
+
+    fn get_fans_min_speed(&self) -> IoctlResult<u8> {
+        Ok(0)
+    }
+
+    fn get_fans_off_available(&self) -> IoctlResult<bool> {
+        Ok(true)
+    }
+
+    fn get_available_odm_performance_profiles(&self) -> IoctlResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    fn set_odm_performance_profile(&self, _performance_profile: &str) -> IoctlResult<()> {
+        Ok(())
+    }
+
+    fn get_default_odm_performance_profile(&self) -> IoctlResult<String> {
+        Ok(String::new())
+    }
+}
+
+/// What the fan control loop did during a trace.
+struct Report {
+    profile: FanProfile,
+    samples: Vec<Sample>,
+    writes: usize,
+}
+
+impl Report {
+    fn target(&self, sample: &Sample) -> u8 {
+        self.profile.calc_target_fan_speed(sample.temperature)
+    }
+
+    /// How far the fan ran faster than the curve asks for at most.
+    fn max_overshoot(&self) -> u8 {
+        self.samples
+            .iter()
+            .map(|sample| sample.speed.saturating_sub(self.target(sample)))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Time from `from` until the speed stays close to the target up to `to`.
+    fn settling_time(&self, from: Duration, to: Duration) -> Option<Duration> {
+        let window: Vec<&Sample> = self
+            .samples
+            .iter()
+            .filter(|sample| sample.at >= from && sample.at <= to)
+            .collect();
+        let unsettled = window
+            .iter()
+            .rposition(|sample| sample.speed.abs_diff(self.target(sample)) > SETTLED_TOLERANCE);
+        match unsettled {
+            None => Some(Duration::ZERO),
+            Some(last) => window.get(last + 1).map(|sample| sample.at - from),
+        }
+    }
+}
+
+impl Report {
+    /// Time from `from` until the fan runs at least at `speed`.
+    fn time_to_reach(&self, from: Duration, speed: u8) -> Option<Duration> {
+        self.samples
+            .iter()
+            .find(|sample| sample.at >= from && sample.speed >= speed)
+            .map(|sample| sample.at - from)
+    }
+}
+
+/// The curve of the default profile, to try other settings with it.
+fn default_curve() -> tailor_api::FanProfile {
+    serde_json::from_value(serde_json::to_value(FanProfile::default()).unwrap()).unwrap()
+}
+
+/// The state of a runtime for `fan`, with power-limit control disabled
+/// to keep the power limits away from the cooling device of the host.
+fn runtime_data(fan: Arc<SimulatedFan>, profile: FanProfile) -> FanRuntimeData {
+    let (_subsystem_sender, subsystems) = watch::channel(SubsystemStates {
+        power_limit_control: false,
+        ..Default::default()
+    });
+    let (status_sender, _status) = watch::channel(FanStatus {
+        index: 0,
+        temperature: 0,
+        speed: 0,
+        target: None,
+        power_limit: 0,
+    });
+    FanRuntimeData {
+        fan_idx: 0,
+        temp_history: TemperatureBuffer::new(0),
+        fan_speed: 0,
+        io: fan,
+        profile,
+        subsystems,
+        status_sender,
+        sensor_failed: false,
+        errors: 0,
+        failed_steps: 0,
+        failsafe_until: None,
+        throttled: false,
+        power_limits: PowerLimits::default(),
+    }
+}
+
+/// Run the fan control loop until the trace ends.
+async fn simulate(trace: Trace, profile: FanProfile) -> Report {
+    let duration = trace.duration();
+    let fan = Arc::new(SimulatedFan::new(trace));
+    let mut data = runtime_data(fan.clone(), profile.clone());
+    data.initialize().unwrap();
+
+    let finished = tokio::time::timeout(duration, data.fan_control_loop()).await;
+    assert!(finished.is_err(), "The fan control loop stopped early");
+
+    let samples = fan.samples.lock().unwrap().clone();
+    let writes = *fan.writes.lock().unwrap();
+    Report {
+        profile,
+        samples,
+        writes,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn idle() {
+    let report = simulate(Trace::idle(), FanProfile::default()).await;
+
+    assert_eq!(report.max_overshoot(), 0);
+    let settled = report.settling_time(Duration::ZERO, Duration::from_secs(5));
+    assert!(
+        settled.is_some_and(|time| time < Duration::from_secs(2)),
+        "{settled:?}"
+    );
+    // The fan shouldn't be adjusted all the time for small changes.
+    assert!(report.writes < 20, "{} writes", report.writes);
+}
+
+#[tokio::test(start_paused = true)]
+async fn gaming_spike() {
+    let report = simulate(Trace::gaming_spike(), FanProfile::default()).await;
+
+    assert!(report.max_overshoot() <= 5, "{}%", report.max_overshoot());
+    let ramp_up = report.settling_time(Duration::from_secs(15), Duration::from_secs(40));
+    assert!(
+        ramp_up.is_some_and(|time| time < Duration::from_secs(5)),
+        "{ramp_up:?}"
+    );
+    let cool_down = report.settling_time(Duration::from_secs(50), Duration::from_secs(90));
+    assert!(
+        cool_down.is_some_and(|time| time < Duration::from_secs(10)),
+        "{cool_down:?}"
+    );
+    assert!(report.writes < 150, "{} writes", report.writes);
+}
+
+#[tokio::test(start_paused = true)]
+async fn sustained_render() {
+    let report = simulate(Trace::sustained_render(), FanProfile::default()).await;
+
+    assert_eq!(report.max_overshoot(), 0);
+    let settled = report.settling_time(Duration::from_secs(30), Duration::from_secs(150));
+    assert!(
+        settled.is_some_and(|time| time < Duration::from_secs(5)),
+        "{settled:?}"
+    );
+    // Once at full speed, nothing is written anymore.
+    assert!(report.writes < 100, "{} writes", report.writes);
+}
+
+#[tokio::test(start_paused = true)]
+async fn prediction() {
+    let mut curve = default_curve();
+    let full_load = FanProfile::default().calc_target_fan_speed(85) - SETTLED_TOLERANCE;
+    let reactive = simulate(Trace::gaming_spike(), FanProfile::default()).await;
+    curve.prediction = Some(FanPrediction {
+        gain: 3,
+        max_boost: 20,
+    });
+    let predictive = simulate(
+        Trace::gaming_spike(),
+        FanProfile::from_curve(curve).unwrap(),
+    )
+    .await;
+
+    let reactive = reactive.time_to_reach(Duration::from_secs(10), full_load);
+    let predictive = predictive.time_to_reach(Duration::from_secs(10), full_load);
+    assert!(
+        predictive.is_some_and(|predictive| reactive.is_some_and(|reactive| predictive < reactive)),
+        "{predictive:?} vs. {reactive:?}"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn smoothing() {
+    let smoothed = |alpha| {
+        let mut curve = default_curve();
+        curve.smoothing = Some(FanSmoothing { alpha });
+        FanProfile::from_curve(curve).unwrap()
+    };
+    let sticky = {
+        let mut curve = default_curve();
+        curve.sticky = true;
+        FanProfile::from_curve(curve).unwrap()
+    };
+
+    // Jitter is ignored instead of being passed on to the fan.
+    let raw = simulate(Trace::noisy(), FanProfile::default()).await;
+    let average = simulate(Trace::noisy(), smoothed(10)).await;
+    assert!(
+        average.writes < raw.writes / 2,
+        "{} vs. {} writes",
+        average.writes,
+        raw.writes
+    );
+
+    // Sustained load is followed sooner than with the minimum of the history.
+    let full_load = FanProfile::default().calc_target_fan_speed(92) - SETTLED_TOLERANCE;
+    let sticky = simulate(Trace::sustained_render(), sticky).await;
+    let average = simulate(Trace::sustained_render(), smoothed(10)).await;
+    let sticky = sticky.time_to_reach(Duration::ZERO, full_load);
+    let average = average.time_to_reach(Duration::ZERO, full_load);
+    assert!(
+        average.is_some_and(|average| sticky.is_some_and(|sticky| average < sticky)),
+        "{average:?} vs. {sticky:?}"
+    );
+}
+
+/// Cooling devices are reset when fan or power-limit control ends,
+/// even though the fan control loop that limited them was interrupted.
+#[tokio::test]
+async fn power_limits_end_with_control() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let device = [CoolingDevice {
+        path: root.join("powerclamp"),
+        max_state: 100,
+    }];
+    let read = || std::fs::read_to_string(&device[0].path).unwrap();
+
+    let fan = Arc::new(SimulatedFan::new(Trace::idle()));
+    let mut data = runtime_data(fan, FanProfile::default());
+    data.initialize().unwrap();
+
+    data.power_limits.apply(&device, 40);
+    assert_eq!(read(), "40");
+    // Power-limit control is disabled in the subsystems of the runtime.
+    data.check_power_limits();
+    assert_eq!(read(), "0");
+
+    data.power_limits.apply(&device, 40);
+    data.release_fans();
+    assert_eq!(read(), "0");
+}
+
+/// The other runtimes leave their fans to the firmware as well,
+/// as it takes over all fans at once.
+#[tokio::test]
+async fn failsafe_is_shared() {
+    let mut failsafe_receiver = super::FAILSAFE_CHANNEL.subscribe();
+    let mut failing = runtime_data(
+        Arc::new(SimulatedFan::new(Trace::idle())),
+        FanProfile::default(),
+    );
+    for _ in 0..settings::fan_control().failsafe_after_errors {
+        failing.check_failsafe(true);
+    }
+    let (fan_idx, until) = failsafe_receiver.try_recv().unwrap();
+    assert_eq!(fan_idx, failing.fan_idx);
+    assert_eq!(failing.failsafe_until, Some(until));
+
+    let mut other = runtime_data(
+        Arc::new(SimulatedFan::new(Trace::idle())),
+        FanProfile::default(),
+    );
+    other.fan_idx = 1;
+    other.follow_failsafe(fan_idx, until);
+    assert_eq!(other.failsafe_until, Some(until));
+}
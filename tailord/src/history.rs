@@ -0,0 +1,94 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use once_cell::sync::Lazy;
+use tailor_api::{ProfileSwitch, ProfileTrigger};
+use tokio::sync::broadcast;
+
+/// Number of profile switches that are kept in memory.
+const MAX_ENTRIES: usize = 100;
+
+static HISTORY: Lazy<Mutex<History>> = Lazy::new(|| Mutex::new(History::new(MAX_ENTRIES)));
+
+static SWITCH_CHANNEL: Lazy<broadcast::Sender<ProfileSwitch>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// Subscribe to all profile switches recorded from now on.
+pub fn get_switch_receiver() -> broadcast::Receiver<ProfileSwitch> {
+    SWITCH_CHANNEL.subscribe()
+}
+
+/// Record the activation of a profile.
+pub fn record(profile: &str, trigger: ProfileTrigger) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default();
+    tracing::info!(
+        profile,
+        %trigger,
+        "Activated profile `{profile}` (trigger: {trigger})"
+    );
+    crate::metrics::count_profile_switch(trigger);
+
+    let entry = ProfileSwitch {
+        timestamp,
+        profile: profile.to_owned(),
+        trigger,
+    };
+    HISTORY.lock().unwrap().push(entry.clone());
+    // Sending only fails if nobody is listening.
+    SWITCH_CHANNEL.send(entry).ok();
+}
+
+/// All recorded profile switches, oldest first.
+pub fn entries() -> Vec<ProfileSwitch> {
+    HISTORY.lock().unwrap().entries.iter().cloned().collect()
+}
+
+/// Ring buffer that drops the oldest entries once it's full.
+struct History {
+    entries: VecDeque<ProfileSwitch>,
+    capacity: usize,
+}
+
+impl History {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, entry: ProfileSwitch) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{ProfileSwitch, ProfileTrigger};
+
+    use super::History;
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = History::new(3);
+        for timestamp in 0..5 {
+            history.push(ProfileSwitch {
+                timestamp,
+                profile: "default".to_owned(),
+                trigger: ProfileTrigger::User,
+            });
+        }
+
+        let timestamps: Vec<u64> = history.entries.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, [2, 3, 4]);
+    }
+}
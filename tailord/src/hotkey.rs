@@ -0,0 +1,224 @@
+//! Boost the fans with the dedicated fan key that some models have.
+//!
+//! The key is read from the input device of the keyboard, so it works
+//! without a desktop session and whatever the desktop maps it to.
+
+use std::{
+    fs, mem,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use tokio::{io::AsyncReadExt, time::Instant};
+
+use crate::{
+    dbus::FanInterface,
+    settings::{BoostKeyMode, BoostKeySettings},
+};
+
+const INPUT_PATH: &str = "/sys/class/input";
+const DEV_INPUT_PATH: &str = "/dev/input";
+const EV_KEY: u16 = 0x01;
+/// Key events are sent for releases (0), presses (1) and auto-repeats (2).
+const KEY_PRESSED: i32 = 1;
+
+/// Listen for the fan boost key if it's enabled.
+///
+/// The input device is opened right away, so it can be read
+/// even after root privileges were dropped.
+pub fn spawn(settings: &BoostKeySettings, fans: FanInterface) {
+    if !settings.enabled {
+        return;
+    }
+    let Some(path) = find_device(Path::new(INPUT_PATH), &settings.device) else {
+        tracing::warn!(
+            "Input device `{}` for the fan boost key wasn't found",
+            settings.device
+        );
+        return;
+    };
+    let file = match fs::File::open(&path) {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::error!("Failed to open `{}`: `{err}`", path.display());
+            return;
+        }
+    };
+    tracing::info!(
+        "Listening for the fan boost key {} on `{}`",
+        settings.key_code,
+        path.display()
+    );
+    tokio::task::spawn_local(listen(
+        tokio::fs::File::from_std(file),
+        settings.clone(),
+        fans,
+    ));
+}
+
+/// The event device of the input device called `name`.
+fn find_device(root: &Path, name: &str) -> Option<PathBuf> {
+    fs::read_dir(root)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name())
+        .filter(|file_name| file_name.to_string_lossy().starts_with("event"))
+        .find(|file_name| {
+            fs::read_to_string(root.join(file_name).join("device/name"))
+                .is_ok_and(|device| device.trim() == name)
+        })
+        .map(|file_name| Path::new(DEV_INPUT_PATH).join(file_name))
+}
+
+async fn listen(mut file: tokio::fs::File, settings: BoostKeySettings, fans: FanInterface) {
+    let mut key = BoostKey::new(&settings);
+    let mut event = vec![0; mem::size_of::<nix::libc::input_event>()];
+
+    loop {
+        if let Err(err) = file.read_exact(&mut event).await {
+            tracing::error!("Stopped listening for the fan boost key: `{err}`");
+            return;
+        }
+        if decode(&event) != (EV_KEY, settings.key_code, KEY_PRESSED) {
+            continue;
+        }
+        let now = Instant::now();
+        let action = key.press(now);
+        let result = match action {
+            Action::Boost => {
+                tracing::info!("Fan boost key pressed, boosting for {}s", settings.seconds);
+                fans.boost_fans_by_key(settings.seconds).await
+            }
+            Action::Release => {
+                tracing::info!("Fan boost key pressed, ending the boost");
+                fans.release_key_boost().await
+            }
+        };
+        match result {
+            Ok(()) => key.done(action, now),
+            Err(err) => tracing::error!("Failed to boost the fans: `{err:?}`"),
+        }
+    }
+}
+
+/// Type, code and value of a `struct input_event`, which follow the timestamp.
+fn decode(event: &[u8]) -> (u16, u16, i32) {
+    let tail = &event[event.len() - 8..];
+    (
+        u16::from_ne_bytes([tail[0], tail[1]]),
+        u16::from_ne_bytes([tail[2], tail[3]]),
+        i32::from_ne_bytes([tail[4], tail[5], tail[6], tail[7]]),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Boost,
+    Release,
+}
+
+/// Tracks whether the last press still boosts the fans.
+struct BoostKey {
+    mode: BoostKeyMode,
+    duration: Duration,
+    boosted_until: Option<Instant>,
+}
+
+impl BoostKey {
+    fn new(settings: &BoostKeySettings) -> Self {
+        Self {
+            mode: settings.mode,
+            duration: Duration::from_secs(settings.seconds.into()),
+            boosted_until: None,
+        }
+    }
+
+    /// In toggle mode, a press during a boost ends it.
+    /// Otherwise, every press starts the boost over.
+    fn press(&self, now: Instant) -> Action {
+        let boosted = self.boosted_until.is_some_and(|until| now < until);
+        if boosted && self.mode == BoostKeyMode::Toggle {
+            Action::Release
+        } else {
+            Action::Boost
+        }
+    }
+
+    /// Remember the boost once the fans accepted it.
+    fn done(&mut self, action: Action, now: Instant) {
+        self.boosted_until = match action {
+            Action::Boost => Some(now + self.duration),
+            Action::Release => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::Path, time::Duration};
+
+    use tokio::time::Instant;
+
+    use super::{decode, find_device, Action, BoostKey};
+    use crate::settings::{BoostKeyMode, BoostKeySettings};
+
+    #[test]
+    fn find_and_decode() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (event, name) in [
+            ("event3", "AT Translated Set 2 keyboard"),
+            ("event7", "TUXEDO Keyboard"),
+        ] {
+            fs::create_dir_all(root.join(event).join("device")).unwrap();
+            fs::write(root.join(event).join("device/name"), format!("{name}\n")).unwrap();
+        }
+
+        assert_eq!(
+            find_device(root, "TUXEDO Keyboard").as_deref(),
+            Some(Path::new("/dev/input/event7"))
+        );
+        assert_eq!(find_device(root, "Missing"), None);
+
+        let mut event = vec![0; 16];
+        event.extend_from_slice(&1u16.to_ne_bytes());
+        event.extend_from_slice(&148u16.to_ne_bytes());
+        event.extend_from_slice(&1i32.to_ne_bytes());
+        assert_eq!(decode(&event), (1, 148, 1));
+    }
+
+    #[test]
+    fn toggle() {
+        let settings = BoostKeySettings {
+            mode: BoostKeyMode::Toggle,
+            seconds: 60,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let mut key = BoostKey::new(&settings);
+        let mut press = |secs| {
+            let now = start + Duration::from_secs(secs);
+            let action = key.press(now);
+            key.done(action, now);
+            action
+        };
+        assert_eq!(press(0), Action::Boost);
+        assert_eq!(press(10), Action::Release);
+        assert_eq!(press(20), Action::Boost);
+        // The boost ended on its own, so the next press starts a new one.
+        assert_eq!(press(90), Action::Boost);
+
+        let mut key = BoostKey::new(&BoostKeySettings {
+            mode: BoostKeyMode::Timed,
+            ..settings.clone()
+        });
+        assert_eq!(key.press(start), Action::Boost);
+        key.done(Action::Boost, start);
+        assert_eq!(key.press(start + Duration::from_secs(10)), Action::Boost);
+
+        // A boost that failed doesn't count, the next press tries again.
+        let key = BoostKey::new(&settings);
+        assert_eq!(key.press(start), Action::Boost);
+        assert_eq!(key.press(start + Duration::from_secs(10)), Action::Boost);
+    }
+}
@@ -0,0 +1,247 @@
+//! Embedded HTTP server for remote monitoring.
+//!
+//! * `GET /api/status` returns the active profile and the status of all fans.
+//! * `GET /api/ws` opens a WebSocket that pushes the status whenever it changes.
+//!   If enabled and a token is configured, clients can switch the active
+//!   profile by sending `{"set_profile": "<name>"}`.
+//! * `GET /metrics` exports temperatures, fan speeds, power limits and
+//!   error counters for Prometheus.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Query, State, WebSocketUpgrade,
+    },
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tailor_api::{metrics::Metrics, FanStatus, ProfileTrigger};
+use tokio::sync::Mutex;
+use tuxedo_ioctl::hal::traits::TdpDevice;
+
+use crate::{
+    dbus::ProfileInterface,
+    fancontrol::{fan_label, FanHandles},
+    metrics, performance, power_source,
+    profiles::Profile,
+    settings::HttpApiSettings,
+};
+
+#[derive(Clone)]
+struct ApiState {
+    fan_handles: FanHandles,
+    tdp: Option<Arc<dyn TdpDevice>>,
+    /// Only set if profile switching is allowed.
+    profiles: Option<Arc<Mutex<ProfileInterface>>>,
+    token: Option<Arc<str>>,
+    update_interval: Duration,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Status {
+    active_profile: String,
+    fans: Vec<FanStatus>,
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Command {
+    set_profile: String,
+}
+
+/// Start the HTTP server configured in `settings`.
+pub fn spawn(
+    settings: &HttpApiSettings,
+    fan_handles: FanHandles,
+    tdp: Option<Arc<dyn TdpDevice>>,
+    profiles: ProfileInterface,
+    update_interval: Duration,
+) {
+    let address: SocketAddr = match settings.address.parse() {
+        Ok(address) => address,
+        Err(err) => {
+            tracing::error!("Invalid HTTP API address `{}`: `{err}`", settings.address);
+            return;
+        }
+    };
+
+    // Any website the user visits could open the WebSocket
+    // and switch profiles if no token were required.
+    let allow_profile_switching = settings.allow_profile_switching && settings.token.is_some();
+    if settings.allow_profile_switching && !allow_profile_switching {
+        tracing::error!("Profile switching over the HTTP API is disabled, it requires a `token`");
+    }
+
+    let state = ApiState {
+        fan_handles,
+        tdp,
+        profiles: allow_profile_switching.then(|| Arc::new(Mutex::new(profiles))),
+        token: settings.token.as_deref().map(Arc::from),
+        update_interval,
+    };
+
+    let app = Router::new()
+        .route("/api/status", get(get_status))
+        .route("/api/ws", get(websocket))
+        .route("/metrics", get(get_metrics))
+        .with_state(state);
+
+    tokio::task::spawn_local(async move {
+        let listener = match tokio::net::TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                tracing::error!("Failed to start HTTP API at `{address}`: `{err}`");
+                return;
+            }
+        };
+        tracing::info!("Serving HTTP API at `{address}`");
+        if let Err(err) = axum::serve(listener, app).await {
+            tracing::error!("HTTP API stopped: `{err}`");
+        }
+    });
+}
+
+async fn current_status(state: &ApiState) -> Status {
+    Status {
+        active_profile: Profile::get_active_profile_name().await.unwrap_or_default(),
+        fans: state
+            .fan_handles
+            .statuses()
+            .iter()
+            .map(|status| *status.borrow())
+            .collect(),
+    }
+}
+
+async fn get_status(State(state): State<ApiState>) -> Json<Status> {
+    Json(current_status(&state).await)
+}
+
+async fn get_metrics(State(state): State<ApiState>) -> impl IntoResponse {
+    let fans = state
+        .fan_handles
+        .statuses()
+        .iter()
+        .map(|status| {
+            let status = *status.borrow();
+            (fan_label(status.index), status)
+        })
+        .collect();
+    let power_limits = match &state.tdp {
+        Some(tdp) => performance::power_limits(tdp.as_ref()).unwrap_or_else(|err| {
+            tracing::warn!("Failed to read the power limits: `{err}`");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let power = *power_source::get_power_receiver().borrow();
+    let metrics = Metrics {
+        fans,
+        active_profile: Profile::get_active_profile_name().await.unwrap_or_default(),
+        power_limits,
+        power_source: power.source,
+        battery: power.battery,
+        counters: Some(metrics::counters()),
+    };
+
+    (
+        [("content-type", "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+}
+
+async fn websocket(
+    State(state): State<ApiState>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    // Browsers can't set headers for WebSockets, so accept the token as query, too.
+    let token = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_owned)
+        .or(query.token);
+    let authenticated = match &state.token {
+        Some(expected) => token.is_some_and(|token| constant_time_eq(&token, expected)),
+        None => true,
+    };
+    if !authenticated {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    upgrade.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(mut socket: WebSocket, state: ApiState) {
+    let mut interval = tokio::time::interval(state.update_interval);
+    let mut last_status = None;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let status = current_status(&state).await;
+                if last_status.as_ref() != Some(&status) {
+                    let message = serde_json::to_string(&status).unwrap();
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        break;
+                    }
+                    last_status = Some(status);
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = handle_command(&state, &text).await;
+                    if socket.send(Message::Text(reply)).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+async fn handle_command(state: &ApiState, text: &str) -> String {
+    let result = match serde_json::from_str::<Command>(text) {
+        Ok(command) => match &state.profiles {
+            Some(profiles) => set_profile(profiles, &command.set_profile).await,
+            None => Err("Profile switching is disabled".to_owned()),
+        },
+        Err(err) => Err(format!("Invalid command: {err}")),
+    };
+    match result {
+        Ok(()) => serde_json::json!({ "ok": true }).to_string(),
+        Err(message) => serde_json::json!({ "ok": false, "error": message }).to_string(),
+    }
+}
+
+async fn set_profile(profiles: &Mutex<ProfileInterface>, name: &str) -> Result<(), String> {
+    let mut profiles = profiles.lock().await;
+    Profile::set_active_profile_name(name)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    profiles
+        .activate(ProfileTrigger::User)
+        .await
+        .map_err(|err| format!("{err:?}"))
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
@@ -1,4 +1,6 @@
-use tailor_api::{Color, ColorProfile, LedDeviceInfo};
+use std::time::Duration;
+
+use tailor_api::{AlertPattern, Color, ColorProfile, LedDeviceInfo};
 use tokio::sync::mpsc;
 use tuxedo_sysfs::led::Controller;
 
@@ -8,6 +10,9 @@ pub struct LedRuntime {
     data: LedRuntimeData,
     profile_receiver: mpsc::Receiver<ColorProfile>,
     color_receiver: mpsc::Receiver<Color>,
+    identify_receiver: mpsc::Receiver<()>,
+    alert_receiver: mpsc::Receiver<Alert>,
+    dump_receiver: mpsc::Receiver<()>,
 }
 
 pub struct LedRuntimeData {
@@ -15,32 +20,49 @@ pub struct LedRuntimeData {
     pub profile: ColorProfile,
 }
 
+/// Flash a color instead of the profile for a while.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub color: Color,
+    pub pattern: AlertPattern,
+    pub duration: Duration,
+}
+
 #[derive(Clone)]
 pub struct LedRuntimeHandle {
     pub info: LedDeviceInfo,
     pub profile_sender: mpsc::Sender<ColorProfile>,
     pub color_sender: mpsc::Sender<Color>,
+    pub identify_sender: mpsc::Sender<()>,
+    pub alert_sender: mpsc::Sender<Alert>,
+    /// Log the internal state of the runtime.
+    pub dump_sender: mpsc::Sender<()>,
 }
 
 impl LedRuntime {
     pub fn new(data: LedRuntimeData) -> (LedRuntimeHandle, Self) {
         let (profile_sender, profile_receiver) = mpsc::channel(1);
         let (color_sender, color_receiver) = mpsc::channel(1);
+        let (identify_sender, identify_receiver) = mpsc::channel(1);
+        let (alert_sender, alert_receiver) = mpsc::channel(1);
+        let (dump_sender, dump_receiver) = mpsc::channel(1);
 
         (
             LedRuntimeHandle {
-                info: LedDeviceInfo {
-                    device_name: data.controller.device_name.clone(),
-                    function: data.controller.function.clone(),
-                    mode: data.controller.mode(),
-                },
+                info: data.controller.info(),
                 profile_sender,
                 color_sender,
+                identify_sender,
+                alert_sender,
+                dump_sender,
             },
             Self {
                 data,
                 profile_receiver,
                 color_receiver,
+                identify_receiver,
+                alert_receiver,
+                dump_receiver,
             },
         )
     }
@@ -1,17 +1,31 @@
 use std::{future::pending, time::Duration};
 
-use tailor_api::{Color, ColorPoint, ColorProfile, ColorTransition};
-use tokio::sync::broadcast;
+use tailor_api::{
+    AlertPattern, Color, ColorPoint, ColorProfile, ColorTransition, LedControllerMode,
+};
+use tokio::time::Instant;
 
-use crate::suspend::{get_suspend_receiver, process_suspend};
+use crate::{settings::get_subsystem_receiver, suspend};
 
-use super::{LedRuntime, LedRuntimeData};
+use super::{Alert, LedRuntime, LedRuntimeData};
+
+/// Number of color changes while identifying a device (5 seconds in total).
+const IDENTIFY_STEPS: usize = 20;
+const IDENTIFY_STEP_TIME: Duration = Duration::from_millis(250);
 
 impl LedRuntime {
+    #[tracing::instrument(
+        name = "led_runtime",
+        skip(self),
+        fields(led = self.data.controller.device_name())
+    )]
     pub async fn run(mut self) {
-        let mut suspend_receiver = get_suspend_receiver();
+        let mut suspend_receiver = suspend::get_suspend_receiver();
+        let mut subsystem_receiver = get_subsystem_receiver();
 
         loop {
+            let led_control = subsystem_receiver.borrow_and_update().led_control;
+
             tokio::select! {
                 new_colors = self.profile_receiver.recv() => {
                     if let Some(colors) = new_colors {
@@ -37,54 +51,233 @@ impl LedRuntime {
                         }
                     }
                 }
-                _ = self.data.update_colors(&mut suspend_receiver) => {}
+                identify = self.identify_receiver.recv() => {
+                    if identify.is_some() {
+                        self.data.run_identify_pattern().await;
+                    }
+                }
+                alert = self.alert_receiver.recv() => {
+                    if let Some(alert) = alert.filter(|_| led_control) {
+                        self.run_alerts(alert).await;
+                    }
+                }
+                Some(()) = self.dump_receiver.recv() => {
+                    tracing::info!(
+                        led_control,
+                        profile = ?self.data.profile,
+                        "State dump of `{}`",
+                        self.data.controller.device_name()
+                    );
+                }
+                changed = subsystem_receiver.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    if led_control && !subsystem_receiver.borrow().led_control {
+                        tracing::info!(
+                            "Stopped LED control for `{}`",
+                            self.data.controller.device_name()
+                        );
+                    }
+                }
+                // Stop animations while the system is suspended, the
+                // colors are applied again after waking up.
+                _ = suspend::wait_for_sleep(&mut suspend_receiver) => {
+                    suspend::sleep_until_wake_up(&mut suspend_receiver).await;
+                    if led_control {
+                        self.data.restore().await;
+                    }
+                }
+                _ = self.data.update_colors(), if led_control => {}
+            }
+        }
+    }
+
+    /// Flash alerts until the last one ended, a new alert replaces the current one.
+    /// Profiles that arrive in the meantime are shown afterwards.
+    async fn run_alerts(&mut self, alert: Alert) {
+        let mut steps = alert_steps(&alert);
+        let mut deadline = Instant::now() + alert.duration;
+        loop {
+            tokio::select! {
+                _ = self.data.run_alert(&steps, deadline) => break,
+                Some(alert) = self.alert_receiver.recv() => {
+                    steps = alert_steps(&alert);
+                    deadline = Instant::now() + alert.duration;
+                }
+                Some(profile) = self.profile_receiver.recv() => self.data.profile = profile,
             }
         }
     }
 }
 
 impl LedRuntimeData {
-    pub async fn update_colors(&mut self, suspend_receiver: &mut broadcast::Receiver<bool>) {
+    pub async fn update_colors(&mut self) {
         match &self.profile {
             ColorProfile::None => {
-                self.controller.set_color(&Color { r: 0, g: 0, b: 0 }).await.unwrap();
+                self.apply_color(&Color { r: 0, g: 0, b: 0 }).await;
                 pending().await
-            },
+            }
             ColorProfile::Single(color) => {
-                self.controller.set_color(color).await.unwrap();
+                let color = color.clone();
+                self.apply_color(&color).await;
                 pending().await
             }
             ColorProfile::Multiple(colors) => {
                 let color_steps = calculate_color_animation_steps(colors);
-                self.run_color_animation(&color_steps, suspend_receiver)
-                    .await;
+                // An animation without any duration would never yield.
+                if color_steps.iter().all(|(_, time)| *time == 0) {
+                    let color = colors
+                        .first()
+                        .map_or(Color { r: 0, g: 0, b: 0 }, |point| point.color.clone());
+                    self.apply_color(&color).await;
+                    pending().await
+                } else {
+                    self.run_color_animation(&color_steps).await;
+                }
+            }
+        }
+    }
+
+    /// Controllers can lose their state while the system is suspended and some
+    /// drivers even register them again. The profile is applied afterwards.
+    async fn restore(&mut self) {
+        if let Err(err) = self.controller.restore_brightness().await {
+            tracing::warn!(
+                "Failed to restore LED device `{}` after waking up: `{err}`",
+                self.controller.device_name()
+            );
+            self.reopen().await;
+        }
+    }
+
+    /// Set a static color and look for the device again if that fails,
+    /// e.g. because the driver was reloaded.
+    async fn apply_color(&mut self, color: &Color) {
+        if let Err(err) = self.controller.set_color(color).await {
+            tracing::error!("Failed setting keyboard colors: `{err}`");
+            if self.reopen().await {
+                if let Err(err) = self.controller.set_color(color).await {
+                    tracing::error!("Failed setting keyboard colors: `{err}`");
+                }
             }
         }
     }
 
-    /// Infinitely run a color animation and
-    /// stop the animation while suspended.
-    async fn run_color_animation(
-        &mut self,
-        color_steps: &[(Color, u32)],
-        suspend_receiver: &mut broadcast::Receiver<bool>,
-    ) {
+    /// Replace the controller with the device of the same name and function
+    /// if it was registered again. Returns whether it was found.
+    async fn reopen(&mut self) -> bool {
+        let controller = crate::init_led_devices()
+            .await
+            .into_iter()
+            .find(|controller| {
+                controller.device_name == self.controller.device_name
+                    && controller.function == self.controller.function
+            });
+        match controller {
+            Some(controller) => {
+                tracing::info!("Reopened LED device `{}`", controller.device_name());
+                self.controller = controller;
+                true
+            }
+            None => {
+                tracing::warn!(
+                    "LED device `{}` isn't available anymore",
+                    self.controller.device_name()
+                );
+                false
+            }
+        }
+    }
+
+    /// Flash a distinctive pattern for a few seconds so users
+    /// can tell which physical device this runtime controls.
+    async fn run_identify_pattern(&mut self) {
+        const WHITE: Color = Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        const OFF: Color = Color { r: 0, g: 0, b: 0 };
+
+        let pattern = match self.controller.mode() {
+            LedControllerMode::Rgb => vec![
+                Color { r: 255, g: 0, b: 0 },
+                OFF,
+                Color { r: 0, g: 255, b: 0 },
+                OFF,
+                Color { r: 0, g: 0, b: 255 },
+                OFF,
+            ],
+            _ => vec![WHITE, OFF],
+        };
+
+        tracing::info!("Identifying LED device `{}`", self.controller.device_name());
+        for color in pattern.iter().cycle().take(IDENTIFY_STEPS) {
+            if let Err(err) = self.controller.set_color(color).await {
+                tracing::error!("Failed to identify LED device: `{err}`");
+                return;
+            }
+            tokio::time::sleep(IDENTIFY_STEP_TIME).await;
+        }
+    }
+
+    /// Cycle through the steps of an alert until `deadline`.
+    async fn run_alert(&mut self, steps: &[(Color, u32)], deadline: Instant) {
+        for (color, time) in steps.iter().cycle() {
+            let now = Instant::now();
+            if now >= deadline {
+                return;
+            }
+            if let Err(err) = self.controller.set_color(color).await {
+                tracing::error!("Failed to flash the alert: `{err}`");
+                return;
+            }
+            let time = Duration::from_millis(u64::from(*time)).min(deadline - now);
+            tokio::time::sleep(time).await;
+        }
+    }
+
+    /// Infinitely run a color animation.
+    async fn run_color_animation(&mut self, color_steps: &[(Color, u32)]) {
         for step in color_steps.iter().cycle() {
             if let Err(err) = self.controller.set_color(&step.0).await {
                 tracing::error!("Failed setting keyboard colors: `{err}`")
             }
 
-            tokio::select! {
-                _ = tokio::time::sleep(Duration::from_millis(step.1 as u64)) => {}
-                _ = process_suspend(suspend_receiver) => {}
-            }
+            tokio::time::sleep(Duration::from_millis(step.1 as u64)).await;
         }
     }
 }
 
+/// One period of the pattern of an alert.
+fn alert_steps(alert: &Alert) -> Vec<(Color, u32)> {
+    const OFF: Color = Color { r: 0, g: 0, b: 0 };
+    let color = alert.color.clone();
+    match alert.pattern {
+        AlertPattern::Solid => vec![(color, 1000)],
+        AlertPattern::Blink => vec![(color, 250), (OFF, 250)],
+        AlertPattern::Pulse => calculate_color_animation_steps(&[
+            ColorPoint {
+                color,
+                transition: ColorTransition::Linear,
+                transition_time: 500,
+            },
+            ColorPoint {
+                color: OFF,
+                transition: ColorTransition::Linear,
+                transition_time: 500,
+            },
+        ]),
+    }
+}
+
 fn calculate_color_animation_steps(colors: &[ColorPoint]) -> Vec<(Color, u32)> {
     let mut color_steps = Vec::new();
-    let mut prev_color = colors.last().unwrap().color.clone();
+    let Some(last) = colors.last() else {
+        return color_steps;
+    };
+    let mut prev_color = last.color.clone();
 
     for color_point in colors {
         let ColorPoint {
@@ -171,7 +364,41 @@ fn decent_linear_steps(transition_time: u32, diffs: &[f64]) -> u32 {
 
 #[cfg(test)]
 mod test {
-    use crate::led::runtime::decent_linear_steps;
+    use std::time::Duration;
+
+    use proptest::prelude::*;
+    use tailor_api::{AlertPattern, Color, ColorPoint, ColorTransition};
+
+    use crate::led::{
+        runtime::{alert_steps, calculate_color_animation_steps, decent_linear_steps},
+        Alert,
+    };
+
+    fn color_point() -> impl Strategy<Value = ColorPoint> {
+        (any::<[u8; 3]>(), any::<bool>(), any::<u32>()).prop_map(
+            |([r, g, b], linear, transition_time)| ColorPoint {
+                color: Color { r, g, b },
+                transition: if linear {
+                    ColorTransition::Linear
+                } else {
+                    ColorTransition::None
+                },
+                transition_time,
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn animation_keeps_duration(colors in proptest::collection::vec(color_point(), 0..8)) {
+            let steps = calculate_color_animation_steps(&colors);
+            let duration: u64 = steps.iter().map(|(_, time)| u64::from(*time)).sum();
+            let expected: u64 = colors.iter().map(|point| u64::from(point.transition_time)).sum();
+            // Linear transitions lose less than one ms per step when dividing.
+            prop_assert!(duration <= expected);
+            prop_assert!(expected - duration < steps.len() as u64 + 1);
+        }
+    }
 
     #[test]
     fn decent_linear_step() {
@@ -190,4 +417,27 @@ mod test {
         let decent_steps = decent_linear_steps(100, &[75.0]);
         assert_eq!(decent_steps, 2);
     }
+
+    #[test]
+    fn alert_patterns() {
+        let red = Color { r: 255, g: 0, b: 0 };
+        let steps = |pattern| {
+            alert_steps(&Alert {
+                color: red.clone(),
+                pattern,
+                duration: Duration::from_secs(5),
+            })
+        };
+
+        assert_eq!(steps(AlertPattern::Solid), [(red.clone(), 1000)]);
+        assert_eq!(
+            steps(AlertPattern::Blink),
+            [(red.clone(), 250), (Color { r: 0, g: 0, b: 0 }, 250)]
+        );
+        // Fades in from off and out again within a second.
+        let pulse = steps(AlertPattern::Pulse);
+        assert_eq!(pulse[0].0, Color { r: 0, g: 0, b: 0 });
+        assert!(pulse.contains(&(red, pulse[0].1)));
+        assert!(pulse.iter().map(|(_, time)| time).sum::<u32>() <= 1000);
+    }
 }
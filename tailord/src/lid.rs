@@ -0,0 +1,91 @@
+//! Apply the lid-closed behavior of the active profile while the lid is closed.
+
+use std::time::Duration;
+
+use futures_lite::StreamExt;
+use tailor_api::LidClosedBehavior;
+use zbus::{proxy, Connection};
+
+use crate::{dbus::ProfileInterface, profiles::Profile};
+
+#[proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+trait UPower {
+    #[zbus(property)]
+    fn lid_is_closed(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn lid_is_present(&self) -> zbus::Result<bool>;
+}
+
+pub async fn watch_lid(profiles: ProfileInterface) {
+    // The behavior that is currently applied, if the lid is closed.
+    let mut applied = None;
+
+    // Don't try to reconnect anymore after 3 attempts
+    for _ in 0..3 {
+        match try_watch_lid(&profiles, &mut applied).await {
+            Ok(()) => return,
+            Err(err) => {
+                tracing::error!("Failed to watch the lid: `{err}`");
+                // Reconnect after 10s
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+        }
+    }
+    tracing::warn!("Stopped watching the lid after 3 errors");
+}
+
+async fn try_watch_lid(
+    profiles: &ProfileInterface,
+    applied: &mut Option<LidClosedBehavior>,
+) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let proxy = UPowerProxy::new(&connection).await?;
+    if !proxy.lid_is_present().await? {
+        tracing::info!("No lid present");
+        return Ok(());
+    }
+
+    update(profiles, applied, proxy.lid_is_closed().await?).await;
+    let mut changes = proxy.receive_lid_is_closed_changed().await;
+    while let Some(change) = changes.next().await {
+        update(profiles, applied, change.get().await?).await;
+    }
+    Ok(())
+}
+
+async fn update(
+    profiles: &ProfileInterface,
+    applied: &mut Option<LidClosedBehavior>,
+    closed: bool,
+) {
+    if closed && applied.is_none() {
+        let behavior = match Profile::get_active_profile_info() {
+            Ok(info) => info.lid_closed,
+            Err(err) => {
+                tracing::warn!("Failed to read the active profile: `{err:?}`");
+                return;
+            }
+        };
+        if behavior.is_unchanged() {
+            return;
+        }
+        tracing::info!("Lid closed, applying {behavior:?}");
+        if let Err(err) = profiles.apply_lid_closed(&behavior).await {
+            tracing::error!("Failed to apply the lid-closed behavior: `{err:?}`");
+        }
+        *applied = Some(behavior);
+    } else if !closed {
+        let Some(behavior) = applied.take() else {
+            return;
+        };
+        tracing::info!("Lid opened, restoring the active profile");
+        if let Err(err) = profiles.revert_lid_closed(&behavior).await {
+            tracing::error!("Failed to restore the active profile: `{err:?}`");
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Logging setup and runtime changes of the log level.
+
+use once_cell::sync::OnceCell;
+use tracing_subscriber::{
+    filter::{Directive, LevelFilter},
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Registry,
+};
+
+use crate::error::{self, TailorError};
+
+static FILTER: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// Log to the journal with structured fields like `FAN` and `PROFILE` when
+/// running as a systemd service, so `journalctl -u tailord FAN=1` works.
+pub fn init() {
+    let journald = std::env::var_os("JOURNAL_STREAM")
+        .and_then(|_| tracing_journald::layer().ok())
+        .map(|layer| layer.with_field_prefix(None));
+    let stderr = journald.is_none().then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .without_time()
+    });
+
+    let (filter, handle) = reload::Layer::new(EnvFilter::from_default_env());
+    FILTER.set(handle).ok();
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(journald)
+        .with(stderr)
+        .init();
+}
+
+/// Change the log level of `target` (e.g. `tailord::fancontrol`) until
+/// tailord is restarted. An empty target changes the default level.
+pub fn set_level(target: &str, level: &str) -> error::Result<()> {
+    level
+        .parse::<LevelFilter>()
+        .map_err(|_| TailorError::InvalidArgs(format!("Invalid log level `{level}`")))?;
+    let directive = if target.is_empty() {
+        level.to_owned()
+    } else {
+        format!("{target}={level}")
+    };
+    let directive: Directive = directive
+        .parse()
+        .map_err(|err| TailorError::InvalidArgs(format!("Invalid log target `{target}`: {err}")))?;
+
+    let handle = FILTER
+        .get()
+        .ok_or_else(|| TailorError::Failed("Logging isn't initialized".to_owned()))?;
+    // A directive for the same target replaces the previous one.
+    handle
+        .modify(|filter| *filter = std::mem::take(filter).add_directive(directive))
+        .map_err(|err| TailorError::Failed(err.to_string()))?;
+    tracing::info!("Set the log level of `{target}` to `{level}`");
+    Ok(())
+}
@@ -1,56 +1,132 @@
+mod backlight;
+mod charging;
+mod conflicts;
+mod cpufreq;
 mod dbus;
+mod diagnostics;
+mod dump;
+mod error;
 mod fancontrol;
+mod history;
+mod hotkey;
+#[cfg(feature = "http-api")]
+mod http;
 pub mod led;
+mod lid;
+mod logging;
+mod metrics;
+#[cfg(feature = "mock")]
+mod mock;
+#[cfg(feature = "mqtt")]
+mod mqtt;
 mod performance;
+mod power_profiles;
+mod power_source;
+mod privileges;
 mod profiles;
+mod reload;
+#[cfg(feature = "rpc-socket")]
+mod rpc;
+mod self_test;
+mod settings;
 pub mod shutdown;
+mod signals;
 mod suspend;
+mod telemetry;
+mod thermal;
 pub mod util;
+mod wire;
 
-use std::future::pending;
+use std::{
+    future::pending,
+    ops::Range,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use dbus::{FanInterface, PerformanceInterface, ProfileInterface};
+use dbus::{
+    ChargingInterface, FanInterface, FnLockInterface, PerformanceInterface, ProfileInterface,
+    SettingsInterface, TouchpadInterface, WebcamInterface,
+};
 use profiles::Profile;
-use tailor_api::{ColorProfile, LedControllerMode};
-use tuxedo_ioctl::hal::IoInterface;
-use zbus::ConnectionBuilder;
+use tailor_api::{LedControllerMode, ProfileTrigger};
+use tokio::sync::watch;
+use tuxedo_ioctl::hal::{traits::HardwareDevice, Backend, IoInterface, IoctlResult};
+use zbus::{ConnectionBuilder, SignalContext};
 
 use crate::{
+    charging::{ChargingHandle, ChargingRuntime},
     dbus::LedInterface,
-    fancontrol::FanRuntime,
+    fancontrol::{FanHandles, FanRuntime, FanRuntimeHandle},
     led::{LedRuntime, LedRuntimeData},
-    performance::PerformanceProfileRuntime,
+    performance::{PerformanceProfile, PerformanceProfileRuntime, PerformanceProfileRuntimeHandle},
     profiles::SupportedFeatures,
+    self_test::SelfTest,
+    settings::Settings,
 };
 
 const DBUS_NAME: &str = "com.tux.Tailor";
 const DBUS_PATH: &str = "/com/tux/Tailor";
 
+/// How long to wait for the ioctl device if the kernel module is
+/// loaded but not ready yet. This happens if tailord is started
+/// early during boot, for example through D-Bus activation.
+/// D-Bus waits about 25s for an activated service to show up.
+const IO_INIT_TIMEOUT: Duration = Duration::from_secs(5);
+const IO_INIT_RETRY_DELAY: Duration = Duration::from_millis(250);
+/// How often the hardware interface is opened again
+/// if it wasn't available at startup.
+const IO_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+const TUXEDO_IO_MODULE_PATH: &str = "/sys/module/tuxedo_io";
+/// Run the full API against simulated hardware, see `mock`.
+const NO_HARDWARE_ARG: &str = "--no-hardware";
+
 fn main() {
     if std::env::var_os("RUST_LOG").is_none() {
         std::env::set_var("RUST_LOG", "info");
     }
 
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .with_writer(std::io::stderr)
-        .without_time()
-        .init();
+    logging::init();
+
+    #[cfg(feature = "mock")]
+    if mock::enabled() {
+        mock::simulate_attributes();
+    }
+    #[cfg(not(feature = "mock"))]
+    if std::env::args().any(|arg| arg == NO_HARDWARE_ARG) {
+        tracing::error!("`{NO_HARDWARE_ARG}` requires tailord to be built with the `mock` feature");
+        std::process::exit(1);
+    }
+
+    // The broker must be forked before the runtime starts any threads.
+    // Without it, tailord would keep running as root against the configuration,
+    // so settings that can't be read are fatal instead of falling back to the defaults.
+    if let Err(err) = Settings::migrate() {
+        tracing::error!("Failed to migrate the settings: `{err}`");
+    }
+    let settings = Settings::try_load().unwrap_or_else(|err| {
+        tracing::error!("Failed to load the settings: `{err}`");
+        std::process::exit(1);
+    });
+    let broker = settings.privileges.user.map(|_| {
+        privileges::Broker::spawn().unwrap_or_else(|err| {
+            tracing::error!("Failed to start the privileged broker: `{err}`");
+            std::process::exit(1);
+        })
+    });
 
-    tokio_uring::start(start_runtime());
+    tuxedo_sysfs::runtime::start(start_runtime(broker));
 }
 
-#[tracing::instrument]
-async fn start_runtime() {
+#[tracing::instrument(skip(broker))]
+async fn start_runtime(broker: Option<privileges::Broker>) {
     tracing::info!("Starting tailord");
 
     // Setup shutdown
     let mut shutdown_receiver = shutdown::setup();
 
-    let led_devices = tuxedo_sysfs::led::Collection::new()
-        .await
-        .map(|c| c.into_inner())
-        .unwrap_or_default();
+    let led_devices = init_led_devices().await;
 
     let mut mode = LedControllerMode::Rgb;
     for device in &led_devices {
@@ -58,59 +134,125 @@ async fn start_runtime() {
             mode = LedControllerMode::Monochrome;
         }
     }
+    // Fan profiles are checked against the trip points when they're loaded.
+    thermal::init();
     Profile::init_if_necessary(SupportedFeatures { mode });
     let profile = Profile::load();
+    if let Ok(name) = Profile::get_active_profile_name().await {
+        history::record(&name, ProfileTrigger::Startup);
+    }
+    conflicts::detect();
+    let settings = Settings::init();
 
-    let (device, _webcam, _tdp) = match IoInterface::new() {
-        Ok(interface) => {
-            let IoInterface {
-                device,
-                webcam,
-                tdp,
-                module_version,
-            } = interface;
-            tracing::info!("Connected to Tuxedo ioctl interface with version {module_version}");
-            (Some(device), webcam, tdp)
-        }
-        Err(err) => {
-            tracing::warn!("No tuxedo ioctl interface available: {err}");
-            (None, None, None)
+    let io_interface = init_io_interface().await;
+    diagnostics::record_hardware(&io_interface);
+    let (device, fans, has_performance_profiles, webcam, tdp, fn_lock, touchpad) =
+        match io_interface {
+            Ok(interface) => {
+                let fans = interface.supported_fans();
+                let has_performance_profiles =
+                    !interface.available_performance_profiles().is_empty();
+                let has_webcam_control = interface.has_webcam_control();
+                let has_tdp_control = interface.has_tdp_control();
+                let has_fn_lock_control = interface.has_fn_lock_control();
+                let has_touchpad_control = interface.has_touchpad_control();
+                let IoInterface {
+                    backend,
+                    device,
+                    webcam,
+                    tdp,
+                    fn_lock,
+                    touchpad,
+                    module_version,
+                } = interface;
+                match backend {
+                    Backend::Ioctl => tracing::info!(
+                        "Connected to Tuxedo ioctl interface with version {module_version}"
+                    ),
+                    Backend::Platform => tracing::warn!(
+                        "Tuxedo ioctl interface is unavailable, using the fans of the platform driver"
+                    ),
+                    Backend::Hwmon => {
+                        tracing::warn!("Tuxedo ioctl interface is unavailable, using hwmon fans")
+                    }
+                    #[cfg(feature = "mock")]
+                    Backend::Mock => tracing::warn!("Using simulated hardware"),
+                }
+                tracing::info!(
+                    "Hardware supports {} fan(s), performance profiles: {has_performance_profiles}, \
+                    webcam control: {has_webcam_control}, TDP control: {has_tdp_control}, \
+                    Fn-lock control: {has_fn_lock_control}, touchpad control: {has_touchpad_control}",
+                    fans.len()
+                );
+                (
+                    Some(device),
+                    fans,
+                    has_performance_profiles,
+                    webcam.filter(|_| has_webcam_control),
+                    tdp.filter(|_| has_tdp_control),
+                    fn_lock.filter(|_| has_fn_lock_control),
+                    touchpad.filter(|_| has_touchpad_control),
+                )
+            }
+            Err(err) => {
+                tracing::warn!("No tuxedo ioctl interface or hwmon fans available: {err}");
+                (None, 0..0, false, None, None, None, None)
+            }
+        };
+
+    if let (Some(webcam), Some(enabled)) = (&webcam, profile.webcam) {
+        if let Err(err) = webcam.set_webcam(enabled) {
+            tracing::warn!("Failed to switch the webcam: {err}");
         }
-    };
+    }
+    if let (Some(fn_lock), Some(enabled)) = (&fn_lock, profile.fn_lock) {
+        if let Err(err) = fn_lock.set_fn_lock(enabled) {
+            tracing::warn!("Failed to switch the Fn-lock: {err}");
+        }
+    }
+    if let (Some(touchpad), Some(enabled)) = (&touchpad, profile.touchpad) {
+        if let Err(err) = touchpad.set_touchpad(enabled) {
+            tracing::warn!("Failed to switch the touchpad: {err}");
+        }
+    }
+    profiles::allow_power_limits(profile.power_limits);
+    let apply_tdp =
+        !profile.tdp.is_empty() && profile.power_limits && settings.subsystems.power_limit_control;
+    if let Some(tdp) = tdp.as_deref().filter(|_| apply_tdp) {
+        if let Err(err) = performance::apply_power_limits(tdp, &profile.tdp) {
+            tracing::warn!("Failed to set the power limits: {err}");
+        }
+    }
+    if let Err(err) = profile.cpufreq.apply() {
+        tracing::warn!("Failed to apply the cpufreq settings: {err}");
+    }
+    if let Some(percent) = profile.display_brightness {
+        if let Err(err) = backlight::apply(percent) {
+            tracing::warn!("Failed to set the display brightness: {err}");
+        }
+    }
 
-    let mut fan_handles = Vec::new();
-    let mut fan_runtimes = Vec::new();
-    if let Some(device) = &device {
-        let available_fans = device.get_number_fans();
-        for fan_idx in 0..available_fans {
-            let profile = profile
-                .fans
-                .get(fan_idx as usize)
-                .cloned()
-                .unwrap_or_default();
-            let (handle, runtime) = FanRuntime::new(fan_idx, device.clone(), profile);
+    let mut self_test = SelfTest::default();
 
-            fan_handles.push(handle);
-            fan_runtimes.push(runtime);
+    let (fan_handles, fan_runtimes) = match &device {
+        Some(device) => {
+            // Fans that fail are retried by their runtime.
+            for fan_idx in fans.clone() {
+                self_test.fan(device.as_ref(), fan_idx);
+            }
+            init_fans(device, fans, &profile)
         }
-    }
+        None => (Vec::new(), Vec::new()),
+    };
+    let (fan_sender, fan_handles) = FanHandles::new(fan_handles);
 
     let mut led_handles = Vec::new();
     let mut led_runtimes = Vec::new();
-    for led_device in led_devices {
-        let profile = profile
-            .leds
-            .iter()
-            .find_map(|(info, profile)| {
-                if info.device_name == led_device.device_name
-                    && info.function == led_device.function
-                {
-                    Some(profile.clone())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| ColorProfile::default(led_device.mode()));
+    for mut led_device in led_devices {
+        if !self_test.led(&mut led_device).await {
+            continue;
+        }
+        let profile = profile.leds.get(&led_device.info());
 
         let (handle, runtime) = LedRuntime::new(LedRuntimeData {
             controller: led_device,
@@ -121,75 +263,225 @@ async fn start_runtime() {
         led_runtimes.push(runtime);
     }
 
-    let (performance_profile_handle, performance_profile_runtime) = match device {
-        Some(device) => {
-            if let Ok(default_performance_profile) = device.get_default_odm_performance_profile() {
-                let (handle, runtime) = PerformanceProfileRuntime::new(
-                    device,
-                    profile.performance_profile,
-                    default_performance_profile,
-                );
-                (Some(handle), Some(runtime))
-            } else {
-                (None, None)
+    let (performance_profile_handle, performance_profile_runtime) = match &device {
+        Some(device) if has_performance_profiles => {
+            match init_performance_profile(device.clone(), profile.performance_profile) {
+                Some((handle, runtime)) => (Some(handle), Some(runtime)),
+                None => (None, None),
             }
         }
+        _ => (None, None),
+    };
+    let (performance_sender, performance_profile_handle) =
+        watch::channel(performance_profile_handle);
+
+    let (charging_handle, charging_runtime) = match init_charging().await {
+        Some((handle, runtime)) => (Some(handle), Some(runtime)),
         None => (None, None),
     };
+    // The charging runtime must run before its handle can be tested.
+    let charging_test_handle = charging_handle.clone();
 
     let profile_interface = ProfileInterface {
         led_handles: led_handles.clone(),
         fan_handles: fan_handles.clone(),
         performance_profile_handle: performance_profile_handle.clone(),
+        webcam: webcam.clone(),
+        fn_lock: fn_lock.clone(),
+        touchpad: touchpad.clone(),
+        tdp: tdp.clone(),
+    };
+
+    #[cfg(feature = "rpc-socket")]
+    let rpc_server = rpc::RpcServer {
+        fan: FanInterface {
+            handles: fan_handles.clone(),
+        },
+        led: LedInterface {
+            handles: led_handles.clone(),
+        },
+        profiles: profile_interface.clone(),
+        performance: PerformanceInterface {
+            handler: performance_profile_handle.clone(),
+            tdp: tdp.clone(),
+        },
+        settings: SettingsInterface,
+        webcam: WebcamInterface {
+            webcam: webcam.clone(),
+        },
+        fn_lock: FnLockInterface {
+            fn_lock: fn_lock.clone(),
+        },
+        touchpad: TouchpadInterface {
+            touchpad: touchpad.clone(),
+        },
+        charging: ChargingInterface {
+            handle: charging_handle.clone(),
+        },
     };
 
+    if settings.http_api.enabled {
+        #[cfg(feature = "http-api")]
+        http::spawn(
+            &settings.http_api,
+            fan_handles.clone(),
+            tdp.clone(),
+            profile_interface.clone(),
+            Duration::from_millis(settings.signals.fan_status_interval_ms),
+        );
+        #[cfg(not(feature = "http-api"))]
+        tracing::warn!("The HTTP API is enabled, but tailord was built without `http-api`");
+    }
+
+    if settings.mqtt.enabled {
+        #[cfg(feature = "mqtt")]
+        mqtt::spawn(
+            &settings.mqtt,
+            fan_handles.clone(),
+            profile_interface.clone(),
+            Duration::from_millis(settings.signals.fan_status_interval_ms),
+        );
+        #[cfg(not(feature = "mqtt"))]
+        tracing::warn!("MQTT is enabled, but tailord was built without `mqtt`");
+    }
+
+    power_source::refresh().await;
+    tokio::task::spawn_local(power_source::watch_power_supplies());
+    tokio::task::spawn_local(power_source::switch_profiles(profile_interface.clone()));
+    if !settings.power_profiles.is_empty() {
+        tokio::task::spawn_local(power_profiles::switch_profiles(profile_interface.clone()));
+    }
+    tokio::task::spawn_local(lid::watch_lid(profile_interface.clone()));
+    dump::spawn(fan_handles.clone(), led_handles.clone());
+    reload::spawn(profile_interface.clone(), charging_handle.clone());
+
+    hotkey::spawn(
+        &settings.boost_key,
+        FanInterface {
+            handles: fan_handles.clone(),
+        },
+    );
+
+    telemetry::spawn(&settings.telemetry, fan_handles.clone());
+
     let led_interface = LedInterface {
         handles: led_handles,
     };
 
     let fan_interface = FanInterface {
-        handles: fan_handles,
+        handles: fan_handles.clone(),
     };
 
     let performance_profile_interface = PerformanceInterface {
         handler: performance_profile_handle,
+        tdp,
+    };
+
+    let webcam_interface = WebcamInterface { webcam };
+
+    let fn_lock_interface = FnLockInterface { fn_lock };
+
+    let touchpad_interface = TouchpadInterface { touchpad };
+
+    let charging_interface = ChargingInterface {
+        handle: charging_handle,
     };
 
+    let rpc_enabled = settings.rpc_socket.enabled;
+    #[cfg(feature = "rpc-socket")]
+    if rpc_enabled {
+        rpc::spawn(&settings.rpc_socket, rpc_server);
+    }
+    #[cfg(not(feature = "rpc-socket"))]
+    if rpc_enabled {
+        tracing::warn!("The RPC socket is enabled, but tailord was built without `rpc-socket`");
+    }
+
+    // The name is requested after all interfaces are served, so
+    // calls that triggered a D-Bus activation are handled correctly.
     tracing::debug!("Connecting to DBUS as {DBUS_NAME}");
-    let _conn = ConnectionBuilder::system()
-        .unwrap()
-        .name(DBUS_NAME)
-        .unwrap()
-        .serve_at(DBUS_PATH, led_interface)
-        .unwrap()
-        .serve_at(DBUS_PATH, fan_interface)
-        .unwrap()
-        .serve_at(DBUS_PATH, profile_interface)
-        .unwrap()
-        .serve_at(DBUS_PATH, performance_profile_interface)
-        .unwrap()
-        .build()
-        .await
-        .unwrap();
+    let conn = ConnectionBuilder::system()
+        .and_then(|builder| builder.name(DBUS_NAME))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, led_interface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, fan_interface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, profile_interface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, performance_profile_interface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, SettingsInterface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, webcam_interface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, fn_lock_interface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, touchpad_interface))
+        .and_then(|builder| builder.serve_at(DBUS_PATH, charging_interface));
+    let conn = match conn {
+        Ok(builder) => builder.build().await,
+        Err(err) => Err(err),
+    };
+
+    // Keep the connection alive until shutdown.
+    let _conn = match conn {
+        Ok(conn) => {
+            tracing::debug!("Starting signal emitters");
+            signals::spawn(
+                SignalContext::new(&conn, DBUS_PATH).unwrap(),
+                &settings.signals,
+                fan_handles,
+            );
+            tokio::task::spawn_local(wire::forget_disconnected(conn.clone()));
+            Some(conn)
+        }
+        Err(err) if cfg!(feature = "rpc-socket") && rpc_enabled => {
+            tracing::error!(
+                "Failed to connect to D-Bus, only the RPC socket is available: `{err}`"
+            );
+            None
+        }
+        Err(err) => panic!("Failed to connect to D-Bus: `{err}`"),
+    };
+
+    if let (Some(broker), Some(user)) = (broker, &settings.privileges.user) {
+        if let Err(err) = broker.drop_privileges(user) {
+            tracing::error!("Failed to drop root privileges: `{err}`");
+            std::process::exit(1);
+        }
+    }
 
     tracing::debug!("Starting suspend watcher runtime");
-    tokio_uring::spawn(suspend::wait_for_suspend());
+    tokio::task::spawn_local(suspend::wait_for_suspend());
 
     tracing::debug!("Starting {} led runtime(s)", led_runtimes.len());
     for runtime in led_runtimes {
-        tokio_uring::spawn(runtime.run());
+        tokio::task::spawn_local(runtime.run());
     }
 
     tracing::debug!("Starting {} fans runtime(s)", fan_runtimes.len());
     for runtime in fan_runtimes {
-        tokio_uring::spawn(runtime.run());
+        tokio::task::spawn_local(runtime.run());
     }
 
     if let Some(performance_profile_runtime) = performance_profile_runtime {
         tracing::debug!("Starting performance profile runtime");
-        tokio_uring::spawn(performance_profile_runtime.run());
+        tokio::task::spawn_local(performance_profile_runtime.run());
     }
 
+    if device.is_none() {
+        if settings.privileges.user.is_some() {
+            tracing::warn!(
+                "Restart tailord once the hardware is available, \
+                it can't be opened after dropping root privileges"
+            );
+        } else {
+            tokio::task::spawn_local(retry_io_interface(fan_sender, performance_sender));
+        }
+    }
+
+    if let Some(charging_runtime) = charging_runtime {
+        tracing::debug!("Starting charging runtime");
+        tokio::task::spawn_local(charging_runtime.run(settings.charging.clone()));
+    }
+
+    self_test.cooling_devices();
+    self_test.charging(charging_test_handle.as_ref()).await;
+    self_test.finish();
+
     tracing::info!("Tailord started");
     tokio::select! {
         _ = pending() => {
@@ -201,3 +493,126 @@ async fn start_runtime() {
         }
     }
 }
+
+async fn init_led_devices() -> Vec<tuxedo_sysfs::led::Controller> {
+    #[cfg(feature = "mock")]
+    if mock::enabled() {
+        tracing::warn!("Using virtual LED devices");
+        return mock::led_devices().await;
+    }
+
+    tuxedo_sysfs::led::Collection::new()
+        .await
+        .map(|c| c.into_inner())
+        .unwrap_or_default()
+}
+
+async fn init_charging() -> Option<(ChargingHandle, ChargingRuntime)> {
+    // Don't change the thresholds of the real battery.
+    #[cfg(feature = "mock")]
+    if mock::enabled() {
+        return None;
+    }
+
+    ChargingRuntime::new().await
+}
+
+fn init_fans(
+    device: &Arc<dyn HardwareDevice>,
+    fans: Range<u8>,
+    profile: &Profile,
+) -> (Vec<FanRuntimeHandle>, Vec<FanRuntime>) {
+    fans.map(|fan_idx| {
+        let profile = profiles::fan_profile(&profile.fans, fan_idx as usize);
+        FanRuntime::new(fan_idx, device.clone(), profile)
+    })
+    .unzip()
+}
+
+fn init_performance_profile(
+    device: Arc<dyn HardwareDevice>,
+    performance_profile: Option<PerformanceProfile>,
+) -> Option<(PerformanceProfileRuntimeHandle, PerformanceProfileRuntime)> {
+    let default_performance_profile = device.get_default_odm_performance_profile().ok()?;
+    Some(PerformanceProfileRuntime::new(
+        device,
+        performance_profile,
+        default_performance_profile,
+    ))
+}
+
+/// Keep trying to open the hardware interface if it wasn't available at startup,
+/// for example because the kernel module was loaded later. Once it opens, the fans
+/// and performance profiles are controlled like they were found at startup.
+#[tracing::instrument(skip_all)]
+async fn retry_io_interface(
+    fan_sender: watch::Sender<Vec<FanRuntimeHandle>>,
+    performance_sender: watch::Sender<Option<PerformanceProfileRuntimeHandle>>,
+) {
+    tracing::info!("Trying to open the hardware interface every {IO_RETRY_INTERVAL:?}");
+    let interface = loop {
+        tokio::time::sleep(IO_RETRY_INTERVAL).await;
+        match IoInterface::ioctl().or_else(|_| IoInterface::hwmon()) {
+            Ok(interface) => break interface,
+            Err(err) => tracing::debug!("Hardware interface is still unavailable: {err}"),
+        }
+    };
+
+    let fans = interface.supported_fans();
+    let has_performance_profiles = !interface.available_performance_profiles().is_empty();
+    tracing::info!(
+        "Hardware interface is available now with {} fan(s), performance profiles: \
+        {has_performance_profiles}",
+        fans.len()
+    );
+    let device = interface.device.clone();
+    diagnostics::record_hardware(&Ok(interface));
+
+    let profile = Profile::load();
+    let (handles, runtimes) = init_fans(&device, fans, &profile);
+    for runtime in runtimes {
+        tokio::task::spawn_local(runtime.run());
+    }
+    fan_sender.send_replace(handles);
+
+    if has_performance_profiles {
+        if let Some((handle, runtime)) =
+            init_performance_profile(device, profile.performance_profile)
+        {
+            tokio::task::spawn_local(runtime.run());
+            performance_sender.send_replace(Some(handle));
+        }
+    }
+}
+
+/// Open the ioctl interface and fall back to hwmon if the module isn't available.
+async fn init_io_interface() -> IoctlResult<IoInterface> {
+    #[cfg(feature = "mock")]
+    if mock::enabled() {
+        return Ok(mock::io_interface());
+    }
+
+    init_ioctl_interface().await.or_else(|err| {
+        tracing::info!("Tuxedo ioctl interface is unavailable, looking for hwmon fans: {err}");
+        IoInterface::hwmon().map_err(|_| err)
+    })
+}
+
+/// Open the ioctl interface and defer the initialization
+/// for a while if the device isn't ready yet.
+async fn init_ioctl_interface() -> IoctlResult<IoInterface> {
+    let deadline = Instant::now() + IO_INIT_TIMEOUT;
+    loop {
+        match IoInterface::ioctl() {
+            Ok(interface) => return Ok(interface),
+            Err(err) => {
+                let module_loaded = Path::new(TUXEDO_IO_MODULE_PATH).exists();
+                if !module_loaded || Instant::now() >= deadline {
+                    return Err(err);
+                }
+                tracing::info!("Tuxedo ioctl interface isn't ready yet, retrying: {err}");
+                tokio::time::sleep(IO_INIT_RETRY_DELAY).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Counters of events that are exported at `GET /metrics` of the HTTP API.
+
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use tailor_api::{metrics::Counters, ProfileTrigger};
+
+static COUNTERS: Lazy<Mutex<Counters>> = Lazy::new(Mutex::default);
+
+pub fn count_profile_switch(trigger: ProfileTrigger) {
+    *COUNTERS
+        .lock()
+        .unwrap()
+        .profile_switches
+        .entry(trigger.name())
+        .or_default() += 1;
+}
+
+pub fn count_sensor_error(fan_idx: u8) {
+    *COUNTERS
+        .lock()
+        .unwrap()
+        .sensor_errors
+        .entry(fan_idx)
+        .or_default() += 1;
+}
+
+pub fn count_fan_error(fan_idx: u8) {
+    *COUNTERS
+        .lock()
+        .unwrap()
+        .fan_errors
+        .entry(fan_idx)
+        .or_default() += 1;
+}
+
+/// The counted events, exported by the HTTP API.
+#[cfg(feature = "http-api")]
+pub fn counters() -> Counters {
+    COUNTERS.lock().unwrap().clone()
+}
@@ -0,0 +1,43 @@
+//! Simulated hardware for development on machines without TUXEDO hardware.
+//!
+//! Requires the `mock` feature and is enabled with `--no-hardware` or by
+//! setting `TAILORD_MOCK`. `TAILORD_MOCK_TEMPERATURES` can hold a comma
+//! separated list of temperatures that the virtual fans report in turn.
+
+use tuxedo_ioctl::hal::{attribute, mock::MockHardware, IoInterface};
+use tuxedo_sysfs::led::{Collection, Controller};
+
+/// Whether tailord should use simulated hardware.
+pub fn enabled() -> bool {
+    std::env::var_os("TAILORD_MOCK").is_some()
+        || std::env::args().any(|arg| arg == crate::NO_HARDWARE_ARG)
+}
+
+/// Only log writes to sysfs attributes like the cpufreq governor and
+/// intel_powerclamp, which would change the real hardware otherwise.
+pub fn simulate_attributes() {
+    attribute::set_writer(Box::new(|path, value| {
+        tracing::info!("Simulated writing `{value}` to `{}`", path.display());
+        Ok(())
+    }));
+}
+
+pub fn io_interface() -> IoInterface {
+    let temperatures = std::env::var("TAILORD_MOCK_TEMPERATURES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|temp| temp.trim().parse().ok())
+        .collect();
+    IoInterface::mock(MockHardware::new(2, temperatures))
+}
+
+pub async fn led_devices() -> Vec<Controller> {
+    let dir = std::env::temp_dir().join("tailord-mock-leds");
+    match Collection::mock(&dir).await {
+        Ok(collection) => collection.into_inner(),
+        Err(err) => {
+            tracing::error!("Failed to create virtual LED devices: `{err}`");
+            Vec::new()
+        }
+    }
+}
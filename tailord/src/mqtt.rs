@@ -0,0 +1,182 @@
+//! MQTT client for home automation systems like Home Assistant.
+//!
+//! Topics below the configured prefix:
+//!
+//! * `<prefix>/availability` is `online` while tailord is connected
+//!   and set to `offline` by the broker otherwise (retained).
+//! * `<prefix>/state` holds the active profile, the power source
+//!   and the status of all fans as JSON (retained).
+//! * `<prefix>/profile/set` switches the active profile to the name
+//!   in the payload, if profile switching is allowed.
+
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use serde::Serialize;
+use tailor_api::{FanStatus, PowerSource, ProfileTrigger};
+
+use crate::{
+    dbus::ProfileInterface, fancontrol::FanHandles, history, power_source, profiles::Profile,
+    settings::MqttSettings,
+};
+
+/// Time to wait before reconnecting after the connection failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, PartialEq, Serialize)]
+struct State {
+    active_profile: String,
+    power_source: Option<PowerSource>,
+    fans: Vec<FanStatus>,
+}
+
+struct Topics {
+    availability: String,
+    state: String,
+    set_profile: String,
+}
+
+impl Topics {
+    fn new(prefix: &str) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        Self {
+            availability: format!("{prefix}/availability"),
+            state: format!("{prefix}/state"),
+            set_profile: format!("{prefix}/profile/set"),
+        }
+    }
+}
+
+/// Connect to the broker configured in `settings`.
+pub fn spawn(
+    settings: &MqttSettings,
+    fan_handles: FanHandles,
+    profiles: ProfileInterface,
+    update_interval: Duration,
+) {
+    let topics = Topics::new(&settings.topic_prefix);
+
+    let mut options = MqttOptions::new(&settings.client_id, &settings.host, settings.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    options.set_last_will(LastWill::new(
+        &topics.availability,
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
+    if let Some(username) = &settings.username {
+        options.set_credentials(username, settings.password.clone().unwrap_or_default());
+    }
+
+    // Only allow profile switching if it's enabled.
+    let profiles = settings.allow_profile_switching.then_some(profiles);
+
+    tokio::task::spawn_local(run(options, topics, fan_handles, profiles, update_interval));
+}
+
+#[tracing::instrument(skip_all)]
+async fn run(
+    options: MqttOptions,
+    topics: Topics,
+    fan_handles: FanHandles,
+    mut profiles: Option<ProfileInterface>,
+    update_interval: Duration,
+) {
+    let (client, mut event_loop) = AsyncClient::new(options, 16);
+    let mut switch_receiver = history::get_switch_receiver();
+    let mut interval = tokio::time::interval(update_interval);
+    let mut last_state = None;
+
+    // The event loop runs in this task, so the client must never wait
+    // for it. `try_*` only fails if the request queue is full.
+    loop {
+        tokio::select! {
+            event = event_loop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    tracing::info!("Connected to the MQTT broker");
+                    publish(&client, &topics.availability, "online");
+                    if profiles.is_some() {
+                        subscribe(&client, &topics.set_profile);
+                    }
+                    // The broker may have lost the retained state.
+                    last_state = None;
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    if message.topic == topics.set_profile {
+                        if let Some(profiles) = &mut profiles {
+                            set_profile(profiles, &message.payload).await;
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::warn!("MQTT connection failed: `{err}`");
+                    // Polling again reconnects.
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            },
+            _ = interval.tick() => {
+                publish_state(&client, &topics, &fan_handles, &mut last_state).await;
+            }
+            // Publish profile switches right away.
+            Ok(_) = switch_receiver.recv() => {
+                publish_state(&client, &topics, &fan_handles, &mut last_state).await;
+            }
+        }
+    }
+}
+
+async fn publish_state(
+    client: &AsyncClient,
+    topics: &Topics,
+    fan_handles: &FanHandles,
+    last_state: &mut Option<State>,
+) {
+    let state = State {
+        active_profile: Profile::get_active_profile_name().await.unwrap_or_default(),
+        power_source: power_source::current(),
+        fans: fan_handles
+            .statuses()
+            .iter()
+            .map(|status| *status.borrow())
+            .collect(),
+    };
+    if last_state.as_ref() != Some(&state) {
+        publish(
+            client,
+            &topics.state,
+            serde_json::to_string(&state).unwrap(),
+        );
+        *last_state = Some(state);
+    }
+}
+
+/// Publish a retained message.
+fn publish(client: &AsyncClient, topic: &str, payload: impl Into<Vec<u8>>) {
+    if let Err(err) = client.try_publish(topic, QoS::AtLeastOnce, true, payload) {
+        tracing::warn!("Failed to publish to `{topic}`: `{err}`");
+    }
+}
+
+fn subscribe(client: &AsyncClient, topic: &str) {
+    if let Err(err) = client.try_subscribe(topic, QoS::AtLeastOnce) {
+        tracing::warn!("Failed to subscribe to `{topic}`: `{err}`");
+    }
+}
+
+async fn set_profile(profiles: &mut ProfileInterface, payload: &[u8]) {
+    let Ok(name) = std::str::from_utf8(payload) else {
+        tracing::warn!("Ignoring profile name that isn't valid UTF-8");
+        return;
+    };
+    let name = name.trim();
+    tracing::info!("Switching to profile `{name}` requested over MQTT");
+
+    if let Err(err) = Profile::set_active_profile_name(name).await {
+        tracing::warn!("Failed to set the active profile to `{name}`: `{err:?}`");
+        return;
+    }
+    if let Err(err) = profiles.activate(ProfileTrigger::User).await {
+        tracing::warn!("Failed to activate profile `{name}`: `{err:?}`");
+    }
+}
@@ -1,7 +1,14 @@
-use std::sync::Arc;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
 
+use tailor_api::PowerLimit;
 use tokio::sync::mpsc;
-use tuxedo_ioctl::hal::{traits::HardwareDevice, IoctlResult};
+use tuxedo_ioctl::hal::{
+    traits::{HardwareDevice, TdpDevice},
+    IoctlResult,
+};
 
 #[derive(Debug)]
 pub struct PerformanceProfile(String);
@@ -24,19 +31,19 @@ pub struct PerformanceProfileRuntimeHandle {
     pub profile_sender: mpsc::Sender<String>,
     /// Device i/o interface.
     io: Arc<dyn HardwareDevice>,
-    /// Current profile.
-    performance_profile: String,
+    /// Current profile, shared between all clones of the handle.
+    performance_profile: Arc<Mutex<String>>,
 }
 
 impl PerformanceProfileRuntimeHandle {
     pub fn get_availables_performance_profiles(&self) -> IoctlResult<Vec<String>> {
         self.io.get_available_odm_performance_profiles()
     }
-    pub fn set_active_performance_profile(&mut self, name: &str) {
-        self.performance_profile = name.to_string();
+    pub fn set_active_performance_profile(&self, name: &str) {
+        *self.performance_profile.lock().unwrap() = name.to_string();
     }
-    pub fn get_active_performance_profile(&self) -> &str {
-        &self.performance_profile
+    pub fn get_active_performance_profile(&self) -> String {
+        self.performance_profile.lock().unwrap().clone()
     }
 }
 
@@ -60,13 +67,14 @@ impl PerformanceProfileRuntime {
             Some(profile) => profile.to_string(),
             None => default_performance_profile.to_string(),
         };
-        io.set_odm_performance_profile(&performance_profile)
-            .unwrap();
+        if let Err(err) = io.set_odm_performance_profile(&performance_profile) {
+            tracing::error!("Failed to set performance profile `{performance_profile}`: `{err}`");
+        }
         (
             PerformanceProfileRuntimeHandle {
                 profile_sender,
                 io: io.clone(),
-                performance_profile,
+                performance_profile: Arc::new(Mutex::new(performance_profile)),
             },
             PerformanceProfileRuntime {
                 profile_receiver,
@@ -80,7 +88,9 @@ impl PerformanceProfileRuntime {
         loop {
             if let Some(profile) = self.profile_receiver.recv().await {
                 tracing::info!("Loading performance profile {profile}");
-                self.io.set_odm_performance_profile(&profile).unwrap();
+                if let Err(err) = self.io.set_odm_performance_profile(&profile) {
+                    tracing::error!("Failed to set performance profile `{profile}`: `{err}`");
+                }
             } else {
                 tracing::warn!(
                     "Stopping runtime, the performance profile channel sender has probably dropped"
@@ -90,3 +100,32 @@ impl PerformanceProfileRuntime {
         }
     }
 }
+
+/// Read the power limits of the platform.
+pub fn power_limits(tdp: &dyn TdpDevice) -> IoctlResult<Vec<PowerLimit>> {
+    tdp.get_tdp_descriptors()?
+        .into_iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let idx = idx as u8;
+            Ok(PowerLimit {
+                name,
+                min: tdp.get_tdp_min(idx)?,
+                max: tdp.get_tdp_max(idx)?,
+                value: tdp.get_tdp(idx)?,
+            })
+        })
+        .collect()
+}
+
+/// Set the power limits of a profile, values are clamped to the supported range.
+pub fn apply_power_limits(tdp: &dyn TdpDevice, limits: &BTreeMap<String, i32>) -> IoctlResult<()> {
+    for (idx, limit) in power_limits(tdp)?.into_iter().enumerate() {
+        if let Some(value) = limits.get(&limit.name) {
+            let value = (*value).clamp(limit.min, limit.max);
+            tracing::info!("Setting power limit {} to {value}W", limit.name);
+            tdp.set_tdp(idx as u8, value)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Follow the power mode of power-profiles-daemon, which GNOME and KDE
+//! offer in their quick settings, and activate the configured profiles.
+
+use std::time::Duration;
+
+use futures_lite::StreamExt;
+use tailor_api::ProfileTrigger;
+use zbus::{proxy, Connection};
+
+use crate::{dbus::ProfileInterface, profiles::Profile, settings};
+
+#[proxy(
+    interface = "org.freedesktop.UPower.PowerProfiles",
+    default_service = "org.freedesktop.UPower.PowerProfiles",
+    default_path = "/org/freedesktop/UPower/PowerProfiles"
+)]
+trait PowerProfiles {
+    /// `power-saver`, `balanced` or `performance`.
+    #[zbus(property)]
+    fn active_profile(&self) -> zbus::Result<String>;
+}
+
+pub async fn switch_profiles(mut profiles: ProfileInterface) {
+    // Don't try to reconnect anymore after 3 attempts
+    for _ in 0..3 {
+        if let Err(err) = try_switch_profiles(&mut profiles).await {
+            tracing::error!("Failed to follow power-profiles-daemon: `{err}`");
+            // Reconnect after 10s
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        }
+    }
+    tracing::warn!("Stopped following power-profiles-daemon after 3 errors");
+}
+
+async fn try_switch_profiles(profiles: &mut ProfileInterface) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let proxy = PowerProfilesProxy::new(&connection).await?;
+    let mut previous = proxy.active_profile().await?;
+    tracing::info!("Following power-profiles-daemon, the power profile is `{previous}`");
+
+    let mut changes = proxy.receive_active_profile_changed().await;
+    while let Some(change) = changes.next().await {
+        let power_profile = change.get().await?;
+        if power_profile == previous {
+            continue;
+        }
+        tracing::info!("Power profile changed to `{power_profile}`");
+        activate(profiles, &power_profile).await;
+        previous = power_profile;
+    }
+    Ok(())
+}
+
+async fn activate(profiles: &mut ProfileInterface, power_profile: &str) {
+    let Some(name) = settings::power_profile(power_profile) else {
+        return;
+    };
+    if Profile::get_active_profile_name().await.ok().as_ref() == Some(&name) {
+        return;
+    }
+    let result = match Profile::set_active_profile_name(&name).await {
+        Ok(()) => profiles.activate(ProfileTrigger::PowerProfile).await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        tracing::error!("Failed to activate profile `{name}` for `{power_profile}`: `{err}`");
+    }
+}
@@ -0,0 +1,280 @@
+use std::{path::Path, time::Duration};
+
+use futures_lite::StreamExt;
+use once_cell::sync::Lazy;
+use tailor_api::{PowerSource, ProfileTrigger};
+use tokio::sync::{broadcast, watch};
+use zbus::{proxy, Connection};
+
+use crate::{dbus::ProfileInterface, profiles::Profile, settings, suspend};
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// How often the power supplies are checked for changes if UPower isn't available.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+trait UPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+}
+
+/// The combined state of all batteries.
+#[proxy(
+    interface = "org.freedesktop.UPower.Device",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower/devices/DisplayDevice"
+)]
+trait DisplayDevice {
+    #[zbus(property)]
+    fn percentage(&self) -> zbus::Result<f64>;
+}
+
+/// State of the power supplies, published by [`watch_power_supplies`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PowerState {
+    /// `None` if the device has no power adapter that reports its state.
+    pub source: Option<PowerSource>,
+    /// Charge of the first battery in percent.
+    pub battery: Option<u8>,
+}
+
+static POWER_STATE: Lazy<watch::Sender<PowerState>> =
+    Lazy::new(|| watch::channel(PowerState::default()).0);
+
+/// Get notified when the power adapter is plugged in or removed
+/// or the battery level changes.
+pub fn get_power_receiver() -> watch::Receiver<PowerState> {
+    POWER_STATE.subscribe()
+}
+
+/// The battery threshold that was reached and the charge of the battery in percent.
+pub type BatteryThreshold = (u8, u8);
+
+static BATTERY_THRESHOLD_CHANNEL: Lazy<broadcast::Sender<BatteryThreshold>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// Subscribe to the battery dropping to one of the configured thresholds.
+pub fn get_battery_threshold_receiver() -> broadcast::Receiver<BatteryThreshold> {
+    BATTERY_THRESHOLD_CHANNEL.subscribe()
+}
+
+/// The last known power source.
+pub fn current() -> Option<PowerSource> {
+    POWER_STATE.borrow().source
+}
+
+/// Read the power supplies once and publish the result.
+pub async fn refresh() {
+    let state = read_state().await;
+    POWER_STATE.send_if_modified(|current| {
+        let modified = *current != state;
+        if let Some(source) = state.source.filter(|_| current.source != state.source) {
+            tracing::info!("Power source changed to {source}");
+        }
+        if let Some(threshold) = reached_threshold(current, &state, &settings::battery_thresholds())
+        {
+            let battery = state.battery.unwrap_or_default();
+            tracing::warn!("Battery dropped to {battery}%");
+            BATTERY_THRESHOLD_CHANNEL.send((threshold, battery)).ok();
+        }
+        *current = state;
+        modified
+    });
+}
+
+/// The lowest threshold that the battery dropped to since `previous` while
+/// discharging. Thresholds are only reached again after charging above them.
+fn reached_threshold(previous: &PowerState, state: &PowerState, thresholds: &[u8]) -> Option<u8> {
+    let (Some(previous), Some(battery)) = (previous.battery, state.battery) else {
+        return None;
+    };
+    if state.source == Some(PowerSource::Ac) {
+        return None;
+    }
+    thresholds
+        .iter()
+        .copied()
+        .filter(|threshold| (battery..previous).contains(threshold))
+        .min()
+}
+
+/// Watch the power supplies, so other tasks don't need to poll sysfs themselves.
+/// UPower tells when they change, they are only polled without it.
+pub async fn watch_power_supplies() {
+    if let Err(err) = follow_upower().await {
+        tracing::warn!("Failed to follow UPower, polling the power supplies instead: `{err}`");
+    }
+    poll_power_supplies().await;
+}
+
+/// Read the power supplies whenever a property of UPower changes.
+/// Returns once UPower stops.
+async fn follow_upower() -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let upower = UPowerProxy::new(&connection).await?;
+    let display_device = DisplayDeviceProxy::new(&connection).await?;
+    // Fails if UPower isn't installed.
+    upower.on_battery().await?;
+    tracing::info!("Following the power supplies through UPower");
+
+    let mut on_battery = upower.receive_on_battery_changed().await;
+    let mut percentage = display_device.receive_percentage_changed().await;
+    let mut suspend_receiver = suspend::get_suspend_receiver();
+    loop {
+        let changed = tokio::select! {
+            change = on_battery.next() => change.is_some(),
+            change = percentage.next() => change.is_some(),
+            _ = suspend::wait_for_sleep(&mut suspend_receiver) => {
+                suspend::sleep_until_wake_up(&mut suspend_receiver).await;
+                true
+            }
+        };
+        if !changed {
+            break;
+        }
+        refresh().await;
+    }
+    Err(zbus::Error::Failure("UPower stopped".to_owned()))
+}
+
+/// Poll the power supplies. They are read again right after waking up, because
+/// the power adapter might have been plugged in while the system was suspended.
+async fn poll_power_supplies() {
+    let mut suspend_receiver = suspend::get_suspend_receiver();
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = suspend::wait_for_sleep(&mut suspend_receiver) => {
+                suspend::sleep_until_wake_up(&mut suspend_receiver).await;
+            }
+        }
+        refresh().await;
+    }
+}
+
+async fn read_state() -> PowerState {
+    let mut state = PowerState::default();
+    let Ok(mut entries) = tokio::fs::read_dir(POWER_SUPPLY_DIR).await else {
+        return state;
+    };
+    let mut supplies = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        supplies.push(entry.path());
+    }
+    supplies.sort();
+
+    let mut found_adapter = false;
+    for path in supplies {
+        match read_attribute(&path, "type").await.as_deref() {
+            Some("Mains") => match read_attribute(&path, "online").await.as_deref() {
+                Some("1") => state.source = Some(PowerSource::Ac),
+                Some(_) => found_adapter = true,
+                None => {}
+            },
+            Some("Battery") if state.battery.is_none() => {
+                state.battery = read_attribute(&path, "capacity")
+                    .await
+                    .and_then(|capacity| capacity.parse().ok());
+            }
+            _ => {}
+        }
+    }
+    if state.source.is_none() && found_adapter {
+        state.source = Some(PowerSource::Battery);
+    }
+    state
+}
+
+async fn read_attribute(path: &Path, name: &str) -> Option<String> {
+    let value = tokio::fs::read_to_string(path.join(name)).await.ok()?;
+    Some(value.trim().to_owned())
+}
+
+/// Activate the profile configured for the new power source
+/// whenever the power adapter is plugged in or removed.
+pub async fn switch_profiles(mut profiles: ProfileInterface) {
+    let mut receiver = get_power_receiver();
+    let mut previous = receiver.borrow_and_update().source;
+    while receiver.changed().await.is_ok() {
+        // Ignore changes of the battery level.
+        let source = receiver.borrow_and_update().source;
+        if source == previous {
+            continue;
+        }
+        previous = source;
+
+        let Some(source) = source else {
+            continue;
+        };
+        let Some(name) = settings::power_source_profiles()
+            .get(source)
+            .map(ToOwned::to_owned)
+        else {
+            continue;
+        };
+
+        if Profile::get_active_profile_name().await.ok().as_ref() == Some(&name) {
+            continue;
+        }
+        let result = match Profile::set_active_profile_name(&name).await {
+            Ok(()) => profiles.activate(ProfileTrigger::PowerSource).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            tracing::error!("Failed to activate profile `{name}` for {source}: `{err}`");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::PowerSource;
+
+    use super::{reached_threshold, PowerState};
+
+    #[test]
+    fn battery_thresholds() {
+        let state = |source, battery| PowerState {
+            source: Some(source),
+            battery: Some(battery),
+        };
+        let thresholds = [20, 10, 5];
+
+        let discharging = state(PowerSource::Battery, 21);
+        assert_eq!(
+            reached_threshold(&discharging, &state(PowerSource::Battery, 20), &thresholds),
+            Some(20)
+        );
+        // Only the lowest threshold is reported if the level dropped past several.
+        assert_eq!(
+            reached_threshold(&discharging, &state(PowerSource::Battery, 9), &thresholds),
+            Some(10)
+        );
+        assert_eq!(
+            reached_threshold(
+                &state(PowerSource::Battery, 20),
+                &state(PowerSource::Battery, 19),
+                &thresholds
+            ),
+            None
+        );
+        // Charging doesn't reach thresholds, in either direction.
+        assert_eq!(
+            reached_threshold(&discharging, &state(PowerSource::Ac, 20), &thresholds),
+            None
+        );
+        assert_eq!(
+            reached_threshold(
+                &state(PowerSource::Ac, 19),
+                &state(PowerSource::Ac, 21),
+                &thresholds
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Privilege separation.
+//!
+//! If a user is configured, tailord forks a broker at startup that keeps
+//! running as root. Once the hardware is initialized, tailord continues as
+//! that user without any capabilities. The ioctl device and the LED and
+//! charging files stay usable because they were opened before. The few
+//! sysfs attributes that are opened on demand are written by the broker,
+//! which refuses to write anything else.
+//!
+//! The profile directories are handed over to the user, but the settings
+//! and the active profile link stay owned by root, because root reads them
+//! at the next start. The broker changes them on behalf of tailord and only
+//! stores the settings that clients can change, never paths or the user.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use nix::{
+    fcntl::AtFlags,
+    sys::prctl,
+    unistd::{self, ForkResult, Gid, Uid, User},
+};
+use serde::{Deserialize, Serialize};
+use tuxedo_ioctl::hal::attribute;
+
+use crate::{
+    error,
+    profiles::{Profile, FAN_DIR, KEYBOARD_DIR, PROFILE_DIR},
+    settings::Settings,
+};
+
+/// Attributes the broker writes on behalf of tailord, besides `pwmN` and `pwmN_enable`.
+const ATTRIBUTES: [&str; 5] = [
+    // Fn-lock of tuxedo_keyboard.
+    "fn_lock",
+    // Touchpad switch of input devices.
+    "inhibited",
+    // intel_powerclamp cooling device.
+    "cur_state",
+    // cpufreq policies.
+    "scaling_governor",
+    "energy_performance_preference",
+];
+
+#[derive(Serialize, Deserialize)]
+enum Request {
+    /// Write a sysfs attribute.
+    Attribute { path: PathBuf, value: String },
+    /// Store the settings that clients can change, see [`Settings::store_runtime`].
+    Settings(Box<Settings>),
+    /// Point the active profile at the profile called `name`.
+    ActiveProfile { name: String },
+}
+
+type Response = Result<(), String>;
+
+/// Connection to the broker once the privileges were dropped.
+static BROKER: OnceLock<Mutex<BufReader<UnixStream>>> = OnceLock::new();
+
+/// Connection to the privileged broker process.
+#[derive(Debug)]
+pub struct Broker {
+    stream: UnixStream,
+}
+
+impl Broker {
+    /// Fork the broker process.
+    ///
+    /// Must be called before any threads are started,
+    /// because only the calling thread survives the fork.
+    pub fn spawn() -> io::Result<Self> {
+        let (stream, broker_stream) = UnixStream::pair()?;
+        // SAFETY: tailord is still single-threaded at this point.
+        match unsafe { unistd::fork() }? {
+            ForkResult::Child => {
+                drop(stream);
+                serve(broker_stream);
+                std::process::exit(0);
+            }
+            ForkResult::Parent { child } => {
+                tracing::info!("Started privileged broker with PID {child}");
+                Ok(Self { stream })
+            }
+        }
+    }
+
+    /// Continue as `user` and send attribute writes to the broker from now on.
+    /// The profile directories are handed over to the user as well.
+    pub fn drop_privileges(self, user: &str) -> io::Result<()> {
+        let user = User::from_name(user)?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("Unknown user `{user}`"))
+        })?;
+
+        for dir in [PROFILE_DIR, KEYBOARD_DIR, FAN_DIR] {
+            chown_recursive(Path::new(dir), user.uid, user.gid)?;
+        }
+
+        prctl::set_no_new_privs()?;
+        unistd::setgroups(&[user.gid])?;
+        unistd::setgid(user.gid)?;
+        // Also clears all capabilities.
+        unistd::setuid(user.uid)?;
+
+        BROKER
+            .set(Mutex::new(BufReader::new(self.stream)))
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Privileges were already dropped"))?;
+        attribute::set_writer(Box::new(|path, value| {
+            request(&Request::Attribute {
+                path: path.to_owned(),
+                value: value.to_owned(),
+            })
+        }));
+        tracing::info!("Dropped root privileges, running as `{}`", user.name);
+        Ok(())
+    }
+}
+
+/// Store the settings that clients can change, through the broker if there is one.
+pub fn store_settings(settings: &Settings) -> error::Result<()> {
+    match BROKER.get() {
+        Some(_) => Ok(request(&Request::Settings(Box::new(settings.clone())))?),
+        None => settings.store_runtime(),
+    }
+}
+
+/// Point the active profile at `name`, through the broker if there is one.
+pub fn set_active_profile(name: &str) -> error::Result<()> {
+    match BROKER.get() {
+        Some(_) => Ok(request(&Request::ActiveProfile {
+            name: name.to_owned(),
+        })?),
+        None => Profile::link_active_profile(name),
+    }
+}
+
+fn request(request: &Request) -> io::Result<()> {
+    let Some(stream) = BROKER.get() else {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "The privileged broker isn't running",
+        ));
+    };
+    let mut stream = stream.lock().unwrap();
+    let mut line = serde_json::to_string(request).unwrap();
+    line.push('\n');
+    stream.get_mut().write_all(line.as_bytes())?;
+
+    line.clear();
+    if stream.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            "The privileged broker stopped",
+        ));
+    }
+    let response: Response = serde_json::from_str(&line)?;
+    response.map_err(|err| io::Error::new(io::ErrorKind::PermissionDenied, err))
+}
+
+/// Handle requests until tailord closes the connection.
+fn serve(stream: UnixStream) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(err) => {
+            tracing::error!("Failed to set up the privileged broker: `{err}`");
+            return;
+        }
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let response: Response = serde_json::from_str(&line)
+            .map_err(|err| err.to_string())
+            .and_then(handle);
+        let mut line = serde_json::to_string(&response).unwrap();
+        line.push('\n');
+        if writer.write_all(line.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+fn handle(request: Request) -> Response {
+    match request {
+        Request::Attribute { path, value } => write_attribute(&path, &value),
+        Request::Settings(settings) => settings.store_runtime().map_err(|err| err.to_string()),
+        Request::ActiveProfile { name } => {
+            Profile::link_active_profile(&name).map_err(|err| err.to_string())
+        }
+    }
+}
+
+fn write_attribute(path: &Path, value: &str) -> Response {
+    let path = path
+        .canonicalize()
+        .map_err(|err| format!("Invalid path `{}`: {err}", path.display()))?;
+    if !is_allowed(&path) {
+        tracing::warn!("Refused to write to `{}`", path.display());
+        return Err(format!("Writing to `{}` isn't allowed", path.display()));
+    }
+    fs::write(&path, value).map_err(|err| err.to_string())
+}
+
+/// Whether the broker may write to the canonical `path`.
+fn is_allowed(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    // `pwmN` of hwmon or `fanN_pwm` of the platform drivers.
+    let attribute = name.strip_suffix("_enable").unwrap_or(name);
+    let is_pwm = attribute
+        .strip_prefix("pwm")
+        .or_else(|| attribute.strip_prefix("fan")?.strip_suffix("_pwm"))
+        .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()));
+
+    path.starts_with("/sys") && (is_pwm || ATTRIBUTES.contains(&name) || is_backlight(path))
+}
+
+/// Whether `path` is the brightness of a display backlight. LEDs have
+/// `brightness` attributes as well, but those stay with tailord.
+fn is_backlight(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "brightness")
+        && path
+            .parent()
+            .and_then(|device| fs::canonicalize(device.join("subsystem")).ok())
+            .is_some_and(|subsystem| subsystem == Path::new("/sys/class/backlight"))
+}
+
+fn chown_recursive(path: &Path, uid: Uid, gid: Gid) -> io::Result<()> {
+    // Don't follow symlinks like the active profile.
+    unistd::fchownat(
+        None,
+        path,
+        Some(uid),
+        Some(gid),
+        AtFlags::AT_SYMLINK_NOFOLLOW,
+    )?;
+    if path.is_dir() && !path.is_symlink() {
+        for entry in fs::read_dir(path)? {
+            chown_recursive(&entry?.path(), uid, gid)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::is_allowed;
+
+    #[test]
+    fn allowed_attributes() {
+        assert!(is_allowed(Path::new(
+            "/sys/devices/platform/tuxedo_keyboard/fn_lock"
+        )));
+        assert!(is_allowed(Path::new(
+            "/sys/devices/platform/nct6775.656/hwmon/hwmon3/pwm2_enable"
+        )));
+        assert!(is_allowed(Path::new(
+            "/sys/devices/platform/uniwill/fan1_pwm_enable"
+        )));
+        assert!(is_allowed(Path::new(
+            "/sys/devices/virtual/thermal/cooling_device20/cur_state"
+        )));
+
+        assert!(!is_allowed(Path::new("/etc/fn_lock")));
+        assert!(!is_allowed(Path::new("/sys/class/hwmon/hwmon3/pwm")));
+        assert!(!is_allowed(Path::new("/sys/class/hwmon/hwmon3/pwm1_mode")));
+        assert!(!is_allowed(Path::new("/sys/class/hwmon/hwmon3/fan1_min")));
+        assert!(!is_allowed(Path::new("/sys/class/hwmon/hwmon3/fan1")));
+        assert!(!is_allowed(Path::new("/sys/power/state")));
+        assert!(!is_allowed(Path::new(
+            "/sys/devices/platform/tuxedo_keyboard/leds/rgb:kbd_backlight/brightness"
+        )));
+    }
+}
@@ -1,8 +1,19 @@
-use std::{collections::HashMap, path::Component, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Component,
+    path::Path,
+};
 
-use crate::{fancontrol::profile::FanProfile, performance::PerformanceProfile};
-use tailor_api::{ColorProfile, LedControllerMode, LedDeviceInfo, LedProfile, ProfileInfo};
-use zbus::fdo;
+use crate::{
+    cpufreq::CpuFreq,
+    error::{self, TailorError},
+    fancontrol::profile::FanProfile,
+    performance::PerformanceProfile,
+    privileges,
+};
+use once_cell::sync::Lazy;
+use tailor_api::{ColorProfile, LedControllerMode, LedDeviceInfo, ProfileInfo};
+use tokio::sync::watch;
 
 use super::util;
 
@@ -12,6 +23,18 @@ pub const KEYBOARD_DIR: &str = "/etc/tailord/keyboard/";
 pub const FAN_DIR: &str = "/etc/tailord/fan/";
 pub const ACTIVE_PROFILE_PATH: &str = "/etc/tailord/active_profile.json";
 
+static POWER_LIMITS_ALLOWED: Lazy<watch::Sender<bool>> = Lazy::new(|| watch::channel(true).0);
+
+/// Whether the active profile allows tailord to apply power limits.
+/// Power-limit control can be switched off for all profiles as subsystem, too.
+pub fn power_limits_allowed() -> bool {
+    *POWER_LIMITS_ALLOWED.borrow()
+}
+
+pub fn allow_power_limits(allowed: bool) {
+    POWER_LIMITS_ALLOWED.send_replace(allowed);
+}
+
 fn init_paths() {
     [PROFILE_DIR, KEYBOARD_DIR, FAN_DIR]
         .into_iter()
@@ -56,35 +79,93 @@ fn init_profiles_if_necessary(supported_features: SupportedFeatures) {
     }
 }
 
-fn led_profile_path(name: &str) -> fdo::Result<String> {
+fn led_profile_path(name: &str) -> error::Result<String> {
     util::normalize_json_path(KEYBOARD_DIR, name)
 }
 
-fn fan_path(name: &str) -> fdo::Result<String> {
+fn fan_path(name: &str) -> error::Result<String> {
     util::normalize_json_path(FAN_DIR, name)
 }
 
-fn load_led_profile(name: &str) -> fdo::Result<ColorProfile> {
+fn load_led_profile(name: &str) -> error::Result<ColorProfile> {
     let color_profile_data = std::fs::read(led_profile_path(name)?)
-        .map_err(|err| fdo::Error::IOError(err.to_string()))?;
+        .map_err(|err| TailorError::from_profile_io(err, name))?;
     serde_json::from_slice(&color_profile_data)
-        .map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))
+        .map_err(|err| TailorError::InvalidFileContent(err.to_string()))
 }
 
-fn load_fan_profile(name: &str) -> fdo::Result<FanProfile> {
+/// Devices whose profile can't be loaded are left out, so they get the default.
+fn load_led_profiles(
+    profile_info: &ProfileInfo,
+    load: impl Fn(&str) -> error::Result<ColorProfile>,
+) -> LedProfiles {
+    let mut leds = HashMap::new();
+    for led in &profile_info.leds {
+        match load(&led.profile) {
+            Ok(profile) => {
+                leds.insert(led.device_id(), profile);
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to load keyboard color profile called `{}`: `{}`",
+                    led.device_id(),
+                    err.to_string(),
+                );
+            }
+        }
+    }
+    LedProfiles(leds)
+}
+
+pub fn load_fan_profile(name: &str) -> error::Result<FanProfile> {
     FanProfile::load_config(fan_path(name)?)
 }
 
+/// The fan profile of the fan at `idx`.
+///
+/// Profiles can list fewer fans than the device has, for example if they
+/// were created on another device. The remaining fans use the last listed profile.
+pub fn fan_profile(fans: &[FanProfile], idx: usize) -> FanProfile {
+    fans.get(idx).or(fans.last()).cloned().unwrap_or_default()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SupportedFeatures {
     pub mode: LedControllerMode,
 }
 
+/// The LED profiles of a profile by device id.
+///
+/// The mode isn't part of the key: profiles in the map form of
+/// [`ProfileInfo`] don't store it, so it's taken from the detected device.
+#[derive(Debug, Default)]
+pub struct LedProfiles(HashMap<String, ColorProfile>);
+
+impl LedProfiles {
+    /// The color profile of `info`, or the default for its mode.
+    pub fn get(&self, info: &LedDeviceInfo) -> ColorProfile {
+        self.0
+            .get(&info.device_id())
+            .cloned()
+            .unwrap_or_else(|| ColorProfile::default(info.mode))
+    }
+}
+
 #[derive(Debug)]
 pub struct Profile {
     pub fans: Vec<FanProfile>,
-    pub leds: HashMap<LedDeviceInfo, ColorProfile>,
+    pub leds: LedProfiles,
     pub performance_profile: Option<PerformanceProfile>,
+    pub webcam: Option<bool>,
+    pub fn_lock: Option<bool>,
+    pub touchpad: Option<bool>,
+    /// Power limits in watts by name.
+    pub tdp: BTreeMap<String, i32>,
+    pub cpufreq: CpuFreq,
+    /// Display brightness in percent.
+    pub display_brightness: Option<u8>,
+    /// Whether power limits may be applied, see [`power_limits_allowed`].
+    pub power_limits: bool,
 }
 
 impl Profile {
@@ -94,40 +175,51 @@ impl Profile {
     }
 
     pub fn load() -> Self {
+        let profile_info = Self::load_active_profile_info();
+        let fans = Self::load_fan_profiles(&profile_info);
+        let leds = load_led_profiles(&profile_info, load_led_profile);
+        let performance_profile = profile_info
+            .performance_profile
+            .map(PerformanceProfile::new);
+
+        Self {
+            fans,
+            leds,
+            performance_profile,
+            webcam: profile_info.webcam,
+            fn_lock: profile_info.fn_lock,
+            touchpad: profile_info.touchpad,
+            tdp: profile_info.tdp,
+            cpufreq: CpuFreq {
+                governor: profile_info.cpu_governor,
+                energy_performance_preference: profile_info.energy_performance_preference,
+            },
+            display_brightness: profile_info.display_brightness,
+            power_limits: !profile_info.disable_power_limits,
+        }
+    }
+
+    /// Load only the fan profiles of the active profile.
+    pub fn load_fans() -> Vec<FanProfile> {
+        Self::load_fan_profiles(&Self::load_active_profile_info())
+    }
+
+    /// Load only the LED profiles of the active profile.
+    pub fn load_leds() -> LedProfiles {
+        load_led_profiles(&Self::load_active_profile_info(), load_led_profile)
+    }
+
+    fn load_active_profile_info() -> ProfileInfo {
         let profile_info = Self::get_active_profile_info().unwrap_or_else(|err| {
             tracing::warn!("Failed to load active profile at `{ACTIVE_PROFILE_PATH}`: {err:?}");
             ProfileInfo::default()
         });
         tracing::info!("Loaded profile at `{ACTIVE_PROFILE_PATH}`: {profile_info:?}");
+        profile_info
+    }
 
-        let mut led = HashMap::new();
-        for data in profile_info.leds {
-            let LedProfile {
-                device_name,
-                function,
-                profile,
-                mode,
-            } = data;
-            let info = LedDeviceInfo {
-                device_name,
-                function,
-                mode,
-            };
-            let profile = match load_led_profile(&profile) {
-                Ok(keyboard) => keyboard,
-                Err(err) => {
-                    tracing::warn!(
-                        "Failed to load keyboard color profile called `{}`: `{}`",
-                        info.device_id(),
-                        err.to_string(),
-                    );
-                    ColorProfile::default(mode)
-                }
-            };
-            led.insert(info, profile);
-        }
-
-        let fan = profile_info
+    fn load_fan_profiles(profile_info: &ProfileInfo) -> Vec<FanProfile> {
+        profile_info
             .fans
             .iter()
             .map(|fan_profile| match load_fan_profile(fan_profile) {
@@ -141,52 +233,86 @@ impl Profile {
                     FanProfile::default()
                 }
             })
-            .collect();
-
-        let performance_profile = profile_info
-            .performance_profile
-            .map(PerformanceProfile::new);
+            .collect()
+    }
 
-        Self {
-            fans: fan,
-            leds: led,
-            performance_profile,
-        }
+    pub async fn set_active_profile_name(name: &str) -> error::Result<()> {
+        std::fs::metadata(util::normalize_json_path(PROFILE_DIR, name)?)
+            .map_err(|err| TailorError::from_profile_io(err, name))?;
+        privileges::set_active_profile(name)
     }
 
-    pub async fn set_active_profile_name(name: &str) -> fdo::Result<()> {
+    /// Replace the link of the active profile, see [`privileges::set_active_profile`].
+    pub fn link_active_profile(name: &str) -> error::Result<()> {
         std::fs::metadata(util::normalize_json_path(PROFILE_DIR, name)?)
-            .map_err(|_| fdo::Error::FileNotFound(format!("Couldn't find profile `{name}`")))?;
+            .map_err(|err| TailorError::from_profile_io(err, name))?;
 
-        std::fs::remove_file(ACTIVE_PROFILE_PATH)
-            .map_err(|err| fdo::Error::IOError(err.to_string()))?;
+        std::fs::remove_file(ACTIVE_PROFILE_PATH)?;
         std::os::unix::fs::symlink(
             util::normalize_json_path("profiles", name)?,
             ACTIVE_PROFILE_PATH,
         )
-        .map_err(|err| fdo::Error::IOError(err.to_string()))
+        .map_err(TailorError::from)
     }
 
-    pub async fn get_active_profile_name() -> fdo::Result<String> {
-        let link = std::fs::read_link(ACTIVE_PROFILE_PATH)
-            .map_err(|err| fdo::Error::IOError(err.to_string()))?;
-        let components: Vec<Component> = link.components().collect();
-        if !components.is_empty() {
-            if let Component::Normal(name) = components.last().unwrap() {
-                if let Some(name) = name.to_str() {
-                    return Ok(name.trim_end_matches(".json").to_string());
-                }
+    pub async fn get_active_profile_name() -> error::Result<String> {
+        let link = std::fs::read_link(ACTIVE_PROFILE_PATH)?;
+        if let Some(Component::Normal(name)) = link.components().next_back() {
+            if let Some(name) = name.to_str() {
+                return Ok(name.trim_end_matches(".json").to_string());
             }
         }
 
-        Err(fdo::Error::InvalidFileContent(
+        Err(TailorError::InvalidFileContent(
             "The active profile isn't set correctly".to_string(),
         ))
     }
 
-    pub fn get_active_profile_info() -> fdo::Result<ProfileInfo> {
-        let data = std::fs::read(ACTIVE_PROFILE_PATH)
-            .map_err(|err| fdo::Error::IOError(err.to_string()))?;
-        serde_json::from_slice(&data).map_err(|err| fdo::Error::InvalidFileContent(err.to_string()))
+    pub fn get_active_profile_info() -> error::Result<ProfileInfo> {
+        let data = std::fs::read(ACTIVE_PROFILE_PATH)?;
+        serde_json::from_slice(&data)
+            .map_err(|err| TailorError::InvalidFileContent(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{Color, ColorProfile, LedControllerMode, LedDeviceInfo, ProfileInfo};
+
+    use crate::error::TailorError;
+
+    use super::load_led_profiles;
+
+    #[test]
+    fn led_map_monochrome() {
+        let profile_info: ProfileInfo = serde_json::from_str(
+            r#"{
+                "fans": [],
+                "leds": { "white:kbd_backlight::kbd_backlight": "dim" },
+                "performance_profile": null
+            }"#,
+        )
+        .unwrap();
+        let dim = ColorProfile::Single(Color {
+            r: 80,
+            g: 80,
+            b: 80,
+        });
+        let leds = load_led_profiles(&profile_info, |name| match name {
+            "dim" => Ok(dim.clone()),
+            _ => Err(TailorError::ProfileNotFound(name.to_owned())),
+        });
+
+        let mut device = LedDeviceInfo {
+            device_name: "white:kbd_backlight".to_owned(),
+            function: "kbd_backlight".to_owned(),
+            mode: LedControllerMode::Monochrome,
+            capabilities: Default::default(),
+        };
+        // The map form doesn't store the mode of the device.
+        assert_eq!(leds.get(&device), dim);
+
+        device.function = "lightbar".to_owned();
+        assert_eq!(leds.get(&device), ColorProfile::None);
     }
 }
@@ -0,0 +1,65 @@
+//! Reload the settings and the active profile on SIGHUP, so package scripts
+//! and configuration management tools don't need a D-Bus client.
+
+use futures::StreamExt;
+use signal_hook::consts::SIGHUP;
+use signal_hook_tokio::Signals;
+use tailor_api::{ChargingSettings, ProfileTrigger};
+
+use crate::{
+    charging::ChargingHandle, dbus::ProfileInterface, profiles::Profile, settings::Settings,
+};
+
+pub fn spawn(profiles: ProfileInterface, charging: Option<ChargingHandle>) {
+    let signals = match Signals::new([SIGHUP]) {
+        Ok(signals) => signals,
+        Err(err) => {
+            tracing::error!("Failed to register the SIGHUP handler: `{err}`");
+            return;
+        }
+    };
+    tokio::task::spawn_local(handle_signals(signals, profiles, charging));
+}
+
+#[tracing::instrument(skip_all)]
+async fn handle_signals(
+    mut signals: Signals,
+    mut profiles: ProfileInterface,
+    charging: Option<ChargingHandle>,
+) {
+    while signals.next().await.is_some() {
+        tracing::info!("Received SIGHUP, reloading the configuration");
+        reload_settings(charging.as_ref()).await;
+        reload_profile(&mut profiles).await;
+    }
+}
+
+/// Only the subsystems, the power source profiles and the charging settings
+/// are applied at runtime. The other settings require a restart.
+async fn reload_settings(charging: Option<&ChargingHandle>) {
+    let settings = match Settings::reload() {
+        Ok(settings) => settings,
+        Err(err) => {
+            tracing::error!("Failed to reload the settings, keeping the current ones: `{err:?}`");
+            return;
+        }
+    };
+    if let Some(charging) = charging {
+        if settings.charging != ChargingSettings::default() {
+            if let Err(err) = charging.set(settings.charging).await {
+                tracing::error!("Failed to apply the charging settings: `{err:?}`");
+            }
+        }
+    }
+}
+
+async fn reload_profile(profiles: &mut ProfileInterface) {
+    // Loading the profile falls back to defaults, so check it first.
+    if let Err(err) = Profile::get_active_profile_info() {
+        tracing::error!("Failed to load the active profile, keeping the current one: `{err:?}`");
+        return;
+    }
+    if let Err(err) = profiles.activate(ProfileTrigger::Reload).await {
+        tracing::error!("Failed to activate the reloaded profile: `{err:?}`");
+    }
+}
@@ -0,0 +1,275 @@
+//! JSON-RPC 2.0 server on a Unix socket for systems without a D-Bus system bus.
+//!
+//! Requests and responses are newline-delimited JSON objects.
+//! The methods mirror the D-Bus API and use the full D-Bus member name,
+//! e.g. `com.tux.Tailor.Fan.BoostFans` with positional parameters.
+//! Profile payloads always use the newest wire format (see `tailor_api::wire`),
+//! so `NegotiateWireVersion` isn't available.
+//! Access is controlled by the file permissions of the socket.
+
+use std::{
+    fs::{DirBuilder, Permissions},
+    os::unix::fs::{DirBuilderExt, PermissionsExt},
+    path::Path,
+    rc::Rc,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::Mutex,
+};
+use zbus::DBusError;
+
+use crate::{
+    dbus::{
+        ChargingInterface, FanInterface, FnLockInterface, LedInterface, PerformanceInterface,
+        ProfileInterface, SettingsInterface, TouchpadInterface, WebcamInterface,
+    },
+    error::{self, TailorError},
+    settings::RpcSocketSettings,
+};
+
+/// The interfaces served over the socket.
+pub struct RpcServer {
+    pub fan: FanInterface,
+    pub led: LedInterface,
+    pub profiles: ProfileInterface,
+    pub performance: PerformanceInterface,
+    pub settings: SettingsInterface,
+    pub webcam: WebcamInterface,
+    pub fn_lock: FnLockInterface,
+    pub touchpad: TouchpadInterface,
+    pub charging: ChargingInterface,
+}
+
+impl RpcServer {
+    async fn call(&mut self, method: &str, params: Params) -> Result<Value, RpcError> {
+        let (interface, member) = method.rsplit_once('.').ok_or(RpcError::MethodNotFound)?;
+        match interface {
+            "com.tux.Tailor.Fan" => self.fan.rpc_call(member, params).await,
+            "com.tux.Tailor.Led" => self.led.rpc_call(member, params).await,
+            "com.tux.Tailor.Profiles" => self.profiles.rpc_call(member, params).await,
+            "com.tux.Tailor.Performance" => self.performance.rpc_call(member, params).await,
+            "com.tux.Tailor.Settings" => self.settings.rpc_call(member, params).await,
+            "com.tux.Tailor.Webcam" => self.webcam.rpc_call(member, params).await,
+            "com.tux.Tailor.FnLock" => self.fn_lock.rpc_call(member, params).await,
+            "com.tux.Tailor.Touchpad" => self.touchpad.rpc_call(member, params).await,
+            "com.tux.Tailor.Charging" => self.charging.rpc_call(member, params).await,
+            _ => Err(RpcError::MethodNotFound),
+        }
+    }
+}
+
+/// Positional parameters of a call.
+pub struct Params(Vec<Value>);
+
+impl Params {
+    pub fn get<T: DeserializeOwned>(&self, idx: usize) -> Result<T, RpcError> {
+        let value = self
+            .0
+            .get(idx)
+            .ok_or_else(|| RpcError::InvalidParams(format!("Missing parameter {idx}")))?;
+        serde_json::from_value(value.clone())
+            .map_err(|err| RpcError::InvalidParams(format!("Parameter {idx}: {err}")))
+    }
+}
+
+pub enum RpcError {
+    InvalidRequest(String),
+    MethodNotFound,
+    InvalidParams(String),
+    Tailor(TailorError),
+}
+
+impl RpcError {
+    fn to_json(&self) -> Value {
+        match self {
+            Self::InvalidRequest(message) => json!({ "code": -32600, "message": message }),
+            Self::MethodNotFound => json!({ "code": -32601, "message": "Method not found" }),
+            Self::InvalidParams(message) => json!({ "code": -32602, "message": message }),
+            Self::Tailor(err) => json!({
+                "code": -32000,
+                "message": err.description().unwrap_or_default(),
+                "data": { "name": err.name().as_str() },
+            }),
+        }
+    }
+}
+
+/// Convert the result of an interface method to a JSON-RPC result.
+pub fn to_value<T: Serialize>(result: error::Result<T>) -> Result<Value, RpcError> {
+    result
+        .map(|value| serde_json::to_value(value).unwrap())
+        .map_err(RpcError::Tailor)
+}
+
+/// Start listening on the socket configured in `settings`.
+pub fn spawn(settings: &RpcSocketSettings, server: RpcServer) {
+    let listener = match bind(settings) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::error!("Failed to open RPC socket at `{}`: `{err}`", settings.path);
+            return;
+        }
+    };
+    tracing::info!("Listening for JSON-RPC requests at `{}`", settings.path);
+
+    let server = Rc::new(Mutex::new(server));
+    tokio::task::spawn_local(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::task::spawn_local(handle_client(stream, server.clone()));
+                }
+                Err(err) => tracing::error!("Failed to accept RPC connection: `{err}`"),
+            }
+        }
+    });
+}
+
+fn bind(settings: &RpcSocketSettings) -> std::io::Result<UnixListener> {
+    let path = Path::new(&settings.path);
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent)?;
+    // Remove the socket of a previous run.
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+
+    let mode = u32::from_str_radix(&settings.mode, 8).map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid socket mode `{}`: {err}", settings.mode),
+        )
+    })?;
+    // The socket is created in a directory only the owner can enter and moved
+    // into place once it has the configured mode, so nobody can connect before.
+    let private = parent.join(".tailord-rpc");
+    if private.exists() {
+        std::fs::remove_dir_all(&private)?;
+    }
+    DirBuilder::new().mode(0o700).create(&private)?;
+    let listener = bind_private(&private.join("rpc.sock"), path, mode);
+    std::fs::remove_dir_all(&private).ok();
+    listener
+}
+
+fn bind_private(private: &Path, path: &Path, mode: u32) -> std::io::Result<UnixListener> {
+    let listener = UnixListener::bind(private)?;
+    std::fs::set_permissions(private, Permissions::from_mode(mode))?;
+    std::fs::rename(private, path)?;
+    Ok(listener)
+}
+
+async fn handle_client(stream: UnixStream, server: Rc<Mutex<RpcServer>>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_request(&line, &server).await {
+            let mut data = response.to_string();
+            data.push('\n');
+            if writer.write_all(data.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Handle a single request. Notifications (requests without id) get no response.
+async fn handle_request(line: &str, server: &Mutex<RpcServer>) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => {
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "error": { "code": -32700, "message": err.to_string() },
+                "id": Value::Null,
+            }))
+        }
+    };
+
+    let id = request.get("id").cloned();
+    let result = match parse_request(&request) {
+        Ok((method, params)) => server.lock().await.call(method, params).await,
+        // Invalid requests are answered even without id, as it can't be told
+        // whether they were meant as notifications.
+        Err(err) => {
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "error": err.to_json(),
+                "id": id.unwrap_or(Value::Null),
+            }))
+        }
+    };
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(err) => json!({ "jsonrpc": "2.0", "error": err.to_json(), "id": id }),
+    })
+}
+
+/// The method and parameters of a request object.
+fn parse_request(request: &Value) -> Result<(&str, Params), RpcError> {
+    if !request.is_object() {
+        return Err(RpcError::InvalidRequest(
+            "The request must be an object".to_owned(),
+        ));
+    }
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::InvalidRequest("Missing method".to_owned()))?;
+    let params = match request.get("params") {
+        None => Vec::new(),
+        Some(Value::Array(params)) => params.clone(),
+        Some(Value::Object(_)) => {
+            return Err(RpcError::InvalidParams(
+                "Only positional parameters are supported".to_owned(),
+            ))
+        }
+        Some(_) => {
+            return Err(RpcError::InvalidRequest(
+                "The parameters must be an array".to_owned(),
+            ))
+        }
+    };
+    Ok((method, Params(params)))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{parse_request, RpcError};
+
+    fn code(request: serde_json::Value) -> Option<i64> {
+        parse_request(&request)
+            .err()
+            .map(|err| err.to_json()["code"].as_i64().unwrap())
+    }
+
+    #[test]
+    fn invalid_requests() {
+        assert_eq!(
+            code(json!({ "method": "com.tux.Tailor.Fan.GetStatus" })),
+            None
+        );
+        assert_eq!(code(json!([1, 2])), Some(-32600));
+        assert_eq!(code(json!({ "id": 1 })), Some(-32600));
+        assert_eq!(code(json!({ "method": 1, "id": 1 })), Some(-32600));
+        assert_eq!(code(json!({ "method": "a.B", "params": 1 })), Some(-32600));
+        assert_eq!(code(json!({ "method": "a.B", "params": {} })), Some(-32602));
+        assert_eq!(RpcError::MethodNotFound.to_json()["code"], -32601);
+    }
+}
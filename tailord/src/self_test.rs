@@ -0,0 +1,127 @@
+//! Probe the hardware at startup, so tailord can continue without the
+//! parts that don't work and clients can show what's broken.
+
+use std::{fmt::Display, fs};
+
+use once_cell::sync::OnceCell;
+use tailor_api::{SelfTestCheck, SelfTestResult};
+use tuxedo_ioctl::hal::traits::HardwareDevice;
+use tuxedo_sysfs::led::Controller;
+
+use crate::{charging::ChargingHandle, settings};
+
+static REPORT: OnceCell<Vec<SelfTestCheck>> = OnceCell::new();
+
+/// The checks of the self-test at startup.
+pub fn report() -> &'static [SelfTestCheck] {
+    REPORT.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+#[derive(Default)]
+pub struct SelfTest {
+    checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTest {
+    /// Read the temperature and speed of a fan and write the speed back.
+    /// The speed is only written if tailord controls the fans, writing
+    /// it takes the fan over from the firmware or another daemon.
+    pub fn fan(&mut self, device: &dyn HardwareDevice, fan_idx: u8) {
+        let speed = device
+            .get_fan_temperature(fan_idx)
+            .and_then(|_| device.get_fan_speed_percent(fan_idx));
+        self.check(format!("fan{fan_idx}_read"), speed.as_ref().map(|_| ()));
+        match speed {
+            Ok(_) if !settings::subsystems().fan_control => {
+                self.record(format!("fan{fan_idx}_write"), SelfTestResult::Skipped);
+            }
+            Ok(speed) => {
+                // Writing the current speed doesn't change anything.
+                let write = device.set_fan_speed_percent(fan_idx, speed);
+                self.check(format!("fan{fan_idx}_write"), write);
+            }
+            Err(_) => {
+                self.record(format!("fan{fan_idx}_write"), SelfTestResult::Unavailable);
+            }
+        }
+    }
+
+    /// Read the color of an LED device. Returns whether it works.
+    pub async fn led(&mut self, controller: &mut Controller) -> bool {
+        let capability = format!("led_{}:{}", controller.device_name, controller.function);
+        let result = controller.get_color().await.map(|_| ());
+        self.check(capability, result)
+    }
+
+    /// Read the cooling devices used for the power limits of fan profiles.
+    pub fn cooling_devices(&mut self) {
+        let fan_control = settings::fan_control();
+        match fan_control.powerclamp_path {
+            Some(path) if path.exists() => {
+                let result = fs::read_to_string(&path).map(|_| ());
+                self.check("powerclamp", result);
+            }
+            _ => self.record("powerclamp", SelfTestResult::Unavailable),
+        }
+        for device in fan_control.cooling_devices {
+            // Named after the directory like `cooling_device3`.
+            let name = device
+                .path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .unwrap_or_default()
+                .to_string_lossy();
+            let result = fs::read_to_string(&device.path).map(|_| ());
+            self.check(format!("cooling:{name}"), result);
+        }
+    }
+
+    /// Read the charging settings of the battery.
+    pub async fn charging(&mut self, handle: Option<&ChargingHandle>) {
+        match handle {
+            Some(handle) => {
+                let result = handle.get().await.map(|_| ());
+                self.check("charging", result.map_err(|err| format!("{err:?}")));
+            }
+            None => self.record("charging", SelfTestResult::Unavailable),
+        }
+    }
+
+    /// Log the failed checks and publish the report.
+    pub fn finish(self) {
+        let failed: Vec<&str> = self
+            .checks
+            .iter()
+            .filter(|check| check.failed())
+            .map(|check| check.capability.as_str())
+            .collect();
+        if failed.is_empty() {
+            tracing::info!("Self-test passed");
+        } else {
+            tracing::warn!("Self-test failed for {failed:?}, continuing without them");
+        }
+        REPORT.set(self.checks).ok();
+    }
+
+    fn check(&mut self, capability: impl Into<String>, result: Result<(), impl Display>) -> bool {
+        let capability = capability.into();
+        match result {
+            Ok(()) => {
+                self.record(capability, SelfTestResult::Passed);
+                true
+            }
+            Err(err) => {
+                tracing::error!(capability, "Self-test of `{capability}` failed: `{err}`");
+                self.record(capability, SelfTestResult::Failed(err.to_string()));
+                false
+            }
+        }
+    }
+
+    fn record(&mut self, capability: impl Into<String>, result: SelfTestResult) {
+        self.checks.push(SelfTestCheck {
+            capability: capability.into(),
+            result,
+        });
+    }
+}
@@ -0,0 +1,585 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use once_cell::sync::Lazy;
+use tailor_api::{
+    ChargingSettings, PowerSourceProfiles, Subsystem, SubsystemStates, TelemetryFormat,
+};
+use tokio::sync::watch;
+
+use crate::{
+    conflicts,
+    error::{self, TailorError},
+    fancontrol, privileges,
+};
+
+pub const SETTINGS_PATH: &str = "/etc/tailord/tailord.toml";
+/// Written by versions before `tailord.toml`, only read to migrate it.
+const LEGACY_SETTINGS_PATH: &str = "/etc/tailord/settings.json";
+
+static SUBSYSTEM_CHANNEL: Lazy<watch::Sender<SubsystemStates>> =
+    Lazy::new(|| watch::channel(SubsystemStates::default()).0);
+
+/// Subscribe to changes of the enabled subsystems.
+pub fn get_subsystem_receiver() -> watch::Receiver<SubsystemStates> {
+    SUBSYSTEM_CHANNEL.subscribe()
+}
+
+pub fn subsystems() -> SubsystemStates {
+    *SUBSYSTEM_CHANNEL.borrow()
+}
+
+static POWER_SOURCE_PROFILES: Lazy<watch::Sender<PowerSourceProfiles>> =
+    Lazy::new(|| watch::channel(PowerSourceProfiles::default()).0);
+
+pub fn power_source_profiles() -> PowerSourceProfiles {
+    POWER_SOURCE_PROFILES.borrow().clone()
+}
+
+static POWER_PROFILES: Lazy<watch::Sender<BTreeMap<String, String>>> =
+    Lazy::new(|| watch::channel(BTreeMap::new()).0);
+
+/// The tailord profile for a power-profiles-daemon profile.
+pub fn power_profile(power_profile: &str) -> Option<String> {
+    POWER_PROFILES.borrow().get(power_profile).cloned()
+}
+
+static BATTERY_THRESHOLDS: Lazy<watch::Sender<Vec<u8>>> =
+    Lazy::new(|| watch::channel(Vec::new()).0);
+
+/// Battery levels in percent that are reported when the battery drops to them.
+pub fn battery_thresholds() -> Vec<u8> {
+    BATTERY_THRESHOLDS.borrow().clone()
+}
+
+static FAN_CONTROL: Lazy<watch::Sender<FanControlSettings>> =
+    Lazy::new(|| watch::channel(FanControlSettings::default()).0);
+
+/// The current tuning of the fan runtimes.
+pub fn fan_control() -> FanControlSettings {
+    FAN_CONTROL.borrow().clone()
+}
+
+/// Daemon-wide settings that don't belong to a profile.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub subsystems: SubsystemStates,
+    pub fan_control: FanControlSettings,
+    pub signals: SignalSettings,
+    pub rpc_socket: RpcSocketSettings,
+    pub http_api: HttpApiSettings,
+    pub mqtt: MqttSettings,
+    pub privileges: PrivilegeSettings,
+    pub conflicts: ConflictSettings,
+    pub telemetry: TelemetrySettings,
+    pub boost_key: BoostKeySettings,
+    /// Profiles to activate on AC and battery power.
+    pub power_source_profiles: PowerSourceProfiles,
+    /// Profiles to activate for the power-profiles-daemon profiles
+    /// (`power-saver`, `balanced` and `performance`).
+    pub power_profiles: BTreeMap<String, String>,
+    /// Charging settings that are applied at startup.
+    pub charging: ChargingSettings,
+    /// Battery levels in percent at which the `BatteryThreshold` signal
+    /// is emitted while the device runs on battery, e.g. `[20, 10]`.
+    pub battery_thresholds: Vec<u8>,
+}
+
+/// Tuning of the fan runtimes, applied on reload.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FanControlSettings {
+    /// Time in milliseconds between two fan speed updates.
+    pub interval_ms: u64,
+    /// Time in milliseconds between two status updates
+    /// while the firmware controls the fans.
+    pub firmware_status_interval_ms: u64,
+    /// The intel_powerclamp cooling device that enforces the power limits
+    /// of fan profiles, they're written to it as they are. An empty path disables it.
+    #[serde(with = "optional_path")]
+    pub powerclamp_path: Option<PathBuf>,
+    /// Further cooling devices that enforce the power limits of fan profiles,
+    /// e.g. `processor` or `acpitz` devices.
+    pub cooling_devices: Vec<CoolingDevice>,
+    /// Upper bound of the power limits in percent, whatever the fan profiles
+    /// ask for, to keep interactive workloads responsive.
+    pub power_limit_max: u8,
+    /// Number of fan control steps in a row with hardware errors, even after
+    /// retrying, after which a fan is handed to the firmware for 30 seconds.
+    pub failsafe_after_errors: u32,
+}
+
+impl Default for FanControlSettings {
+    fn default() -> Self {
+        Self {
+            interval_ms: 100,
+            firmware_status_interval_ms: 1000,
+            powerclamp_path: Some("/sys/class/thermal/cooling_device20/cur_state".into()),
+            cooling_devices: Vec::new(),
+            power_limit_max: 100,
+            failsafe_after_errors: 10,
+        }
+    }
+}
+
+impl FanControlSettings {
+    /// All cooling devices that enforce the power limits of fan profiles.
+    pub fn cooling_devices(&self) -> Vec<CoolingDevice> {
+        self.powerclamp_path
+            .iter()
+            .map(|path| CoolingDevice {
+                path: path.clone(),
+                max_state: 100,
+            })
+            .chain(self.cooling_devices.iter().cloned())
+            .collect()
+    }
+}
+
+/// A thermal cooling device, see `crate::fancontrol::power_limit`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CoolingDevice {
+    /// The `cur_state` attribute of the device.
+    pub path: PathBuf,
+    /// The state for a power limit of 100%, usually `max_state` of the device.
+    pub max_state: u32,
+}
+
+/// Maximum emission rate of the D-Bus signals.
+///
+/// Each value is the minimum time in milliseconds between two signals
+/// of the same kind. Changes in between are coalesced so that only
+/// the latest value is sent.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SignalSettings {
+    pub fan_status_interval_ms: u64,
+    pub subsystems_interval_ms: u64,
+}
+
+impl Default for SignalSettings {
+    fn default() -> Self {
+        Self {
+            fan_status_interval_ms: 1000,
+            subsystems_interval_ms: 250,
+        }
+    }
+}
+
+/// JSON-RPC control socket, see `crate::rpc`.
+/// Only used if tailord was built with the `rpc-socket` feature.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RpcSocketSettings {
+    pub enabled: bool,
+    pub path: String,
+    /// Octal file mode of the socket, which controls who may connect.
+    pub mode: String,
+}
+
+impl Default for RpcSocketSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "/run/tailord/rpc.sock".to_owned(),
+            mode: "0600".to_owned(),
+        }
+    }
+}
+
+/// HTTP API for remote monitoring, see `crate::http`.
+/// Only used if tailord was built with the `http-api` feature.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    pub enabled: bool,
+    pub address: String,
+    /// Allow switching the active profile over the WebSocket, only if `token` is set.
+    pub allow_profile_switching: bool,
+    /// If set, WebSocket clients must send this token either as
+    /// `Authorization: Bearer <token>` header or `token` query parameter.
+    pub token: Option<String>,
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:8337".to_owned(),
+            allow_profile_switching: false,
+            token: None,
+        }
+    }
+}
+
+/// MQTT client, see `crate::mqtt`.
+/// Only used if tailord was built with the `mqtt` feature.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Prefix of all topics tailord publishes and subscribes to.
+    pub topic_prefix: String,
+    /// Allow switching the active profile through `<prefix>/profile/set`.
+    pub allow_profile_switching: bool,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_owned(),
+            port: 1883,
+            client_id: "tailord".to_owned(),
+            username: None,
+            password: None,
+            topic_prefix: "tailord".to_owned(),
+            allow_profile_switching: false,
+        }
+    }
+}
+
+/// Recording of the fan status at startup, see `crate::telemetry`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    /// File to record to, the recording is disabled without it.
+    pub path: Option<PathBuf>,
+    /// `csv` or `jsonl`, the format is picked by the file extension without it.
+    pub format: Option<TelemetryFormat>,
+    /// How long to record in seconds.
+    pub duration_s: u64,
+    /// Time in milliseconds between two samples.
+    pub interval_ms: u64,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            path: None,
+            format: None,
+            duration_s: 600,
+            interval_ms: 1000,
+        }
+    }
+}
+
+/// The fan boost key of some models, see `crate::hotkey`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct BoostKeySettings {
+    pub enabled: bool,
+    /// Name of the input device that sends the key.
+    pub device: String,
+    /// Linux key code of the key, e.g. as shown by `evtest`.
+    pub key_code: u16,
+    pub mode: BoostKeyMode,
+    /// How long a press boosts the fans (max. 600).
+    #[serde(deserialize_with = "pin_seconds")]
+    pub seconds: u32,
+}
+
+impl Default for BoostKeySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            device: "TUXEDO Keyboard".to_owned(),
+            // KEY_PROG1
+            key_code: 148,
+            mode: BoostKeyMode::Timed,
+            seconds: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoostKeyMode {
+    /// Every press boosts the fans for the configured time.
+    Timed,
+    /// A press during a boost ends it early.
+    Toggle,
+}
+
+/// Privilege separation, see `crate::privileges`.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PrivilegeSettings {
+    /// Continue as this user after the hardware was initialized.
+    /// tailord keeps running as root if this isn't set.
+    pub user: Option<String>,
+}
+
+/// What to do if another fan control daemon is running, see `crate::conflicts`.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ConflictSettings {
+    /// Leave the fans to the firmware and the other daemon instead of only warning.
+    /// Enabling fan control explicitly still takes them over.
+    pub defer_fan_control: bool,
+}
+
+/// The settings that clients can change, see [`Settings::store_runtime`].
+#[derive(serde::Serialize)]
+struct RuntimeSettings {
+    subsystems: SubsystemStates,
+    power_source_profiles: PowerSourceProfiles,
+    charging: ChargingSettings,
+}
+
+impl Settings {
+    /// Load the settings and publish them to the runtimes.
+    pub fn init() -> Self {
+        let settings = Self::load();
+        settings.publish();
+        settings
+    }
+
+    /// Load the settings again and publish them to the runtimes.
+    /// Keeps the current settings if the file is invalid.
+    pub fn reload() -> error::Result<Self> {
+        let settings = Self::try_load()?;
+        settings.publish();
+        Ok(settings)
+    }
+
+    fn publish(&self) {
+        let mut subsystems = self.subsystems;
+        if self.conflicts.defer_fan_control
+            && subsystems.fan_control
+            && !conflicts::get().is_empty()
+        {
+            tracing::warn!("Deferring fan control to {:?}", conflicts::get());
+            subsystems.fan_control = false;
+        }
+        tracing::info!("Enabled subsystems: {:?}", subsystems);
+        SUBSYSTEM_CHANNEL.send_replace(subsystems);
+        POWER_SOURCE_PROFILES.send_replace(self.power_source_profiles.clone());
+        POWER_PROFILES.send_replace(self.power_profiles.clone());
+        FAN_CONTROL.send_replace(self.fan_control.clone());
+        BATTERY_THRESHOLDS.send_replace(self.battery_thresholds.clone());
+    }
+
+    pub fn load() -> Self {
+        Self::try_load().unwrap_or_else(|err| {
+            tracing::warn!("Failed to parse settings, using defaults: `{err}`");
+            Self::default()
+        })
+    }
+
+    /// Missing settings aren't an error, they just use the defaults.
+    /// The JSON file of older versions is read until it was migrated.
+    pub fn try_load() -> error::Result<Self> {
+        match std::fs::read_to_string(SETTINGS_PATH) {
+            Ok(data) => toml_edit::de::from_str(&data)
+                .map_err(|err| TailorError::InvalidFileContent(err.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::load_legacy(),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn load_legacy() -> error::Result<Self> {
+        match std::fs::read(LEGACY_SETTINGS_PATH) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|err| TailorError::InvalidFileContent(err.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Convert the JSON file of older versions to `tailord.toml`.
+    /// The old file is kept as `settings.json.bak`.
+    pub fn migrate() -> error::Result<()> {
+        if Path::new(SETTINGS_PATH).exists() || !Path::new(LEGACY_SETTINGS_PATH).exists() {
+            return Ok(());
+        }
+        let settings = Self::load_legacy()?;
+        let data = toml_edit::ser::to_string_pretty(&settings)
+            .map_err(|err| TailorError::Failed(err.to_string()))?;
+        std::fs::write(SETTINGS_PATH, data)?;
+        std::fs::rename(LEGACY_SETTINGS_PATH, format!("{LEGACY_SETTINGS_PATH}.bak"))?;
+        tracing::info!("Migrated `{LEGACY_SETTINGS_PATH}` to `{SETTINGS_PATH}`");
+        Ok(())
+    }
+
+    /// Change the settings file. Fails if the file can't be read, so
+    /// a typo in it doesn't get the whole file replaced with the defaults.
+    async fn update(change: impl FnOnce(&mut Self)) -> error::Result<()> {
+        let mut settings = Self::try_load()?;
+        change(&mut settings);
+        privileges::store_settings(&settings)
+    }
+
+    /// Store the settings that clients can change and keep the rest
+    /// of the file as it is, including comments.
+    ///
+    /// The privileged broker calls this for tailord, so the paths and the
+    /// user that root reads at the next start can't be changed this way.
+    pub fn store_runtime(&self) -> error::Result<()> {
+        let data = match std::fs::read_to_string(SETTINGS_PATH) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let data = self.merge_runtime(&data)?;
+        std::fs::write(SETTINGS_PATH, data).map_err(TailorError::from)
+    }
+
+    /// Replace the settings that clients can change in the TOML `data`.
+    fn merge_runtime(&self, data: &str) -> error::Result<String> {
+        let invalid =
+            |err: &dyn std::fmt::Display| TailorError::InvalidFileContent(err.to_string());
+        let mut document: toml_edit::DocumentMut = data.parse().map_err(|err| invalid(&err))?;
+        if document.is_empty() {
+            // Carry over the settings of older versions.
+            document =
+                toml_edit::ser::to_document(&Self::try_load()?).map_err(|err| invalid(&err))?;
+        }
+        let runtime = RuntimeSettings {
+            subsystems: self.subsystems,
+            power_source_profiles: self.power_source_profiles.clone(),
+            charging: self.charging.clone(),
+        };
+        let runtime = toml_edit::ser::to_document(&runtime).map_err(|err| invalid(&err))?;
+        for (key, item) in runtime.iter() {
+            let Some(table) = item.as_inline_table() else {
+                continue;
+            };
+            if table.is_empty() {
+                document.remove(key);
+            } else {
+                // Sections like the ones written by hand, not `key = { ... }`.
+                document[key] = toml_edit::Item::Table(table.clone().into_table());
+            }
+        }
+        Ok(document.to_string())
+    }
+
+    /// Enable or disable a subsystem and persist it.
+    ///
+    /// Only this subsystem changes, so fan control that was deferred
+    /// to another daemon is neither persisted as disabled nor taken over.
+    pub async fn set_subsystem_enabled(subsystem: Subsystem, enabled: bool) -> error::Result<()> {
+        Self::update(|settings| settings.subsystems.set_enabled(subsystem, enabled)).await?;
+
+        SUBSYSTEM_CHANNEL.send_modify(|subsystems| subsystems.set_enabled(subsystem, enabled));
+        Ok(())
+    }
+
+    /// Update the profiles for AC and battery power and persist them.
+    pub async fn set_power_source_profiles(profiles: PowerSourceProfiles) -> error::Result<()> {
+        Self::update(|settings| settings.power_source_profiles = profiles.clone()).await?;
+
+        POWER_SOURCE_PROFILES.send_replace(profiles);
+        Ok(())
+    }
+
+    /// Persist the charging settings.
+    pub async fn set_charging(charging: ChargingSettings) -> error::Result<()> {
+        Self::update(|settings| settings.charging = charging).await
+    }
+}
+
+/// Clamp the boost duration to what the fan runtimes accept,
+/// so a typo doesn't disable the key.
+fn pin_seconds<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let seconds = <u32 as serde::Deserialize>::deserialize(deserializer)?;
+    let clamped = seconds.clamp(1, fancontrol::MAX_PIN_SECONDS);
+    if clamped != seconds {
+        tracing::warn!("Fan boost key: Using {clamped}s instead of {seconds}s");
+    }
+    Ok(clamped)
+}
+
+/// TOML has no `null`, so an empty path stands for none.
+mod optional_path {
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        path: &Option<PathBuf>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&path.as_deref().unwrap_or(Path::new("")).to_string_lossy())
+    }
+
+    /// `null` is still accepted, as written by older versions to `settings.json`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<PathBuf>, D::Error> {
+        Ok(Option::<PathBuf>::deserialize(deserializer)?
+            .filter(|path| !path.as_os_str().is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{PowerSourceProfiles, SubsystemStates};
+
+    use super::Settings;
+
+    #[test]
+    fn store_runtime() {
+        let data = r#"# Leave the fans to tccd.
+[conflicts]
+defer_fan_control = true
+
+[fan_control]
+powerclamp_path = ""
+failsafe_after_errors = 5
+
+[subsystems]
+fan_control = false
+"#;
+        let settings = Settings {
+            subsystems: SubsystemStates {
+                led_control: false,
+                ..Default::default()
+            },
+            power_source_profiles: PowerSourceProfiles {
+                ac: Some("performance".to_owned()),
+                battery: None,
+            },
+            ..Default::default()
+        };
+        let data = settings.merge_runtime(data).unwrap();
+        assert!(data.starts_with("# Leave the fans to tccd.\n[conflicts]\n"));
+
+        let stored: Settings = toml_edit::de::from_str(&data).unwrap();
+        assert!(stored.conflicts.defer_fan_control);
+        assert_eq!(stored.fan_control.powerclamp_path, None);
+        assert_eq!(stored.fan_control.failsafe_after_errors, 5);
+        assert_eq!(stored.subsystems, settings.subsystems);
+        assert_eq!(stored.power_source_profiles, settings.power_source_profiles);
+    }
+
+    #[test]
+    fn migrate() {
+        let legacy: Settings = serde_json::from_str(
+            r#"{ "fan_control": { "powerclamp_path": null }, "battery_thresholds": [10] }"#,
+        )
+        .unwrap();
+        let data = toml_edit::ser::to_string_pretty(&legacy).unwrap();
+        let migrated: Settings = toml_edit::de::from_str(&data).unwrap();
+        assert_eq!(migrated.fan_control, legacy.fan_control);
+        assert_eq!(migrated.fan_control.powerclamp_path, None);
+        assert_eq!(migrated.battery_thresholds, [10]);
+    }
+    #[test]
+    fn boost_key_seconds() {
+        let settings: Settings = toml_edit::de::from_str("[boost_key]\nseconds = 3600").unwrap();
+        assert_eq!(settings.boost_key.seconds, 600);
+        let settings: Settings = toml_edit::de::from_str("[boost_key]\nseconds = 0").unwrap();
+        assert_eq!(settings.boost_key.seconds, 1);
+    }
+}
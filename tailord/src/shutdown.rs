@@ -9,7 +9,7 @@ pub fn setup() -> broadcast::Receiver<()> {
 
     let signals = Signals::new([SIGTERM, SIGINT, SIGQUIT]).unwrap();
     tracing::debug!("Starting signal handler runtime");
-    tokio_uring::spawn(handle_signals(signals, shutdown_sender));
+    tokio::task::spawn_local(handle_signals(signals, shutdown_sender));
 
     shutdown_receiver
 }
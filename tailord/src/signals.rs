@@ -0,0 +1,152 @@
+use std::{future::Future, time::Duration};
+
+use tailor_api::{ProfileSwitch, SubsystemStates};
+use tokio::sync::{broadcast, watch};
+use zbus::SignalContext;
+
+use crate::{
+    dbus::{ChargingInterface, FanInterface, ProfileInterface, SettingsInterface},
+    fancontrol::{
+        get_sensor_failure_receiver, get_thermal_trip_receiver, FanHandles, SensorFailure,
+        ThermalTrip,
+    },
+    history::get_switch_receiver,
+    power_source::{get_battery_threshold_receiver, BatteryThreshold},
+    settings::{get_subsystem_receiver, SignalSettings},
+};
+
+/// Start forwarding status changes as D-Bus signals.
+pub fn spawn(ctxt: SignalContext<'static>, settings: &SignalSettings, fan_handles: FanHandles) {
+    let fan_interval = Duration::from_millis(settings.fan_status_interval_ms);
+    tokio::task::spawn_local(emit_fan_status(ctxt.clone(), fan_handles, fan_interval));
+
+    let subsystems_interval = Duration::from_millis(settings.subsystems_interval_ms);
+    {
+        let ctxt = ctxt.clone();
+        tokio::task::spawn_local(emit_rate_limited(
+            get_subsystem_receiver(),
+            subsystems_interval,
+            move |subsystems: SubsystemStates| {
+                let ctxt = ctxt.clone();
+                async move {
+                    let subsystems = serde_json::to_string(&subsystems).unwrap();
+                    SettingsInterface::subsystems_changed(&ctxt, &subsystems).await
+                }
+            },
+        ));
+    }
+
+    // Events are rare and each of them matters, so they aren't rate limited.
+    {
+        let ctxt = ctxt.clone();
+        tokio::task::spawn_local(emit_all(
+            get_switch_receiver(),
+            move |switch: ProfileSwitch| {
+                let ctxt = ctxt.clone();
+                async move {
+                    let switch = serde_json::to_string(&switch).unwrap();
+                    ProfileInterface::profile_activated(&ctxt, &switch).await
+                }
+            },
+        ));
+    }
+    {
+        let ctxt = ctxt.clone();
+        tokio::task::spawn_local(emit_all(
+            get_sensor_failure_receiver(),
+            move |(fan_idx, error): SensorFailure| {
+                let ctxt = ctxt.clone();
+                async move { FanInterface::sensor_failed(&ctxt, fan_idx, &error).await }
+            },
+        ));
+    }
+    {
+        let ctxt = ctxt.clone();
+        tokio::task::spawn_local(emit_all(
+            get_thermal_trip_receiver(),
+            move |(fan_idx, temperature): ThermalTrip| {
+                let ctxt = ctxt.clone();
+                async move { FanInterface::thermal_trip(&ctxt, fan_idx, temperature).await }
+            },
+        ));
+    }
+    tokio::task::spawn_local(emit_all(
+        get_battery_threshold_receiver(),
+        move |(threshold, battery): BatteryThreshold| {
+            let ctxt = ctxt.clone();
+            async move { ChargingInterface::battery_threshold(&ctxt, threshold, battery).await }
+        },
+    ));
+}
+
+/// Emit the status changes of every fan, including fans that are added later.
+async fn emit_fan_status(
+    ctxt: SignalContext<'static>,
+    mut fan_handles: FanHandles,
+    interval: Duration,
+) {
+    let mut emitting = 0;
+    loop {
+        for receiver in fan_handles.statuses().into_iter().skip(emitting) {
+            let ctxt = ctxt.clone();
+            tokio::task::spawn_local(emit_rate_limited(receiver, interval, move |status| {
+                let ctxt = ctxt.clone();
+                async move {
+                    let status = serde_json::to_string(&status).unwrap();
+                    FanInterface::status_changed(&ctxt, &status).await
+                }
+            }));
+            emitting += 1;
+        }
+        if !fan_handles.changed().await {
+            break;
+        }
+    }
+}
+
+/// Emit a signal for each change of `receiver`, but at most once per `interval`.
+///
+/// Values that arrive while waiting are coalesced by the watch
+/// channel, so only the latest one is emitted afterwards.
+async fn emit_rate_limited<T, F, Fut>(
+    mut receiver: watch::Receiver<T>,
+    interval: Duration,
+    mut emit: F,
+) where
+    T: Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = zbus::Result<()>>,
+{
+    // Only emit changes, not the initial value.
+    receiver.borrow_and_update();
+
+    while receiver.changed().await.is_ok() {
+        let value = receiver.borrow_and_update().clone();
+        if let Err(err) = emit(value).await {
+            tracing::warn!("Failed to emit signal: `{err}`");
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Emit a signal for every value sent through `receiver`.
+async fn emit_all<T, F, Fut>(mut receiver: broadcast::Receiver<T>, mut emit: F)
+where
+    T: Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = zbus::Result<()>>,
+{
+    loop {
+        match receiver.recv().await {
+            Ok(value) => {
+                if let Err(err) = emit(value).await {
+                    tracing::warn!("Failed to emit signal: `{err}`");
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(count)) => {
+                tracing::warn!("Dropped {count} events that couldn't be emitted in time");
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
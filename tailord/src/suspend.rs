@@ -1,13 +1,25 @@
 use futures_lite::StreamExt;
 use once_cell::sync::Lazy;
-use tokio::sync::broadcast;
-use zbus::{proxy, Connection};
+use tokio::sync::{broadcast, Notify};
+use zbus::{proxy, zvariant::OwnedFd, Connection};
 
-use std::{future::pending, time::Duration};
+use std::{
+    future::pending,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
 
 static SUSPEND_CHANNEL: Lazy<(broadcast::Sender<bool>, broadcast::Receiver<bool>)> =
     Lazy::new(|| broadcast::channel(1));
 
+/// Number of runtimes that haven't prepared for the current suspend yet.
+static PENDING_RUNTIMES: AtomicUsize = AtomicUsize::new(0);
+static RUNTIMES_PREPARED: Notify = Notify::const_new();
+
+/// How long suspend is delayed at most to let the runtimes prepare.
+/// logind doesn't wait longer than `InhibitDelayMaxSec` (5s by default).
+const PREPARE_TIMEOUT: Duration = Duration::from_secs(3);
+
 pub fn get_suspend_receiver() -> broadcast::Receiver<bool> {
     SUSPEND_CHANNEL.0.subscribe()
 }
@@ -18,6 +30,9 @@ pub fn get_suspend_receiver() -> broadcast::Receiver<bool> {
     default_path = "/org/freedesktop/login1"
 )]
 trait Suspend {
+    /// Take an inhibitor lock that lasts until the returned file descriptor is closed.
+    fn inhibit(&self, what: &str, who: &str, why: &str, mode: &str) -> zbus::Result<OwnedFd>;
+
     #[zbus(signal)]
     fn prepare_for_sleep(&self, arg1: bool) -> fdo::Result<()>;
 }
@@ -41,54 +56,115 @@ async fn try_wait_for_suspend(sender: &mut broadcast::Sender<bool>) -> Result<()
     let connection = Connection::system().await?;
     let proxy = SuspendProxy::new(&connection).await?;
     let mut receiver = proxy.receive_prepare_for_sleep().await?;
+    let mut inhibitor = inhibit(&proxy).await;
 
     while let Some(msg) = receiver.next().await {
         let value = *msg.args()?.arg1();
 
         if value {
+            tracing::info!("Preparing for suspend.");
+            // The channel itself holds one receiver that never receives.
+            PENDING_RUNTIMES.store(sender.receiver_count() - 1, Ordering::SeqCst);
+            if let Err(err) = sender.send(true) {
+                tracing::warn!("Error sending suspend signal: `{err}`");
+            }
+            if tokio::time::timeout(PREPARE_TIMEOUT, wait_until_prepared())
+                .await
+                .is_err()
+            {
+                tracing::warn!(
+                    "{} runtime(s) didn't prepare for suspend in time",
+                    PENDING_RUNTIMES.load(Ordering::SeqCst)
+                );
+            }
+            // Closing the file descriptor lets the system suspend.
+            inhibitor = None;
             tracing::info!("Suspended, sleeping until wake up.");
         } else {
             tracing::info!("Woken up, continue service.");
-        };
-
-        if let Err(err) = sender.send(value) {
-            tracing::warn!("Error sending shutdown signal: `{err}`");
+            PENDING_RUNTIMES.store(0, Ordering::SeqCst);
+            if let Err(err) = sender.send(false) {
+                tracing::warn!("Error sending wake-up signal: `{err}`");
+            }
+            // Delay the next suspend as well.
+            if inhibitor.is_none() {
+                inhibitor = inhibit(&proxy).await;
+            }
         }
     }
+    drop(inhibitor);
 
     Ok(())
 }
 
-pub async fn process_suspend(receiver: &mut broadcast::Receiver<bool>) {
-    match receiver.recv().await {
-        Ok(msg) => {
-            // Suspended!
-            if msg {
-                wait_for_wake_up(receiver).await
-            } else {
-                tracing::warn!("Wake up message without suspend.");
-            }
+/// Take a delay inhibitor, so logind waits for tailord before suspending.
+async fn inhibit(proxy: &SuspendProxy<'_>) -> Option<OwnedFd> {
+    match proxy
+        .inhibit(
+            "sleep",
+            "tailord",
+            "Hand the fans back to the firmware",
+            "delay",
+        )
+        .await
+    {
+        Ok(fd) => Some(fd),
+        Err(err) => {
+            tracing::warn!("Failed to take a sleep inhibitor: `{err}`");
+            None
         }
-        Err(_) => {
-            tracing::warn!("Stop listening for suspend messages");
-            pending::<()>().await;
+    }
+}
+
+async fn wait_until_prepared() {
+    while PENDING_RUNTIMES.load(Ordering::SeqCst) > 0 {
+        RUNTIMES_PREPARED.notified().await;
+    }
+}
+
+/// Report that a runtime is ready for suspend.
+fn prepared_for_sleep() {
+    let previous = PENDING_RUNTIMES.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+        pending.checked_sub(1)
+    });
+    if previous == Ok(1) {
+        RUNTIMES_PREPARED.notify_one();
+    }
+}
+
+/// Wait until the system is about to suspend.
+pub async fn wait_for_sleep(receiver: &mut broadcast::Receiver<bool>) {
+    loop {
+        match receiver.recv().await {
+            Ok(true) => return,
+            Ok(false) => tracing::warn!("Wake up message without suspend."),
+            Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => {
+                tracing::warn!("Stop listening for suspend messages");
+                pending::<()>().await;
+            }
         }
     }
 }
 
-async fn wait_for_wake_up(receiver: &mut broadcast::Receiver<bool>) {
+/// Report that the runtime is prepared for suspend and wait until the system woke up.
+pub async fn sleep_until_wake_up(receiver: &mut broadcast::Receiver<bool>) {
+    prepared_for_sleep();
     // Wait until wake up (suspend msg == false).
     loop {
         match receiver.recv().await {
             Ok(msg) => {
                 if msg {
-                    tracing::warn!("Wake up message without suspend.");
+                    tracing::warn!("Suspend message while suspended.");
                 } else {
                     return;
                 }
             }
             Err(err) => {
                 tracing::error!("Error receiving wake-up message: `{err}`");
+                if matches!(err, broadcast::error::RecvError::Closed) {
+                    return;
+                }
             }
         }
     }
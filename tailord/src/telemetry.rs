@@ -0,0 +1,89 @@
+//! Record the status of all fans to a file for a limited time after startup,
+//! so fan curves can be tuned with plots from spreadsheets or other tools.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tailor_api::{TelemetryFormat, TelemetrySample};
+use tokio::time::Instant;
+
+use crate::{fancontrol::FanHandles, settings::TelemetrySettings};
+
+/// Start recording if a file is configured.
+///
+/// The file is created right away, so it can be written
+/// even after root privileges were dropped.
+pub fn spawn(settings: &TelemetrySettings, fan_handles: FanHandles) {
+    let Some(path) = &settings.path else {
+        return;
+    };
+    let format = settings
+        .format
+        .unwrap_or_else(|| TelemetryFormat::from_path(path));
+    let file = match create(path, format) {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::error!("Failed to create `{}`: `{err}`", path.display());
+            return;
+        }
+    };
+    tracing::info!(
+        "Recording fan telemetry to `{}` for {}s",
+        path.display(),
+        settings.duration_s
+    );
+    tokio::task::spawn_local(record(
+        file,
+        format,
+        fan_handles,
+        Duration::from_secs(settings.duration_s),
+        Duration::from_millis(settings.interval_ms),
+    ));
+}
+
+fn create(path: &Path, format: TelemetryFormat) -> io::Result<BufWriter<File>> {
+    let mut file = BufWriter::new(File::create(path)?);
+    if let Some(header) = format.header() {
+        writeln!(file, "{header}")?;
+    }
+    Ok(file)
+}
+
+async fn record(
+    mut file: BufWriter<File>,
+    format: TelemetryFormat,
+    fan_handles: FanHandles,
+    duration: Duration,
+    interval: Duration,
+) {
+    let deadline = Instant::now() + duration;
+    let mut interval = tokio::time::interval(interval);
+
+    while Instant::now() < deadline {
+        interval.tick().await;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let result = fan_handles
+            .statuses()
+            .iter()
+            .try_for_each(|status| {
+                let sample = TelemetrySample {
+                    timestamp,
+                    status: *status.borrow(),
+                };
+                writeln!(file, "{}", format.format(&sample))
+            })
+            .and_then(|()| file.flush());
+        if let Err(err) = result {
+            tracing::error!("Stopped recording fan telemetry: `{err}`");
+            return;
+        }
+    }
+    tracing::info!("Finished recording fan telemetry");
+}
@@ -0,0 +1,143 @@
+//! Trip points of the thermal zones of the CPU, where the kernel or the
+//! firmware starts to throttle (passive) or shuts the system down (critical).
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use once_cell::sync::OnceCell;
+
+const THERMAL_PATH: &str = "/sys/class/thermal";
+
+/// Thermal zones that follow the CPU temperature. Others, like the skin
+/// temperature sensors of some laptops, trip far below the CPU.
+const CPU_ZONES: [&str; 3] = ["x86_pkg_temp", "acpitz", "TCPU"];
+
+static TRIP_POINTS: OnceCell<TripPoints> = OnceCell::new();
+
+/// The lowest trip points of all CPU zones in °C.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TripPoints {
+    pub passive: Option<u8>,
+    pub critical: Option<u8>,
+}
+
+/// Read the trip points once at startup.
+pub fn init() {
+    let trip_points = read(Path::new(THERMAL_PATH));
+    match trip_points {
+        TripPoints {
+            passive: None,
+            critical: None,
+        } => tracing::info!("No thermal trip points available"),
+        TripPoints { passive, critical } => {
+            tracing::info!("Thermal trip points: passive {passive:?}°C, critical {critical:?}°C")
+        }
+    }
+    TRIP_POINTS.set(trip_points).ok();
+}
+
+/// The trip points read at startup, none before.
+pub fn trip_points() -> TripPoints {
+    TRIP_POINTS.get().copied().unwrap_or_default()
+}
+
+fn read(root: &Path) -> TripPoints {
+    let mut trip_points = TripPoints::default();
+    for zone in zones(root) {
+        for (kind, temp) in zone_trip_points(&zone) {
+            let lowest = match kind.as_str() {
+                "passive" => &mut trip_points.passive,
+                "critical" => &mut trip_points.critical,
+                _ => continue,
+            };
+            *lowest = Some(lowest.map_or(temp, |lowest| lowest.min(temp)));
+        }
+    }
+    trip_points
+}
+
+/// The `thermal_zoneN` directories of CPU zones.
+fn zones(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("thermal_zone")
+        })
+        .map(|entry| entry.path())
+        .filter(|zone| {
+            fs::read_to_string(zone.join("type")).is_ok_and(|kind| CPU_ZONES.contains(&kind.trim()))
+        })
+        .collect()
+}
+
+/// The type and temperature in °C of all valid trip points of a zone.
+fn zone_trip_points(zone: &Path) -> Vec<(String, u8)> {
+    (0..)
+        .map_while(|idx| {
+            let kind = fs::read_to_string(zone.join(format!("trip_point_{idx}_type"))).ok()?;
+            let temp = fs::read_to_string(zone.join(format!("trip_point_{idx}_temp"))).ok();
+            Some((kind, temp))
+        })
+        .filter_map(|(kind, temp)| {
+            // Unused trip points are 0 or negative, temperatures are in m°C.
+            let millidegrees: i64 = temp?.trim().parse().ok()?;
+            let temp = u8::try_from(millidegrees / 1000)
+                .ok()
+                .filter(|temp| *temp > 0)?;
+            Some((kind.trim().to_owned(), temp))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{read, TripPoints};
+
+    #[test]
+    fn trip_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let zones = [
+            (
+                "thermal_zone0",
+                "acpitz",
+                &[("passive", "97000"), ("critical", "105000")][..],
+            ),
+            (
+                "thermal_zone1",
+                "x86_pkg_temp",
+                &[("passive", "0"), ("critical", "100000")],
+            ),
+            // Skin temperature, which is ignored.
+            ("thermal_zone2", "SEN1", &[("passive", "50000")]),
+        ];
+        for (name, kind, trip_points) in zones {
+            let zone = root.join(name);
+            fs::create_dir_all(&zone).unwrap();
+            fs::write(zone.join("type"), format!("{kind}\n")).unwrap();
+            for (idx, (kind, temp)) in trip_points.iter().enumerate() {
+                fs::write(zone.join(format!("trip_point_{idx}_type")), kind).unwrap();
+                fs::write(zone.join(format!("trip_point_{idx}_temp")), temp).unwrap();
+            }
+        }
+
+        assert_eq!(
+            read(root),
+            TripPoints {
+                passive: Some(97),
+                critical: Some(100),
+            }
+        );
+        assert_eq!(read(&root.join("missing")), TripPoints::default());
+    }
+}
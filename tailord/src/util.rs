@@ -1,14 +1,15 @@
 use serde::{de::DeserializeOwned, Serialize};
-use zbus::fdo;
 
-pub fn normalize_json_path(base_path: &str, name: &str) -> fdo::Result<String> {
+use crate::error::{self, TailorError};
+
+pub fn normalize_json_path(base_path: &str, name: &str) -> error::Result<String> {
     // Make sure the name doesn't contain any illegal characters.
     if name.contains('/') {
-        Err(fdo::Error::InvalidArgs(format!(
+        Err(TailorError::InvalidArgs(format!(
             "Can't use '/' in profile names: `{name}`"
         )))
     } else if name.contains('.') {
-        Err(fdo::Error::InvalidArgs(format!(
+        Err(TailorError::InvalidArgs(format!(
             "Can't use '.' in profile names: `{name}`"
         )))
     } else if base_path.is_empty() {
@@ -19,66 +20,56 @@ pub fn normalize_json_path(base_path: &str, name: &str) -> fdo::Result<String> {
     }
 }
 
-pub async fn write_file(base_path: &str, name: &str, data: &[u8]) -> Result<(), fdo::Error> {
+pub async fn write_file(base_path: &str, name: &str, data: &[u8]) -> error::Result<()> {
     tokio::fs::write(normalize_json_path(base_path, name)?, data)
         .await
-        .map_err(|err| fdo::Error::IOError(err.to_string()))
+        .map_err(TailorError::from)
 }
 
-pub async fn write_json<T: Serialize>(
-    base_path: &str,
-    name: &str,
-    data: &T,
-) -> Result<(), fdo::Error> {
+pub async fn write_json<T: Serialize>(base_path: &str, name: &str, data: &T) -> error::Result<()> {
     write_file(base_path, name, to_pretty_json(data)?.as_bytes()).await
 }
 
-pub fn write_json_sync<T: Serialize>(
-    base_path: &str,
-    name: &str,
-    data: &T,
-) -> Result<(), fdo::Error> {
+pub fn write_json_sync<T: Serialize>(base_path: &str, name: &str, data: &T) -> error::Result<()> {
     std::fs::write(
         normalize_json_path(base_path, name)?,
         to_pretty_json(data)?.as_bytes(),
     )
-    .map_err(|err| fdo::Error::IOError(err.to_string()))
+    .map_err(TailorError::from)
 }
 
-fn to_pretty_json<T: Serialize>(data: &T) -> Result<String, fdo::Error> {
-    serde_json::to_string_pretty(data).map_err(|err| fdo::Error::Failed(err.to_string()))
+fn to_pretty_json<T: Serialize>(data: &T) -> error::Result<String> {
+    serde_json::to_string_pretty(data).map_err(|err| TailorError::Failed(err.to_string()))
 }
 
-pub async fn read_file(base_path: &str, name: &str) -> Result<String, fdo::Error> {
+pub async fn read_file(base_path: &str, name: &str) -> error::Result<String> {
     tokio::fs::read_to_string(normalize_json_path(base_path, name)?)
         .await
-        .map_err(|err| fdo::Error::IOError(err.to_string()))
+        .map_err(|err| TailorError::from_profile_io(err, name))
 }
 
-pub async fn read_json<T: DeserializeOwned>(base_path: &str, name: &str) -> Result<T, fdo::Error> {
+pub async fn read_json<T: DeserializeOwned>(base_path: &str, name: &str) -> error::Result<T> {
     let data = read_file(base_path, name).await?;
-    serde_json::from_str(&data).map_err(|err| fdo::Error::Failed(err.to_string()))
+    serde_json::from_str(&data).map_err(|err| TailorError::InvalidFileContent(err.to_string()))
 }
 
-pub async fn remove_file(base_path: &str, name: &str) -> Result<(), fdo::Error> {
+pub async fn remove_file(base_path: &str, name: &str) -> error::Result<()> {
     tokio::fs::remove_file(normalize_json_path(base_path, name)?)
         .await
-        .map_err(|err| fdo::Error::IOError(err.to_string()))
+        .map_err(|err| TailorError::from_profile_io(err, name))
 }
 
-pub async fn move_file(base_path: &str, from: &str, to: &str) -> Result<(), fdo::Error> {
+pub async fn move_file(base_path: &str, from: &str, to: &str) -> error::Result<()> {
     tokio::fs::rename(
         normalize_json_path(base_path, from)?,
         normalize_json_path(base_path, to)?,
     )
     .await
-    .map_err(|err| fdo::Error::IOError(err.to_string()))
+    .map_err(|err| TailorError::from_profile_io(err, from))
 }
 
-pub async fn get_profiles(base_path: &str) -> fdo::Result<Vec<String>> {
-    let mut dir_entries = tokio::fs::read_dir(base_path)
-        .await
-        .map_err(|err| fdo::Error::IOError(err.to_string()))?;
+pub async fn get_profiles(base_path: &str) -> error::Result<Vec<String>> {
+    let mut dir_entries = tokio::fs::read_dir(base_path).await?;
 
     let mut entries = Vec::new();
     while let Ok(Some(entry)) = dir_entries.next_entry().await {
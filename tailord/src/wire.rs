@@ -0,0 +1,81 @@
+//! Wire format versions negotiated by D-Bus clients.
+//!
+//! Clients that never negotiated a version receive the legacy format,
+//! which keeps clients from before the versioned format working.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use tailor_api::wire::{self, WirePayload, LEGACY_VERSION, WIRE_VERSION};
+use zbus::{fdo::DBusProxy, message::Header, Connection};
+
+use crate::{
+    error::{self, TailorError},
+    util,
+};
+
+/// Negotiated versions by unique bus name.
+static VERSIONS: Lazy<Mutex<HashMap<String, u32>>> = Lazy::new(Default::default);
+
+/// Agree on the newest version supported by both sides.
+pub fn negotiate(header: &Header<'_>, client_version: u32) -> u32 {
+    let version = client_version.min(WIRE_VERSION);
+    if let Some(sender) = header.sender() {
+        tracing::debug!("Client `{sender}` uses wire format version {version}");
+        VERSIONS.lock().unwrap().insert(sender.to_string(), version);
+    }
+    version
+}
+
+/// The version negotiated by the sender of a message.
+pub fn version(header: &Header<'_>) -> u32 {
+    header
+        .sender()
+        .and_then(|sender| VERSIONS.lock().unwrap().get(sender.as_str()).copied())
+        .unwrap_or(LEGACY_VERSION)
+}
+
+/// Read a stored profile and serialize it for a client.
+///
+/// Profiles written by older versions of tailord are upgraded as well.
+pub async fn read_profile<T: WirePayload>(
+    base_path: &str,
+    name: &str,
+    version: u32,
+) -> error::Result<String> {
+    let data = util::read_file(base_path, name).await?;
+    let profile: T =
+        wire::decode(&data).map_err(|err| TailorError::InvalidFileContent(err.to_string()))?;
+    wire::encode(&profile, version).map_err(|err| TailorError::Failed(err.to_string()))
+}
+
+/// Deserialize a payload sent by a client.
+pub fn decode<T: WirePayload>(value: &str) -> error::Result<T> {
+    wire::decode(value).map_err(|err| TailorError::InvalidArgs(err.to_string()))
+}
+
+/// Forget the versions of clients that left the bus.
+pub async fn forget_disconnected(connection: Connection) {
+    let mut stream = match DBusProxy::new(&connection).await {
+        Ok(proxy) => match proxy.receive_name_owner_changed().await {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!("Failed to watch D-Bus clients: `{err}`");
+                return;
+            }
+        },
+        Err(err) => {
+            tracing::warn!("Failed to watch D-Bus clients: `{err}`");
+            return;
+        }
+    };
+
+    while let Some(signal) = stream.next().await {
+        if let Ok(args) = signal.args() {
+            if args.new_owner().is_none() {
+                VERSIONS.lock().unwrap().remove(args.name().as_str());
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Writes to sysfs attributes that are opened on demand.
+//!
+//! A process that gave up its root privileges can route these
+//! writes through a privileged helper with [`set_writer`].
+
+use std::{fs, io, path::Path, sync::OnceLock};
+
+pub type AttributeWriter = Box<dyn Fn(&Path, &str) -> io::Result<()> + Send + Sync>;
+
+static WRITER: OnceLock<AttributeWriter> = OnceLock::new();
+
+/// Use `writer` for all following attribute writes.
+/// Can only be set once, later calls are ignored.
+pub fn set_writer(writer: AttributeWriter) {
+    if WRITER.set(writer).is_err() {
+        tracing::warn!("The attribute writer was already set");
+    }
+}
+
+/// Write `value` to the attribute at `path`.
+pub fn write(path: &Path, value: &str) -> io::Result<()> {
+    match WRITER.get() {
+        Some(writer) => writer(path, value),
+        None => fs::write(path, value),
+    }
+}
@@ -0,0 +1,71 @@
+//! Fn-lock switch of the tuxedo_keyboard module.
+//!
+//! The switch isn't part of tuxedo_io, so it is available
+//! independently of the fan backend.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::IoctlError;
+
+use super::traits::FnLockDevice;
+use super::{attribute, IoctlResult};
+
+const FN_LOCK_PATH: &str = "/sys/devices/platform/tuxedo_keyboard/fn_lock";
+
+#[derive(Debug)]
+pub struct SysfsFnLock {
+    path: PathBuf,
+}
+
+impl SysfsFnLock {
+    pub fn init() -> IoctlResult<Self> {
+        Self::open(Path::new(FN_LOCK_PATH))
+    }
+
+    fn open(path: &Path) -> IoctlResult<Self> {
+        if path.exists() {
+            Ok(Self {
+                path: path.to_owned(),
+            })
+        } else {
+            Err(IoctlError::NotAvailable)
+        }
+    }
+}
+
+impl FnLockDevice for SysfsFnLock {
+    fn set_fn_lock(&self, enabled: bool) -> IoctlResult<()> {
+        attribute::write(&self.path, if enabled { "1" } else { "0" })?;
+        Ok(())
+    }
+
+    fn get_fn_lock(&self) -> IoctlResult<bool> {
+        match fs::read_to_string(&self.path)?.trim() {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(IoctlError::InvalidArgs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fn_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fn_lock");
+        assert!(SysfsFnLock::open(&path).is_err());
+
+        fs::write(&path, "0\n").unwrap();
+        let device = SysfsFnLock::open(&path).unwrap();
+        assert!(!device.get_fn_lock().unwrap());
+
+        device.set_fn_lock(true).unwrap();
+        assert!(device.get_fn_lock().unwrap());
+    }
+}
@@ -0,0 +1,456 @@
+//! Fallbacks that control fans through sysfs if the tuxedo_io module isn't
+//! available: the fan attributes of the tuxedo_keyboard and uniwill platform
+//! drivers, or the generic hwmon interface.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::IoctlError;
+
+use super::traits::HardwareDevice;
+use super::{attribute, IoctlResult};
+
+const HWMON_PATH: &str = "/sys/class/hwmon";
+
+/// Maximum value of the `pwmN` attributes.
+const MAX_PWM: u8 = 255;
+
+/// A platform driver that exposes the fans of the embedded controller
+/// as `fanN_pwm` and `fanN_pwm_enable`, with the same modes as hwmon.
+struct PlatformDriver {
+    name: &'static str,
+    path: &'static str,
+    /// The raw value of full speed, as used by the embedded controller.
+    max_pwm: u8,
+}
+
+const PLATFORM_DRIVERS: [PlatformDriver; 2] = [
+    PlatformDriver {
+        name: "tuxedo_keyboard",
+        path: "/sys/devices/platform/tuxedo_keyboard",
+        max_pwm: 0xff,
+    },
+    PlatformDriver {
+        name: "uniwill",
+        path: "/sys/devices/platform/uniwill",
+        max_pwm: 0xc8,
+    },
+];
+
+/// Value of `pwmN_enable` for manual control.
+const PWM_MANUAL: u8 = 1;
+/// Value of `pwmN_enable` for automatic control by the firmware.
+const PWM_AUTO: u8 = 2;
+
+/// Chips that report the CPU temperature, in order of preference.
+/// Used for fans whose chip has no temperature sensor of the CPU.
+const CPU_SENSORS: [&str; 4] = ["coretemp", "k10temp", "zenpower", "acpitz"];
+
+/// Super I/O chips, their first temperature sensor is on the board, not the CPU.
+const SUPER_IO_CHIPS: [&str; 3] = ["nct", "it87", "w83"];
+
+#[derive(Debug)]
+struct PwmFan {
+    pwm: PathBuf,
+    enable: PathBuf,
+    temp: PathBuf,
+    /// Control mode before tailord took over.
+    auto_mode: u8,
+    max_pwm: u8,
+}
+
+#[derive(Debug)]
+pub struct HwmonHardware {
+    interface: &'static str,
+    /// Names of the chips that provide the fans.
+    chips: Vec<String>,
+    fans: Vec<PwmFan>,
+}
+
+impl HwmonHardware {
+    pub fn init() -> IoctlResult<Self> {
+        Self::scan(Path::new(HWMON_PATH))
+    }
+
+    /// Use the fans of the first platform driver that exposes them.
+    pub fn platform() -> IoctlResult<Self> {
+        let root = Path::new(HWMON_PATH);
+        PLATFORM_DRIVERS
+            .iter()
+            .find_map(|driver| Self::scan_platform(driver, Path::new(driver.path), root).ok())
+            .ok_or(IoctlError::DevNotAvailable)
+    }
+
+    /// Find the fans of `driver` in `path`, the CPU temperature is read from `root`.
+    fn scan_platform(driver: &PlatformDriver, path: &Path, root: &Path) -> IoctlResult<Self> {
+        let cpu_temp = cpu_temp(&chips(root).unwrap_or_default());
+        let fans = pwm_fans(path, "fan", "_pwm")?
+            .into_iter()
+            .filter_map(|(index, pwm, enable, auto_mode)| {
+                let temp = Some(path.join(format!("temp{index}_input")))
+                    .filter(|temp| temp.exists())
+                    .or_else(|| cpu_temp.clone())?;
+                Some(PwmFan {
+                    pwm,
+                    enable,
+                    temp,
+                    auto_mode,
+                    max_pwm: driver.max_pwm,
+                })
+            })
+            .collect();
+        Self::new("platform", vec![driver.name.to_owned()], fans)
+    }
+
+    fn new(interface: &'static str, chips: Vec<String>, fans: Vec<PwmFan>) -> IoctlResult<Self> {
+        if fans.is_empty() {
+            Err(IoctlError::DevNotAvailable)
+        } else {
+            Ok(Self {
+                interface,
+                chips,
+                fans,
+            })
+        }
+    }
+
+    /// Find all controllable fans of the platform devices in `root`.
+    fn scan(root: &Path) -> IoctlResult<Self> {
+        let chips = chips(root)?;
+        let cpu_temp = cpu_temp(&chips);
+
+        let mut names = Vec::new();
+        let mut fans = Vec::new();
+        for (name, path) in chips.iter().filter(|(_, path)| is_platform_chip(path)) {
+            let Some(temp) = chip_cpu_temp(name, path).or_else(|| cpu_temp.clone()) else {
+                continue;
+            };
+            for (_, pwm, enable, auto_mode) in pwm_fans(path, "pwm", "")? {
+                fans.push(PwmFan {
+                    pwm,
+                    enable,
+                    temp: temp.clone(),
+                    auto_mode,
+                    max_pwm: MAX_PWM,
+                });
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        Self::new("hwmon", names, fans)
+    }
+
+    fn fan(&self, fan: u8) -> IoctlResult<&PwmFan> {
+        self.fans
+            .get(fan as usize)
+            .ok_or(IoctlError::DevNotAvailable)
+    }
+}
+
+impl HardwareDevice for HwmonHardware {
+    fn device_interface_id_str(&self) -> IoctlResult<String> {
+        Ok(self.interface.to_owned())
+    }
+
+    fn device_model_id_str(&self) -> IoctlResult<String> {
+        Ok(self.chips.join(", "))
+    }
+
+    fn set_enable_mode_set(&self, _enabled: bool) -> IoctlResult<()> {
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn get_number_fans(&self) -> u8 {
+        tracing::trace!("Available number of fans: {}", self.fans.len());
+        self.fans.len() as u8
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn set_fans_auto(&self) -> IoctlResult<()> {
+        // Hand as many fans as possible to the firmware, even if one fails.
+        let mut result = Ok(());
+        for fan in &self.fans {
+            if let Err(err) = attribute::write(&fan.enable, &fan.auto_mode.to_string()) {
+                tracing::warn!("Failed to set `{}` to auto: `{err}`", fan.enable.display());
+                result = result.and(Err(err.into()));
+            }
+        }
+        tracing::trace!("Set fan mode to auto");
+        result
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn set_fan_speed_percent(&self, fan: u8, fan_speed_percent: u8) -> IoctlResult<()> {
+        let fan = self.fan(fan)?;
+        let fan_speed_raw =
+            (fan.max_pwm as f64 * fan_speed_percent.min(100) as f64 / 100.0).round() as u8;
+
+        attribute::write(&fan.enable, &PWM_MANUAL.to_string())?;
+        attribute::write(&fan.pwm, &fan_speed_raw.to_string())?;
+        tracing::trace!(
+            "Set fan speed percentage to {fan_speed_percent}, fan speed raw: {fan_speed_raw}"
+        );
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn get_fan_speed_percent(&self, fan: u8) -> IoctlResult<u8> {
+        let pwm_fan = self.fan(fan)?;
+        let fan_speed_raw = parse(&read_trimmed(&pwm_fan.pwm)?)?;
+        let speed = (fan_speed_raw as f64 * 100.0 / pwm_fan.max_pwm as f64)
+            .round()
+            .min(100.0) as u8;
+        tracing::trace!("Fan {fan} speed percentage is {speed}, fan speed raw: {fan_speed_raw}");
+        Ok(speed)
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn get_fan_temperature(&self, fan: u8) -> IoctlResult<u8> {
+        let millidegrees: i32 = read_trimmed(&self.fan(fan)?.temp)?
+            .parse()
+            .map_err(|_| IoctlError::InvalidArgs)?;
+        let temp = millidegrees / 1000;
+
+        if temp <= 0 {
+            Err(IoctlError::DevNotAvailable)
+        } else {
+            tracing::trace!("Fan {fan} temperature is {temp} C");
+            Ok(temp.min(u8::MAX as i32) as u8)
+        }
+    }
+
+    fn get_fan_sensor(&self, fan: u8) -> IoctlResult<String> {
+        Ok(sensor_label(&self.fan(fan)?.temp))
+    }
+
+    fn get_fans_min_speed(&self) -> IoctlResult<u8> {
+        Ok(0)
+    }
+
+    fn get_fans_off_available(&self) -> IoctlResult<bool> {
+        Ok(true)
+    }
+
+    fn get_available_odm_performance_profiles(&self) -> IoctlResult<Vec<String>> {
+        Err(IoctlError::NotAvailable)
+    }
+
+    fn set_odm_performance_profile(&self, _performance_profile: &str) -> IoctlResult<()> {
+        Err(IoctlError::NotAvailable)
+    }
+
+    fn get_default_odm_performance_profile(&self) -> IoctlResult<String> {
+        Err(IoctlError::NotAvailable)
+    }
+}
+
+/// The hwmon chips in `root` by name, sorted by path.
+fn chips(root: &Path) -> IoctlResult<Vec<(String, PathBuf)>> {
+    let mut devices: Vec<PathBuf> = fs::read_dir(root)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    devices.sort();
+
+    Ok(devices
+        .into_iter()
+        .filter_map(|path| Some((read_trimmed(&path.join("name")).ok()?, path)))
+        .collect())
+}
+
+/// The temperature of the first chip that measures the CPU.
+fn cpu_temp(chips: &[(String, PathBuf)]) -> Option<PathBuf> {
+    CPU_SENSORS.iter().find_map(|sensor| {
+        chips
+            .iter()
+            .find(|(name, _)| name == sensor)
+            .map(|(_, path)| path.join("temp1_input"))
+            .filter(|temp| temp.exists())
+    })
+}
+
+/// The CPU temperature as measured by a fan chip. Sensors labelled as the CPU
+/// are preferred, Super I/O chips have none otherwise.
+fn chip_cpu_temp(name: &str, path: &Path) -> Option<PathBuf> {
+    let labelled = (1..=10).find_map(|index| {
+        let label = read_trimmed(&path.join(format!("temp{index}_label"))).ok()?;
+        (label.contains("CPU") || label.starts_with("PECI"))
+            .then(|| path.join(format!("temp{index}_input")))
+    });
+    let first = Some(path.join("temp1_input"))
+        .filter(|_| !SUPER_IO_CHIPS.iter().any(|chip| name.starts_with(chip)));
+    labelled.or(first).filter(|temp| temp.exists())
+}
+
+/// The chip and the label of a `tempN_input` attribute, e.g. `coretemp Package id 0`.
+fn sensor_label(temp: &Path) -> String {
+    let dir = temp.parent().unwrap_or(Path::new(""));
+    let chip = read_trimmed(&dir.join("name")).unwrap_or_else(|_| {
+        dir.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    });
+    let sensor = temp
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .and_then(|file_name| file_name.strip_suffix("_input"))
+        .unwrap_or_default();
+    let label =
+        read_trimmed(&dir.join(format!("{sensor}_label"))).unwrap_or_else(|_| sensor.to_owned());
+    format!("{chip} {label}")
+}
+
+/// The controllable fans in `dir`, named `<prefix>N<suffix>` with a
+/// `<prefix>N<suffix>_enable` attribute, as index, pwm, enable and auto mode.
+fn pwm_fans(
+    dir: &Path,
+    prefix: &str,
+    suffix: &str,
+) -> IoctlResult<Vec<(u32, PathBuf, PathBuf, u8)>> {
+    let mut fans: Vec<(u32, PathBuf, PathBuf, u8)> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name();
+            let index = file_name
+                .to_str()?
+                .strip_prefix(prefix)?
+                .strip_suffix(suffix)?
+                .parse()
+                .ok()?;
+            let enable = dir.join(format!("{prefix}{index}{suffix}_enable"));
+            let mode = read_trimmed(&enable).and_then(|mode| parse(&mode)).ok()?;
+            let auto_mode = if mode == PWM_MANUAL { PWM_AUTO } else { mode };
+            Some((index, dir.join(file_name), enable, auto_mode))
+        })
+        .collect();
+    fans.sort();
+    Ok(fans)
+}
+
+/// Only platform devices, like the embedded controller or a Super I/O chip, drive
+/// the system fans. Fans of GPUs (PCI) or USB controllers are left alone.
+fn is_platform_chip(path: &Path) -> bool {
+    fs::read_link(path.join("device/subsystem"))
+        .is_ok_and(|subsystem| subsystem.ends_with("platform"))
+}
+
+fn read_trimmed(path: &Path) -> IoctlResult<String> {
+    Ok(fs::read_to_string(path)?.trim().to_owned())
+}
+
+fn parse(value: &str) -> IoctlResult<u8> {
+    value.parse().map_err(|_| IoctlError::InvalidArgs)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, os::unix::fs::symlink};
+
+    use super::*;
+
+    fn create_chip(root: &Path, chip: &str, name: &str, subsystem: &str) -> PathBuf {
+        let path = root.join(chip);
+        fs::create_dir_all(path.join("device")).unwrap();
+        fs::create_dir_all(root.join("bus").join(subsystem)).unwrap();
+        symlink(
+            root.join("bus").join(subsystem),
+            path.join("device/subsystem"),
+        )
+        .unwrap();
+        fs::write(path.join("name"), format!("{name}\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn hwmon_fans() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let chip = create_chip(root, "hwmon0", "nct6775", "platform");
+        let cpu = create_chip(root, "hwmon1", "k10temp", "pci");
+        let gpu = create_chip(root, "hwmon2", "amdgpu", "pci");
+
+        fs::write(chip.join("pwm1"), "128\n").unwrap();
+        fs::write(chip.join("pwm1_enable"), "5\n").unwrap();
+        // Not controllable without an enable attribute.
+        fs::write(chip.join("pwm2"), "0\n").unwrap();
+        fs::write(cpu.join("temp1_input"), "54250\n").unwrap();
+        // The first sensor of Super I/O chips is on the board.
+        fs::write(chip.join("temp1_input"), "31000\n").unwrap();
+        // GPU fans are controlled by the GPU driver.
+        fs::write(gpu.join("pwm1"), "100\n").unwrap();
+        fs::write(gpu.join("pwm1_enable"), "2\n").unwrap();
+        fs::write(gpu.join("temp1_input"), "48000\n").unwrap();
+
+        let device = HwmonHardware::scan(root).unwrap();
+        assert_eq!(device.get_number_fans(), 1);
+        assert_eq!(device.device_model_id_str().unwrap(), "nct6775");
+        assert_eq!(device.get_fan_temperature(0).unwrap(), 54);
+        assert_eq!(device.get_fan_speed_percent(0).unwrap(), 50);
+
+        device.set_fan_speed_percent(0, 100).unwrap();
+        assert_eq!(read_trimmed(&chip.join("pwm1")).unwrap(), "255");
+        assert_eq!(read_trimmed(&chip.join("pwm1_enable")).unwrap(), "1");
+
+        // The original mode is restored.
+        device.set_fans_auto().unwrap();
+        assert_eq!(read_trimmed(&chip.join("pwm1_enable")).unwrap(), "5");
+        assert!(device.get_fan_temperature(1).is_err());
+    }
+
+    #[test]
+    fn sensor_labels() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let chip = create_chip(root, "hwmon0", "nct6775", "platform");
+
+        fs::write(chip.join("pwm1"), "128\n").unwrap();
+        fs::write(chip.join("pwm1_enable"), "2\n").unwrap();
+        fs::write(chip.join("temp1_input"), "31000\n").unwrap();
+        fs::write(chip.join("temp2_input"), "54000\n").unwrap();
+        fs::write(chip.join("temp2_label"), "CPUTIN\n").unwrap();
+
+        let device = HwmonHardware::scan(root).unwrap();
+        assert_eq!(device.get_fan_sensor(0).unwrap(), "nct6775 CPUTIN");
+        assert!(device.get_fan_sensor(1).is_err());
+    }
+
+    #[test]
+    fn platform_fans() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let driver = &PLATFORM_DRIVERS[1];
+        let platform = root.join(driver.name);
+        fs::create_dir_all(&platform).unwrap();
+        let cpu = create_chip(root, "hwmon0", "coretemp", "platform");
+
+        fs::write(platform.join("fan1_pwm"), "100\n").unwrap();
+        fs::write(platform.join("fan1_pwm_enable"), "2\n").unwrap();
+        fs::write(platform.join("fan2_pwm"), "200\n").unwrap();
+        fs::write(platform.join("fan2_pwm_enable"), "1\n").unwrap();
+        fs::write(cpu.join("temp1_input"), "61000\n").unwrap();
+
+        let device = HwmonHardware::scan_platform(driver, &platform, root).unwrap();
+        assert_eq!(device.get_number_fans(), 2);
+        assert_eq!(device.device_interface_id_str().unwrap(), "platform");
+        assert_eq!(device.get_fan_temperature(1).unwrap(), 61);
+        assert_eq!(device.get_fan_sensor(1).unwrap(), "coretemp temp1");
+        // The embedded controller of Uniwill devices runs at full speed at 200.
+        assert_eq!(device.get_fan_speed_percent(0).unwrap(), 50);
+        assert_eq!(device.get_fan_speed_percent(1).unwrap(), 100);
+
+        device.set_fan_speed_percent(0, 100).unwrap();
+        assert_eq!(read_trimmed(&platform.join("fan1_pwm")).unwrap(), "200");
+
+        // The other fans are still handed to the firmware if one fails.
+        fs::remove_file(platform.join("fan1_pwm_enable")).unwrap();
+        fs::create_dir(platform.join("fan1_pwm_enable")).unwrap();
+        assert!(device.set_fans_auto().is_err());
+        assert_eq!(
+            read_trimmed(&platform.join("fan2_pwm_enable")).unwrap(),
+            "2"
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Simulated hardware for development and tests on machines
+//! without the tuxedo_io module.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+    Mutex,
+};
+
+use crate::error::IoctlError;
+
+use super::traits::{FnLockDevice, HardwareDevice, TdpDevice, TouchpadDevice, WebcamDevice};
+use super::IoctlResult;
+
+const PERFORMANCE_PROFILES: [&str; 3] = ["quiet", "balanced", "performance"];
+const DEFAULT_PERFORMANCE_PROFILE: &str = "balanced";
+
+/// Name, minimum and maximum of each power limit in watts.
+const TDPS: [(&str, i32, i32); 2] = [("pl1", 10, 45), ("pl2", 15, 65)];
+
+/// Temperatures of the default script: a slow heat up
+/// followed by a cool down.
+const DEFAULT_TEMPERATURES: [u8; 12] = [40, 45, 50, 55, 65, 75, 85, 90, 80, 65, 55, 45];
+
+#[derive(Debug, Default)]
+struct MockFan {
+    speed: AtomicU8,
+    /// Position in the temperature script.
+    step: AtomicUsize,
+}
+
+/// Virtual fans whose temperatures follow a script.
+///
+/// Every temperature read of a fan returns the next value of
+/// the script, starting over after the last one.
+#[derive(Debug)]
+pub struct MockHardware {
+    temperatures: Vec<u8>,
+    fans: Vec<MockFan>,
+    performance_profile: Mutex<String>,
+    webcam: AtomicBool,
+    fn_lock: AtomicBool,
+    touchpad: AtomicBool,
+    tdps: Mutex<Vec<i32>>,
+}
+
+impl MockHardware {
+    pub fn new(number_of_fans: u8, temperatures: Vec<u8>) -> Self {
+        let temperatures = if temperatures.is_empty() {
+            DEFAULT_TEMPERATURES.to_vec()
+        } else {
+            temperatures
+        };
+
+        Self {
+            temperatures,
+            fans: (0..number_of_fans).map(|_| MockFan::default()).collect(),
+            performance_profile: Mutex::new(DEFAULT_PERFORMANCE_PROFILE.to_owned()),
+            webcam: AtomicBool::new(true),
+            fn_lock: AtomicBool::new(false),
+            touchpad: AtomicBool::new(true),
+            tdps: Mutex::new(TDPS.iter().map(|(_, _, max)| *max).collect()),
+        }
+    }
+
+    fn fan(&self, fan: u8) -> IoctlResult<&MockFan> {
+        self.fans
+            .get(fan as usize)
+            .ok_or(IoctlError::DevNotAvailable)
+    }
+
+    fn tdp(&self, tdp_index: u8) -> IoctlResult<(&'static str, i32, i32)> {
+        TDPS.get(tdp_index as usize)
+            .copied()
+            .ok_or(IoctlError::DevNotAvailable)
+    }
+}
+
+impl Default for MockHardware {
+    fn default() -> Self {
+        Self::new(2, DEFAULT_TEMPERATURES.to_vec())
+    }
+}
+
+impl HardwareDevice for MockHardware {
+    fn device_interface_id_str(&self) -> IoctlResult<String> {
+        Ok("mock".to_owned())
+    }
+
+    fn device_model_id_str(&self) -> IoctlResult<String> {
+        Ok("Simulated device".to_owned())
+    }
+
+    fn set_enable_mode_set(&self, _enabled: bool) -> IoctlResult<()> {
+        Ok(())
+    }
+
+    fn get_number_fans(&self) -> u8 {
+        self.fans.len() as u8
+    }
+
+    fn set_fans_auto(&self) -> IoctlResult<()> {
+        tracing::debug!("Set fan mode to auto");
+        Ok(())
+    }
+
+    fn set_fan_speed_percent(&self, fan: u8, fan_speed_percent: u8) -> IoctlResult<()> {
+        let speed = fan_speed_percent.min(100);
+        self.fan(fan)?.speed.store(speed, Ordering::Relaxed);
+        tracing::debug!("Set fan {fan} speed percentage to {speed}");
+        Ok(())
+    }
+
+    fn get_fan_speed_percent(&self, fan: u8) -> IoctlResult<u8> {
+        Ok(self.fan(fan)?.speed.load(Ordering::Relaxed))
+    }
+
+    fn get_fan_temperature(&self, fan: u8) -> IoctlResult<u8> {
+        let step = self.fan(fan)?.step.fetch_add(1, Ordering::Relaxed);
+        Ok(self.temperatures[step % self.temperatures.len()])
+    }
+
+    fn get_fans_min_speed(&self) -> IoctlResult<u8> {
+        Ok(0)
+    }
+
+    fn get_fans_off_available(&self) -> IoctlResult<bool> {
+        Ok(true)
+    }
+
+    fn get_available_odm_performance_profiles(&self) -> IoctlResult<Vec<String>> {
+        Ok(PERFORMANCE_PROFILES.map(str::to_owned).to_vec())
+    }
+
+    fn set_odm_performance_profile(&self, performance_profile: &str) -> IoctlResult<()> {
+        if PERFORMANCE_PROFILES.contains(&performance_profile) {
+            *self.performance_profile.lock().unwrap() = performance_profile.to_owned();
+            tracing::debug!("Set performance profile to {performance_profile}");
+            Ok(())
+        } else {
+            Err(IoctlError::InvalidArgs)
+        }
+    }
+
+    fn get_default_odm_performance_profile(&self) -> IoctlResult<String> {
+        Ok(DEFAULT_PERFORMANCE_PROFILE.to_owned())
+    }
+}
+
+impl WebcamDevice for MockHardware {
+    fn set_webcam(&self, status: bool) -> IoctlResult<()> {
+        self.webcam.store(status, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn get_webcam(&self) -> IoctlResult<bool> {
+        Ok(self.webcam.load(Ordering::Relaxed))
+    }
+}
+
+impl FnLockDevice for MockHardware {
+    fn set_fn_lock(&self, enabled: bool) -> IoctlResult<()> {
+        self.fn_lock.store(enabled, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn get_fn_lock(&self) -> IoctlResult<bool> {
+        Ok(self.fn_lock.load(Ordering::Relaxed))
+    }
+}
+
+impl TouchpadDevice for MockHardware {
+    fn set_touchpad(&self, enabled: bool) -> IoctlResult<()> {
+        self.touchpad.store(enabled, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn get_touchpad(&self) -> IoctlResult<bool> {
+        Ok(self.touchpad.load(Ordering::Relaxed))
+    }
+}
+
+impl TdpDevice for MockHardware {
+    fn get_number_tdps(&self) -> IoctlResult<u8> {
+        Ok(TDPS.len() as u8)
+    }
+
+    fn get_tdp_descriptors(&self) -> IoctlResult<Vec<String>> {
+        Ok(TDPS.iter().map(|(name, _, _)| name.to_string()).collect())
+    }
+
+    fn get_tdp_min(&self, tdp_index: u8) -> IoctlResult<i32> {
+        Ok(self.tdp(tdp_index)?.1)
+    }
+
+    fn get_tdp_max(&self, tdp_index: u8) -> IoctlResult<i32> {
+        Ok(self.tdp(tdp_index)?.2)
+    }
+
+    fn set_tdp(&self, tdp_index: u8, tdp_value: i32) -> IoctlResult<()> {
+        let (_, min, max) = self.tdp(tdp_index)?;
+        if !(min..=max).contains(&tdp_value) {
+            return Err(IoctlError::InvalidArgs);
+        }
+        self.tdps.lock().unwrap()[tdp_index as usize] = tdp_value;
+        Ok(())
+    }
+
+    fn get_tdp(&self, tdp_index: u8) -> IoctlResult<i32> {
+        self.tdp(tdp_index)?;
+        Ok(self.tdps.lock().unwrap()[tdp_index as usize])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scripted_temperatures() {
+        let device = MockHardware::new(2, vec![40, 60, 80]);
+
+        let temps: Vec<u8> = (0..4)
+            .map(|_| device.get_fan_temperature(0).unwrap())
+            .collect();
+        assert_eq!(temps, [40, 60, 80, 40]);
+        // Every fan has its own position in the script.
+        assert_eq!(device.get_fan_temperature(1).unwrap(), 40);
+        assert!(matches!(
+            device.get_fan_temperature(2).unwrap_err(),
+            IoctlError::DevNotAvailable
+        ));
+
+        device.set_fan_speed_percent(1, 120).unwrap();
+        assert_eq!(device.get_fan_speed_percent(1).unwrap(), 100);
+        assert_eq!(device.get_fan_speed_percent(0).unwrap(), 0);
+    }
+}
@@ -1,29 +1,73 @@
-use std::sync::Arc;
+use std::{ops::Range, path::Path, sync::Arc};
 
 use crate::{config::open_device_file, error::IoctlError, read};
 
 use self::{
     clevo::ClevoHardware,
-    traits::{HardwareDevice, TdpDevice, WebcamDevice},
+    fn_lock::SysfsFnLock,
+    hwmon::HwmonHardware,
+    touchpad::InputTouchpad,
+    traits::{FnLockDevice, HardwareDevice, TdpDevice, TouchpadDevice, WebcamDevice},
     uniwill::UniwillHardware,
 };
 
+pub mod attribute;
 mod clevo;
+mod fn_lock;
+mod hwmon;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod touchpad;
 pub mod traits;
 mod uniwill;
 
 pub type IoctlResult<T> = Result<T, IoctlError>;
 
+/// Charging settings aren't part of tuxedo_io, but are provided by
+/// the tuxedo_keyboard module or the battery driver.
+const CHARGING_PROFILE_PATH: &str =
+    "/sys/devices/platform/tuxedo_keyboard/charging_profile/charging_profile";
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+/// How the hardware is accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// The tuxedo_io kernel module.
+    Ioctl,
+    /// The fan attributes of the tuxedo_keyboard or uniwill platform
+    /// drivers, only fan control is available.
+    Platform,
+    /// Generic hwmon fans, only fan control is available.
+    Hwmon,
+    /// Simulated hardware.
+    #[cfg(feature = "mock")]
+    Mock,
+}
+
 #[derive(Debug)]
 pub struct IoInterface {
+    pub backend: Backend,
+    /// Version of the tuxedo_io module, empty for other backends.
     pub module_version: String,
     pub device: Arc<dyn HardwareDevice>,
     pub webcam: Option<Arc<dyn WebcamDevice>>,
     pub tdp: Option<Arc<dyn TdpDevice>>,
+    pub fn_lock: Option<Arc<dyn FnLockDevice>>,
+    pub touchpad: Option<Arc<dyn TouchpadDevice>>,
 }
 
 impl IoInterface {
+    /// Use the tuxedo_io module or fall back to the fans of the platform
+    /// drivers or hwmon if it isn't available.
     pub fn new() -> IoctlResult<Self> {
+        Self::ioctl().or_else(|err| {
+            tracing::debug!("Falling back to sysfs fans: {err}");
+            Self::platform().or_else(|_| Self::hwmon()).map_err(|_| err)
+        })
+    }
+
+    /// Only use the tuxedo_io module.
+    pub fn ioctl() -> IoctlResult<Self> {
         let file = open_device_file()?;
         let module_version = read::mod_version(&file)?;
 
@@ -31,24 +75,136 @@ impl IoInterface {
             let clevo_hardware = ClevoHardware::init(file)?;
             let interface = Arc::new(clevo_hardware);
             Ok(Self {
+                backend: Backend::Ioctl,
                 module_version,
                 device: interface.clone(),
                 webcam: Some(interface),
                 tdp: None,
+                fn_lock: fn_lock(),
+                touchpad: touchpad(),
             })
         } else if matches!(read::uw::hw_check(&file), Ok(1)) {
             let uniwill_hardware = UniwillHardware::init(file)?;
             let interface = Arc::new(uniwill_hardware);
             Ok(Self {
+                backend: Backend::Ioctl,
                 module_version,
                 device: interface.clone(),
                 webcam: None,
                 tdp: Some(interface),
+                fn_lock: fn_lock(),
+                touchpad: touchpad(),
             })
         } else {
             Err(IoctlError::DevNotAvailable)
         }
     }
+
+    /// Only use the fans of the tuxedo_keyboard or uniwill platform drivers.
+    pub fn platform() -> IoctlResult<Self> {
+        Ok(Self {
+            backend: Backend::Platform,
+            module_version: String::new(),
+            device: Arc::new(HwmonHardware::platform()?),
+            webcam: None,
+            tdp: None,
+            fn_lock: fn_lock(),
+            touchpad: touchpad(),
+        })
+    }
+
+    /// Only use the fans of hwmon devices.
+    pub fn hwmon() -> IoctlResult<Self> {
+        Ok(Self {
+            backend: Backend::Hwmon,
+            module_version: String::new(),
+            device: Arc::new(HwmonHardware::init()?),
+            webcam: None,
+            tdp: None,
+            fn_lock: fn_lock(),
+            touchpad: touchpad(),
+        })
+    }
+
+    /// Use simulated hardware instead of real devices.
+    #[cfg(feature = "mock")]
+    pub fn mock(hardware: mock::MockHardware) -> Self {
+        let interface = Arc::new(hardware);
+        Self {
+            backend: Backend::Mock,
+            module_version: String::new(),
+            device: interface.clone(),
+            webcam: Some(interface.clone()),
+            tdp: Some(interface.clone()),
+            fn_lock: Some(interface.clone()),
+            touchpad: Some(interface),
+        }
+    }
+
+    /// Indices of the fans that can be controlled.
+    pub fn supported_fans(&self) -> Range<u8> {
+        0..self.device.get_number_fans()
+    }
+
+    /// Whether the webcam can be switched on and off.
+    pub fn has_webcam_control(&self) -> bool {
+        self.webcam
+            .as_ref()
+            .is_some_and(|webcam| webcam.get_webcam().is_ok())
+    }
+
+    /// Whether the power limits can be changed.
+    pub fn has_tdp_control(&self) -> bool {
+        self.tdp
+            .as_ref()
+            .is_some_and(|tdp| matches!(tdp.get_number_tdps(), Ok(n) if n > 0))
+    }
+
+    /// Whether the Fn-lock can be switched on and off.
+    pub fn has_fn_lock_control(&self) -> bool {
+        self.fn_lock
+            .as_ref()
+            .is_some_and(|fn_lock| fn_lock.get_fn_lock().is_ok())
+    }
+
+    /// Whether the touchpad can be switched on and off.
+    pub fn has_touchpad_control(&self) -> bool {
+        self.touchpad
+            .as_ref()
+            .is_some_and(|touchpad| touchpad.get_touchpad().is_ok())
+    }
+
+    /// Whether a charging profile or charge thresholds can be set.
+    pub fn has_charging_control(&self) -> bool {
+        if Path::new(CHARGING_PROFILE_PATH).exists() {
+            return true;
+        }
+        let Ok(supplies) = std::fs::read_dir(POWER_SUPPLY_PATH) else {
+            return false;
+        };
+        supplies
+            .filter_map(Result::ok)
+            .any(|supply| supply.path().join("charge_control_end_threshold").exists())
+    }
+
+    /// Performance profiles of the firmware, empty if they aren't supported.
+    pub fn available_performance_profiles(&self) -> Vec<String> {
+        self.device
+            .get_available_odm_performance_profiles()
+            .unwrap_or_default()
+    }
+}
+
+fn fn_lock() -> Option<Arc<dyn FnLockDevice>> {
+    SysfsFnLock::init()
+        .ok()
+        .map(|fn_lock| Arc::new(fn_lock) as Arc<dyn FnLockDevice>)
+}
+
+fn touchpad() -> Option<Arc<dyn TouchpadDevice>> {
+    InputTouchpad::init()
+        .ok()
+        .map(|touchpad| Arc::new(touchpad) as Arc<dyn TouchpadDevice>)
 }
 
 #[cfg(test)]
@@ -0,0 +1,98 @@
+//! Touchpad switch through the `inhibited` attribute of the input device.
+//!
+//! Inhibited input devices don't send events, which works the same
+//! on all models and doesn't depend on the desktop environment.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::IoctlError;
+
+use super::traits::TouchpadDevice;
+use super::{attribute, IoctlResult};
+
+const INPUT_PATH: &str = "/sys/class/input";
+
+#[derive(Debug)]
+pub struct InputTouchpad {
+    /// `inhibited` attributes of all touchpads.
+    inhibited: Vec<PathBuf>,
+}
+
+impl InputTouchpad {
+    pub fn init() -> IoctlResult<Self> {
+        Self::scan(Path::new(INPUT_PATH))
+    }
+
+    /// Find the input devices in `root` that are touchpads.
+    fn scan(root: &Path) -> IoctlResult<Self> {
+        let mut inhibited: Vec<PathBuf> = fs::read_dir(root)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                fs::read_to_string(path.join("name"))
+                    .is_ok_and(|name| name.to_lowercase().contains("touchpad"))
+            })
+            .map(|path| path.join("inhibited"))
+            .filter(|inhibited| inhibited.exists())
+            .collect();
+        inhibited.sort();
+
+        if inhibited.is_empty() {
+            Err(IoctlError::NotAvailable)
+        } else {
+            Ok(Self { inhibited })
+        }
+    }
+}
+
+impl TouchpadDevice for InputTouchpad {
+    fn set_touchpad(&self, enabled: bool) -> IoctlResult<()> {
+        for inhibited in &self.inhibited {
+            attribute::write(inhibited, if enabled { "0" } else { "1" })?;
+        }
+        Ok(())
+    }
+
+    fn get_touchpad(&self) -> IoctlResult<bool> {
+        // The touchpad counts as enabled if any of the devices is.
+        for inhibited in &self.inhibited {
+            if fs::read_to_string(inhibited)?.trim() == "0" {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn touchpad() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let touchpad = root.join("input3");
+        let keyboard = root.join("input4");
+        fs::create_dir_all(&touchpad).unwrap();
+        fs::create_dir_all(&keyboard).unwrap();
+
+        fs::write(touchpad.join("name"), "UNIW0001:00 093A:0255 Touchpad\n").unwrap();
+        fs::write(touchpad.join("inhibited"), "0\n").unwrap();
+        fs::write(keyboard.join("name"), "AT Translated Set 2 keyboard\n").unwrap();
+        fs::write(keyboard.join("inhibited"), "0\n").unwrap();
+
+        let device = InputTouchpad::scan(root).unwrap();
+        assert!(device.get_touchpad().unwrap());
+
+        device.set_touchpad(false).unwrap();
+        assert!(!device.get_touchpad().unwrap());
+        assert_eq!(fs::read_to_string(touchpad.join("inhibited")).unwrap(), "1");
+        assert_eq!(
+            fs::read_to_string(keyboard.join("inhibited")).unwrap(),
+            "0\n"
+        );
+    }
+}
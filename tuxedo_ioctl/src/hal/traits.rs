@@ -22,6 +22,11 @@ pub trait HardwareDevice: Send + Sync + Debug {
     /// Get the fan temperature in °C
     fn get_fan_temperature(&self, fan: u8) -> IoctlResult<u8>;
 
+    /// Describe the sensor that `get_fan_temperature` reads.
+    fn get_fan_sensor(&self, _fan: u8) -> IoctlResult<String> {
+        Ok("Embedded controller".to_owned())
+    }
+
     /// Get the minimum supported speed of the fans
     fn get_fans_min_speed(&self) -> IoctlResult<u8>;
     fn get_fans_off_available(&self) -> IoctlResult<bool>;
@@ -43,3 +48,13 @@ pub trait TdpDevice: Send + Sync + Debug {
     fn set_tdp(&self, tdp_index: u8, tdp_value: i32) -> IoctlResult<()>;
     fn get_tdp(&self, tdp_index: u8) -> IoctlResult<i32>;
 }
+
+pub trait TouchpadDevice: Send + Sync + Debug {
+    fn set_touchpad(&self, enabled: bool) -> IoctlResult<()>;
+    fn get_touchpad(&self) -> IoctlResult<bool>;
+}
+
+pub trait FnLockDevice: Send + Sync + Debug {
+    fn set_fn_lock(&self, enabled: bool) -> IoctlResult<()>;
+    fn get_fn_lock(&self) -> IoctlResult<bool>;
+}
@@ -1,8 +1,8 @@
 use std::io;
 
 use crate::sysfs_util::{
-    r_file, read_int_list, read_path_to_int_list, read_path_to_string, read_to_string, write_int,
-    write_string,
+    read_int_list, read_path_to_int_list, read_path_to_string, read_to_string, rw_file, write_int,
+    write_string, File,
 };
 
 use super::BatteryChargeControl;
@@ -20,9 +20,9 @@ impl BatteryChargeControl {
         name: String,
         available_start_thresholds: Option<Vec<u32>>,
         available_end_thresholds: Option<Vec<u32>>,
-        start_threshold_file: tokio_uring::fs::File,
-        end_threshold_file: tokio_uring::fs::File,
-        charge_type_file: tokio_uring::fs::File,
+        start_threshold_file: File,
+        end_threshold_file: File,
+        charge_type_file: File,
     ) -> Result<Self, io::Error> {
         Ok(Self {
             name,
@@ -55,21 +55,21 @@ impl BatteryChargeControl {
             }
 
             let start_threshold_file =
-                if let Ok(start_threshold_file) = r_file(path.join(START_THRESHOLD)).await {
+                if let Ok(start_threshold_file) = rw_file(path.join(START_THRESHOLD)).await {
                     start_threshold_file
                 } else {
                     // thresholds not supported
                     continue;
                 };
             let end_threshold_file =
-                if let Ok(end_threshold_file) = r_file(path.join(END_THRESHOLD)).await {
+                if let Ok(end_threshold_file) = rw_file(path.join(END_THRESHOLD)).await {
                     end_threshold_file
                 } else {
                     // thresholds not supported
                     continue;
                 };
             let charge_type_file =
-                if let Ok(charge_type_file) = r_file(path.join(CHARGE_TYPE)).await {
+                if let Ok(charge_type_file) = rw_file(path.join(CHARGE_TYPE)).await {
                     charge_type_file
                 } else {
                     // thresholds not supported
@@ -2,6 +2,8 @@ mod charge_control;
 mod charging_priority;
 mod charging_profile;
 
+use crate::File;
+
 /// A type that manages all sysfs files related to
 /// charging profile options provided by the tuxedo_keyboard driver.
 ///
@@ -9,7 +11,7 @@ mod charging_profile;
 /// battery.
 pub struct ChargingProfile {
     pub available_charging_profiles: Vec<String>,
-    charging_profile_file: tokio_uring::fs::File,
+    charging_profile_file: File,
 }
 
 /// A type that manages all sysfs files related to
@@ -19,7 +21,7 @@ pub struct ChargingProfile {
 /// performance is prioritized when charging over USB-C.
 pub struct ChargingPriority {
     pub available_charging_priorities: Vec<String>,
-    charging_priority_file: tokio_uring::fs::File,
+    charging_priority_file: File,
 }
 
 /// A type that manages all sysfs files related to charging start/end thresholds.
@@ -29,12 +31,12 @@ pub struct BatteryChargeControl {
     pub available_end_thresholds: Option<Vec<u32>>,
     /// Percentage value between 0-100,
     /// [`Self::available_start_thresholds`] lists further restrictions on accepted values.
-    start_threshold_file: tokio_uring::fs::File,
+    start_threshold_file: File,
     /// Percentage value between 0-100,
     /// [`Self::available_end_thresholds`] lists further restrictions on accepted values.
-    end_threshold_file: tokio_uring::fs::File,
+    end_threshold_file: File,
     /// Must be 'Custom' to allow for custom thresholds.
     ///
     /// Possible values listed at <https://www.kernel.org/doc/Documentation/ABI/testing/sysfs-class-power> (section: /sys/class/power_supply/<supply_name>/charge_type)
-    charge_type_file: tokio_uring::fs::File,
+    charge_type_file: File,
 }
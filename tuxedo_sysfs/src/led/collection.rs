@@ -1,13 +1,14 @@
 use std::{
     io,
     ops::{Index, IndexMut},
+    path::Path,
 };
 
 use tailor_api::Color;
 
 use crate::sysfs_util::{r_file, read_int_list, read_path_to_string, rw_file};
 
-use super::{Collection, Controller};
+use super::{tuxedo_keyboard::TUXEDO_KEYBOARD_PATH, Collection, Controller};
 
 const SYSFS_LED_PATH: &str = "/sys/class/leds";
 const BRIGHTNESS: &str = "brightness";
@@ -16,12 +17,63 @@ const MULTI_INDEX: &str = "multi_index";
 const MULTI_INTENSITIES: &str = "multi_intensity";
 const DEVICE_NAME: &str = "device/name";
 const DEVICE_MODALIAS: &str = "device/modalias";
+const KBD_BACKLIGHT: &str = "kbd_backlight";
+/// Firmware revision of USB devices, in the USB device above the HID device.
+const BCD_DEVICE: &str = "bcdDevice";
 
 impl Collection {
     pub async fn new() -> Result<Self, io::Error> {
+        let mut collection = Self::from_path(Path::new(SYSFS_LED_PATH)).await?;
+
+        // Older tuxedo_keyboard modules don't register an LED class device.
+        let has_keyboard = collection
+            .controllers
+            .iter()
+            .any(|controller| controller.function == KBD_BACKLIGHT);
+        if !has_keyboard {
+            match Controller::tuxedo_keyboard(Path::new(TUXEDO_KEYBOARD_PATH)).await {
+                Ok(Some(controller)) => {
+                    tracing::info!("Using the keyboard backlight of the tuxedo_keyboard module");
+                    collection.controllers.push(controller);
+                }
+                Ok(None) => {}
+                Err(err) => tracing::warn!("Failed to open the tuxedo_keyboard backlight: {err}"),
+            }
+        }
+        Ok(collection)
+    }
+
+    /// Create virtual LED devices in `dir`, an RGB keyboard
+    /// and a monochrome status LED.
+    ///
+    /// Unlike sysfs attributes, regular files keep their old content
+    /// if a shorter value is written. The maximum brightness is therefore
+    /// limited to a single digit.
+    #[cfg(feature = "mock")]
+    pub async fn mock(dir: &Path) -> Result<Self, io::Error> {
+        let devices = [
+            ("mock::kbd_backlight", "mock_keyboard", true),
+            ("mock::status", "mock_status", false),
+        ];
+        for (led, device_name, rgb) in devices {
+            let path = dir.join(led);
+            tokio::fs::create_dir_all(path.join("device")).await?;
+            tokio::fs::write(path.join(DEVICE_NAME), device_name).await?;
+            tokio::fs::write(path.join(MAX_BRIGHTNESS), "9").await?;
+            tokio::fs::write(path.join(BRIGHTNESS), "9").await?;
+            if rgb {
+                tokio::fs::write(path.join(MULTI_INDEX), "red green blue").await?;
+                tokio::fs::write(path.join(MULTI_INTENSITIES), "9 9 9").await?;
+            }
+        }
+        Self::from_path(dir).await
+    }
+
+    /// Detect all LED devices in a directory with the layout of `/sys/class/leds`.
+    async fn from_path(root: &Path) -> Result<Self, io::Error> {
         let mut controllers = Vec::new();
 
-        let mut dirs = tokio::fs::read_dir(SYSFS_LED_PATH).await?;
+        let mut dirs = tokio::fs::read_dir(root).await?;
         while let Some(dir) = dirs.next_entry().await? {
             let path = dir.path();
             let file_name = path
@@ -82,13 +134,15 @@ impl Collection {
                 continue;
             }
 
+            let firmware_version = firmware_version(&path);
+
             let multi_index_path = path.join(MULTI_INDEX);
             let rgb_support = tokio::fs::read_to_string(multi_index_path)
                 .await
                 .map(|content| content.trim().to_lowercase() == "red green blue")
                 .unwrap_or_default();
 
-            if rgb_support {
+            let mut controller = if rgb_support {
                 // Get intensities
                 let intensities_path = path.join(MULTI_INTENSITIES);
                 let (intensities_file, intensities) =
@@ -109,34 +163,27 @@ impl Collection {
                     };
 
                 if intensities.len() == 3 {
-                    // Push controller with RGB capabilities
-                    controllers.push(
-                        Controller::new_rgb(
-                            max_brightness,
-                            device_name,
-                            function,
-                            brightness_file,
-                            intensities_file,
-                        )
-                        .await?,
-                    );
+                    // Controller with RGB capabilities
+                    Controller::new_rgb(
+                        max_brightness,
+                        device_name,
+                        function,
+                        brightness_file,
+                        intensities_file,
+                    )
+                    .await?
                 } else {
                     // Should be 3 for an RGB device
                     tracing::warn!("RGB device should have 3 intensities: {:?}", file_name);
                     continue;
                 }
             } else {
-                // Push controller with monochrome capabilities
-                controllers.push(
-                    Controller::new_monochrome(
-                        max_brightness,
-                        device_name,
-                        function,
-                        brightness_file,
-                    )
-                    .await?,
-                );
-            }
+                // Controller with monochrome capabilities
+                Controller::new_monochrome(max_brightness, device_name, function, brightness_file)
+                    .await?
+            };
+            controller.firmware_version = firmware_version;
+            controllers.push(controller);
         }
 
         Ok(Self { controllers })
@@ -170,6 +217,17 @@ impl Collection {
     }
 }
 
+/// The `bcdDevice` of the USB device that an LED device belongs to, e.g. `0003`.
+fn firmware_version(path: &Path) -> Option<String> {
+    let device = std::fs::canonicalize(path.join("device")).ok()?;
+    // The HID device is below the USB interface, which is below the USB device.
+    device
+        .ancestors()
+        .take(4)
+        .find_map(|dir| std::fs::read_to_string(dir.join(BCD_DEVICE)).ok())
+        .map(|version| version.trim().to_owned())
+}
+
 impl Index<usize> for Collection {
     type Output = Controller;
 
@@ -196,7 +254,7 @@ mod test {
 
         tracing_subscriber::fmt::init();
 
-        tokio_uring::start(async {
+        crate::runtime::start(async {
             let collection = Collection::new().await.unwrap();
             let mut led_controller = collection.into_inner().pop().unwrap();
 
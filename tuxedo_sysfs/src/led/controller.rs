@@ -1,19 +1,18 @@
 use std::io;
 
-use tailor_api::Color;
-use tailor_api::LedControllerMode;
+use tailor_api::{Color, LedCapabilities, LedControllerMode, LedDeviceInfo};
 
-use crate::sysfs_util::{read_int_list, write_string};
+use crate::sysfs_util::{read_int_list, read_to_string, write_string, File};
 
-use super::Controller;
+use super::{tuxedo_keyboard, ColorFiles, Controller};
 
 impl Controller {
     pub async fn new_rgb(
         max_brightness: u32,
         device_name: String,
         function: String,
-        mut brightness_file: tokio_uring::fs::File,
-        intensities_file: tokio_uring::fs::File,
+        mut brightness_file: File,
+        intensities_file: File,
     ) -> Result<Self, io::Error> {
         // Set brightness to 100% so the individual intensities represent their colors without additional scaling.
         write_string(&mut brightness_file, max_brightness.to_string()).await?;
@@ -22,8 +21,9 @@ impl Controller {
             max_brightness,
             device_name,
             function,
+            firmware_version: None,
             brightness_file,
-            intensities_file: Some(intensities_file),
+            colors: ColorFiles::Intensities(intensities_file),
         })
     }
 
@@ -31,14 +31,15 @@ impl Controller {
         max_brightness: u32,
         device_name: String,
         function: String,
-        brightness_file: tokio_uring::fs::File,
+        brightness_file: File,
     ) -> Result<Self, io::Error> {
         Ok(Self {
             max_brightness,
             device_name,
             function,
+            firmware_version: None,
             brightness_file,
-            intensities_file: None,
+            colors: ColorFiles::None,
         })
     }
 
@@ -46,18 +47,48 @@ impl Controller {
         let Self {
             max_brightness,
             brightness_file,
-            intensities_file,
+            colors,
             ..
         } = self;
 
-        if let Some(intensities) = intensities_file {
-            write_string(intensities, color.sysfs_rgb_string(*max_brightness)).await
-        } else {
-            write_string(
-                brightness_file,
-                color.sysfs_monochrome_string(*max_brightness),
-            )
-            .await
+        match colors {
+            ColorFiles::None => {
+                write_string(
+                    brightness_file,
+                    color.sysfs_monochrome_string(*max_brightness),
+                )
+                .await
+            }
+            ColorFiles::Intensities(intensities) => {
+                write_string(intensities, color.sysfs_rgb_string(*max_brightness)).await
+            }
+            ColorFiles::Zones(zones) => {
+                for zone in zones {
+                    write_string(zone, tuxedo_keyboard::color_string(color)).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Set the brightness to the maximum again, e.g. after the firmware reset
+    /// the controller during suspend. The color must be set afterwards.
+    ///
+    /// Fails if the device was removed, even if it was registered again since.
+    pub async fn restore_brightness(&mut self) -> Result<(), io::Error> {
+        let Self {
+            max_brightness,
+            brightness_file,
+            colors,
+            ..
+        } = self;
+
+        match colors {
+            // The brightness is the color, only check that the device is still there.
+            ColorFiles::None => read_int_list(brightness_file).await.map(drop),
+            ColorFiles::Intensities(_) | ColorFiles::Zones(_) => {
+                write_string(brightness_file, max_brightness.to_string()).await
+            }
         }
     }
 
@@ -65,22 +96,32 @@ impl Controller {
         let Self {
             max_brightness,
             brightness_file,
-            intensities_file,
+            colors,
             ..
         } = self;
 
-        if let Some(intensities) = intensities_file {
-            let values = read_int_list(intensities).await?;
-            let values: [u32; 3] = values.try_into().map_err(|_| {
-                io::Error::new(io::ErrorKind::InvalidData, "Invalid number of values")
-            })?;
-            Ok(Color::from_sysfs_rgb_value(values, *max_brightness))
-        } else {
-            let value = read_int_list(brightness_file).await?[0];
-            Ok(Color::from_sysfs_rgb_value(
-                [value, value, value],
-                *max_brightness,
-            ))
+        match colors {
+            ColorFiles::None => {
+                let value = read_int_list(brightness_file).await?[0];
+                Ok(Color::from_sysfs_rgb_value(
+                    [value, value, value],
+                    *max_brightness,
+                ))
+            }
+            ColorFiles::Intensities(intensities) => {
+                let values = read_int_list(intensities).await?;
+                let values: [u32; 3] = values.try_into().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Invalid number of values")
+                })?;
+                Ok(Color::from_sysfs_rgb_value(values, *max_brightness))
+            }
+            // All zones share the same color.
+            ColorFiles::Zones(zones) => {
+                let zone = zones
+                    .first_mut()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No keyboard zones"))?;
+                tuxedo_keyboard::parse_color(&read_to_string(zone).await?)
+            }
         }
     }
 
@@ -93,10 +134,36 @@ impl Controller {
     }
 
     pub fn mode(&self) -> LedControllerMode {
-        if self.intensities_file.is_some() {
-            LedControllerMode::Rgb
-        } else {
-            LedControllerMode::Monochrome
+        match self.colors {
+            ColorFiles::None => LedControllerMode::Monochrome,
+            ColorFiles::Intensities(_) | ColorFiles::Zones(_) => LedControllerMode::Rgb,
+        }
+    }
+
+    pub fn capabilities(&self) -> LedCapabilities {
+        let zones = match &self.colors {
+            ColorFiles::Zones(zones) => zones.len() as u32,
+            ColorFiles::None | ColorFiles::Intensities(_) => 1,
+        };
+        let supported_modes = match self.mode() {
+            // RGB devices can be white.
+            LedControllerMode::Rgb => vec![LedControllerMode::Rgb, LedControllerMode::Monochrome],
+            mode => vec![mode],
+        };
+        LedCapabilities {
+            firmware_version: self.firmware_version.clone(),
+            zones,
+            max_brightness: self.max_brightness,
+            supported_modes,
+        }
+    }
+
+    pub fn info(&self) -> LedDeviceInfo {
+        LedDeviceInfo {
+            device_name: self.device_name.clone(),
+            function: self.function.clone(),
+            mode: self.mode(),
+            capabilities: self.capabilities(),
         }
     }
 }
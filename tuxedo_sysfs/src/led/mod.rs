@@ -1,5 +1,8 @@
 mod collection;
 mod controller;
+mod tuxedo_keyboard;
+
+use crate::File;
 
 /// A collection of controllers for LED devices.
 /// Stores a [`Vec`] of [`Controller`] and initializes by
@@ -16,6 +19,19 @@ pub struct Controller {
     pub device_name: String,
     pub function: String,
     max_brightness: u32,
-    brightness_file: tokio_uring::fs::File,
-    intensities_file: Option<tokio_uring::fs::File>,
+    firmware_version: Option<String>,
+    brightness_file: File,
+    colors: ColorFiles,
+}
+
+/// Files that store the color of an LED device.
+#[derive(Debug)]
+enum ColorFiles {
+    /// Monochrome devices only have a brightness.
+    None,
+    /// The `multi_intensity` file of an RGB LED class device.
+    Intensities(File),
+    /// The color files of the keyboard zones of the
+    /// legacy tuxedo_keyboard interface.
+    Zones(Vec<File>),
 }
@@ -0,0 +1,122 @@
+//! Keyboard backlight of older tuxedo_keyboard modules that don't
+//! register an LED class device but expose a color file per zone.
+
+use std::{io, path::Path};
+
+use tailor_api::Color;
+
+use crate::sysfs_util::{read_int_list, read_to_string, rw_file, write_string, File};
+
+use super::{ColorFiles, Controller};
+
+pub(super) const TUXEDO_KEYBOARD_PATH: &str = "/sys/devices/platform/tuxedo_keyboard";
+const ZONES: [&str; 4] = ["color_left", "color_center", "color_right", "color_extra"];
+const BRIGHTNESS: &str = "brightness";
+const MAX_BRIGHTNESS: u32 = 255;
+
+impl Controller {
+    /// Open the keyboard backlight of the tuxedo_keyboard module in `root`.
+    /// Returns `None` if the module doesn't provide color files.
+    pub(super) async fn tuxedo_keyboard(root: &Path) -> Result<Option<Self>, io::Error> {
+        let Ok(mut brightness_file) = rw_file(root.join(BRIGHTNESS)).await else {
+            return Ok(None);
+        };
+        // Some models list a zone they don't have and fail to read it.
+        let mut zones = Vec::new();
+        for zone in ZONES {
+            if let Ok(mut file) = rw_file(root.join(zone)).await {
+                if read_color(&mut file).await.is_ok() {
+                    zones.push(file);
+                }
+            }
+        }
+        if zones.is_empty() {
+            return Ok(None);
+        }
+        read_int_list(&mut brightness_file).await?;
+
+        // Like with the LED class, the brightness stays at the maximum
+        // and the colors determine the brightness.
+        write_string(&mut brightness_file, MAX_BRIGHTNESS.to_string()).await?;
+
+        Ok(Some(Self {
+            device_name: "tuxedo_keyboard".to_owned(),
+            function: "kbd_backlight".to_owned(),
+            max_brightness: MAX_BRIGHTNESS,
+            firmware_version: None,
+            brightness_file,
+            colors: ColorFiles::Zones(zones),
+        }))
+    }
+}
+
+async fn read_color(file: &mut File) -> Result<Color, io::Error> {
+    parse_color(&read_to_string(file).await?)
+}
+
+/// The module only parses hexadecimal values with a `0x` prefix.
+pub(super) fn color_string(color: &Color) -> String {
+    let Color { r, g, b } = *color;
+    format!("0x{r:02x}{g:02x}{b:02x}")
+}
+
+/// Parse a color like `0xff8800` or `ff8800`.
+pub(super) fn parse_color(value: &str) -> Result<Color, io::Error> {
+    let value = value.trim();
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    let value = u32::from_str_radix(hex, 16)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let [_, r, g, b] = value.to_be_bytes();
+    Ok(Color { r, g, b })
+}
+
+#[cfg(test)]
+mod test {
+    use tailor_api::{Color, LedControllerMode};
+
+    use super::*;
+
+    #[test]
+    fn color_format() {
+        let color = Color {
+            r: 255,
+            g: 136,
+            b: 0,
+        };
+        assert_eq!(color_string(&color), "0xff8800");
+        assert_eq!(parse_color("0xff8800\n").unwrap(), color);
+        assert_eq!(parse_color("ff8800").unwrap(), color);
+        assert!(parse_color("red").is_err());
+    }
+
+    #[test]
+    fn zones() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(BRIGHTNESS), "100").unwrap();
+        std::fs::write(root.join("color_left"), "0x000000").unwrap();
+        std::fs::write(root.join("color_right"), "0x000000").unwrap();
+        std::fs::write(root.join("color_extra"), "").unwrap();
+
+        crate::runtime::start(async {
+            let mut controller = Controller::tuxedo_keyboard(root).await.unwrap().unwrap();
+            assert_eq!(controller.mode(), LedControllerMode::Rgb);
+
+            let color = Color {
+                r: 18,
+                g: 52,
+                b: 86,
+            };
+            controller.set_color(&color).await.unwrap();
+            assert_eq!(controller.get_color().await.unwrap(), color);
+        });
+        assert_eq!(
+            std::fs::read_to_string(root.join("color_right")).unwrap(),
+            "0x123456"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join(BRIGHTNESS)).unwrap(),
+            "255"
+        );
+    }
+}
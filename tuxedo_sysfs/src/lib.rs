@@ -1,3 +1,6 @@
 pub mod charging;
 pub mod led;
+pub mod runtime;
 pub(crate) mod sysfs_util;
+
+pub use sysfs_util::File;
@@ -0,0 +1,49 @@
+//! The async runtime that the sysfs files are used on.
+//!
+//! With the `io-uring` feature, files are accessed through io_uring if the
+//! kernel allows it. Older kernels and hardened configurations that disable
+//! io_uring fall back to the blocking thread pool of tokio.
+
+use std::future::Future;
+
+#[cfg(feature = "io-uring")]
+use std::cell::Cell;
+
+use tokio::{runtime::Builder, task::LocalSet};
+
+#[cfg(feature = "io-uring")]
+thread_local! {
+    /// Whether the runtime of this thread supports io_uring.
+    static IO_URING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `future` on a single-threaded runtime on the current thread.
+///
+/// Tasks can be spawned with [`tokio::task::spawn_local`],
+/// they don't need to be [`Send`].
+pub fn start<F: Future>(future: F) -> F::Output {
+    #[cfg(feature = "io-uring")]
+    match tokio_uring::Runtime::new(&tokio_uring::builder()) {
+        Ok(runtime) => {
+            IO_URING.with(|io_uring| io_uring.set(true));
+            let output = runtime.block_on(future);
+            IO_URING.with(|io_uring| io_uring.set(false));
+            return output;
+        }
+        Err(err) => {
+            tracing::warn!("io_uring is unavailable, using blocking file access: `{err}`");
+        }
+    }
+
+    let runtime = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start the async runtime");
+    LocalSet::new().block_on(&runtime, future)
+}
+
+/// Whether files are accessed through io_uring on this thread.
+#[cfg(feature = "io-uring")]
+pub(crate) fn uses_io_uring() -> bool {
+    IO_URING.with(Cell::get)
+}
@@ -1,24 +1,85 @@
-use std::io;
+use std::io::{self, SeekFrom};
 use std::path::Path;
 
-use tokio_uring::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
-pub(crate) async fn rw_file<P>(path: P) -> Result<fs::File, io::Error>
+/// An open sysfs file, which is always read and written from the start.
+#[derive(Debug)]
+pub struct File(Backend);
+
+#[derive(Debug)]
+enum Backend {
+    #[cfg(feature = "io-uring")]
+    IoUring(tokio_uring::fs::File),
+    Blocking(tokio::fs::File),
+}
+
+impl File {
+    async fn open(path: &Path, write: bool) -> Result<Self, io::Error> {
+        #[cfg(feature = "io-uring")]
+        if crate::runtime::uses_io_uring() {
+            let file = tokio_uring::fs::OpenOptions::new()
+                .read(true)
+                .write(write)
+                .open(path)
+                .await?;
+            return Ok(Self(Backend::IoUring(file)));
+        }
+        let file = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(write)
+            .open(path)
+            .await?;
+        Ok(Self(Backend::Blocking(file)))
+    }
+
+    async fn read(&mut self) -> Result<Vec<u8>, io::Error> {
+        match &mut self.0 {
+            #[cfg(feature = "io-uring")]
+            Backend::IoUring(file) => {
+                let buffer = Vec::with_capacity(256);
+                let (res, buffer) = file.read_at(buffer, 0).await;
+                res?;
+                Ok(buffer)
+            }
+            Backend::Blocking(file) => {
+                let mut buffer = Vec::with_capacity(256);
+                file.seek(SeekFrom::Start(0)).await?;
+                file.read_to_end(&mut buffer).await?;
+                Ok(buffer)
+            }
+        }
+    }
+
+    async fn write(&mut self, value: Vec<u8>) -> Result<(), io::Error> {
+        match &mut self.0 {
+            #[cfg(feature = "io-uring")]
+            Backend::IoUring(file) => {
+                file.write_at(value, 0).submit().await.0?;
+                Ok(())
+            }
+            Backend::Blocking(file) => {
+                file.seek(SeekFrom::Start(0)).await?;
+                file.write_all(&value).await?;
+                // Writes only fail once they reached the driver.
+                file.flush().await
+            }
+        }
+    }
+}
+
+pub(crate) async fn rw_file<P>(path: P) -> Result<File, io::Error>
 where
     P: AsRef<Path>,
 {
-    fs::OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(path)
-        .await
+    File::open(path.as_ref(), true).await
 }
 
-pub(crate) async fn r_file<P>(path: P) -> Result<fs::File, io::Error>
+pub(crate) async fn r_file<P>(path: P) -> Result<File, io::Error>
 where
     P: AsRef<Path>,
 {
-    fs::File::open(path).await
+    File::open(path.as_ref(), false).await
 }
 
 pub(crate) async fn read_path_to_string<P>(path: P) -> Result<String, io::Error>
@@ -29,10 +90,8 @@ where
     read_to_string(&mut file).await
 }
 
-pub(crate) async fn read_to_string(file: &mut fs::File) -> Result<String, io::Error> {
-    let buffer = Vec::with_capacity(256);
-    let (res, buffer) = file.read_at(buffer, 0).await;
-    res?;
+pub(crate) async fn read_to_string(file: &mut File) -> Result<String, io::Error> {
+    let buffer = file.read().await?;
     String::from_utf8(buffer).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
 }
 
@@ -44,7 +103,7 @@ where
     read_int_list(&mut file).await
 }
 
-pub(crate) async fn read_int_list(file: &mut fs::File) -> Result<Vec<u32>, io::Error> {
+pub(crate) async fn read_int_list(file: &mut File) -> Result<Vec<u32>, io::Error> {
     let content = read_to_string(file).await?;
 
     let mut output = Vec::new();
@@ -63,7 +122,7 @@ pub(crate) async fn read_int_list(file: &mut fs::File) -> Result<Vec<u32>, io::E
     }
 }
 
-pub(crate) async fn read_to_string_list(file: &mut fs::File) -> Result<Vec<String>, io::Error> {
+pub(crate) async fn read_to_string_list(file: &mut File) -> Result<Vec<String>, io::Error> {
     let output = read_to_string(file).await?;
     Ok(output
         .split(' ')
@@ -71,18 +130,10 @@ pub(crate) async fn read_to_string_list(file: &mut fs::File) -> Result<Vec<Strin
         .collect::<Vec<String>>())
 }
 
-async fn write_buffer<V>(file: &mut fs::File, value: V) -> Result<(), io::Error>
-where
-    V: tokio_uring::buf::IoBuf,
-{
-    file.write_at(value, 0).submit().await.0?;
-    Ok(())
-}
-
-pub(crate) async fn write_string(file: &mut fs::File, string: String) -> Result<(), io::Error> {
-    write_buffer(file, string.into_bytes()).await
+pub(crate) async fn write_string(file: &mut File, string: String) -> Result<(), io::Error> {
+    file.write(string.into_bytes()).await
 }
 
-pub(crate) async fn write_int(file: &mut fs::File, int: u32) -> Result<(), io::Error> {
+pub(crate) async fn write_int(file: &mut File, int: u32) -> Result<(), io::Error> {
     write_string(file, format!("{}", int)).await
 }